@@ -62,6 +62,29 @@ define_set_collection_element_command!(
     }
 );
 
+define_set_collection_element_command!(
+    SetAdditiveBlendPoseSourceCommand<Handle<PoseNode<Handle<N>>>, Handle<PoseNode<Handle<N>>>>(self, context) {
+        let machine = fetch_machine(context, self.node_handle);
+        if let PoseNode::AdditiveBlend(ref mut definition) = machine.layers_mut()[self.layer_index].nodes_mut()[self.handle] {
+            let source = match self.index {
+                0 => &mut definition.base_pose_source,
+                1 => &mut definition.additive_pose_source,
+                _ => &mut definition.reference_pose_source,
+            };
+            std::mem::swap(source, &mut self.value);
+        }
+    }
+);
+
+define_set_collection_element_command!(
+    SetMirrorPoseSourceCommand<Handle<PoseNode<Handle<N>>>, Handle<PoseNode<Handle<N>>>>(self, context) {
+        let machine = fetch_machine(context, self.node_handle);
+        if let PoseNode::MirrorPose(ref mut definition) = machine.layers_mut()[self.layer_index].nodes_mut()[self.handle] {
+            std::mem::swap(&mut definition.pose_source, &mut self.value);
+        }
+    }
+);
+
 define_set_collection_element_command!(
     SetBlendSpacePointPositionCommand<Handle<PoseNode<Handle<N>>>, Vector2<f32>>(self, context) {
         let machine = fetch_machine(context, self.node_handle);
@@ -264,6 +264,11 @@ impl<N: Debug + 'static> CommandTrait for AddStateCommand<N> {
 
                 layer.set_entry_state(prev_entry_state);
 
+                // Make sure the layer won't reference the removed state.
+                if layer.active_state() == handle {
+                    layer.set_active_state(prev_entry_state);
+                }
+
                 let (ticket, state) = layer.states_mut().take_reserve(handle);
 
                 *self = AddStateCommand::Reverted {
@@ -734,6 +739,8 @@ impl<N: Debug + 'static> SetMachineEntryStateCommand<N> {
 
         let prev = layer.entry_state();
         layer.set_entry_state(self.entry);
+        // Keep previewing the entry state in the editor.
+        layer.set_active_state(self.entry);
         self.entry = prev;
     }
 }
@@ -656,6 +656,9 @@ impl AbsmEditor {
                                             BlendSpacePoint::default(),
                                         ));
                                     }
+                                    PoseNode::AdditiveBlend(_) | PoseNode::MirrorPose(_) => {
+                                        // Fixed input sockets
+                                    }
                                 }
                             }
                         }
@@ -89,9 +89,7 @@ impl CanvasContextMenu {
                     State {
                         position: ui.node(self.canvas).screen_to_local(screen_position),
                         name: "New State".to_string(),
-                        on_enter_actions: Default::default(),
-                        on_leave_actions: Default::default(),
-                        root: Default::default(),
+                        ..Default::default()
                     },
                 ));
             } else if message.destination() == self.connect_all_nodes {
@@ -457,14 +457,25 @@ impl StateGraphViewer {
         // Force update layout to be able to fetch positions of nodes for transitions.
         ui.update_layout(ui.screen_size());
 
-        // Sync transitions.
-        match transitions
-            .len()
-            .cmp(&(machine_layer.transitions().alive_count() as usize))
-        {
+        // Sync transitions. Transitions of interrupts and any-state transitions have no source (or
+        // destination) state while they're inactive, such transitions have no views.
+        let has_view = |transition: &Transition<Handle<N>>| {
+            transition.source().is_some() && transition.dest().is_some()
+        };
+        match transitions.len().cmp(
+            &machine_layer
+                .transitions()
+                .iter()
+                .filter(|transition| has_view(transition))
+                .count(),
+        ) {
             Ordering::Less => {
                 // A transition was added.
-                for (transition_handle, transition) in machine_layer.transitions().pair_iter() {
+                for (transition_handle, transition) in machine_layer
+                    .transitions()
+                    .pair_iter()
+                    .filter(|(_, transition)| has_view(transition))
+                {
                     if transitions.iter().all(|transition_view| {
                         transition_handle
                             != ui
@@ -534,8 +545,8 @@ impl StateGraphViewer {
                 {
                     if machine_layer
                         .transitions()
-                        .pair_iter()
-                        .all(|(h, _)| h != transition_model_handle.into())
+                        .try_borrow(transition_model_handle.into())
+                        .map_or(true, |transition| !has_view(transition))
                     {
                         send_sync_message(
                             ui,
@@ -3,7 +3,8 @@ use crate::fyrox::{
     core::{algebra::Vector2, pool::Handle},
     generic_animation::machine::{
         node::{blendspace::BlendSpace, blendspace::BlendSpacePoint, BasePoseNode},
-        BlendAnimations, BlendAnimationsByIndex, MachineLayer, PlayAnimation, PoseNode, State,
+        AdditiveBlend, BlendAnimations, BlendAnimationsByIndex, MachineLayer, MirrorPose,
+        PlayAnimation, PoseNode, State,
     },
     graph::BaseSceneGraph,
     gui::{
@@ -19,8 +20,9 @@ use crate::{
     absm::{
         command::{
             blend::{
-                SetBlendAnimationByIndexInputPoseSourceCommand,
+                SetAdditiveBlendPoseSourceCommand, SetBlendAnimationByIndexInputPoseSourceCommand,
                 SetBlendAnimationsPoseSourceCommand, SetBlendSpacePoseSourceCommand,
+                SetMirrorPoseSourceCommand,
             },
             AddPoseNodeCommand, DeletePoseNodeCommand, SetStateRootPoseCommand,
         },
@@ -40,6 +42,8 @@ pub struct CanvasContextMenu {
     create_blend_animations: Handle<UiNode>,
     create_blend_by_index: Handle<UiNode>,
     create_blend_space: Handle<UiNode>,
+    create_additive_blend: Handle<UiNode>,
+    create_mirror_pose: Handle<UiNode>,
     pub menu: RcUiNodeHandle,
     pub canvas: Handle<UiNode>,
     pub node_context_menu: Option<RcUiNodeHandle>,
@@ -51,6 +55,8 @@ impl CanvasContextMenu {
         let create_blend_animations;
         let create_blend_by_index;
         let create_blend_space;
+        let create_additive_blend;
+        let create_mirror_pose;
         let menu = ContextMenuBuilder::new(
             PopupBuilder::new(
                 WidgetBuilder::new()
@@ -76,6 +82,14 @@ impl CanvasContextMenu {
                         .with_child({
                             create_blend_space = create_menu_item("Blend Space", vec![], ctx);
                             create_blend_space
+                        })
+                        .with_child({
+                            create_additive_blend = create_menu_item("Additive Blend", vec![], ctx);
+                            create_additive_blend
+                        })
+                        .with_child({
+                            create_mirror_pose = create_menu_item("Mirror Pose", vec![], ctx);
+                            create_mirror_pose
                         }),
                 )
                 .build(ctx),
@@ -89,6 +103,8 @@ impl CanvasContextMenu {
             create_blend_animations,
             create_blend_by_index,
             create_blend_space,
+            create_additive_blend,
+            create_mirror_pose,
             menu,
             canvas: Default::default(),
             node_context_menu: Default::default(),
@@ -125,6 +141,7 @@ impl CanvasContextMenu {
                         parent_state: current_state,
                     },
                     pose_sources: Default::default(),
+                    evaluate_inactive_inputs: false,
                     output_pose: Default::default(),
                 }))
             } else if message.destination() == self.create_blend_by_index {
@@ -164,6 +181,20 @@ impl CanvasContextMenu {
                 ]);
 
                 Some(PoseNode::BlendSpace(blend_space))
+            } else if message.destination() == self.create_additive_blend {
+                let mut additive_blend = AdditiveBlend::default();
+
+                additive_blend.position = position;
+                additive_blend.parent_state = current_state;
+
+                Some(PoseNode::AdditiveBlend(additive_blend))
+            } else if message.destination() == self.create_mirror_pose {
+                let mut mirror_pose = MirrorPose::default();
+
+                mirror_pose.position = position;
+                mirror_pose.parent_state = current_state;
+
+                Some(PoseNode::MirrorPose(mirror_pose))
             } else {
                 None
             };
@@ -361,6 +392,22 @@ impl ConnectionContextMenu {
                         index,
                         value: Default::default(),
                     }),
+                    PoseNode::AdditiveBlend(_) => {
+                        sender.do_command(SetAdditiveBlendPoseSourceCommand {
+                            node_handle: absm_node_handle,
+                            layer_index,
+                            handle: model_handle,
+                            index,
+                            value: Default::default(),
+                        })
+                    }
+                    PoseNode::MirrorPose(_) => sender.do_command(SetMirrorPoseSourceCommand {
+                        node_handle: absm_node_handle,
+                        layer_index,
+                        handle: model_handle,
+                        index,
+                        value: Default::default(),
+                    }),
                 }
             }
         } else if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
@@ -23,8 +23,9 @@ use crate::{
         canvas::{AbsmCanvasBuilder, AbsmCanvasMessage},
         command::{
             blend::{
-                SetBlendAnimationByIndexInputPoseSourceCommand,
+                SetAdditiveBlendPoseSourceCommand, SetBlendAnimationByIndexInputPoseSourceCommand,
                 SetBlendAnimationsPoseSourceCommand, SetBlendSpacePoseSourceCommand,
+                SetMirrorPoseSourceCommand,
             },
             MovePoseNodeCommand,
         },
@@ -141,6 +142,8 @@ where
         PoseNode::BlendSpace(blend_space) => {
             format!("Blend Space: {:?} animations", blend_space.points().len())
         }
+        PoseNode::AdditiveBlend(_) => "Additive Blend".to_string(),
+        PoseNode::MirrorPose(_) => "Mirror Pose".to_string(),
     }
 }
 
@@ -354,6 +357,24 @@ impl StateViewer {
                                         value: source_node,
                                     });
                                 }
+                                PoseNode::AdditiveBlend(_) => {
+                                    sender.do_command(SetAdditiveBlendPoseSourceCommand {
+                                        node_handle: absm_node_handle,
+                                        layer_index,
+                                        handle: dest_node,
+                                        index: dest_socket_ref.index,
+                                        value: source_node,
+                                    });
+                                }
+                                PoseNode::MirrorPose(_) => {
+                                    sender.do_command(SetMirrorPoseSourceCommand {
+                                        node_handle: absm_node_handle,
+                                        layer_index,
+                                        handle: dest_node,
+                                        index: dest_socket_ref.index,
+                                        value: source_node,
+                                    });
+                                }
                             }
                         }
                         _ => (),
@@ -490,6 +511,11 @@ impl StateViewer {
                                     PoseNode::BlendSpace(blend_space) => {
                                         (blend_space.points().len(), "Blend Space", true, true)
                                     }
+                                    PoseNode::AdditiveBlend(_) => {
+                                        // Base, additive and reference poses.
+                                        (3, "Additive Blend", false, false)
+                                    }
+                                    PoseNode::MirrorPose(_) => (1, "Mirror Pose", false, false),
                                 };
 
                             let node_view = AbsmNodeBuilder::new(
@@ -2,9 +2,12 @@ use crate::fyrox::asset::state::ResourceState;
 use crate::fyrox::{
     asset::{
         graph::{ResourceDependencyGraph, ResourceGraphNode},
+        io::ResourceIo,
+        manager::ResourceManager,
         untyped::UntypedResource,
     },
     core::{log::Log, pool::Handle},
+    fxhash::FxHashMap,
     gui::{
         button::{ButtonBuilder, ButtonMessage},
         copypasta::ClipboardProvider,
@@ -12,8 +15,8 @@ use crate::fyrox::{
         message::{MessageDirection, UiMessage},
         scroll_viewer::ScrollViewerBuilder,
         stack_panel::StackPanelBuilder,
-        text::TextBuilder,
-        tree::{TreeBuilder, TreeRootBuilder, TreeRootMessage},
+        text::{TextBuilder, TextMessage},
+        tree::{TreeBuilder, TreeMessage, TreeRootBuilder, TreeRootMessage},
         widget::WidgetBuilder,
         window::{WindowBuilder, WindowMessage, WindowTitle},
         BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
@@ -27,15 +30,34 @@ pub struct DependencyViewer {
     close: Handle<UiNode>,
     copy_to_clipboard: Handle<UiNode>,
     resource_graph: Option<ResourceDependencyGraph>,
+    items: FxHashMap<Handle<UiNode>, DependencyItem>,
 }
 
-fn build_tree_recursively(node: &ResourceGraphNode, ctx: &mut BuildContext) -> Handle<UiNode> {
-    let children = node
-        .children
-        .iter()
-        .map(|c| build_tree_recursively(c, ctx))
-        .collect();
+/// Location of a graph node (a sequence of child indices starting from the root) along with the
+/// text widgets that display the node and its children.
+struct DependencyItem {
+    path: Vec<usize>,
+    text: Handle<UiNode>,
+    children_texts: Vec<Handle<UiNode>>,
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
 
+fn node_text(node: &ResourceGraphNode, is_root: bool, io: Option<&dyn ResourceIo>) -> String {
     let data_type = if let ResourceState::Ok(ref data) = node.resource.0.lock().state {
         data.type_name().to_string()
     } else {
@@ -44,16 +66,66 @@ fn build_tree_recursively(node: &ResourceGraphNode, ctx: &mut BuildContext) -> H
 
     let name = node.resource.kind().to_string();
 
-    TreeBuilder::new(WidgetBuilder::new())
+    // Sizes are fetched only when an io is given, so the file system is not touched for the
+    // nodes that were never shown.
+    let Some(io) = io else {
+        return format!("{name} ({data_type})");
+    };
+
+    let mut text = match node.size(io) {
+        Some(size) => format!("{name} ({data_type}, {})", format_size(size)),
+        None => format!("{name} ({data_type})"),
+    };
+    if is_root {
+        text += &format!(" - Total: {}", format_size(node.total_size(io)));
+    }
+    text
+}
+
+fn build_tree_recursively(
+    node: &ResourceGraphNode,
+    path: &mut Vec<usize>,
+    items: &mut FxHashMap<Handle<UiNode>, DependencyItem>,
+    ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    let children = node
+        .children
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            path.push(i);
+            let child = build_tree_recursively(c, path, items, ctx);
+            path.pop();
+            child
+        })
+        .collect::<Vec<_>>();
+    let children_texts = children.iter().map(|c| items[c].text).collect();
+
+    let text =
+        TextBuilder::new(WidgetBuilder::new().with_vertical_alignment(VerticalAlignment::Center))
+            .with_text(node_text(node, path.is_empty(), None))
+            .build(ctx);
+
+    let tree = TreeBuilder::new(WidgetBuilder::new())
         .with_items(children)
-        .with_content(
-            TextBuilder::new(
-                WidgetBuilder::new().with_vertical_alignment(VerticalAlignment::Center),
-            )
-            .with_text(format!("{name} ({data_type})"))
-            .build(ctx),
-        )
-        .build(ctx)
+        .with_content(text)
+        .build(ctx);
+
+    items.insert(
+        tree,
+        DependencyItem {
+            path: path.clone(),
+            text,
+            children_texts,
+        },
+    );
+
+    tree
+}
+
+fn find_node<'a>(root: &'a ResourceGraphNode, path: &[usize]) -> Option<&'a ResourceGraphNode> {
+    path.iter()
+        .try_fold(root, |node, &index| node.children.get(index))
 }
 
 impl DependencyViewer {
@@ -122,12 +194,19 @@ impl DependencyViewer {
             copy_to_clipboard,
             close,
             resource_graph: None,
+            items: Default::default(),
         }
     }
 
     pub fn open(&mut self, resource: &UntypedResource, ui: &mut UserInterface) {
         let resource_graph = ResourceDependencyGraph::new(resource);
-        let root = build_tree_recursively(&resource_graph.root, &mut ui.build_ctx());
+        self.items.clear();
+        let root = build_tree_recursively(
+            &resource_graph.root,
+            &mut Vec::new(),
+            &mut self.items,
+            &mut ui.build_ctx(),
+        );
         ui.send_message(TreeRootMessage::items(
             self.tree_root,
             MessageDirection::ToWidget,
@@ -142,8 +221,51 @@ impl DependencyViewer {
         self.resource_graph = Some(resource_graph);
     }
 
-    pub fn handle_ui_message(&mut self, message: &UiMessage, ui: &mut UserInterface) {
-        if let Some(ButtonMessage::Click) = message.data() {
+    /// Shows the sizes of the expanded node and its children. Sizes are calculated lazily, because
+    /// the calculation requires access to every resource file of the graph.
+    fn show_sizes(
+        &self,
+        tree: Handle<UiNode>,
+        ui: &UserInterface,
+        resource_manager: &ResourceManager,
+    ) {
+        let (Some(resource_graph), Some(item)) =
+            (self.resource_graph.as_ref(), self.items.get(&tree))
+        else {
+            return;
+        };
+        let Some(node) = find_node(&resource_graph.root, &item.path) else {
+            return;
+        };
+
+        let io = resource_manager.resource_io();
+
+        ui.send_message(TextMessage::text(
+            item.text,
+            MessageDirection::ToWidget,
+            node_text(node, item.path.is_empty(), Some(&*io)),
+        ));
+
+        for (child, &child_text) in node.children.iter().zip(item.children_texts.iter()) {
+            ui.send_message(TextMessage::text(
+                child_text,
+                MessageDirection::ToWidget,
+                node_text(child, false, Some(&*io)),
+            ));
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &mut UserInterface,
+        resource_manager: &ResourceManager,
+    ) {
+        if let Some(TreeMessage::Expand { expand: true, .. }) = message.data() {
+            if message.direction() == MessageDirection::ToWidget {
+                self.show_sizes(message.destination(), ui, resource_manager);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data() {
             if message.destination() == self.close {
                 ui.send_message(WindowMessage::close(
                     self.window,
@@ -158,6 +280,7 @@ impl DependencyViewer {
             }
         } else if let Some(WindowMessage::Close) = message.data() {
             self.resource_graph = None;
+            self.items.clear();
         }
     }
 }
@@ -811,8 +811,11 @@ impl AssetBrowser {
         self.preview.handle_message(message, engine);
         self.context_menu
             .handle_ui_message(message, &sender, engine);
-        self.dependency_viewer
-            .handle_ui_message(message, engine.user_interfaces.first_mut());
+        self.dependency_viewer.handle_ui_message(
+            message,
+            engine.user_interfaces.first_mut(),
+            &engine.resource_manager,
+        );
         if let Some(resource_creator) = self.resource_creator.as_mut() {
             let asset_added = resource_creator.handle_ui_message(
                 message,
@@ -131,6 +131,7 @@ impl AssetPreviewGenerator for TexturePreview {
                 PropertyValue::Sampler {
                     value: Some(texture),
                     fallback: Default::default(),
+                    priority: Default::default(),
                 },
             ));
             let material = MaterialResource::new_ok(Default::default(), material);
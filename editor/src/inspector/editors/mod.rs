@@ -77,7 +77,7 @@ use crate::{
                 Attenuate, AudioBus, Biquad, DistanceModel, Effect, SoundBuffer,
                 SoundBufferResource, Status,
             },
-            terrain::{Chunk, Layer},
+            terrain::{Chunk, Layer, LayerBlendMode},
             tilemap::{tileset::TileSet, Tile},
             transform::Transform,
         },
@@ -139,9 +139,12 @@ where
             BasePoseNode,
         },
         state::{StateAction, StateActionWrapper},
-        transition::{AndNode, LogicNode, NotNode, OrNode, XorNode},
-        BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput, Machine,
-        PlayAnimation, PoseNode, PoseWeight, State,
+        transition::{
+            AndNode, LogicNode, NotNode, OrNode, TransitionInterruptSource, TransitionTime, XorNode,
+        },
+        AdditiveBlend, BlendAnimations, BlendAnimationsByIndex, BlendPose, BonePair,
+        IndexedBlendInput, Machine, MirrorAxis, MirrorPose, PlayAnimation, PoseNode, PoseWeight,
+        State,
     };
 
     container.insert(InspectablePropertyEditorDefinition::<BasePoseNode<Handle<T>>>::new());
@@ -174,6 +177,13 @@ where
         BlendAnimations<Handle<T>>,
     >::new());
     container.insert(InspectablePropertyEditorDefinition::<BlendSpace<Handle<T>>>::new());
+    container.insert(InspectablePropertyEditorDefinition::<
+        AdditiveBlend<Handle<T>>,
+    >::new());
+    container.insert(InspectablePropertyEditorDefinition::<BonePair<Handle<T>>>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<BonePair<Handle<T>>>::new());
+    container.insert(EnumPropertyEditorDefinition::<MirrorAxis>::new());
+    container.insert(InspectablePropertyEditorDefinition::<MirrorPose<Handle<T>>>::new());
     container.insert(InspectablePropertyEditorDefinition::<
         PlayAnimation<Handle<T>>,
     >::new());
@@ -189,10 +199,13 @@ where
     container.insert(InheritablePropertyEditorDefinition::<Machine<Handle<T>>>::new());
 
     container.insert(EnumPropertyEditorDefinition::<LogicNode<Handle<T>>>::new());
+    container.insert(EnumPropertyEditorDefinition::<LogicNode<Handle<T>>>::new_optional());
     container.insert(InspectablePropertyEditorDefinition::<AndNode<Handle<T>>>::new());
     container.insert(InspectablePropertyEditorDefinition::<OrNode<Handle<T>>>::new());
     container.insert(InspectablePropertyEditorDefinition::<XorNode<Handle<T>>>::new());
     container.insert(InspectablePropertyEditorDefinition::<NotNode<Handle<T>>>::new());
+    container.insert(EnumPropertyEditorDefinition::<TransitionTime>::new());
+    container.insert(EnumPropertyEditorDefinition::<TransitionInterruptSource>::new());
 }
 
 pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorDefinitionContainer {
@@ -231,6 +244,7 @@ pub fn make_property_editors_container(sender: MessageSender) -> PropertyEditorD
 
     container.register_inheritable_vec_collection::<Layer>();
     container.register_inheritable_inspectable::<Layer>();
+    container.insert(EnumPropertyEditorDefinition::<LayerBlendMode>::new());
 
     container.register_inheritable_vec_collection::<Emitter>();
 
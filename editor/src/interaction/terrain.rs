@@ -34,7 +34,9 @@ use crate::fyrox::{
             MeshBuilder, RenderPath,
         },
         node::Node,
-        terrain::{Brush, BrushMode, BrushShape, Terrain, TerrainRayCastResult},
+        terrain::{
+            Brush, BrushDelta, BrushFalloff, BrushMode, BrushShape, Terrain, TerrainRayCastResult,
+        },
     },
 };
 use crate::interaction::make_interaction_mode_button;
@@ -45,7 +47,9 @@ use crate::{
     make_color_material,
     message::MessageSender,
     scene::{
-        commands::terrain::{ModifyTerrainHeightCommand, ModifyTerrainLayerMaskCommand},
+        commands::terrain::{
+            ModifyTerrainHeightCommand, ModifyTerrainLayerMaskCommand, TerrainStroke,
+        },
         GameScene, Selection,
     },
     settings::Settings,
@@ -55,8 +59,9 @@ use fyrox::asset::untyped::ResourceKind;
 use std::sync::Arc;
 
 pub struct TerrainInteractionMode {
-    heightmaps: Vec<Vec<f32>>,
-    masks: Vec<Vec<u8>>,
+    height_stroke: TerrainStroke<f32>,
+    mask_stroke: TerrainStroke<u8>,
+    mask_layer: usize,
     message_sender: MessageSender,
     interacting: bool,
     brush_gizmo: BrushGizmo,
@@ -76,6 +81,7 @@ impl TerrainInteractionMode {
             center: Default::default(),
             shape: BrushShape::Circle { radius: 1.0 },
             mode: BrushMode::ModifyHeightMap { amount: 1.0 },
+            falloff: Default::default(),
         };
 
         let brush_panel =
@@ -83,15 +89,51 @@ impl TerrainInteractionMode {
 
         Self {
             brush_panel,
-            heightmaps: Default::default(),
+            height_stroke: Default::default(),
             brush_gizmo: BrushGizmo::new(game_scene, engine),
             interacting: false,
             message_sender,
             brush,
-            masks: Default::default(),
+            mask_stroke: Default::default(),
+            mask_layer: 0,
             scene_viewer_frame,
         }
     }
+
+    /// Draws on the terrain with the given brush and remembers the original data of every chunk touched
+    /// by the current stroke, so the whole stroke could be undone at once.
+    fn draw(&mut self, terrain: &mut Terrain, brush: &Brush) {
+        let delta = terrain.compute_brush(brush);
+        let chunks = terrain.chunks_ref();
+
+        // The delta contains only the chunks touched by the brush, so their original data must be saved
+        // before the delta is applied.
+        match &delta {
+            BrushDelta::HeightMaps(height_maps) => {
+                for &chunk_index in height_maps.keys() {
+                    if let Some(chunk) = chunks.get(chunk_index) {
+                        self.height_stroke
+                            .touch(chunk_index, || chunk.heightmap_owned());
+                    }
+                }
+            }
+            BrushDelta::LayerMasks { layer, masks } => {
+                self.mask_layer = *layer;
+                for &chunk_index in masks.keys() {
+                    if let Some(mask) = chunks
+                        .get(chunk_index)
+                        .and_then(|chunk| chunk.layer_masks.get(*layer))
+                    {
+                        self.mask_stroke
+                            .touch(chunk_index, || mask.data_ref().data().to_vec());
+                    }
+                }
+            }
+            BrushDelta::None => {}
+        }
+
+        terrain.apply_brush_delta(&delta);
+    }
 }
 
 pub struct BrushGizmo {
@@ -129,19 +171,6 @@ impl BrushGizmo {
     }
 }
 
-fn copy_layer_masks(terrain: &Terrain, layer: usize) -> Vec<Vec<u8>> {
-    let mut masks = Vec::new();
-
-    for chunk in terrain.chunks_ref() {
-        match chunk.layer_masks.get(layer) {
-            Some(mask) => masks.push(mask.data_ref().data().to_vec()),
-            None => Log::err("layer index out of range"),
-        }
-    }
-
-    masks
-}
-
 impl TypeUuidProvider for TerrainInteractionMode {
     fn type_uuid() -> Uuid {
         uuid!("bc19eff3-3e3a-49c0-9a9d-17d36fccc34e")
@@ -168,7 +197,7 @@ impl InteractionMode for TerrainInteractionMode {
                 let handle = selection.nodes()[0];
                 if let Some(terrain) = &graph[handle].cast::<Terrain>() {
                     // Pick height value at the point of interaction.
-                    if let BrushMode::FlattenHeightMap { height } = &mut self.brush.mode {
+                    if let BrushMode::FlattenHeightMap { height, .. } = &mut self.brush.mode {
                         let camera = &graph[game_scene.camera_controller.camera];
                         if let Some(camera) = camera.cast::<Camera>() {
                             let ray = camera.make_ray(mouse_pos, frame_size);
@@ -182,19 +211,8 @@ impl InteractionMode for TerrainInteractionMode {
                         }
                     }
 
-                    match self.brush.mode {
-                        BrushMode::ModifyHeightMap { .. } | BrushMode::FlattenHeightMap { .. } => {
-                            self.heightmaps = terrain
-                                .chunks_ref()
-                                .iter()
-                                .map(|c| c.heightmap_owned())
-                                .collect();
-                        }
-                        BrushMode::DrawOnMask { layer, .. } => {
-                            self.masks = copy_layer_masks(terrain, layer);
-                        }
-                    }
-
+                    self.height_stroke = Default::default();
+                    self.mask_stroke = Default::default();
                     self.interacting = true;
                 }
             }
@@ -221,31 +239,32 @@ impl InteractionMode for TerrainInteractionMode {
 
                 if let Some(terrain) = &graph[handle].cast::<Terrain>() {
                     if self.interacting {
-                        let new_heightmaps = terrain
-                            .chunks_ref()
-                            .iter()
-                            .map(|c| c.heightmap_owned())
-                            .collect();
-
-                        match self.brush.mode {
-                            BrushMode::ModifyHeightMap { .. }
-                            | BrushMode::FlattenHeightMap { .. } => {
-                                self.message_sender
-                                    .do_command(ModifyTerrainHeightCommand::new(
-                                        handle,
-                                        std::mem::take(&mut self.heightmaps),
-                                        new_heightmaps,
-                                    ));
-                            }
-                            BrushMode::DrawOnMask { layer, .. } => {
-                                self.message_sender
-                                    .do_command(ModifyTerrainLayerMaskCommand::new(
-                                        handle,
-                                        std::mem::take(&mut self.masks),
-                                        copy_layer_masks(terrain, layer),
-                                        layer,
-                                    ));
-                            }
+                        // The whole stroke is already applied to the terrain, so the commands will
+                        // skip their first execution.
+                        if !self.height_stroke.is_empty() {
+                            let chunks = self.height_stroke.finish(|chunk_index| {
+                                terrain
+                                    .chunks_ref()
+                                    .get(chunk_index)
+                                    .map(|chunk| chunk.heightmap_owned())
+                            });
+                            self.message_sender.do_command(
+                                ModifyTerrainHeightCommand::from_stroke(handle, chunks),
+                            );
+                        }
+
+                        if !self.mask_stroke.is_empty() {
+                            let layer = self.mask_layer;
+                            let chunks = self.mask_stroke.finish(|chunk_index| {
+                                terrain
+                                    .chunks_ref()
+                                    .get(chunk_index)
+                                    .and_then(|chunk| chunk.layer_masks.get(layer))
+                                    .map(|mask| mask.data_ref().data().to_vec())
+                            });
+                            self.message_sender.do_command(
+                                ModifyTerrainLayerMaskCommand::from_stroke(handle, chunks, layer),
+                            );
                         }
 
                         self.interacting = false;
@@ -306,7 +325,9 @@ impl InteractionMode for TerrainInteractionMode {
                                         *alpha = -1.0;
                                     }
                                 }
-                                BrushMode::FlattenHeightMap { height } => {
+                                // Smoothing has no inverse operation.
+                                BrushMode::SmoothHeightMap { .. } => (),
+                                BrushMode::FlattenHeightMap { height, .. } => {
                                     if engine
                                         .user_interfaces
                                         .first_mut()
@@ -319,7 +340,7 @@ impl InteractionMode for TerrainInteractionMode {
                             }
 
                             if self.interacting {
-                                terrain.draw(&brush_copy);
+                                self.draw(terrain, &brush_copy);
                             }
 
                             let scale = match self.brush.shape {
@@ -432,7 +453,10 @@ impl InteractionMode for TerrainInteractionMode {
             self.brush.mode = BrushMode::ModifyHeightMap { amount: 1.0 };
             processed = true;
         } else if hotkey == &key_bindings.flatten_slopes_mode {
-            self.brush.mode = BrushMode::FlattenHeightMap { height: 0.0 };
+            self.brush.mode = BrushMode::FlattenHeightMap {
+                height: 0.0,
+                strength: 1.0,
+            };
             processed = true;
         } else if hotkey == &key_bindings.increase_brush_size {
             match &mut self.brush.shape {
@@ -457,7 +481,8 @@ impl InteractionMode for TerrainInteractionMode {
                 BrushMode::ModifyHeightMap { amount } => {
                     *amount -= 0.01;
                 }
-                BrushMode::FlattenHeightMap { height } => {
+                BrushMode::SmoothHeightMap { strength } => modify_clamp(strength, -0.01, 0.0, 1.0),
+                BrushMode::FlattenHeightMap { height, .. } => {
                     *height -= 0.01;
                 }
                 BrushMode::DrawOnMask { alpha, .. } => modify_clamp(alpha, -0.01, 0.0, 1.0),
@@ -468,7 +493,8 @@ impl InteractionMode for TerrainInteractionMode {
                 BrushMode::ModifyHeightMap { amount } => {
                     *amount += 0.01;
                 }
-                BrushMode::FlattenHeightMap { height } => {
+                BrushMode::SmoothHeightMap { strength } => modify_clamp(strength, 0.01, 0.0, 1.0),
+                BrushMode::FlattenHeightMap { height, .. } => {
                     *height += 0.01;
                 }
                 BrushMode::DrawOnMask { alpha, .. } => modify_clamp(alpha, 0.01, 0.0, 1.0),
@@ -525,19 +551,25 @@ fn make_brush_mode_enum_property_editor_definition() -> EnumPropertyEditorDefini
                 layer: 0,
                 alpha: 1.0,
             },
-            2 => BrushMode::FlattenHeightMap { height: 0.0 },
+            2 => BrushMode::FlattenHeightMap {
+                height: 0.0,
+                strength: 1.0,
+            },
+            3 => BrushMode::SmoothHeightMap { strength: 0.5 },
             _ => unreachable!(),
         },
         index_generator: |v| match v {
             BrushMode::ModifyHeightMap { .. } => 0,
             BrushMode::DrawOnMask { .. } => 1,
             BrushMode::FlattenHeightMap { .. } => 2,
+            BrushMode::SmoothHeightMap { .. } => 3,
         },
         names_generator: || {
             vec![
                 "Modify Height Map".to_string(),
                 "Draw On Mask".to_string(),
                 "Flatten Height Map".to_string(),
+                "Smooth Height Map".to_string(),
             ]
         },
     }
@@ -566,6 +598,7 @@ impl BrushPanel {
         let property_editors = PropertyEditorDefinitionContainer::with_default_editors();
         property_editors.insert(make_brush_mode_enum_property_editor_definition());
         property_editors.insert(make_brush_shape_enum_property_editor_definition());
+        property_editors.insert(EnumPropertyEditorDefinition::<BrushFalloff>::new());
 
         let context = InspectorContext::from_object(
             brush,
@@ -2,6 +2,7 @@ use crate::fyrox::{
     asset::manager::ResourceManager,
     core::{
         algebra::{Matrix4, Vector2, Vector3, Vector4},
+        color::Color,
         futures::executor::block_on,
         make_relative_path,
         parking_lot::Mutex,
@@ -170,6 +171,18 @@ fn create_float_view(ctx: &mut BuildContext, value: f32) -> Handle<UiNode> {
         .build(ctx)
 }
 
+fn create_double_view(ctx: &mut BuildContext, value: f64) -> Handle<UiNode> {
+    NumericUpDownBuilder::new(WidgetBuilder::new().with_height(24.0))
+        .with_value(value)
+        .build(ctx)
+}
+
+fn create_color_view(ctx: &mut BuildContext, value: Color) -> Handle<UiNode> {
+    ColorFieldBuilder::new(WidgetBuilder::new().with_height(24.0))
+        .with_color(value)
+        .build(ctx)
+}
+
 fn create_int_view(ctx: &mut BuildContext, value: i32) -> Handle<UiNode> {
     NumericUpDownBuilder::new(WidgetBuilder::new().with_height(24.0))
         .with_value(value as f32)
@@ -462,6 +475,13 @@ impl MaterialEditor {
                         )
                         .with_opt_texture(value.clone().map(Into::into))
                         .build(ctx),
+                        PropertyValue::Double(value) => create_double_view(ctx, *value),
+                        PropertyValue::DoubleArray(value) => {
+                            create_array_view(ctx, value, create_double_view)
+                        }
+                        PropertyValue::ColorArray(value) => {
+                            create_array_view(ctx, value, create_color_view)
+                        }
                     };
 
                     self.properties.insert(name.to_owned(), item);
@@ -592,6 +612,18 @@ impl MaterialEditor {
                             value.clone().map(Into::into),
                         ),
                     ),
+                    PropertyValue::Double(value) => {
+                        send_sync_message(
+                            ui,
+                            NumericUpDownMessage::value(item, MessageDirection::ToWidget, *value),
+                        );
+                    }
+                    PropertyValue::DoubleArray(value) => {
+                        sync_array(ui, item, value, create_double_view)
+                    }
+                    PropertyValue::ColorArray(value) => {
+                        sync_array(ui, item, value, create_color_view)
+                    }
                 }
             }
 
@@ -669,6 +701,7 @@ impl MaterialEditor {
                             PropertyValue::Sampler {
                                 value: None,
                                 fallback: Default::default(),
+                                priority: Default::default(),
                             },
                         ));
                     }
@@ -695,6 +728,14 @@ impl MaterialEditor {
                     } else {
                         None
                     }
+                } else if let Some(NumericUpDownMessage::Value(value)) =
+                    message.data::<NumericUpDownMessage<f64>>()
+                {
+                    if message.direction() == MessageDirection::FromWidget {
+                        Some(PropertyValue::Double(*value))
+                    } else {
+                        None
+                    }
                 } else if let Some(Vec2EditorMessage::Value(value)) =
                     message.data::<Vec2EditorMessage<f32>>()
                 {
@@ -750,6 +791,7 @@ impl MaterialEditor {
                             Some(PropertyValue::Sampler {
                                 value: texture,
                                 fallback: Default::default(),
+                                priority: Default::default(),
                             })
                         } else {
                             None
@@ -62,6 +62,7 @@ impl CommandTrait for SetMeshTextureCommand {
                         PropertyValue::Sampler {
                             value: Some(texture.clone()),
                             fallback: SamplerFallback::White,
+                            priority: Default::default(),
                         },
                     )
                     .unwrap();
@@ -98,6 +99,7 @@ impl CommandTrait for SetMeshTextureCommand {
                         PropertyValue::Sampler {
                             value: old_texture.clone(),
                             fallback: SamplerFallback::White,
+                            priority: Default::default(),
                         },
                     )
                     .unwrap();
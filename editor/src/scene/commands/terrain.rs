@@ -4,13 +4,20 @@ use crate::fyrox::resource::texture::{
     TextureKind, TexturePixelKind, TextureResourceExtension, TextureWrapMode,
 };
 use crate::fyrox::{
-    core::pool::Handle,
+    core::{algebra::Vector2, pool::Handle},
+    material::MaterialResource,
     resource::texture::TextureResource,
-    scene::{node::Node, terrain::Layer},
+    scene::{
+        node::Node,
+        terrain::{BrushDelta, HeightMapStamp, Layer, LayerBlendMode, NoiseParams, Terrain},
+    },
 };
 use crate::{
-    command::CommandTrait, create_terrain_layer_material, scene::commands::GameSceneContext,
+    command::CommandTrait,
+    create_terrain_layer_material,
+    scene::commands::{graph::ReplaceNodeCommand, GameSceneContext},
 };
+use std::collections::BTreeMap;
 
 #[derive(Debug)]
 pub struct AddTerrainLayerCommand {
@@ -25,6 +32,7 @@ impl AddTerrainLayerCommand {
             terrain: terrain_handle,
             layer: Some(Layer {
                 material: create_terrain_layer_material(),
+                blend_mode: LayerBlendMode::Normal,
                 ..Default::default()
             }),
             masks: Default::default(),
@@ -97,26 +105,160 @@ impl CommandTrait for DeleteTerrainLayerCommand {
     }
 }
 
+#[derive(Debug)]
+pub struct MoveTerrainLayerCommand {
+    terrain: Handle<Node>,
+    from: usize,
+    to: usize,
+}
+
+impl MoveTerrainLayerCommand {
+    pub fn new(terrain: Handle<Node>, from: usize, to: usize) -> Self {
+        Self { terrain, from, to }
+    }
+
+    fn move_layer(&self, context: &mut dyn CommandContext, from: usize, to: usize) {
+        let context = context.get_mut::<GameSceneContext>();
+        let terrain = context.scene.graph[self.terrain].as_terrain_mut();
+        if !terrain.move_layer(from, to) {
+            Log::err(format!(
+                "Unable to move terrain layer {} to {}: invalid layer index.",
+                from, to
+            ));
+        }
+    }
+}
+
+impl CommandTrait for MoveTerrainLayerCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Move Terrain Layer".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        self.move_layer(context, self.from, self.to);
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        self.move_layer(context, self.to, self.from);
+    }
+}
+
+#[derive(Debug)]
+pub struct SetTerrainLayerMaterialCommand {
+    terrain: Handle<Node>,
+    index: usize,
+    material: MaterialResource,
+}
+
+impl SetTerrainLayerMaterialCommand {
+    pub fn new(terrain: Handle<Node>, index: usize, material: MaterialResource) -> Self {
+        Self {
+            terrain,
+            index,
+            material,
+        }
+    }
+
+    fn swap(&mut self, context: &mut dyn CommandContext) {
+        let context = context.get_mut::<GameSceneContext>();
+        let terrain = context.scene.graph[self.terrain].as_terrain_mut();
+        match terrain.set_layer_material(self.index, self.material.clone()) {
+            Some(old_material) => self.material = old_material,
+            None => Log::err(format!("Invalid terrain layer index {}.", self.index)),
+        }
+    }
+}
+
+impl CommandTrait for SetTerrainLayerMaterialCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Set Terrain Layer Material".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        self.swap(context);
+    }
+}
+
+/// Data of a single chunk of a terrain before and after a modification.
+#[derive(Debug)]
+pub struct ChunkData<T> {
+    pub chunk_index: usize,
+    pub old: Vec<T>,
+    pub new: Vec<T>,
+}
+
+/// Accumulates the data of the chunks touched by a brush stroke from mouse down to mouse up, so the
+/// whole stroke could be undone at once. Only the original data of each chunk is stored during the
+/// stroke, the new data is taken from the terrain when the stroke is finished.
+#[derive(Debug)]
+pub struct TerrainStroke<T> {
+    old: BTreeMap<usize, Vec<T>>,
+}
+
+impl<T> Default for TerrainStroke<T> {
+    fn default() -> Self {
+        Self {
+            old: Default::default(),
+        }
+    }
+}
+
+impl<T> TerrainStroke<T> {
+    /// Remembers the original data of a chunk, if it was not touched by the stroke before.
+    pub fn touch(&mut self, chunk_index: usize, data: impl FnOnce() -> Vec<T>) {
+        self.old.entry(chunk_index).or_insert_with(data);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.old.is_empty()
+    }
+
+    /// Finishes the stroke and pairs the original data of every touched chunk with its current data.
+    pub fn finish(
+        &mut self,
+        mut current: impl FnMut(usize) -> Option<Vec<T>>,
+    ) -> Vec<ChunkData<T>> {
+        std::mem::take(&mut self.old)
+            .into_iter()
+            .filter_map(|(chunk_index, old)| {
+                current(chunk_index).map(|new| ChunkData {
+                    chunk_index,
+                    old,
+                    new,
+                })
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug)]
 pub struct ModifyTerrainHeightCommand {
     terrain: Handle<Node>,
-    // TODO: This is very memory-inefficient solution, it could be done
-    //  better by either pack/unpack data on the fly, or by saving changes
-    //  for sub-chunks.
-    old_heightmaps: Vec<Vec<f32>>,
-    new_heightmaps: Vec<Vec<f32>>,
+    chunks: Vec<ChunkData<f32>>,
+    // The terrain already contains the new data when the command is created from a live edit.
+    skip_first_execute: bool,
 }
 
 impl ModifyTerrainHeightCommand {
-    pub fn new(
-        terrain: Handle<Node>,
-        old_heightmaps: Vec<Vec<f32>>,
-        new_heightmaps: Vec<Vec<f32>>,
-    ) -> Self {
+    pub fn new(terrain: Handle<Node>, chunks: Vec<ChunkData<f32>>) -> Self {
         Self {
             terrain,
-            old_heightmaps,
-            new_heightmaps,
+            chunks,
+            skip_first_execute: false,
+        }
+    }
+
+    /// Creates a command from the chunks touched by a brush stroke, that was already applied to the
+    /// terrain. The first execution of the command does nothing.
+    pub fn from_stroke(terrain: Handle<Node>, chunks: Vec<ChunkData<f32>>) -> Self {
+        Self {
+            terrain,
+            chunks,
+            skip_first_execute: true,
         }
     }
 
@@ -124,29 +266,30 @@ impl ModifyTerrainHeightCommand {
         let context = context.get_mut::<GameSceneContext>();
         let terrain = context.scene.graph[self.terrain].as_terrain_mut();
         let heigth_map_size = terrain.height_map_size();
-        for (chunk, (old, new)) in terrain.chunks_mut().iter_mut().zip(
-            self.old_heightmaps
-                .iter_mut()
-                .zip(self.new_heightmaps.iter_mut()),
-        ) {
+        for data in self.chunks.iter_mut() {
+            let Some(chunk) = terrain.chunks_mut().get_mut(data.chunk_index) else {
+                Log::err("Invalid chunk index.");
+                continue;
+            };
+
             let height_map = TextureResource::from_bytes(
                 TextureKind::Rectangle {
                     width: heigth_map_size.x,
                     height: heigth_map_size.y,
                 },
                 TexturePixelKind::R32F,
-                fyrox::core::transmute_vec_as_bytes(new.clone()),
+                fyrox::core::transmute_vec_as_bytes(data.new.clone()),
                 Default::default(),
             )
             .unwrap();
 
-            let mut data = height_map.data_ref();
-            data.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
-            data.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
-            drop(data);
+            let mut texture_data = height_map.data_ref();
+            texture_data.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+            texture_data.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+            drop(texture_data);
 
             chunk.replace_height_map(height_map).unwrap();
-            std::mem::swap(old, new);
+            std::mem::swap(&mut data.old, &mut data.new);
         }
     }
 }
@@ -157,7 +300,15 @@ impl CommandTrait for ModifyTerrainHeightCommand {
     }
 
     fn execute(&mut self, context: &mut dyn CommandContext) {
-        self.swap(context);
+        if self.skip_first_execute {
+            // Keep the data in the same order as it would be after a swap.
+            for data in self.chunks.iter_mut() {
+                std::mem::swap(&mut data.old, &mut data.new);
+            }
+            self.skip_first_execute = false;
+        } else {
+            self.swap(context);
+        }
     }
 
     fn revert(&mut self, context: &mut dyn CommandContext) {
@@ -165,29 +316,155 @@ impl CommandTrait for ModifyTerrainHeightCommand {
     }
 }
 
+#[derive(Debug)]
+pub struct StampTerrainHeightCommand {
+    terrain: Handle<Node>,
+    stamp: HeightMapStamp,
+    // Height maps are computed when the command is executed for the first time.
+    modify: Option<ModifyTerrainHeightCommand>,
+}
+
+impl StampTerrainHeightCommand {
+    pub fn new(terrain: Handle<Node>, stamp: HeightMapStamp) -> Self {
+        Self {
+            terrain,
+            stamp,
+            modify: None,
+        }
+    }
+}
+
+impl CommandTrait for StampTerrainHeightCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Stamp Terrain Height".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        if self.modify.is_none() {
+            let game_scene = context.get_mut::<GameSceneContext>();
+            let terrain = game_scene.scene.graph[self.terrain].as_terrain_mut();
+            self.modify = make_modify_height_command(
+                self.terrain,
+                terrain,
+                terrain.compute_stamp(&self.stamp),
+            );
+            if self.modify.is_none() {
+                Log::err("Unable to stamp terrain: the stamp texture is not ready.");
+            }
+        }
+
+        if let Some(modify) = self.modify.as_mut() {
+            modify.swap(context);
+        }
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        if let Some(modify) = self.modify.as_mut() {
+            modify.swap(context);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ApplyTerrainNoiseCommand {
+    terrain: Handle<Node>,
+    params: NoiseParams,
+    // Height maps are computed when the command is executed for the first time.
+    modify: Option<ModifyTerrainHeightCommand>,
+}
+
+impl ApplyTerrainNoiseCommand {
+    pub fn new(terrain: Handle<Node>, params: NoiseParams) -> Self {
+        Self {
+            terrain,
+            params,
+            modify: None,
+        }
+    }
+}
+
+impl CommandTrait for ApplyTerrainNoiseCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Apply Terrain Noise".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        if self.modify.is_none() {
+            let game_scene = context.get_mut::<GameSceneContext>();
+            let terrain = game_scene.scene.graph[self.terrain].as_terrain_mut();
+            self.modify = make_modify_height_command(
+                self.terrain,
+                terrain,
+                terrain.compute_noise(&self.params),
+            );
+        }
+
+        if let Some(modify) = self.modify.as_mut() {
+            modify.swap(context);
+        }
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        if let Some(modify) = self.modify.as_mut() {
+            modify.swap(context);
+        }
+    }
+}
+
+/// Creates a command, that replaces current height maps of the terrain with the height maps of the given delta.
+fn make_modify_height_command(
+    terrain_handle: Handle<Node>,
+    terrain: &Terrain,
+    delta: BrushDelta,
+) -> Option<ModifyTerrainHeightCommand> {
+    match delta {
+        BrushDelta::HeightMaps(new_heightmaps) => {
+            let chunks = new_heightmaps
+                .into_iter()
+                .filter_map(|(chunk_index, new)| {
+                    terrain
+                        .chunks_ref()
+                        .get(chunk_index)
+                        .map(|chunk| ChunkData {
+                            chunk_index,
+                            old: chunk.heightmap_owned(),
+                            new,
+                        })
+                })
+                .collect();
+            Some(ModifyTerrainHeightCommand::new(terrain_handle, chunks))
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug)]
 pub struct ModifyTerrainLayerMaskCommand {
     terrain: Handle<Node>,
-    // TODO: This is very memory-inefficient solution, it could be done
-    //  better by either pack/unpack data on the fly, or by saving changes
-    //  for sub-chunks.
-    old_masks: Vec<Vec<u8>>,
-    new_masks: Vec<Vec<u8>>,
+    chunks: Vec<ChunkData<u8>>,
     layer: usize,
+    // The terrain already contains the new data when the command is created from a live edit.
+    skip_first_execute: bool,
 }
 
 impl ModifyTerrainLayerMaskCommand {
-    pub fn new(
-        terrain: Handle<Node>,
-        old_masks: Vec<Vec<u8>>,
-        new_masks: Vec<Vec<u8>>,
-        layer: usize,
-    ) -> Self {
+    pub fn new(terrain: Handle<Node>, chunks: Vec<ChunkData<u8>>, layer: usize) -> Self {
         Self {
             terrain,
-            old_masks,
-            new_masks,
+            chunks,
             layer,
+            skip_first_execute: false,
+        }
+    }
+
+    /// Creates a command from the chunks touched by a brush stroke, that was already applied to the
+    /// terrain. The first execution of the command does nothing.
+    pub fn from_stroke(terrain: Handle<Node>, chunks: Vec<ChunkData<u8>>, layer: usize) -> Self {
+        Self {
+            terrain,
+            chunks,
+            layer,
+            skip_first_execute: true,
         }
     }
 
@@ -195,24 +472,30 @@ impl ModifyTerrainLayerMaskCommand {
         let context = context.get_mut::<GameSceneContext>();
         let terrain = context.scene.graph[self.terrain].as_terrain_mut();
 
-        for (i, chunk) in terrain.chunks_mut().iter_mut().enumerate() {
-            if i >= self.old_masks.len() || i >= self.new_masks.len() {
-                Log::err("Invalid mask index.")
-            } else {
-                let old = &mut self.old_masks[i];
-                let new = &mut self.new_masks[i];
-                let chunk_mask = &mut chunk.layer_masks[self.layer];
-
-                let mut texture_data = chunk_mask.data_ref();
+        for data in self.chunks.iter_mut() {
+            let Some(chunk_mask) = terrain
+                .chunks_mut()
+                .get_mut(data.chunk_index)
+                .and_then(|chunk| chunk.layer_masks.get(self.layer))
+            else {
+                Log::err("Invalid mask index.");
+                continue;
+            };
 
-                for (mask_pixel, new_pixel) in
-                    texture_data.modify().data_mut().iter_mut().zip(new.iter())
-                {
-                    *mask_pixel = *new_pixel;
-                }
+            let mut texture_data = chunk_mask.data_ref();
 
-                std::mem::swap(old, new);
+            for (mask_pixel, new_pixel) in texture_data
+                .modify()
+                .data_mut()
+                .iter_mut()
+                .zip(data.new.iter())
+            {
+                *mask_pixel = *new_pixel;
             }
+
+            drop(texture_data);
+
+            std::mem::swap(&mut data.old, &mut data.new);
         }
     }
 }
@@ -223,10 +506,72 @@ impl CommandTrait for ModifyTerrainLayerMaskCommand {
     }
 
     fn execute(&mut self, context: &mut dyn CommandContext) {
-        self.swap(context);
+        if self.skip_first_execute {
+            // Keep the data in the same order as it would be after a swap.
+            for data in self.chunks.iter_mut() {
+                std::mem::swap(&mut data.old, &mut data.new);
+            }
+            self.skip_first_execute = false;
+        } else {
+            self.swap(context);
+        }
     }
 
     fn revert(&mut self, context: &mut dyn CommandContext) {
         self.swap(context);
     }
 }
+
+#[derive(Debug)]
+pub struct ResampleTerrainCommand {
+    terrain: Handle<Node>,
+    height_map_size: Vector2<u32>,
+    mask_size: Vector2<u32>,
+    // Resampling is lossy, so the whole terrain is replaced with its resampled copy and the original
+    // terrain is kept for undo.
+    replace: Option<ReplaceNodeCommand>,
+}
+
+impl ResampleTerrainCommand {
+    pub fn new(
+        terrain: Handle<Node>,
+        height_map_size: Vector2<u32>,
+        mask_size: Vector2<u32>,
+    ) -> Self {
+        Self {
+            terrain,
+            height_map_size,
+            mask_size,
+            replace: None,
+        }
+    }
+}
+
+impl CommandTrait for ResampleTerrainCommand {
+    fn name(&mut self, _context: &dyn CommandContext) -> String {
+        "Resample Terrain".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut dyn CommandContext) {
+        if self.replace.is_none() {
+            let game_scene = context.get_mut::<GameSceneContext>();
+            let mut node = game_scene.scene.graph[self.terrain].clone();
+            node.as_terrain_mut()
+                .resample(self.height_map_size, self.mask_size);
+            self.replace = Some(ReplaceNodeCommand {
+                handle: self.terrain,
+                node,
+            });
+        }
+
+        if let Some(replace) = self.replace.as_mut() {
+            replace.execute(context);
+        }
+    }
+
+    fn revert(&mut self, context: &mut dyn CommandContext) {
+        if let Some(replace) = self.replace.as_mut() {
+            replace.revert(context);
+        }
+    }
+}
@@ -55,6 +55,7 @@ impl SceneLoader {
                 PropertyValue::Sampler {
                     value: Some(resource_manager.request::<Texture>("examples/Crate.png")),
                     fallback: Default::default(),
+                    priority: Default::default(),
                 },
             )
             .unwrap();
@@ -28,6 +28,11 @@ pub enum Event<T: EntityId> {
 
     /// Occurs when active transition was changed.
     ActiveTransitionChanged(Handle<Transition<T>>),
+
+    /// Occurs when all animations of an active state with play-once looping override (see [`State::looping`])
+    /// have ended. It could be used to trigger a transition to some other state. The event is emitted only once
+    /// per activation of the state.
+    StatePlaybackEnded(Handle<State<T>>),
 }
 
 /// A simple event queue with fixed capacity. It is used to store a fixed amount of events and discard any
@@ -67,4 +72,9 @@ impl<T: EntityId> FixedEventQueue<T> {
     pub fn pop(&mut self) -> Option<Event<T>> {
         self.queue.pop_front()
     }
+
+    /// Removes all events from the queue and returns them in the order they were pushed.
+    pub fn drain(&mut self) -> impl Iterator<Item = Event<T>> + '_ {
+        self.queue.drain(..)
+    }
 }
@@ -0,0 +1,415 @@
+//! Parameter expressions are used to compute parameters from other parameters. See [`ParameterExpression`]
+//! docs for more info.
+
+use crate::core::{reflect::prelude::*, visitor::prelude::*};
+use std::fmt::{Display, Formatter};
+
+/// An error that may occur during parsing or evaluation of a parameter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionError {
+    /// Expression has invalid syntax.
+    Syntax(String),
+    /// Expression refers to a parameter that does not exist.
+    UnknownParameter(String),
+    /// Expression calls a function that does not exist or calls it with wrong amount of arguments.
+    InvalidCall(String),
+    /// Computed parameters depend on each other in a cycle. Contains names of the parameters that
+    /// form the cycle.
+    Cycle(Vec<String>),
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpressionError::Syntax(reason) => write!(f, "Syntax error: {reason}"),
+            ExpressionError::UnknownParameter(name) => write!(f, "Unknown parameter {name}"),
+            ExpressionError::InvalidCall(reason) => write!(f, "Invalid function call: {reason}"),
+            ExpressionError::Cycle(names) => {
+                write!(
+                    f,
+                    "Computed parameters form a cycle: {}",
+                    names.join(" -> ")
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Number(f32),
+    Parameter(String),
+    Negate(Box<Expression>),
+    Binary {
+        operator: BinaryOperator,
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+    },
+    Call {
+        function: String,
+        args: Vec<Expression>,
+    },
+}
+
+impl Expression {
+    fn collect_parameters<'a>(&'a self, parameters: &mut Vec<&'a str>) {
+        match self {
+            Expression::Number(_) => {}
+            Expression::Parameter(name) => parameters.push(name),
+            Expression::Negate(expression) => expression.collect_parameters(parameters),
+            Expression::Binary { lhs, rhs, .. } => {
+                lhs.collect_parameters(parameters);
+                rhs.collect_parameters(parameters);
+            }
+            Expression::Call { args, .. } => {
+                for arg in args {
+                    arg.collect_parameters(parameters);
+                }
+            }
+        }
+    }
+
+    fn evaluate<F>(&self, fetch: &mut F) -> Result<f32, ExpressionError>
+    where
+        F: FnMut(&str) -> Result<f32, ExpressionError>,
+    {
+        match self {
+            Expression::Number(value) => Ok(*value),
+            Expression::Parameter(name) => fetch(name),
+            Expression::Negate(expression) => Ok(-expression.evaluate(fetch)?),
+            Expression::Binary { operator, lhs, rhs } => {
+                let lhs = lhs.evaluate(fetch)?;
+                let rhs = rhs.evaluate(fetch)?;
+                Ok(match operator {
+                    BinaryOperator::Add => lhs + rhs,
+                    BinaryOperator::Sub => lhs - rhs,
+                    BinaryOperator::Mul => lhs * rhs,
+                    BinaryOperator::Div => lhs / rhs,
+                })
+            }
+            Expression::Call { function, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.evaluate(fetch))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match (function.as_str(), args.as_slice()) {
+                    ("abs", [x]) => Ok(x.abs()),
+                    ("min", [a, b]) => Ok(a.min(*b)),
+                    ("max", [a, b]) => Ok(a.max(*b)),
+                    ("clamp", [x, min, max]) => Ok(x.max(*min).min(*max)),
+                    _ => Err(ExpressionError::InvalidCall(format!(
+                        "{function} with {} argument(s)",
+                        args.len()
+                    ))),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Identifier(String),
+    Operator(char),
+    OpenParen,
+    CloseParen,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ExpressionError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_digit() || c == '.' {
+                    number.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let value = number
+                .parse()
+                .map_err(|_| ExpressionError::Syntax(format!("invalid number {number}")))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut identifier = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    identifier.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Identifier(identifier));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' => Token::Operator(c),
+                '(' => Token::OpenParen,
+                ')' => Token::CloseParen,
+                ',' => Token::Comma,
+                _ => return Err(ExpressionError::Syntax(format!("unexpected character {c}"))),
+            });
+            chars.next();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), ExpressionError> {
+        match self.next() {
+            Some(next) if next == token => Ok(()),
+            next => Err(ExpressionError::Syntax(format!(
+                "expected {token:?}, got {next:?}"
+            ))),
+        }
+    }
+
+    // expression = term (('+' | '-') term)*
+    fn parse_expression(&mut self) -> Result<Expression, ExpressionError> {
+        let mut lhs = self.parse_term()?;
+        while let Some(Token::Operator(c @ ('+' | '-'))) = self.peek() {
+            let operator = if *c == '+' {
+                BinaryOperator::Add
+            } else {
+                BinaryOperator::Sub
+            };
+            self.next();
+            let rhs = self.parse_term()?;
+            lhs = Expression::Binary {
+                operator,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    // term = unary (('*' | '/') unary)*
+    fn parse_term(&mut self) -> Result<Expression, ExpressionError> {
+        let mut lhs = self.parse_unary()?;
+        while let Some(Token::Operator(c @ ('*' | '/'))) = self.peek() {
+            let operator = if *c == '*' {
+                BinaryOperator::Mul
+            } else {
+                BinaryOperator::Div
+            };
+            self.next();
+            let rhs = self.parse_unary()?;
+            lhs = Expression::Binary {
+                operator,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    // unary = '-' unary | primary
+    fn parse_unary(&mut self) -> Result<Expression, ExpressionError> {
+        if let Some(Token::Operator('-')) = self.peek() {
+            self.next();
+            return Ok(Expression::Negate(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    // primary = number | identifier | identifier '(' args ')' | '(' expression ')'
+    fn parse_primary(&mut self) -> Result<Expression, ExpressionError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::Identifier(name)) => {
+                if let Some(Token::OpenParen) = self.peek() {
+                    self.next();
+                    let mut args = Vec::new();
+                    if let Some(Token::CloseParen) = self.peek() {
+                        self.next();
+                    } else {
+                        loop {
+                            args.push(self.parse_expression()?);
+                            match self.next() {
+                                Some(Token::Comma) => continue,
+                                Some(Token::CloseParen) => break,
+                                token => {
+                                    return Err(ExpressionError::Syntax(format!(
+                                        "expected , or ), got {token:?}"
+                                    )))
+                                }
+                            }
+                        }
+                    }
+                    Ok(Expression::Call {
+                        function: name,
+                        args,
+                    })
+                } else {
+                    Ok(Expression::Parameter(name))
+                }
+            }
+            Some(Token::OpenParen) => {
+                let expression = self.parse_expression()?;
+                self.expect(Token::CloseParen)?;
+                Ok(expression)
+            }
+            token => Err(ExpressionError::Syntax(format!(
+                "unexpected token {token:?}"
+            ))),
+        }
+    }
+}
+
+fn parse(source: &str) -> Result<Expression, ExpressionError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+    };
+    let expression = parser.parse_expression()?;
+    if let Some(token) = parser.peek() {
+        return Err(ExpressionError::Syntax(format!(
+            "unexpected token {token:?}"
+        )));
+    }
+    Ok(expression)
+}
+
+/// A small arithmetic expression over other parameters, that is used to calculate a value of a computed
+/// parameter (see [`super::ParameterContainer::add_computed`]).
+///
+/// # Syntax
+///
+/// Expressions support numbers (`1`, `0.5`), parameter names (`speed`), binary operators `+`, `-`, `*`, `/`
+/// with usual precedence, unary minus, parentheses and a few functions: `abs(x)`, `min(a, b)`, `max(a, b)`,
+/// `clamp(x, min, max)`. For example: `clamp(speed / max_speed, 0, 1)`.
+///
+/// Parameters are converted to numbers as follows: `Weight` - as is, `Index` - as a real number, `Rule` -
+/// `1.0` if `true` and `0.0` otherwise. `SamplingPoint` parameters cannot be used in expressions.
+#[derive(Default, Debug, Clone, Reflect)]
+pub struct ParameterExpression {
+    source: String,
+
+    #[reflect(hidden)]
+    parsed: Option<Expression>,
+}
+
+impl PartialEq for ParameterExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Visit for ParameterExpression {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        self.source.visit(name, visitor)?;
+
+        if visitor.is_reading() {
+            self.parsed = parse(&self.source).ok();
+        }
+
+        Ok(())
+    }
+}
+
+impl ParameterExpression {
+    /// Parses the expression from the given source. See [`ParameterExpression`] docs for syntax.
+    pub fn parse(source: &str) -> Result<Self, ExpressionError> {
+        Ok(Self {
+            source: source.to_string(),
+            parsed: Some(parse(source)?),
+        })
+    }
+
+    /// Returns the source of the expression.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns names of the parameters used by the expression.
+    pub fn dependencies(&self) -> Vec<&str> {
+        let mut parameters = Vec::new();
+        if let Some(parsed) = self.parsed.as_ref() {
+            parsed.collect_parameters(&mut parameters);
+        }
+        parameters
+    }
+
+    /// Evaluates the expression. The given closure is used to fetch values of the parameters used by the
+    /// expression.
+    pub fn evaluate<F>(&self, mut fetch: F) -> Result<f32, ExpressionError>
+    where
+        F: FnMut(&str) -> Result<f32, ExpressionError>,
+    {
+        match self.parsed.as_ref() {
+            Some(parsed) => parsed.evaluate(&mut fetch),
+            // Report the actual syntax error.
+            None => parse(&self.source).and_then(|parsed| parsed.evaluate(&mut fetch)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::machine::expression::{ExpressionError, ParameterExpression};
+
+    fn eval(source: &str) -> Result<f32, ExpressionError> {
+        ParameterExpression::parse(source)?.evaluate(|name| match name {
+            "speed" => Ok(2.0),
+            "max_speed" => Ok(4.0),
+            _ => Err(ExpressionError::UnknownParameter(name.to_string())),
+        })
+    }
+
+    #[test]
+    fn test_expression_evaluation() {
+        assert_eq!(eval("speed / max_speed"), Ok(0.5));
+        assert_eq!(eval("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(eval("-speed + 1"), Ok(-1.0));
+        assert_eq!(eval("clamp(speed * 10, 0, 1)"), Ok(1.0));
+        assert_eq!(eval("max(speed, max_speed)"), Ok(4.0));
+        assert_eq!(
+            eval("foo * 2"),
+            Err(ExpressionError::UnknownParameter("foo".to_string()))
+        );
+        assert!(matches!(eval("1 +"), Err(ExpressionError::Syntax(_))));
+        assert!(matches!(
+            eval("min(1)"),
+            Err(ExpressionError::InvalidCall(_))
+        ));
+    }
+
+    #[test]
+    fn test_expression_dependencies() {
+        let expression = ParameterExpression::parse("min(a, b * 2) - c").unwrap();
+        assert_eq!(expression.dependencies(), vec!["a", "b", "c"]);
+    }
+}
@@ -5,22 +5,182 @@ use crate::{
     core::{
         log::{Log, MessageKind},
         pool::{Handle, Pool},
+        rand::{self, rngs::StdRng, Rng, SeedableRng},
         reflect::prelude::*,
         visitor::prelude::*,
     },
     machine::{
         event::FixedEventQueue, node::AnimationEventCollectionStrategy, AnimationPoseSource, Event,
-        LayerMask, ParameterContainer, PoseNode, State, Transition,
+        LayerMask, Parameter, ParameterContainer, PoseNode, State, Transition,
+        TransitionInterruptSource,
     },
-    Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId,
+    Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId, RootMotionSettings,
 };
+use fxhash::FxHashMap;
 use fyrox_core::{find_by_name_mut, find_by_name_ref, NameProvider};
+use std::fmt::Write;
+
+/// A set of entities that depend on a pose node, see [`MachineLayer::node_dependents`] for more info.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDependents<T: EntityId> {
+    /// Pose nodes that use the node as an input, directly or indirectly.
+    pub nodes: Vec<Handle<PoseNode<T>>>,
+    /// States whose pose node tree contains the node.
+    pub states: Vec<Handle<State<T>>>,
+}
+
+/// Interrupt is a pair of transitions, that allows to cut from any state of a layer to a handler state and then
+/// return back to the interrupted state when the handler state is done. See [`MachineLayer::add_interrupt`] for
+/// more info.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Visit, Reflect)]
+pub struct Interrupt<T: EntityId> {
+    /// A transition from any state to the handler state. Its source state is set to the interrupted state only
+    /// while the transition is active, otherwise it is [`Handle::NONE`] (which means "any state").
+    pub enter: Handle<Transition<T>>,
+    /// A transition from the handler state back to the interrupted state. Its destination state is set to the
+    /// interrupted state only while the transition is active, otherwise it is [`Handle::NONE`].
+    pub exit: Handle<Transition<T>>,
+}
+
+/// Any-state transition is a transition, that could be activated from any state of a layer. See
+/// [`MachineLayer::add_any_state_transition`] for more info.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Visit, Reflect)]
+pub struct AnyStateTransition<T: EntityId> {
+    /// A transition to the destination state. Its source state is set to the state, that was active when the
+    /// transition was activated, only while the transition is active, otherwise it is [`Handle::NONE`] (which
+    /// means "any state").
+    pub transition: Handle<Transition<T>>,
+    /// If `true`, the transition cannot be activated when its destination state is the active state.
+    pub exclude_active: bool,
+}
+
+/// A problem found in a layer by [`MachineLayer::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MachineValidationIssue<T: EntityId> {
+    /// The layer has states, but its entry state is not set (or it does not belong to the layer).
+    NoEntryState,
+    /// The state cannot be reached from the entry state by any sequence of transitions.
+    UnreachableState(Handle<State<T>>),
+    /// The state has no transitions leaving it, so the layer will stay in the state forever once it is entered.
+    DeadEndState(Handle<State<T>>),
+    /// The transition refers to a destination (or source) state, that does not belong to the layer.
+    InvalidTransition(Handle<Transition<T>>),
+    /// The condition of the transition uses a parameter, that does not exist or is not a `Rule` parameter. Such
+    /// parameter is always treated as `false`.
+    UnknownRuleParameter {
+        /// A handle of the transition.
+        transition: Handle<Transition<T>>,
+        /// Name of the parameter.
+        name: String,
+    },
+}
+
+impl<T: EntityId> Default for NodeDependents<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Default::default(),
+            states: Default::default(),
+        }
+    }
+}
+
+/// A detached copy of a part of a layer, that consists of a set of states, their pose nodes and transitions
+/// between them. It could be inserted into the same or some other layer. See [`MachineLayer::extract_subgraph`]
+/// for more info.
+///
+/// Every entity is stored together with its handle in the source layer, the entities still refer each other
+/// using these handles. The handles are remapped when the sub-graph is inserted into a layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MachineSubgraph<T: EntityId> {
+    /// Copied states with their handles in the source layer.
+    pub states: Vec<(Handle<State<T>>, State<T>)>,
+    /// Copied pose nodes with their handles in the source layer.
+    pub nodes: Vec<(Handle<PoseNode<T>>, PoseNode<T>)>,
+    /// Copied transitions with their handles in the source layer.
+    pub transitions: Vec<(Handle<Transition<T>>, Transition<T>)>,
+}
+
+impl<T: EntityId> Default for MachineSubgraph<T> {
+    fn default() -> Self {
+        Self {
+            states: Default::default(),
+            nodes: Default::default(),
+            transitions: Default::default(),
+        }
+    }
+}
+
+impl<T: EntityId> MachineSubgraph<T> {
+    /// Returns `true` if the sub-graph contains a state with the given handle (in the source layer).
+    pub fn contains_state(&self, state: Handle<State<T>>) -> bool {
+        self.states.iter().any(|(handle, _)| *handle == state)
+    }
+
+    /// Returns `true` if the sub-graph contains a pose node with the given handle (in the source layer).
+    pub fn contains_node(&self, node: Handle<PoseNode<T>>) -> bool {
+        self.nodes.iter().any(|(handle, _)| *handle == node)
+    }
+
+    /// Returns `true` if the sub-graph has no states.
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+/// A mapping between handles of entities of a [`MachineSubgraph`] in its source layer and handles of their
+/// copies in a layer, where the sub-graph was inserted. See [`MachineLayer::insert_subgraph`] for more info.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandleRemap<T: EntityId> {
+    /// Source state handle -> new state handle.
+    pub states: FxHashMap<Handle<State<T>>, Handle<State<T>>>,
+    /// Source pose node handle -> new pose node handle.
+    pub nodes: FxHashMap<Handle<PoseNode<T>>, Handle<PoseNode<T>>>,
+    /// Source transition handle -> new transition handle.
+    pub transitions: FxHashMap<Handle<Transition<T>>, Handle<Transition<T>>>,
+}
+
+impl<T: EntityId> Default for HandleRemap<T> {
+    fn default() -> Self {
+        Self {
+            states: Default::default(),
+            nodes: Default::default(),
+            transitions: Default::default(),
+        }
+    }
+}
+
+impl<T: EntityId> HandleRemap<T> {
+    /// Returns a new handle of a state with the given source handle, or [`Handle::NONE`] if the state was not
+    /// a part of the sub-graph.
+    pub fn state(&self, state: Handle<State<T>>) -> Handle<State<T>> {
+        self.states.get(&state).cloned().unwrap_or_default()
+    }
+
+    /// Returns a new handle of a pose node with the given source handle, or [`Handle::NONE`] if the node was not
+    /// a part of the sub-graph.
+    pub fn node(&self, node: Handle<PoseNode<T>>) -> Handle<PoseNode<T>> {
+        self.nodes.get(&node).cloned().unwrap_or_default()
+    }
+
+    /// Returns a new handle of a transition with the given source handle, or [`Handle::NONE`] if the transition
+    /// was not a part of the sub-graph.
+    pub fn transition(&self, transition: Handle<Transition<T>>) -> Handle<Transition<T>> {
+        self.transitions
+            .get(&transition)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
 
 /// Layer is a separate state graph. Layers mainly used to animate different parts of humanoid (but not only) characters. For
 /// example there could a layer for upper body and a layer for lower body. Upper body layer could contain animations for aiming,
 /// melee attacks while lower body layer could contain animations for standing, running, crouching, etc. This gives you an
 /// ability to have running character that could aim or melee attack, or crouching and aiming, and so on with any combination.
 /// Both layers use the same set of parameters, so a change in a parameter will affect all layers that use it.
+/// A layer could also have its own local parameters (see [`Self::set_local_parameter`]), that shadow the machine
+/// parameters with the same name. When the layer is evaluated, a parameter is searched in the local parameters
+/// of the layer first and only then in the parameters of the machine. Local parameters are serialized together
+/// with the layer.
 ///
 /// # Example
 ///
@@ -61,43 +221,209 @@ use fyrox_core::{find_by_name_mut, find_by_name_ref, NameProvider};
 /// root_layer.add_transition(Transition::new("Idle->Walk", idle_state, walk_state, 1.0, "IdleToWalk"));
 ///
 /// ```
-#[derive(Default, Debug, Visit, Reflect, Clone, PartialEq)]
+///
+/// # Reflection
+///
+/// `name`, `weight`, `time_scale`, `mask` and `root_motion_settings` fields are editable via reflection. The graph structure
+/// (`nodes`, `transitions`, `states`) as well as `entry_state`, `active_state` and `active_transition` handles are reflected as
+/// read-only fields, so generic reflection-based tools (such as the inspector) could display the structure of
+/// the layer without a bespoke editor. The graph must be modified using the respective methods of the layer
+/// (such as [`Self::add_state`], [`Self::add_transition`], etc.), because its parts refer each other by handles.
+///
+/// # Synchronization groups
+///
+/// States could be put in synchronization groups (see [`State::set_sync_group`]). When a transition blends two states
+/// of the same group, the animations of the states are played with the same normalized time (phase). The state with
+/// the largest blend weight is the leader of the group, the normalized time of its first animation is taken as the phase
+/// of the group. Time positions of the animations of the other states of the group are never moved, instead their time
+/// delta is scaled (by overriding their speed) so they reach the phase of the leader on the next update and then advance
+/// at the same normalized rate. The original speed of an animation is restored when its state leaves the group blend.
+/// This keeps animations of different length aligned (for example, feet of walk and run animations) and removes foot
+/// sliding when blending locomotion. Synchronization works only within a single layer, states in different layers are
+/// never synchronized.
+///
+/// # Serialization
+///
+/// Layers are serialized with an explicit version, so the data saved by older versions of the engine could be upgraded
+/// on load. See [`super::Machine`] docs for more info.
+#[derive(Debug, Reflect, Clone, PartialEq)]
 pub struct MachineLayer<T: EntityId> {
     name: String,
 
     weight: f32,
 
+    #[reflect(min_value = 0.0)]
+    time_scale: f32,
+
     mask: LayerMask<T>,
 
-    #[reflect(hidden)]
+    #[reflect(read_only, immutable_collection)]
     nodes: Pool<PoseNode<T>>,
 
-    #[reflect(hidden)]
+    #[reflect(read_only, immutable_collection)]
     transitions: Pool<Transition<T>>,
 
-    #[reflect(hidden)]
+    #[reflect(read_only, immutable_collection)]
     states: Pool<State<T>>,
 
-    #[reflect(hidden)]
+    #[reflect(read_only)]
     active_state: Handle<State<T>>,
 
-    #[reflect(hidden)]
+    #[reflect(read_only)]
     entry_state: Handle<State<T>>,
 
-    #[reflect(hidden)]
+    #[reflect(read_only)]
     active_transition: Handle<Transition<T>>,
 
-    #[visit(skip)]
+    local_parameters: ParameterContainer,
+
+    #[reflect(read_only)]
+    interrupts: Vec<Interrupt<T>>,
+
+    #[reflect(read_only)]
+    any_state_transitions: Vec<AnyStateTransition<T>>,
+
+    root_motion_settings: Option<RootMotionSettings<T>>,
+
+    // A state that was interrupted by an interrupt and to which the layer will return when the
+    // interrupt handler state is done.
+    #[reflect(read_only)]
+    interrupted_state: Handle<State<T>>,
+
+    #[reflect(hidden)]
+    merged_parameters: ParameterContainer,
+
     #[reflect(hidden)]
     final_pose: AnimationPose<T>,
 
-    #[visit(skip)]
     #[reflect(hidden)]
     events: FixedEventQueue<T>,
 
-    #[visit(skip)]
     #[reflect(hidden)]
     debug: bool,
+
+    // Frozen layer does not update its states and transitions, and keeps its last pose.
+    #[reflect(hidden)]
+    frozen: bool,
+
+    // Original looping flags of the animations, that were overridden by active states.
+    #[reflect(hidden)]
+    looping_backup: FxHashMap<Handle<Animation<T>>, bool>,
+
+    // Original speeds of the animations, that were overridden by synchronization groups.
+    #[reflect(hidden)]
+    speed_backup: FxHashMap<Handle<Animation<T>>, f32>,
+
+    // Amount of time the active state is active.
+    #[reflect(hidden)]
+    active_state_time: f32,
+
+    // A state, for which `StatePlaybackEnded` event was already emitted.
+    #[reflect(hidden)]
+    ended_state: Handle<State<T>>,
+
+    // A seed of the random number generator, that is used to pick weighted transitions.
+    #[reflect(hidden)]
+    rng_seed: Option<u64>,
+
+    // Current state of the random number generator. It is initialized on first use.
+    #[reflect(hidden)]
+    rng_state: Option<u64>,
+
+    // A pose of an interrupted transition at the moment of interruption, it is used instead of the pose of
+    // the source state of the active transition.
+    #[reflect(hidden)]
+    frozen_source_pose: Option<AnimationPose<T>>,
+}
+
+impl<T: EntityId> Default for MachineLayer<T> {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            weight: Default::default(),
+            time_scale: 1.0,
+            mask: Default::default(),
+            nodes: Default::default(),
+            transitions: Default::default(),
+            states: Default::default(),
+            active_state: Default::default(),
+            entry_state: Default::default(),
+            active_transition: Default::default(),
+            local_parameters: Default::default(),
+            interrupts: Default::default(),
+            any_state_transitions: Default::default(),
+            root_motion_settings: Default::default(),
+            interrupted_state: Default::default(),
+            merged_parameters: Default::default(),
+            final_pose: Default::default(),
+            events: Default::default(),
+            debug: Default::default(),
+            frozen: false,
+            looping_backup: Default::default(),
+            speed_backup: Default::default(),
+            active_state_time: Default::default(),
+            ended_state: Default::default(),
+            rng_seed: None,
+            rng_state: None,
+            frozen_source_pose: None,
+        }
+    }
+}
+
+// Version history of the serialized data of the layer:
+//
+// 1 - the data without explicit version.
+// 2 - explicit version was added along with any-state transitions, root motion settings, time scale and the seed
+//     of random transition selection.
+const VERSION: u8 = 2;
+
+// Manual implementation of the trait to be able to upgrade the data saved by older versions.
+impl<T: EntityId> Visit for MachineLayer<T> {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        // The data without version is the data of the first version.
+        let mut version = if region.is_reading() { 1 } else { VERSION };
+        let _ = version.visit("Version", &mut region);
+
+        if !(1..=VERSION).contains(&version) {
+            return Err(VisitError::User(format!(
+                "Unsupported machine layer version {version}!"
+            )));
+        }
+
+        self.name.visit("Name", &mut region)?;
+        self.weight.visit("Weight", &mut region)?;
+        self.mask.visit("Mask", &mut region)?;
+        self.visit_graph(&mut region)?;
+        let _ = self.local_parameters.visit("LocalParameters", &mut region);
+        let _ = self.interrupts.visit("Interrupts", &mut region);
+        let _ = self
+            .interrupted_state
+            .visit("InterruptedState", &mut region);
+
+        if version == 1 {
+            let _ = self
+                .any_state_transitions
+                .visit("AnyStateTransitions", &mut region);
+            let _ = self
+                .root_motion_settings
+                .visit("RootMotionSettings", &mut region);
+            if self.time_scale.visit("TimeScale", &mut region).is_err() {
+                self.time_scale = 1.0;
+            }
+            let _ = self.rng_seed.visit("RngSeed", &mut region);
+        } else {
+            self.any_state_transitions
+                .visit("AnyStateTransitions", &mut region)?;
+            self.root_motion_settings
+                .visit("RootMotionSettings", &mut region)?;
+            self.time_scale.visit("TimeScale", &mut region)?;
+            self.rng_seed.visit("RngSeed", &mut region)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<T: EntityId> NameProvider for MachineLayer<T> {
@@ -158,12 +484,60 @@ impl<T: EntityId> MachineLayer<T> {
             entry_state: Default::default(),
             active_transition: Default::default(),
             weight: 1.0,
+            time_scale: 1.0,
             events: FixedEventQueue::new(2048),
             debug: false,
+            frozen: false,
             mask: Default::default(),
+            local_parameters: Default::default(),
+            interrupts: Default::default(),
+            interrupted_state: Default::default(),
+            any_state_transitions: Default::default(),
+            root_motion_settings: None,
+            merged_parameters: Default::default(),
+            looping_backup: Default::default(),
+            speed_backup: Default::default(),
+            active_state_time: 0.0,
+            ended_state: Default::default(),
+            rng_seed: None,
+            rng_state: None,
+            frozen_source_pose: None,
         }
     }
 
+    // Visits the graph of the layer. It is also used to read the graph of the machines saved before
+    // layers were added, such machines were storing the graph directly in their regions.
+    pub(super) fn visit_graph(&mut self, region: &mut Visitor) -> VisitResult {
+        self.nodes.visit("Nodes", region)?;
+        self.transitions.visit("Transitions", region)?;
+        self.states.visit("States", region)?;
+        self.active_state.visit("ActiveState", region)?;
+        self.entry_state.visit("EntryState", region)?;
+        self.active_transition.visit("ActiveTransition", region)
+    }
+
+    /// Sets a local parameter of the layer. Local parameters shadow the parameters of the machine with the same
+    /// name during the evaluation of the layer. It could be used to reuse the same parameter name across multiple
+    /// layers with different values. Computed parameters of the machine (see
+    /// [`ParameterContainer::add_computed`]) are re-calculated for the layer using the local values.
+    #[inline]
+    pub fn set_local_parameter(&mut self, name: &str, value: Parameter) {
+        self.local_parameters.set(name, value)
+    }
+
+    /// Removes a local parameter of the layer, so the parameter of the machine with the same name (if any) will be
+    /// used instead.
+    #[inline]
+    pub fn remove_local_parameter(&mut self, name: &str) -> Option<Parameter> {
+        self.local_parameters.remove(name)
+    }
+
+    /// Returns a reference to local parameters of the layer.
+    #[inline]
+    pub fn local_parameters(&self) -> &ParameterContainer {
+        &self.local_parameters
+    }
+
     /// Sets new name for the layer. The name can then be used to find a layer in a parent state machine.
     #[inline]
     pub fn set_name<S: AsRef<str>>(&mut self, name: S) {
@@ -183,11 +557,83 @@ impl<T: EntityId> MachineLayer<T> {
     }
 
     /// Sets new entry state of the layer. Entry state will always be active on the first frame and will remain active
-    /// until some transition won't change it.
+    /// until some transition won't change it. The handle must belong to the layer or be [`Handle::NONE`] (to clear
+    /// the entry state), otherwise the method does nothing and returns `false`.
+    ///
+    /// This method changes the active state only if the layer has not started yet (i.e. there's no active state and
+    /// no active transition). To change the active state of a running layer use [`Self::set_active_state`], the new
+    /// entry state will be used after [`Self::reset`].
     #[inline]
-    pub fn set_entry_state(&mut self, entry_state: Handle<State<T>>) {
-        self.active_state = entry_state;
+    pub fn set_entry_state(&mut self, entry_state: Handle<State<T>>) -> bool {
+        if entry_state.is_some() && !self.states.is_valid_handle(entry_state) {
+            Log::err(format!(
+                "Unable to set entry state of {} layer, because {} handle does not belong to the layer!",
+                self.name, entry_state
+            ));
+            return false;
+        }
+
         self.entry_state = entry_state;
+
+        if self.active_state.is_none() && self.active_transition.is_none() {
+            self.active_state = entry_state;
+        }
+
+        true
+    }
+
+    /// Forcibly changes the active state of the layer, as if a transition to the state was done instantly. Any active
+    /// transition is interrupted. The method emits the same events as a normal transition does: [`Event::StateLeave`]
+    /// for the previous state, [`Event::StateEnter`] for the new state and [`Event::ActiveStateChanged`]. Enter and
+    /// leave actions of the states are not executed, because they need access to animations. The handle must belong
+    /// to the layer or be [`Handle::NONE`] (to stop the layer), otherwise the method does nothing and returns `false`.
+    pub fn set_active_state(&mut self, state: Handle<State<T>>) -> bool {
+        if state.is_some() && !self.states.is_valid_handle(state) {
+            Log::err(format!(
+                "Unable to set active state of {} layer, because {} handle does not belong to the layer!",
+                self.name, state
+            ));
+            return false;
+        }
+
+        // The state that was entered, but not left yet. When a transition has started, the source state was
+        // already left and the destination state was already entered.
+        let (prev, entered) =
+            if let Some(transition) = self.transitions.try_borrow_mut(self.active_transition) {
+                let (source, dest) = (transition.source(), transition.dest());
+                transition.reset();
+                self.active_transition = Handle::NONE;
+                self.events
+                    .push(Event::ActiveTransitionChanged(self.active_transition));
+                (source, dest)
+            } else {
+                (self.active_state, self.active_state)
+            };
+
+        self.cancel_interrupt();
+        self.release_any_state_transitions();
+        self.frozen_source_pose = None;
+
+        if prev == state && entered == state {
+            return true;
+        }
+
+        if entered != state {
+            if entered.is_some() {
+                self.events.push(Event::StateLeave(entered));
+            }
+            if state.is_some() {
+                self.events.push(Event::StateEnter(state));
+            }
+        }
+
+        self.active_state = state;
+        self.active_state_time = 0.0;
+        self.ended_state = Handle::NONE;
+        self.events
+            .push(Event::ActiveStateChanged { prev, new: state });
+
+        true
     }
 
     /// Returns a handle of current entry state.
@@ -203,6 +649,28 @@ impl<T: EntityId> MachineLayer<T> {
         self.debug = state;
     }
 
+    /// Freezes or unfreezes the layer. Frozen layer keeps its last evaluated pose and does not update its states and
+    /// transitions (no events are produced as well), until it is unfrozen. The pose of a frozen layer is still
+    /// blended with the poses of other layers using the weight of the layer. It could be used to hold the pose of a
+    /// character exactly while a game is paused (for example, when a menu is opened). Root motion of a frozen layer
+    /// is discarded, so a character does not move while the layer is frozen.
+    ///
+    /// Keep in mind, that the animations of the layer are owned by their container (usually an animation player)
+    /// and they're still updated, unless they're paused as well.
+    #[inline]
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+        if frozen {
+            self.final_pose.set_root_motion(None);
+        }
+    }
+
+    /// Returns `true` if the layer is frozen, `false` - otherwise. See [`Self::set_frozen`] for more info.
+    #[inline]
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
     /// Adds a new state to the layer and returns its handle.
     #[inline]
     pub fn add_state(&mut self, state: State<T>) -> Handle<State<T>> {
@@ -213,12 +681,216 @@ impl<T: EntityId> MachineLayer<T> {
         state
     }
 
+    /// Renames a state with the given handle and returns its previous name. Returns [`None`] if the handle is
+    /// invalid.
+    #[inline]
+    pub fn rename_state<S: AsRef<str>>(
+        &mut self,
+        state: Handle<State<T>>,
+        name: S,
+    ) -> Option<String> {
+        let state = self.states.try_borrow_mut(state)?;
+        let prev_name = std::mem::take(&mut state.name);
+        state.set_name(name);
+        Some(prev_name)
+    }
+
     /// Adds a new transition to the layer and returns its handle.
     #[inline]
     pub fn add_transition(&mut self, transition: Transition<T>) -> Handle<Transition<T>> {
         self.transitions.spawn(transition)
     }
 
+    /// Adds a new interrupt to the layer. Interrupt is a special kind of transition, that could be activated from
+    /// any state of the layer (except the handler state itself) when the given `trigger` rule parameter is `true`.
+    /// The layer remembers the interrupted state and blends to the handler state (`dest`) for `time` seconds. When
+    /// all animations of the handler state have ended, the layer automatically blends back to the interrupted state
+    /// for the same amount of time. It is a common gameplay pattern, for example, for hit reactions: a "stagger"
+    /// trigger cuts from any locomotion state to a hit reaction state and then the locomotion resumes.
+    ///
+    /// The interrupt is represented by a pair of transitions that are added to the layer, their handles are returned
+    /// in [`Interrupt`]. The transitions could be tweaked as any other transitions (for example, a different time
+    /// could be set for the return transition), but their source (for [`Interrupt::enter`]) and destination (for
+    /// [`Interrupt::exit`]) states are managed by the layer.
+    ///
+    /// # Notes
+    ///
+    /// - Interrupts are checked before the other transitions and only when there's no active transition.
+    /// - Only one interrupt could be active at a time, other interrupts are ignored until the layer returns to the
+    /// interrupted state.
+    /// - Animations of the handler state must not loop (see [`State::set_looping`]), otherwise the layer will never
+    /// return to the interrupted state. The animations should also be rewound when the handler state is entered
+    /// (see [`crate::machine::state::StateAction::RewindAnimation`]), otherwise the interrupt will end immediately
+    /// on the second activation.
+    /// - If some other transition leaves the handler state or the active state is changed manually (see
+    /// [`Self::set_active_state`]), the interrupt is cancelled and the layer won't return to the interrupted state.
+    /// - The interrupted state is serialized with the layer, so an interrupt that was active while saving will
+    /// continue after loading.
+    pub fn add_interrupt(
+        &mut self,
+        dest: Handle<State<T>>,
+        time: f32,
+        trigger: &str,
+    ) -> Interrupt<T> {
+        let dest_name = self
+            .states
+            .try_borrow(dest)
+            .map(|s| s.name.clone())
+            .unwrap_or_default();
+
+        let interrupt = Interrupt {
+            enter: self.transitions.spawn(Transition::new(
+                &format!("Any->{dest_name}"),
+                Handle::NONE,
+                dest,
+                time,
+                trigger,
+            )),
+            exit: self.transitions.spawn(Transition::new(
+                &format!("{dest_name}->Interrupted"),
+                dest,
+                Handle::NONE,
+                time,
+                "",
+            )),
+        };
+
+        self.interrupts.push(interrupt);
+
+        interrupt
+    }
+
+    /// Removes the given interrupt (and its transitions) from the layer. Returns `true` if the interrupt was found,
+    /// `false` - otherwise.
+    pub fn remove_interrupt(&mut self, interrupt: &Interrupt<T>) -> bool {
+        let Some(position) = self.interrupts.iter().position(|i| i == interrupt) else {
+            return false;
+        };
+
+        if self.active_transition == interrupt.enter || self.active_transition == interrupt.exit {
+            let dest = self.transitions[self.active_transition].dest();
+            self.set_active_state(dest);
+        }
+        self.cancel_interrupt();
+
+        self.interrupts.remove(position);
+        for transition in [interrupt.enter, interrupt.exit] {
+            if self.transitions.is_valid_handle(transition) {
+                self.transitions.free(transition);
+            }
+        }
+
+        true
+    }
+
+    /// Returns a reference to the interrupts of the layer. See [`Self::add_interrupt`] for more info.
+    #[inline]
+    pub fn interrupts(&self) -> &[Interrupt<T>] {
+        &self.interrupts
+    }
+
+    /// Returns a state that was interrupted by an interrupt and to which the layer will return when the interrupt
+    /// handler state is done. It is [`Handle::NONE`] if there's no active interrupt.
+    #[inline]
+    pub fn interrupted_state(&self) -> Handle<State<T>> {
+        self.interrupted_state
+    }
+
+    // Restores managed states of interrupt transitions and forgets the interrupted state.
+    fn cancel_interrupt(&mut self) {
+        for interrupt in self.interrupts.iter() {
+            if let Some(enter) = self.transitions.try_borrow_mut(interrupt.enter) {
+                if interrupt.enter != self.active_transition {
+                    enter.source = Handle::NONE;
+                }
+            }
+            if let Some(exit) = self.transitions.try_borrow_mut(interrupt.exit) {
+                if interrupt.exit != self.active_transition {
+                    exit.dest = Handle::NONE;
+                }
+            }
+        }
+
+        self.interrupted_state = Handle::NONE;
+    }
+
+    /// Adds a new any-state transition to the layer. Such transition could be activated from any state of the layer,
+    /// so there's no need to add a transition from every state to the destination state (for example, "from any state
+    /// go to Hit state when Hit rule is `true`"). The source state of the given transition is ignored, it is managed
+    /// by the layer. If `exclude_active` is `true`, the transition is not activated when its destination state is
+    /// already active, otherwise the destination state could transition to itself (which restarts the transition
+    /// each time it is done, as long as the condition is `true`).
+    ///
+    /// Any-state transitions are stored in the transitions pool of the layer, so they are blended and emit events
+    /// exactly as normal transitions. They are checked after interrupts (see [`Self::add_interrupt`]), but before
+    /// normal transitions of the active state, in the order they were added. Minimum source time of the transition
+    /// (see [`Transition::set_min_source_time`]) is checked against the time of the active state.
+    pub fn add_any_state_transition(
+        &mut self,
+        mut transition: Transition<T>,
+        exclude_active: bool,
+    ) -> AnyStateTransition<T> {
+        transition.source = Handle::NONE;
+
+        let any_state_transition = AnyStateTransition {
+            transition: self.transitions.spawn(transition),
+            exclude_active,
+        };
+
+        self.any_state_transitions.push(any_state_transition);
+
+        any_state_transition
+    }
+
+    /// Removes the given any-state transition (and its transition) from the layer. Returns `true` if the any-state
+    /// transition was found, `false` - otherwise.
+    pub fn remove_any_state_transition(
+        &mut self,
+        any_state_transition: &AnyStateTransition<T>,
+    ) -> bool {
+        let Some(position) = self
+            .any_state_transitions
+            .iter()
+            .position(|t| t == any_state_transition)
+        else {
+            return false;
+        };
+
+        let handle = any_state_transition.transition;
+        if self.active_transition == handle {
+            let dest = self.transitions[handle].dest();
+            self.set_active_state(dest);
+        }
+
+        self.any_state_transitions.remove(position);
+        if self.transitions.is_valid_handle(handle) {
+            self.transitions.free(handle);
+        }
+
+        true
+    }
+
+    /// Returns a reference to the any-state transitions of the layer. See [`Self::add_any_state_transition`] for
+    /// more info.
+    #[inline]
+    pub fn any_state_transitions(&self) -> &[AnyStateTransition<T>] {
+        &self.any_state_transitions
+    }
+
+    // Restores managed source states of the any-state transitions, that are not active.
+    fn release_any_state_transitions(&mut self) {
+        for any_state_transition in self.any_state_transitions.iter() {
+            if any_state_transition.transition != self.active_transition {
+                if let Some(transition) = self
+                    .transitions
+                    .try_borrow_mut(any_state_transition.transition)
+                {
+                    transition.source = Handle::NONE;
+                }
+            }
+        }
+    }
+
     /// Borrows a state using its handle, panics if the handle is invalid.
     #[inline]
     pub fn get_state(&self, state: Handle<State<T>>) -> &State<T> {
@@ -256,6 +928,9 @@ impl<T: EntityId> MachineLayer<T> {
     ///         Event::ActiveTransitionChanged(transition_handle) => {
     ///             // Occurs when active transition has changed.
     ///         }
+    ///         Event::StatePlaybackEnded(state_handle) => {
+    ///             // Occurs when all animations of a play-once state have ended.
+    ///         }
     ///     }
     /// }
     /// ```
@@ -264,6 +939,13 @@ impl<T: EntityId> MachineLayer<T> {
         self.events.pop()
     }
 
+    /// Removes all pending events from the inner event queue and returns them in the order they were emitted. It is
+    /// the same as calling [`Self::pop_event`] until it returns `None`. See also [`super::Machine::poll_events`].
+    #[inline]
+    pub fn drain_events(&mut self) -> impl Iterator<Item = Event<T>> + '_ {
+        self.events.drain()
+    }
+
     /// Resets layer state; deactivates all active transitions and sets active state to entry state.
     #[inline]
     pub fn reset(&mut self) {
@@ -271,7 +953,14 @@ impl<T: EntityId> MachineLayer<T> {
             transition.reset();
         }
 
+        self.active_transition = Handle::NONE;
+        self.cancel_interrupt();
+        self.release_any_state_transitions();
+        self.frozen_source_pose = None;
+
         self.active_state = self.entry_state;
+        self.active_state_time = 0.0;
+        self.ended_state = Handle::NONE;
     }
 
     /// Fetches animation events from an active state (or a transition). It could be used to fetch animation events from a layer
@@ -411,6 +1100,38 @@ impl<T: EntityId> MachineLayer<T> {
         self.active_state
     }
 
+    /// Returns a name of active state or an empty string if there's no active state (for example, while a
+    /// transition is active).
+    #[inline]
+    pub fn active_state_name(&self) -> &str {
+        self.states
+            .try_borrow(self.active_state)
+            .map_or("", |state| state.name.as_str())
+    }
+
+    /// Returns the amount of time (in seconds) the current state is active. The time is reset when a
+    /// transition to other state is done and does not include the time of the transition itself.
+    #[inline]
+    pub fn active_state_time(&self) -> f32 {
+        self.active_state_time
+    }
+
+    /// Sets a seed of the random number generator, that is used to pick one of the weighted transitions that could
+    /// be activated at the same time (see [`Transition::set_selection_weight`]). The same seed produces the same
+    /// sequence of choices, which could be useful for deterministic tests or replays. By default, the layer uses
+    /// a random seed.
+    #[inline]
+    pub fn set_rng_seed(&mut self, seed: Option<u64>) {
+        self.rng_seed = seed;
+        self.rng_state = seed;
+    }
+
+    /// Returns a seed of the random number generator, that is used to pick weighted transitions.
+    #[inline]
+    pub fn rng_seed(&self) -> Option<u64> {
+        self.rng_seed
+    }
+
     /// Returns a handle of active transition. It is not empty only while a transition is active (doing blending
     /// between states).
     #[inline]
@@ -418,6 +1139,34 @@ impl<T: EntityId> MachineLayer<T> {
         self.active_transition
     }
 
+    /// Returns a name of active transition or an empty string if there's no active transition.
+    #[inline]
+    pub fn active_transition_name(&self) -> &str {
+        self.transitions
+            .try_borrow(self.active_transition)
+            .map_or("", |transition| transition.name())
+    }
+
+    /// Returns the progress of active transition (if any) in `[0; 1]` range, where 0 means that the transition has
+    /// just started and 1 - the transition is done. It is the same as the blend factor of the transition (see
+    /// [`Transition::blend_factor`]).
+    #[inline]
+    pub fn active_transition_progress(&self) -> Option<f32> {
+        self.transitions
+            .try_borrow(self.active_transition)
+            .map(|transition| transition.blend_factor())
+    }
+
+    /// Returns the amount of time (in seconds, not affected by the time scale of the layer) left until active
+    /// transition (if any) is done. It could be used to sync gameplay with animations, for example to commit to an
+    /// attack after a windup blend is done.
+    #[inline]
+    pub fn active_transition_remaining(&self) -> Option<f32> {
+        self.transitions
+            .try_borrow(self.active_transition)
+            .map(|transition| (transition.transition_time - transition.elapsed_time).max(0.0))
+    }
+
     /// Tries to borrow a transition using its handle, panics if the handle is invalid.
     #[inline]
     pub fn transition(&self, handle: Handle<Transition<T>>) -> &Transition<T> {
@@ -460,6 +1209,14 @@ impl<T: EntityId> MachineLayer<T> {
         find_by_name_mut(self.transitions.pair_iter_mut(), name)
     }
 
+    /// Tries to find a transition by its name. Returns [`Handle::NONE`] if there's no such transition.
+    #[inline]
+    pub fn find_transition_by_name<S: AsRef<str>>(&self, name: S) -> Handle<Transition<T>> {
+        self.find_transition_by_name_ref(name)
+            .map(|(handle, _)| handle)
+            .unwrap_or_default()
+    }
+
     /// Tries to borrow a state using its handle, panics if the handle is invalid.
     #[inline]
     pub fn state(&self, handle: Handle<State<T>>) -> &State<T> {
@@ -490,6 +1247,14 @@ impl<T: EntityId> MachineLayer<T> {
         find_by_name_mut(self.states.pair_iter_mut(), name)
     }
 
+    /// Tries to find a state by its name. Returns [`Handle::NONE`] if there's no such state.
+    #[inline]
+    pub fn find_state_by_name<S: AsRef<str>>(&self, name: S) -> Handle<State<T>> {
+        self.find_state_by_name_ref(name)
+            .map(|(handle, _)| handle)
+            .unwrap_or_default()
+    }
+
     /// Returns a reference to inner states container.
     #[inline]
     pub fn states(&self) -> &Pool<State<T>> {
@@ -521,12 +1286,47 @@ impl<T: EntityId> MachineLayer<T> {
         std::mem::replace(&mut self.mask, mask)
     }
 
+    /// Sets time scale of the layer. The time step passed to [`super::Machine::evaluate_pose`] is multiplied by this
+    /// value before updating the states and the transitions of the layer, so the transitions of the layer could be
+    /// slowed down (values less than `1.0`) or sped up (values greater than `1.0`) independently of other layers. It
+    /// could be used for slow-motion effects or speed ramps. Note, that the animations are updated outside of the
+    /// machine, their speed must be changed separately (see [`Animation::set_speed`]). Negative values are clamped
+    /// to zero. Default value is `1.0`.
+    #[inline]
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    /// Returns time scale of the layer. See [`Self::set_time_scale`] for more info.
+    #[inline]
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
     /// Returns a reference to current layer mask.
     #[inline]
     pub fn mask(&self) -> &LayerMask<T> {
         &self.mask
     }
 
+    /// Sets root motion settings of the layer. When set, the settings are applied to every animation used by the
+    /// layer, so there's no need to configure each animation separately. The motion of the root node is extracted
+    /// by the animations when they're updated (so the new settings take effect on the next update of the animations)
+    /// and is blended the same way as the poses of the states: the motion of a transition is a weighted mix of the
+    /// motions of its source and destination states. The resulting motion is available in [`Self::pose`] (and in
+    /// [`crate::machine::Machine::root_motion`] for the whole machine). `None` means that the layer does not change
+    /// the settings of the animations.
+    #[inline]
+    pub fn set_root_motion_settings(&mut self, settings: Option<RootMotionSettings<T>>) {
+        self.root_motion_settings = settings;
+    }
+
+    /// Returns a reference to the root motion settings of the layer (if any).
+    #[inline]
+    pub fn root_motion_settings_ref(&self) -> Option<&RootMotionSettings<T>> {
+        self.root_motion_settings.as_ref()
+    }
+
     /// Returns final pose of the layer.
     #[inline]
     pub fn pose(&self) -> &AnimationPose<T> {
@@ -553,17 +1353,735 @@ impl<T: EntityId> MachineLayer<T> {
         })
     }
 
-    /// Returns `true` if all animations of the given state has ended, `false` - otherwise.
-    pub fn is_all_animations_of_state_ended(
-        &self,
-        state: Handle<State<T>>,
-        animations: &AnimationContainer<T>,
-    ) -> bool {
+    /// Returns a list of all entities that depend on the given pose node, directly or indirectly. It walks the pose
+    /// node graph upwards and collects every node that uses the given node as its input (including nested blend
+    /// trees) and every state whose root is either the given node or any of its dependent nodes. This method is
+    /// intended to be used by tooling, for example to warn a user before deleting a node that is still in use.
+    pub fn node_dependents(&self, node: Handle<PoseNode<T>>) -> NodeDependents<T> {
+        let mut dependents = NodeDependents::default();
+
+        let mut stack = vec![node];
+        while let Some(current) = stack.pop() {
+            for (handle, other) in self.nodes.pair_iter() {
+                if other.children().contains(&current) && !dependents.nodes.contains(&handle) {
+                    dependents.nodes.push(handle);
+                    stack.push(handle);
+                }
+            }
+        }
+
+        for (handle, state) in self.states.pair_iter() {
+            if state.root.is_some()
+                && (state.root == node || dependents.nodes.contains(&state.root))
+            {
+                dependents.states.push(handle);
+            }
+        }
+
+        dependents
+    }
+
+    /// Copies the given states together with their pose node trees and the transitions between them into a
+    /// detached sub-graph, that could be inserted into the same or some other layer using [`Self::insert_subgraph`].
+    /// It is intended to be used by tooling to copy and paste parts of state graphs, for example to reuse an
+    /// animation pattern for a different character. Invalid and duplicate handles are ignored.
+    ///
+    /// Pose nodes are copied deeply: a node used by some other state (that is not copied) is copied as well,
+    /// so the sub-graph never refers to the nodes of the source layer. Only the transitions, whose source and
+    /// destination states are both in the sub-graph, are copied. External transitions (from or to states, that
+    /// are not copied) as well as interrupt and any-state transitions are dropped, because there's nothing they
+    /// could be connected to in the destination layer. Interrupts, any-state transitions, local parameters and the
+    /// runtime state of the layer (active state, events, etc.) are not copied.
+    pub fn extract_subgraph(&self, states: &[Handle<State<T>>]) -> MachineSubgraph<T> {
+        let mut subgraph = MachineSubgraph::default();
+
+        for &handle in states {
+            if let Some(state) = self.states.try_borrow(handle) {
+                if !subgraph.contains_state(handle) {
+                    subgraph.states.push((handle, state.clone()));
+                }
+            }
+        }
+
+        let mut stack = subgraph
+            .states
+            .iter()
+            .map(|(_, state)| state.root)
+            .collect::<Vec<_>>();
+        while let Some(handle) = stack.pop() {
+            if subgraph.contains_node(handle) {
+                continue;
+            }
+
+            if let Some(node) = self.nodes.try_borrow(handle) {
+                stack.extend(node.children());
+                subgraph.nodes.push((handle, node.clone()));
+            }
+        }
+
+        for (handle, transition) in self.transitions.pair_iter() {
+            if subgraph.contains_state(transition.source())
+                && subgraph.contains_state(transition.dest())
+            {
+                subgraph.transitions.push((handle, transition.clone()));
+            }
+        }
+
+        subgraph
+    }
+
+    /// Inserts a copy of the given sub-graph (see [`Self::extract_subgraph`]) into the layer and returns the mapping
+    /// between the handles of the entities in the source layer and the handles of their copies. All the internal
+    /// references (state roots, pose node inputs, transition endpoints) are remapped to the new handles. Parent
+    /// states of the pose nodes, that were not copied, are reset to [`Handle::NONE`]. Names of the states are kept
+    /// as is, so pasting a sub-graph into the same layer produces states with duplicate names.
+    pub fn insert_subgraph(&mut self, subgraph: MachineSubgraph<T>) -> HandleRemap<T> {
+        let mut remap = HandleRemap::default();
+
+        // Spawn everything first, internal references can be fixed only when every entity has its new handle.
+        for (handle, node) in subgraph.nodes {
+            remap.nodes.insert(handle, self.add_node(node));
+        }
+        for (handle, state) in subgraph.states {
+            remap.states.insert(handle, self.add_state(state));
+        }
+        for (handle, transition) in subgraph.transitions {
+            remap
+                .transitions
+                .insert(handle, self.add_transition(transition));
+        }
+
+        for &handle in remap.nodes.values() {
+            let node = &mut self.nodes[handle];
+            node.remap_children(|child| remap.node(child));
+            node.parent_state = remap.state(node.parent_state);
+        }
+        for &handle in remap.states.values() {
+            let state = &mut self.states[handle];
+            state.root = remap.node(state.root);
+        }
+        for &handle in remap.transitions.values() {
+            let transition = &mut self.transitions[handle];
+            transition.source = remap.state(transition.source);
+            transition.dest = remap.state(transition.dest);
+            transition.reset();
+        }
+
+        remap
+    }
+
+    /// Returns a list of states that use the given pose node, either directly as a root node or indirectly as an
+    /// input of any node of the state's pose node tree. See [`Self::node_dependents`] for more info.
+    pub fn states_using_node(&self, node: Handle<PoseNode<T>>) -> Vec<Handle<State<T>>> {
+        self.node_dependents(node).states
+    }
+
+    /// Checks the layer for common authoring problems: states, that cannot be reached from the entry state, states
+    /// without outgoing transitions, transitions with invalid endpoints and transition conditions that use unknown
+    /// `Rule` parameters. The parameters are searched in the local parameters of the layer first and then in the given
+    /// parameters (usually the parameters of the machine, see [`super::Machine::validate`]). Interrupts (see
+    /// [`Self::add_interrupt`]) and any-state transitions (see [`Self::add_any_state_transition`]) are taken into
+    /// account: their destination states are reachable from any reachable state and their transitions leave any
+    /// state (except their destination states). The returned issues contain handles of the problematic entities, so
+    /// tooling could point a user to them. An empty list means that no problems were found.
+    pub fn validate(&self, parameters: &ParameterContainer) -> Vec<MachineValidationIssue<T>> {
+        let mut issues = Vec::new();
+
+        let exits = self.interrupts.iter().map(|i| i.exit).collect::<Vec<_>>();
+        let any_state_dests = self
+            .interrupts
+            .iter()
+            .map(|i| i.enter)
+            .chain(self.any_state_transitions.iter().map(|t| t.transition))
+            .filter_map(|t| self.transitions.try_borrow(t))
+            .map(|t| t.dest())
+            .filter(|dest| self.states.is_valid_handle(*dest))
+            .collect::<Vec<_>>();
+
+        for (handle, transition) in self.transitions.pair_iter() {
+            let is_managed = exits.contains(&handle)
+                || self
+                    .interrupts
+                    .iter()
+                    .any(|i| i.enter == handle && transition.source().is_none())
+                || self
+                    .any_state_transitions
+                    .iter()
+                    .any(|t| t.transition == handle && transition.source().is_none());
+
+            let source_is_valid = self.states.is_valid_handle(transition.source())
+                || (is_managed && transition.source().is_none());
+            let dest_is_valid = self.states.is_valid_handle(transition.dest())
+                || (exits.contains(&handle) && transition.dest().is_none());
+            if !source_is_valid || !dest_is_valid {
+                issues.push(MachineValidationIssue::InvalidTransition(handle));
+            }
+
+            // Conditions of exit transitions of the interrupts are not used.
+            if exits.contains(&handle) {
+                continue;
+            }
+
+            let mut reported = Vec::new();
+            for name in transition.condition.rule_names() {
+                let is_rule = |container: &ParameterContainer| {
+                    matches!(container.get(name), Some(Parameter::Rule(_)))
+                        || container.computed().iter().any(|c| c.name == name)
+                };
+
+                if !is_rule(&self.local_parameters)
+                    && !is_rule(parameters)
+                    && !reported.contains(&name)
+                {
+                    reported.push(name);
+                    issues.push(MachineValidationIssue::UnknownRuleParameter {
+                        transition: handle,
+                        name: name.to_string(),
+                    });
+                }
+            }
+        }
+
+        if self.states.alive_count() == 0 {
+            return issues;
+        }
+
+        if !self.states.is_valid_handle(self.entry_state) {
+            issues.push(MachineValidationIssue::NoEntryState);
+        } else {
+            let mut reachable = vec![self.entry_state];
+            let mut stack = vec![self.entry_state];
+            while let Some(state) = stack.pop() {
+                let next = self
+                    .transitions
+                    .iter()
+                    .filter(|t| t.source() == state)
+                    .map(|t| t.dest())
+                    .chain(any_state_dests.iter().cloned());
+                for dest in next {
+                    if self.states.is_valid_handle(dest) && !reachable.contains(&dest) {
+                        reachable.push(dest);
+                        stack.push(dest);
+                    }
+                }
+            }
+
+            for handle in self.states.pair_iter().map(|(h, _)| h) {
+                if !reachable.contains(&handle) {
+                    issues.push(MachineValidationIssue::UnreachableState(handle));
+                }
+            }
+        }
+
+        for handle in self.states.pair_iter().map(|(h, _)| h) {
+            let has_exit = self
+                .transitions
+                .iter()
+                .any(|t| t.source() == handle && t.dest() != handle)
+                || any_state_dests.iter().any(|dest| *dest != handle);
+            if !has_exit {
+                issues.push(MachineValidationIssue::DeadEndState(handle));
+            }
+        }
+
+        issues
+    }
+
+    /// Returns `true` if all animations of the given state has ended, `false` - otherwise.
+    pub fn is_all_animations_of_state_ended(
+        &self,
+        state: Handle<State<T>>,
+        animations: &AnimationContainer<T>,
+    ) -> bool {
         self.animations_of_state(state)
             .filter_map(|a| animations.try_get(a))
             .all(|a| a.has_ended())
     }
 
+    /// Exports the layer as a graph in [Graphviz DOT](https://graphviz.org/doc/info/lang.html) format. States are
+    /// exported as nodes, transitions - as edges labeled with their name, condition and transition time. Entry state
+    /// is drawn with a double border and active state is filled. It could be useful for debugging and documentation
+    /// purposes, the output could be rendered with `dot -Tsvg layer.dot -o layer.svg`.
+    pub fn to_dot(&self) -> String {
+        fn escape(str: &str) -> String {
+            str.replace('\\', "\\\\").replace('"', "\\\"")
+        }
+
+        let mut dot = String::new();
+
+        let _ = writeln!(dot, "digraph \"{}\" {{", escape(&self.name));
+        let _ = writeln!(dot, "    node [shape=box, style=rounded];");
+
+        for (handle, state) in self.states.pair_iter() {
+            let mut attributes = format!("label=\"{}\"", escape(&state.name));
+            if handle == self.entry_state {
+                attributes += ", peripheries=2";
+            }
+            if handle == self.active_state {
+                attributes += ", style=\"rounded,filled\", fillcolor=lightblue";
+            }
+            let _ = writeln!(dot, "    s{} [{}];", handle.index(), attributes);
+        }
+
+        for (handle, transition) in self.transitions.pair_iter() {
+            // Interrupt transitions have no fixed source (or destination) state.
+            if transition.source.is_none() || transition.dest.is_none() {
+                continue;
+            }
+
+            let mut attributes = format!(
+                "label=\"{}\\n{}\\n{}\"",
+                escape(&transition.name),
+                escape(&transition.condition.to_string()),
+                transition.time
+            );
+            if handle == self.active_transition {
+                attributes += ", color=blue, penwidth=2";
+            }
+            let _ = writeln!(
+                dot,
+                "    s{} -> s{} [{}];",
+                transition.source.index(),
+                transition.dest.index(),
+                attributes
+            );
+        }
+
+        dot.push('}');
+        dot.push('\n');
+
+        dot
+    }
+
+    fn synchronize_animations(&mut self, animations: &mut AnimationContainer<T>, dt: f32) {
+        let weighted_states =
+            if let Some(transition) = self.transitions.try_borrow(self.active_transition) {
+                vec![
+                    (transition.source(), 1.0 - transition.blend_factor()),
+                    (transition.dest(), transition.blend_factor()),
+                ]
+            } else {
+                vec![(self.active_state, 1.0)]
+            };
+
+        let mut overrides = FxHashMap::default();
+        for (i, (leader, leader_weight)) in weighted_states.iter().enumerate() {
+            let Some(leader_state) = self.states.try_borrow(*leader) else {
+                continue;
+            };
+
+            if leader_state.sync_group.is_empty() {
+                continue;
+            }
+
+            // Only the state with the largest weight in the group can lead.
+            let is_leader = weighted_states
+                .iter()
+                .enumerate()
+                .all(|(j, (other, weight))| {
+                    i == j
+                        || self
+                            .states
+                            .try_borrow(*other)
+                            .map_or(true, |s| s.sync_group != leader_state.sync_group)
+                        || weight < leader_weight
+                        || (weight == leader_weight && i < j)
+                });
+
+            if !is_leader {
+                continue;
+            }
+
+            // The speed of the leader could be overridden while it was a follower.
+            let Some((phase, phase_rate)) = self
+                .animations_of_state(*leader)
+                .filter_map(|a| animations.try_get(a).map(|animation| (a, animation)))
+                .find(|(_, a)| a.length() > 0.0)
+                .map(|(handle, a)| {
+                    let speed = self.speed_backup.get(&handle).cloned().unwrap_or(a.speed());
+                    (
+                        (a.time_position() - a.time_slice().start) / a.length(),
+                        speed / a.length(),
+                    )
+                })
+            else {
+                continue;
+            };
+
+            // Phase of the leader after the next update.
+            let target_phase = phase + phase_rate * dt;
+
+            for (follower, _) in weighted_states.iter() {
+                if follower == leader
+                    || self
+                        .states
+                        .try_borrow(*follower)
+                        .map_or(true, |s| s.sync_group != leader_state.sync_group)
+                {
+                    continue;
+                }
+
+                for handle in self.animations_of_state(*follower) {
+                    let Some(animation) = animations.try_get(handle) else {
+                        continue;
+                    };
+                    if animation.length() <= 0.0 {
+                        continue;
+                    }
+
+                    let follower_phase = (animation.time_position() - animation.time_slice().start)
+                        / animation.length();
+                    let mut phase_delta = target_phase - follower_phase;
+                    if animation.is_loop() {
+                        // Take the shortest way around the cycle.
+                        phase_delta -= phase_delta.round();
+                    }
+
+                    let speed = if dt > 0.0 {
+                        phase_delta * animation.length() / dt
+                    } else {
+                        phase_rate * animation.length()
+                    };
+                    overrides.insert(handle, speed);
+                }
+            }
+        }
+
+        // Restore the animations that are no longer synchronized.
+        self.speed_backup.retain(|animation, original| {
+            if overrides.contains_key(animation) {
+                true
+            } else {
+                if let Some(animation) = animations.try_get_mut(*animation) {
+                    animation.set_speed(*original);
+                }
+                false
+            }
+        });
+
+        for (handle, speed) in overrides {
+            if let Some(animation) = animations.try_get_mut(handle) {
+                self.speed_backup
+                    .entry(handle)
+                    .or_insert_with(|| animation.speed());
+                animation.set_speed(speed);
+            }
+        }
+    }
+
+    // Looks for an interrupt that should be activated (or for a return from an active interrupt) and prepares
+    // its transition.
+    fn find_interrupt_transition(
+        &mut self,
+        parameters: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+    ) -> Option<Handle<Transition<T>>> {
+        if self.interrupted_state.is_none() {
+            let enter = self.interrupts.iter().map(|i| i.enter).find(|enter| {
+                self.transitions.try_borrow(*enter).map_or(false, |t| {
+                    t.dest().is_some()
+                        && t.dest() != self.active_state
+                        && t.condition.calculate_value(parameters, animations)
+                })
+            })?;
+
+            self.interrupted_state = self.active_state;
+            self.transitions[enter].source = self.active_state;
+
+            Some(enter)
+        } else {
+            let exit = self.interrupts.iter().map(|i| i.exit).find(|exit| {
+                self.transitions
+                    .try_borrow(*exit)
+                    .map_or(false, |t| t.source() == self.active_state)
+            })?;
+
+            if !self.is_all_animations_of_state_ended(self.active_state, animations) {
+                return None;
+            }
+
+            self.transitions[exit].dest = self.interrupted_state;
+
+            Some(exit)
+        }
+    }
+
+    // Looks for an any-state transition that should be activated and prepares it.
+    fn find_any_state_transition(
+        &mut self,
+        parameters: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+    ) -> Option<Handle<Transition<T>>> {
+        let handle = self.any_state_transitions.iter().find_map(|any| {
+            let transition = self.transitions.try_borrow(any.transition)?;
+            if transition.dest().is_none()
+                || (any.exclude_active && transition.dest() == self.active_state)
+                || self.active_state_time < transition.min_source_time
+                || !transition.condition.calculate_value(parameters, animations)
+            {
+                return None;
+            }
+            Some(any.transition)
+        })?;
+
+        self.transitions[handle].source = self.active_state;
+
+        Some(handle)
+    }
+
+    // Looks for a transition from the active state that should be activated. If the first transition that
+    // could be activated has a selection weight, the transition is picked randomly among all weighted
+    // transitions that could be activated.
+    fn find_transition(
+        &mut self,
+        parameters: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+    ) -> Option<Handle<Transition<T>>> {
+        let mut candidates = self.transitions.pair_iter().filter(|(_, transition)| {
+            transition.dest() != self.active_state
+                && transition.dest().is_some()
+                && transition.source() == self.active_state
+                && self.active_state_time >= transition.min_source_time
+                && transition.condition.calculate_value(parameters, animations)
+        });
+
+        let (first, first_transition) = candidates.next()?;
+        let Some(first_weight) = first_transition.selection_weight else {
+            return Some(first);
+        };
+
+        let weighted = std::iter::once((first, first_weight))
+            .chain(candidates.filter_map(|(handle, transition)| {
+                transition.selection_weight.map(|weight| (handle, weight))
+            }))
+            .collect::<Vec<_>>();
+
+        let total = weighted.iter().map(|(_, weight)| *weight).sum::<f32>();
+        if weighted.len() == 1 || total <= 0.0 {
+            return Some(first);
+        }
+
+        let mut point = self.next_random() * total;
+        for (handle, weight) in weighted.iter() {
+            if point < *weight {
+                return Some(*handle);
+            }
+            point -= *weight;
+        }
+
+        weighted.last().map(|(handle, _)| *handle)
+    }
+
+    // Returns a random number in `[0; 1)` range.
+    fn next_random(&mut self) -> f32 {
+        let state = *self
+            .rng_state
+            .get_or_insert_with(|| self.rng_seed.unwrap_or_else(rand::random));
+        let mut rng = StdRng::seed_from_u64(state);
+        let value = rng.gen::<f32>();
+        self.rng_state = Some(rng.gen());
+        value
+    }
+
+    // Looks for a transition that should interrupt the active transition (if it is interruptible).
+    fn find_transition_interrupt(
+        &self,
+        parameters: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+    ) -> Option<Handle<Transition<T>>> {
+        let active = self.active_transition;
+        let transition = self.transitions.try_borrow(active)?;
+
+        // Source and destination states of interrupt transitions are managed by the layer.
+        if !transition.interruptible
+            || self
+                .interrupts
+                .iter()
+                .any(|interrupt| interrupt.enter == active || interrupt.exit == active)
+        {
+            return None;
+        }
+
+        if let Some(rules) = transition.interrupt_rules.as_ref() {
+            if !rules.calculate_value(parameters, animations) {
+                return None;
+            }
+        }
+
+        // Only the transitions with higher priority (that were added earlier) could interrupt the active
+        // transition from its source state, otherwise the transitions would interrupt each other endlessly.
+        let find = |state: Handle<State<T>>, higher_priority_only: bool| {
+            self.transitions.pair_iter().find_map(|(handle, other)| {
+                (handle != active
+                    && other.source() == state
+                    && other.dest().is_some()
+                    && other.dest() != transition.dest()
+                    && (!higher_priority_only || handle.index() < active.index())
+                    && other.condition.calculate_value(parameters, animations))
+                .then_some(handle)
+            })
+        };
+
+        let (source, dest) = (transition.source(), transition.dest());
+        match transition.interrupt_source {
+            TransitionInterruptSource::Destination => find(dest, false),
+            TransitionInterruptSource::Source => find(source, true),
+            TransitionInterruptSource::DestinationThenSource => {
+                find(dest, false).or_else(|| find(source, true))
+            }
+            TransitionInterruptSource::SourceThenDestination => {
+                find(source, true).or_else(|| find(dest, false))
+            }
+        }
+    }
+
+    // Replaces the active transition with the given one. The new transition blends from the current pose of
+    // the layer, so there's no pose pop.
+    fn interrupt_transition(
+        &mut self,
+        next: Handle<Transition<T>>,
+        animations: &mut AnimationContainer<T>,
+    ) {
+        let mut frozen_pose = self.final_pose.clone();
+        frozen_pose.set_root_motion(None);
+
+        let interrupted = &mut self.transitions[self.active_transition];
+        interrupted.reset();
+
+        if self.debug {
+            Log::writeln(
+                MessageKind::Information,
+                format!("Transition {} was interrupted", interrupted.name()),
+            );
+        }
+
+        // The destination state of the interrupted transition was already entered, so it is the one to leave.
+        self.active_state = interrupted.dest();
+        self.begin_transition(next, animations);
+        self.release_any_state_transitions();
+
+        self.frozen_source_pose = Some(frozen_pose);
+    }
+
+    // Restores managed states of an interrupt transition, that has just finished.
+    fn finish_interrupt_transition(&mut self, transition: Handle<Transition<T>>) {
+        for interrupt in self.interrupts.iter() {
+            if interrupt.enter == transition {
+                self.transitions[transition].source = Handle::NONE;
+            } else if interrupt.exit == transition {
+                self.transitions[transition].dest = Handle::NONE;
+                self.interrupted_state = Handle::NONE;
+            }
+        }
+    }
+
+    fn begin_transition(
+        &mut self,
+        handle: Handle<Transition<T>>,
+        animations: &mut AnimationContainer<T>,
+    ) {
+        let dest = self.transitions[handle].dest();
+
+        self.frozen_source_pose = None;
+
+        let target_duration = self
+            .animations_of_state(dest)
+            .filter_map(|a| animations.try_get(a))
+            .find(|a| a.length() > 0.0 && a.speed() != 0.0)
+            .map(|a| a.length() / a.speed().abs());
+        self.transitions[handle].resolve_time(target_duration);
+
+        if let Some(active_state) = self.states.try_borrow(self.active_state) {
+            for action in active_state.on_leave_actions.iter() {
+                action.apply(animations);
+            }
+        }
+
+        self.events.push(Event::StateLeave(self.active_state));
+        if self.debug {
+            Log::writeln(
+                MessageKind::Information,
+                format!("Leaving state: {}", self.states[self.active_state].name),
+            );
+        }
+
+        if let Some(dest_state) = self.states.try_borrow(dest) {
+            for action in dest_state.on_enter_actions.iter() {
+                action.apply(animations);
+            }
+        }
+
+        self.events.push(Event::StateEnter(dest));
+        if self.debug {
+            Log::writeln(
+                MessageKind::Information,
+                format!("Entering state: {}", self.states[dest].name),
+            );
+        }
+
+        self.active_state = Handle::NONE;
+        self.ended_state = Handle::NONE;
+
+        self.active_transition = handle;
+        self.events
+            .push(Event::ActiveTransitionChanged(self.active_transition));
+    }
+
+    fn apply_root_motion_settings(&self, animations: &mut AnimationContainer<T>) {
+        let Some(settings) = self.root_motion_settings.as_ref() else {
+            return;
+        };
+
+        for node in self.nodes.iter() {
+            if let PoseNode::PlayAnimation(play_animation) = node {
+                if let Some(animation) = animations.try_get_mut(play_animation.animation) {
+                    if animation.root_motion_settings_ref() != Some(settings) {
+                        animation.set_root_motion_settings(Some(settings.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    fn apply_looping_overrides(&mut self, animations: &mut AnimationContainer<T>) {
+        let active_states =
+            if let Some(transition) = self.transitions.try_borrow(self.active_transition) {
+                vec![transition.source(), transition.dest()]
+            } else {
+                vec![self.active_state]
+            };
+
+        let mut overrides = FxHashMap::default();
+        for state in active_states {
+            if let Some(looping) = self.states.try_borrow(state).and_then(|s| s.looping) {
+                for animation in self.animations_of_state(state) {
+                    overrides.insert(animation, looping);
+                }
+            }
+        }
+
+        // Restore the animations that are no longer overridden.
+        self.looping_backup.retain(|animation, original| {
+            if overrides.contains_key(animation) {
+                true
+            } else {
+                if let Some(animation) = animations.try_get_mut(*animation) {
+                    animation.set_loop(*original);
+                }
+                false
+            }
+        });
+
+        for (handle, looping) in overrides {
+            if let Some(animation) = animations.try_get_mut(handle) {
+                self.looping_backup
+                    .entry(handle)
+                    .or_insert_with(|| animation.is_loop());
+                animation.set_loop(looping);
+            }
+        }
+    }
+
     #[inline]
     pub(super) fn evaluate_pose(
         &mut self,
@@ -571,100 +2089,130 @@ impl<T: EntityId> MachineLayer<T> {
         parameters: &ParameterContainer,
         dt: f32,
     ) -> &AnimationPose<T> {
+        if self.frozen {
+            return &self.final_pose;
+        }
+
         self.final_pose.reset();
 
+        // Animations are advanced by their owner, the time scale of the layer does not affect them.
+        let animation_dt = dt;
+        let dt = dt * self.time_scale;
+
+        // Local parameters shadow the parameters of the machine.
+        let mut merged_parameters = std::mem::take(&mut self.merged_parameters);
+        let parameters = if self.local_parameters.is_empty() {
+            parameters
+        } else {
+            merged_parameters.overlay(parameters, &self.local_parameters);
+            merged_parameters.update_computed();
+            &merged_parameters
+        };
+
         if self.active_state.is_some() || self.active_transition.is_some() {
+            self.apply_root_motion_settings(animations);
+
             // Gather actual poses for each state.
             for state in self.states.iter_mut() {
                 state.update(&self.nodes, parameters, animations, dt);
             }
 
             if self.active_transition.is_none() {
-                // Find transition.
-                for (handle, transition) in self.transitions.pair_iter_mut() {
-                    if transition.dest() == self.active_state
-                        || transition.source() != self.active_state
-                    {
-                        continue;
-                    }
+                self.active_state_time += dt;
 
-                    if transition.condition.calculate_value(parameters, animations) {
-                        if let Some(active_state) = self.states.try_borrow(self.active_state) {
-                            for action in active_state.on_leave_actions.iter() {
-                                action.apply(animations);
-                            }
-                        }
-
-                        self.events.push(Event::StateLeave(self.active_state));
-                        if self.debug {
-                            Log::writeln(
-                                MessageKind::Information,
-                                format!("Leaving state: {}", self.states[self.active_state].name),
-                            );
-                        }
-
-                        if let Some(source) = self.states.try_borrow(transition.dest()) {
-                            for action in source.on_enter_actions.iter() {
-                                action.apply(animations);
-                            }
-                        }
-
-                        self.events.push(Event::StateEnter(transition.dest()));
-                        if self.debug {
-                            Log::writeln(
-                                MessageKind::Information,
-                                format!("Entering state: {}", self.states[transition.dest()].name),
-                            );
-                        }
+                let mut next = self.find_interrupt_transition(parameters, animations);
 
-                        self.active_state = Handle::NONE;
+                if next.is_none() {
+                    // Any-state transitions have priority over the transitions of the active state.
+                    next = self.find_any_state_transition(parameters, animations);
 
-                        self.active_transition = handle;
-                        self.events
-                            .push(Event::ActiveTransitionChanged(self.active_transition));
+                    // Find transition.
+                    if next.is_none() {
+                        next = self.find_transition(parameters, animations);
+                    }
 
-                        break;
+                    if next.is_some() {
+                        // The handler state was left by a regular transition.
+                        self.cancel_interrupt();
                     }
                 }
+
+                if let Some(next) = next {
+                    self.begin_transition(next, animations);
+                }
             }
 
+            self.apply_looping_overrides(animations);
+            self.synchronize_animations(animations, animation_dt);
+
             // Double check for active transition because we can have empty machine.
             if self.active_transition.is_some() {
                 let transition = &mut self.transitions[self.active_transition];
 
                 // Blend between source and dest states.
-                if let Some(source_pose) = self.states[transition.source()].pose(&self.nodes) {
+                let mut source_motion = None;
+                if let Some(frozen_pose) = self.frozen_source_pose.as_ref() {
+                    self.final_pose
+                        .blend_with(frozen_pose, 1.0 - transition.blend_factor());
+                } else if let Some(source_pose) = self.states[transition.source()].pose(&self.nodes)
+                {
                     self.final_pose
                         .blend_with(&source_pose, 1.0 - transition.blend_factor());
+                    source_motion = source_pose.root_motion().cloned();
                 }
+                let mut dest_motion = None;
                 if let Some(dest_pose) = self.states[transition.dest()].pose(&self.nodes) {
                     self.final_pose
                         .blend_with(&dest_pose, transition.blend_factor());
+                    dest_motion = dest_pose.root_motion().cloned();
                 }
 
-                transition.update(dt);
+                // Root motion of each state contributes with the same weight as its pose.
+                let root_motion = if source_motion.is_none() && dest_motion.is_none() {
+                    None
+                } else {
+                    let mut root_motion = source_motion.unwrap_or_default();
+                    root_motion
+                        .blend_with(&dest_motion.unwrap_or_default(), transition.blend_factor());
+                    Some(root_motion)
+                };
+                self.final_pose.set_root_motion(root_motion);
 
-                if transition.is_done() {
-                    transition.reset();
+                if let Some(next) = self.find_transition_interrupt(parameters, animations) {
+                    self.interrupt_transition(next, animations);
+                } else {
+                    let transition = &mut self.transitions[self.active_transition];
 
-                    self.active_transition = Handle::NONE;
-                    self.events
-                        .push(Event::ActiveTransitionChanged(self.active_transition));
+                    transition.update(dt);
 
-                    self.active_state = transition.dest();
-                    self.events.push(Event::ActiveStateChanged {
-                        prev: transition.source(),
-                        new: transition.dest(),
-                    });
+                    if transition.is_done() {
+                        transition.reset();
+
+                        let completed = self.active_transition;
+                        self.active_transition = Handle::NONE;
+                        self.events
+                            .push(Event::ActiveTransitionChanged(self.active_transition));
+
+                        self.active_state = transition.dest();
+                        self.active_state_time = 0.0;
+                        self.events.push(Event::ActiveStateChanged {
+                            prev: transition.source(),
+                            new: transition.dest(),
+                        });
+
+                        if self.debug {
+                            Log::writeln(
+                                MessageKind::Information,
+                                format!(
+                                    "Active state changed: {}",
+                                    self.states[self.active_state].name
+                                ),
+                            );
+                        }
 
-                    if self.debug {
-                        Log::writeln(
-                            MessageKind::Information,
-                            format!(
-                                "Active state changed: {}",
-                                self.states[self.active_state].name
-                            ),
-                        );
+                        self.finish_interrupt_transition(completed);
+                        self.release_any_state_transitions();
+                        self.frozen_source_pose = None;
                     }
                 }
             } else {
@@ -673,9 +2221,20 @@ impl<T: EntityId> MachineLayer<T> {
                 if let Some(active_state_pose) = self.states[self.active_state].pose(&self.nodes) {
                     active_state_pose.clone_into(&mut self.final_pose);
                 }
+
+                if self.ended_state != self.active_state
+                    && self.states[self.active_state].looping == Some(false)
+                    && self.is_all_animations_of_state_ended(self.active_state, animations)
+                {
+                    self.ended_state = self.active_state;
+                    self.events
+                        .push(Event::StatePlaybackEnded(self.active_state));
+                }
             }
         }
 
+        self.merged_parameters = merged_parameters;
+
         self.final_pose
             .poses_mut()
             .retain(|h, _| self.mask.should_animate(*h));
@@ -683,3 +2242,813 @@ impl<T: EntityId> MachineLayer<T> {
         &self.final_pose
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            pool::{ErasedHandle, Handle},
+            visitor::{Visit, Visitor},
+        },
+        machine::{
+            transition::LogicNode, BlendAnimations, BlendPose, Event, MachineLayer,
+            MachineValidationIssue, Parameter, ParameterContainer, PlayAnimation, PoseNode,
+            PoseWeight, State, Transition, TransitionInterruptSource, TransitionTime,
+        },
+        Animation, AnimationContainer, RootMotionSettings,
+    };
+
+    #[test]
+    fn test_subgraph_round_trip() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+
+        let walk = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::new(
+            1, 1,
+        ))));
+        let aim = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::new(
+            2, 1,
+        ))));
+        let blend = layer.add_node(PoseNode::BlendAnimations(BlendAnimations::new(vec![
+            BlendPose::new(PoseWeight::Constant(0.5), walk),
+            BlendPose::new(PoseWeight::Constant(0.5), aim),
+        ])));
+        let idle = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::new(
+            3, 1,
+        ))));
+
+        let walk_state = layer.add_state(State::new("Walk", blend));
+        let idle_state = layer.add_state(State::new("Idle", idle));
+        let other_state = layer.add_state(State::new("Other", walk));
+
+        let internal = layer.add_transition(Transition::new(
+            "Walk->Idle",
+            walk_state,
+            idle_state,
+            1.0,
+            "WalkToIdle",
+        ));
+        // Goes to a state, that is not copied, so it must be dropped.
+        layer.add_transition(Transition::new(
+            "Idle->Other",
+            idle_state,
+            other_state,
+            1.0,
+            "IdleToOther",
+        ));
+
+        let subgraph = layer.extract_subgraph(&[walk_state, idle_state, walk_state]);
+        assert_eq!(subgraph.states.len(), 2);
+        assert_eq!(subgraph.nodes.len(), 4);
+        assert_eq!(subgraph.transitions.len(), 1);
+
+        // Paste into another layer.
+        let mut other_layer = MachineLayer::<ErasedHandle>::new();
+        let remap = other_layer.insert_subgraph(subgraph.clone());
+        assert_eq!(other_layer.states().alive_count(), 2);
+        assert_eq!(other_layer.nodes().alive_count(), 4);
+        assert_eq!(other_layer.transitions().alive_count(), 1);
+
+        let new_walk_state = remap.state(walk_state);
+        let new_blend = other_layer.state(new_walk_state).root;
+        assert_eq!(new_blend, remap.node(blend));
+        assert_eq!(
+            other_layer.node(new_blend).children(),
+            vec![remap.node(walk), remap.node(aim)]
+        );
+
+        let new_transition = other_layer.transition(remap.transition(internal));
+        assert_eq!(new_transition.source(), new_walk_state);
+        assert_eq!(new_transition.dest(), remap.state(idle_state));
+
+        // Copying the pasted states back must produce the same sub-graph, except for the handles.
+        let round_trip = other_layer.extract_subgraph(&[new_walk_state, remap.state(idle_state)]);
+        for (handle, state) in subgraph.states.iter() {
+            let (_, copy) = round_trip
+                .states
+                .iter()
+                .find(|(h, _)| *h == remap.state(*handle))
+                .unwrap();
+            assert_eq!(copy.name, state.name);
+            assert_eq!(copy.root, remap.node(state.root));
+        }
+        for (handle, node) in subgraph.nodes.iter() {
+            let (_, copy) = round_trip
+                .nodes
+                .iter()
+                .find(|(h, _)| *h == remap.node(*handle))
+                .unwrap();
+            let children = node
+                .children()
+                .into_iter()
+                .map(|c| remap.node(c))
+                .collect::<Vec<_>>();
+            assert_eq!(copy.children(), children);
+        }
+        assert_eq!(round_trip.transitions.len(), 1);
+
+        // Pasting into the same layer creates new entities.
+        let remap = layer.insert_subgraph(subgraph);
+        assert_eq!(layer.states().alive_count(), 5);
+        assert_ne!(remap.state(walk_state), walk_state);
+    }
+
+    #[test]
+    fn test_any_state_transition() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+
+        let idle = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let idle_state = layer.add_state(State::new("Idle", idle));
+        let walk = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let walk_state = layer.add_state(State::new("Walk", walk));
+        let hit = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let hit_state = layer.add_state(State::new("Hit", hit));
+
+        layer.add_transition(Transition::new(
+            "Idle->Walk",
+            idle_state,
+            walk_state,
+            0.5,
+            "Walk",
+        ));
+        let any = layer.add_any_state_transition(
+            Transition::new("Any->Hit", Handle::NONE, hit_state, 0.5, "Hit"),
+            true,
+        );
+        layer.set_entry_state(idle_state);
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Walk", Parameter::Rule(true));
+        parameters.add("Hit", Parameter::Rule(true));
+
+        // Any-state transitions are checked before the transitions of the active state.
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(layer.active_transition(), any.transition);
+        assert_eq!(layer.transition(any.transition).source(), idle_state);
+        assert!(std::iter::from_fn(|| layer.pop_event())
+            .any(|e| e == Event::ActiveTransitionChanged(any.transition)));
+
+        for _ in 0..10 {
+            layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        }
+        assert_eq!(layer.active_state(), hit_state);
+        assert!(layer.active_transition().is_none());
+        assert!(layer.transition(any.transition).source().is_none());
+
+        // The destination state is excluded, so the transition must not restart.
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert!(layer.active_transition().is_none());
+
+        assert!(layer.remove_any_state_transition(&any));
+        assert!(layer.any_state_transitions().is_empty());
+        assert_eq!(layer.transitions().alive_count(), 1);
+    }
+
+    #[test]
+    fn test_root_motion_settings() {
+        let mut animations = AnimationContainer::new();
+        let animation = animations.add(Animation::default());
+
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+        let node = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(animation)));
+        let state = layer.add_state(State::new("Walk", node));
+        layer.set_entry_state(state);
+
+        let settings = RootMotionSettings {
+            node: ErasedHandle::new(1, 1),
+            ignore_y_movement: true,
+            ..Default::default()
+        };
+        layer.set_root_motion_settings(Some(settings.clone()));
+
+        layer.evaluate_pose(&mut animations, &ParameterContainer::default(), 0.1);
+        assert_eq!(
+            animations[animation].root_motion_settings_ref(),
+            Some(&settings)
+        );
+        // The animation has no root motion yet.
+        assert!(layer.pose().root_motion().is_none());
+    }
+
+    #[test]
+    fn test_relative_transition_time() {
+        let mut animations = AnimationContainer::new();
+        let mut run_animation = Animation::default();
+        run_animation.set_time_slice(0.0..2.0);
+        let run_animation = animations.add(run_animation);
+
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+        let idle = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let idle_state = layer.add_state(State::new("Idle", idle));
+        let run = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(run_animation)));
+        let run_state = layer.add_state(State::new("Run", run));
+        layer.set_entry_state(idle_state);
+
+        let mut transition = Transition::new("Idle->Run", idle_state, run_state, 1.0, "Run");
+        transition.set_time(TransitionTime::RelativeToTarget(0.25));
+        let transition = layer.add_transition(transition);
+
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Run", Parameter::Rule(true));
+
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(layer.active_transition(), transition);
+        assert_eq!(layer.transition(transition).transition_time(), 0.5);
+
+        // Changing the animation length changes the transition time on the next activation.
+        animations[run_animation].set_time_slice(0.0..4.0);
+        layer.reset();
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(layer.transition(transition).transition_time(), 1.0);
+    }
+
+    #[test]
+    fn test_sync_group_scales_time_delta() {
+        let mut animations = AnimationContainer::new();
+        let mut walk_animation = Animation::default();
+        walk_animation.set_time_slice(0.0..1.0);
+        walk_animation.set_time_position(0.25);
+        let walk_animation = animations.add(walk_animation);
+        let mut run_animation = Animation::default();
+        run_animation.set_time_slice(0.0..2.0);
+        let run_animation = animations.add(run_animation);
+
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+        let walk = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(walk_animation)));
+        let mut walk_state = State::new("Walk", walk);
+        walk_state.set_sync_group("Locomotion");
+        let walk_state = layer.add_state(walk_state);
+        let run = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(run_animation)));
+        let mut run_state = State::new("Run", run);
+        run_state.set_sync_group("Locomotion");
+        let run_state = layer.add_state(run_state);
+        layer.set_entry_state(walk_state);
+        layer.add_transition(Transition::new(
+            "Walk->Run",
+            walk_state,
+            run_state,
+            10.0,
+            "Run",
+        ));
+
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Run", Parameter::Rule(true));
+
+        // Walk leads the blend, run must reach the phase of walk after the next update (0.35) without jumps.
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(animations[run_animation].time_position(), 0.0);
+        assert!((animations[run_animation].speed() - 7.0).abs() < 1.0e-4);
+
+        for animation in [walk_animation, run_animation] {
+            animations[animation].tick(0.1);
+        }
+        assert!((animations[run_animation].time_position() - 0.7).abs() < 1.0e-4);
+
+        // The phases are aligned, so run advances at the normalized rate of walk.
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert!((animations[run_animation].speed() - 2.0).abs() < 1.0e-4);
+        assert_eq!(animations[walk_animation].speed(), 1.0);
+
+        // The original speed is restored when the state leaves the group.
+        layer.state_mut(run_state).set_sync_group("");
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(animations[run_animation].speed(), 1.0);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+        let idle_state = layer.add_state(State::new("Idle", Handle::NONE));
+        let walk_state = layer.add_state(State::new("Walk", Handle::NONE));
+        let orphan_state = layer.add_state(State::new("Orphan", Handle::NONE));
+
+        let mut parameters = ParameterContainer::default();
+        assert_eq!(
+            layer.validate(&parameters),
+            [
+                MachineValidationIssue::NoEntryState,
+                MachineValidationIssue::DeadEndState(idle_state),
+                MachineValidationIssue::DeadEndState(walk_state),
+                MachineValidationIssue::DeadEndState(orphan_state),
+            ]
+        );
+
+        layer.set_entry_state(idle_state);
+        layer.add_transition(Transition::new(
+            "Idle->Walk",
+            idle_state,
+            walk_state,
+            0.5,
+            "Walk",
+        ));
+        let walk_to_idle = layer.add_transition(Transition::new(
+            "Walk->Idle",
+            walk_state,
+            idle_state,
+            0.5,
+            "Idle",
+        ));
+        parameters.add("Walk", Parameter::Rule(true));
+        parameters.add("Idle", Parameter::Weight(1.0));
+
+        assert_eq!(
+            layer.validate(&parameters),
+            [
+                MachineValidationIssue::UnknownRuleParameter {
+                    transition: walk_to_idle,
+                    name: "Idle".to_string()
+                },
+                MachineValidationIssue::UnreachableState(orphan_state),
+                MachineValidationIssue::DeadEndState(orphan_state),
+            ]
+        );
+
+        // Local parameters shadow the parameters of the machine, any-state transitions make the orphan reachable.
+        layer.set_local_parameter("Idle", Parameter::Rule(false));
+        layer.add_any_state_transition(
+            Transition::new("Any->Orphan", Handle::NONE, orphan_state, 0.5, "Walk"),
+            true,
+        );
+        assert_eq!(
+            layer.validate(&parameters),
+            [MachineValidationIssue::DeadEndState(orphan_state)]
+        );
+    }
+
+    #[test]
+    fn test_time_scale() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+        assert_eq!(layer.time_scale(), 1.0);
+        assert_eq!(MachineLayer::<ErasedHandle>::default().time_scale(), 1.0);
+
+        let idle_state = layer.add_state(State::new("Idle", Handle::NONE));
+        let walk_state = layer.add_state(State::new("Walk", Handle::NONE));
+        let transition = layer.add_transition(Transition::new(
+            "Idle->Walk",
+            idle_state,
+            walk_state,
+            1.0,
+            "Walk",
+        ));
+        layer.set_entry_state(idle_state);
+        layer.set_time_scale(0.5);
+
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Walk", Parameter::Rule(true));
+
+        layer.evaluate_pose(&mut AnimationContainer::new(), &parameters, 0.2);
+        assert_eq!(layer.active_transition(), transition);
+        assert_eq!(layer.transition(transition).blend_factor(), 0.1);
+
+        layer.set_time_scale(-1.0);
+        assert_eq!(layer.time_scale(), 0.0);
+    }
+
+    #[test]
+    fn test_query_by_name() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+
+        let idle = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let idle_state = layer.add_state(State::new("Idle", idle));
+        let walk = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let walk_state = layer.add_state(State::new("Walk", walk));
+        let idle_to_walk = layer.add_transition(Transition::new(
+            "Idle->Walk",
+            idle_state,
+            walk_state,
+            0.5,
+            "Walk",
+        ));
+        layer.set_entry_state(idle_state);
+
+        assert_eq!(layer.find_state_by_name("Walk"), walk_state);
+        assert!(layer.find_state_by_name("Run").is_none());
+        assert_eq!(layer.find_transition_by_name("Idle->Walk"), idle_to_walk);
+        assert!(layer.find_transition_by_name("Walk->Idle").is_none());
+
+        assert_eq!(layer.active_state_name(), "Idle");
+        assert_eq!(layer.active_transition_name(), "");
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Walk", Parameter::Rule(true));
+
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(layer.active_state_name(), "");
+        assert_eq!(layer.active_transition_name(), "Idle->Walk");
+    }
+
+    #[test]
+    fn test_weighted_transitions() {
+        fn make_layer(seed: u64) -> (MachineLayer<ErasedHandle>, [Handle<State<ErasedHandle>>; 3]) {
+            let mut layer = MachineLayer::<ErasedHandle>::new();
+
+            let mut add_state = |name: &str| {
+                let node =
+                    layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+                layer.add_state(State::new(name, node))
+            };
+            let idle = add_state("Idle");
+            let scratch = add_state("IdleScratch");
+            let look_around = add_state("IdleLookAround");
+
+            for (dest, weight) in [(scratch, 0.7), (look_around, 0.3)] {
+                let handle =
+                    layer.add_transition(Transition::new("Variation", idle, dest, 0.0, "Bored"));
+                layer
+                    .transition_mut(handle)
+                    .set_selection_weight(Some(weight));
+                layer.add_transition(Transition::new("Back", dest, idle, 0.0, "Rested"));
+            }
+            layer.set_entry_state(idle);
+            layer.set_rng_seed(Some(seed));
+
+            (layer, [idle, scratch, look_around])
+        }
+
+        let mut animations = AnimationContainer::new();
+        let mut bored = ParameterContainer::default();
+        bored.add("Bored", Parameter::Rule(true));
+
+        let mut pick = |layer: &mut MachineLayer<ErasedHandle>| {
+            layer.reset();
+            for _ in 0..3 {
+                layer.evaluate_pose(&mut animations, &bored, 0.1);
+            }
+            layer.active_state()
+        };
+
+        let (mut layer, [idle, scratch, look_around]) = make_layer(42);
+        let picks = (0..200).map(|_| pick(&mut layer)).collect::<Vec<_>>();
+        let scratches = picks.iter().filter(|s| **s == scratch).count();
+        let look_arounds = picks.iter().filter(|s| **s == look_around).count();
+        assert!(!picks.contains(&idle));
+        assert_eq!(scratches + look_arounds, picks.len());
+        assert!(scratches > look_arounds && look_arounds > 0);
+
+        // The same seed gives the same choices.
+        let (mut other, _) = make_layer(42);
+        assert_eq!(
+            picks,
+            (0..200).map(|_| pick(&mut other)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_interruptible_transition() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+
+        let idle = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let idle_state = layer.add_state(State::new("Idle", idle));
+        let attack = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let attack_state = layer.add_state(State::new("Attack", attack));
+        let dodge = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let dodge_state = layer.add_state(State::new("Dodge", dodge));
+
+        let idle_to_attack = layer.add_transition(Transition::new(
+            "Idle->Attack",
+            idle_state,
+            attack_state,
+            1.0,
+            "Attack",
+        ));
+        let attack_to_dodge = layer.add_transition(Transition::new(
+            "Attack->Dodge",
+            attack_state,
+            dodge_state,
+            0.2,
+            "Dodge",
+        ));
+        layer.set_entry_state(idle_state);
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Attack", Parameter::Rule(true));
+        parameters.add("Dodge", Parameter::Rule(true));
+        parameters.add("CanCancel", Parameter::Rule(false));
+
+        // Transitions are not interruptible by default.
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(layer.active_transition(), idle_to_attack);
+
+        let transition = layer.transition_mut(idle_to_attack);
+        transition.set_interruptible(true);
+        transition.set_interrupt_source(TransitionInterruptSource::Destination);
+        transition.set_interrupt_rules(Some(LogicNode::Parameter("CanCancel".to_string())));
+
+        // Interrupt rules do not allow the interruption.
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(layer.active_transition(), idle_to_attack);
+        while layer.pop_event().is_some() {}
+
+        parameters.set_rule("CanCancel", true);
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(layer.active_transition(), attack_to_dodge);
+        assert_eq!(layer.transition(attack_to_dodge).blend_factor(), 0.0);
+        assert_eq!(layer.transition(idle_to_attack).blend_factor(), 0.0);
+        let events = std::iter::from_fn(|| layer.pop_event()).collect::<Vec<_>>();
+        assert!(events.contains(&Event::StateLeave(attack_state)));
+        assert!(events.contains(&Event::ActiveTransitionChanged(attack_to_dodge)));
+
+        for _ in 0..3 {
+            layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        }
+        assert_eq!(layer.active_state(), dodge_state);
+        assert!(layer.active_transition().is_none());
+    }
+
+    #[test]
+    fn test_active_transition_progress() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+
+        let idle = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let idle_state = layer.add_state(State::new("Idle", idle));
+        let walk = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let walk_state = layer.add_state(State::new("Walk", walk));
+        layer.add_transition(Transition::new(
+            "Idle->Walk",
+            idle_state,
+            walk_state,
+            1.0,
+            "Walk",
+        ));
+        layer.set_entry_state(idle_state);
+
+        assert_eq!(layer.active_transition_progress(), None);
+        assert_eq!(layer.active_transition_remaining(), None);
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Walk", Parameter::Rule(true));
+
+        layer.evaluate_pose(&mut animations, &parameters, 0.25);
+        assert_eq!(layer.active_transition_progress(), Some(0.25));
+        assert_eq!(layer.active_transition_remaining(), Some(0.75));
+    }
+
+    #[test]
+    fn test_frozen_layer() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+
+        let idle = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let idle_state = layer.add_state(State::new("Idle", idle));
+        let walk = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let walk_state = layer.add_state(State::new("Walk", walk));
+        layer.add_transition(Transition::new(
+            "Idle->Walk",
+            idle_state,
+            walk_state,
+            0.1,
+            "Walk",
+        ));
+        layer.set_entry_state(idle_state);
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Walk", Parameter::Rule(false));
+
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        while layer.pop_event().is_some() {}
+
+        layer.set_frozen(true);
+        parameters.set_rule("Walk", true);
+        for _ in 0..5 {
+            layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        }
+        assert_eq!(layer.active_state(), idle_state);
+        assert!(layer.active_transition().is_none());
+        assert_eq!(layer.active_state_time(), 0.1);
+        assert!(layer.pop_event().is_none());
+
+        layer.set_frozen(false);
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert!(layer.active_transition().is_some());
+    }
+
+    #[test]
+    fn test_versioned_fields_round_trip() {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+        layer.set_time_scale(0.5);
+        layer.set_rng_seed(Some(42));
+
+        let mut visitor = Visitor::new();
+        layer.visit("Layer", &mut visitor).unwrap();
+        let bytes = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&bytes).unwrap();
+        let mut loaded = MachineLayer::<ErasedHandle>::default();
+        loaded.visit("Layer", &mut visitor).unwrap();
+
+        assert_eq!(loaded.time_scale(), 0.5);
+        assert_eq!(loaded.rng_seed(), Some(42));
+    }
+
+    #[test]
+    fn test_transition_versions() {
+        let mut transition = Transition::<ErasedHandle>::new(
+            "Idle->Walk",
+            Handle::new(1, 1),
+            Handle::NONE,
+            0.3,
+            "Walk",
+        );
+        transition.set_min_source_time(0.2);
+        transition.set_selection_weight(Some(2.0));
+        transition.set_interruptible(true);
+
+        let mut visitor = Visitor::new();
+        transition.visit("Transition", &mut visitor).unwrap();
+        let bytes = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&bytes).unwrap();
+        let mut loaded = Transition::<ErasedHandle>::default();
+        loaded.visit("Transition", &mut visitor).unwrap();
+        assert_eq!(loaded, transition);
+
+        // The first version has no explicit version and stores an absolute time and a single rule.
+        let mut visitor = Visitor::new();
+        {
+            let mut region = visitor.enter_region("Transition").unwrap();
+            let mut name = "Idle->Walk".to_string();
+            name.visit("Name", &mut region).unwrap();
+            let mut transition_time = 0.3f32;
+            transition_time
+                .visit("TransitionTime", &mut region)
+                .unwrap();
+            let mut source = Handle::<State<ErasedHandle>>::new(1, 1);
+            source.visit("Source", &mut region).unwrap();
+            let mut dest = Handle::<State<ErasedHandle>>::NONE;
+            dest.visit("Dest", &mut region).unwrap();
+            let mut blend_factor = 0.0f32;
+            blend_factor.visit("BlendFactor", &mut region).unwrap();
+            let mut invert_rule = false;
+            invert_rule.visit("InvertRule", &mut region).unwrap();
+            let mut rule = "Walk".to_string();
+            rule.visit("Rule", &mut region).unwrap();
+        }
+        let bytes = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&bytes).unwrap();
+        let mut loaded = Transition::<ErasedHandle>::default();
+        loaded.visit("Transition", &mut visitor).unwrap();
+        assert_eq!(loaded.time(), TransitionTime::Absolute(0.3));
+        assert_eq!(loaded.transition_time(), 0.3);
+        assert_eq!(
+            loaded.condition(),
+            &LogicNode::Parameter("Walk".to_string())
+        );
+        assert_eq!(loaded.min_source_time(), 0.0);
+        assert_eq!(loaded.selection_weight(), None);
+    }
+
+    fn make_set_active_state_layer() -> (
+        MachineLayer<ErasedHandle>,
+        [Handle<State<ErasedHandle>>; 3],
+        Handle<Transition<ErasedHandle>>,
+    ) {
+        let mut layer = MachineLayer::<ErasedHandle>::new();
+        let mut states = [Handle::NONE; 3];
+        for (state, name) in states.iter_mut().zip(["Idle", "Walk", "Run"]) {
+            let node = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+            *state = layer.add_state(State::new(name, node));
+        }
+        let transition = layer.add_transition(Transition::new(
+            "Idle->Walk",
+            states[0],
+            states[1],
+            1.0,
+            "Walk",
+        ));
+        layer.set_entry_state(states[0]);
+        (layer, states, transition)
+    }
+
+    #[test]
+    fn test_set_active_state_idle() {
+        let (mut layer, [idle, walk, _], _) = make_set_active_state_layer();
+
+        assert!(layer.set_active_state(walk));
+        assert_eq!(layer.active_state(), walk);
+        assert_eq!(
+            layer.drain_events().collect::<Vec<_>>(),
+            [
+                Event::StateLeave(idle),
+                Event::StateEnter(walk),
+                Event::ActiveStateChanged {
+                    prev: idle,
+                    new: walk
+                }
+            ]
+        );
+
+        // Setting the same state does nothing.
+        assert!(layer.set_active_state(walk));
+        assert_eq!(layer.drain_events().count(), 0);
+    }
+
+    #[test]
+    fn test_set_active_state_during_transition() {
+        let (mut layer, [idle, walk, run], idle_to_walk) = make_set_active_state_layer();
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Walk", Parameter::Rule(true));
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(layer.active_transition(), idle_to_walk);
+        layer.drain_events().for_each(drop);
+
+        // The destination of the transition was entered, so it must be left.
+        assert!(layer.set_active_state(run));
+        assert_eq!(layer.active_state(), run);
+        assert!(layer.active_transition().is_none());
+        assert_eq!(
+            layer.drain_events().collect::<Vec<_>>(),
+            [
+                Event::ActiveTransitionChanged(Handle::NONE),
+                Event::StateLeave(walk),
+                Event::StateEnter(run),
+                Event::ActiveStateChanged {
+                    prev: idle,
+                    new: run
+                }
+            ]
+        );
+
+        // The destination of the transition is already entered, no enter/leave events are needed.
+        assert!(layer.set_active_state(idle));
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(layer.active_transition(), idle_to_walk);
+        layer.drain_events().for_each(drop);
+        assert!(layer.set_active_state(walk));
+        assert_eq!(
+            layer.drain_events().collect::<Vec<_>>(),
+            [
+                Event::ActiveTransitionChanged(Handle::NONE),
+                Event::ActiveStateChanged {
+                    prev: idle,
+                    new: walk
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_active_state_invalid_handle() {
+        let (mut layer, [idle, ..], _) = make_set_active_state_layer();
+
+        assert!(!layer.set_active_state(Handle::new(123, 1)));
+        assert_eq!(layer.active_state(), idle);
+        assert_eq!(layer.drain_events().count(), 0);
+    }
+
+    #[test]
+    fn test_local_parameter_changes_transition() {
+        let (mut layer, [idle, walk, _], _) = make_set_active_state_layer();
+        let (mut other, _, _) = make_set_active_state_layer();
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Walk", Parameter::Rule(true));
+
+        layer.set_local_parameter("Walk", Parameter::Rule(false));
+        for _ in 0..15 {
+            layer.evaluate_pose(&mut animations, &parameters, 0.1);
+            other.evaluate_pose(&mut animations, &parameters, 0.1);
+        }
+        assert_eq!(layer.active_state(), idle);
+        assert_eq!(other.active_state(), walk);
+
+        // Removal of the local parameter brings the parameter of the machine back.
+        assert_eq!(
+            layer.remove_local_parameter("Walk"),
+            Some(Parameter::Rule(false))
+        );
+        for _ in 0..15 {
+            layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        }
+        assert_eq!(layer.active_state(), walk);
+    }
+
+    #[test]
+    fn test_local_parameters_in_computed_parameters() {
+        let (mut layer, _, _) = make_set_active_state_layer();
+
+        let mut animations = AnimationContainer::new();
+        let mut parameters = ParameterContainer::default();
+        parameters.add("Speed", Parameter::Weight(2.0));
+        parameters.add("MaxSpeed", Parameter::Weight(4.0));
+        parameters
+            .add_computed("NormalizedSpeed", "Speed / MaxSpeed")
+            .unwrap();
+        parameters.update_computed();
+
+        layer.set_local_parameter("Speed", Parameter::Weight(4.0));
+        layer.evaluate_pose(&mut animations, &parameters, 0.1);
+        assert_eq!(
+            layer.merged_parameters.get_weight("NormalizedSpeed"),
+            Some(1.0)
+        );
+        assert_eq!(parameters.get_weight("NormalizedSpeed"), Some(0.5));
+    }
+}
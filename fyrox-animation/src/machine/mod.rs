@@ -8,25 +8,33 @@
 use crate::{
     core::{
         reflect::prelude::*,
-        visitor::{Visit, VisitResult, Visitor},
+        visitor::{Visit, VisitError, VisitResult, Visitor},
     },
-    AnimationContainer, AnimationPose, EntityId,
+    AnimationContainer, AnimationPose, EntityId, RootMotion,
 };
 
 pub use event::Event;
+pub use expression::{ExpressionError, ParameterExpression};
 use fyrox_core::{find_by_name_mut, find_by_name_ref};
-pub use layer::MachineLayer;
+pub use layer::{
+    AnyStateTransition, HandleRemap, Interrupt, MachineLayer, MachineSubgraph,
+    MachineValidationIssue, NodeDependents,
+};
 pub use mask::LayerMask;
 pub use node::{
-    blend::{BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput},
+    blend::{AdditiveBlend, BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput},
+    mirror::{BonePair, MirrorAxis, MirrorPose},
     play::PlayAnimation,
     AnimationPoseSource, PoseNode,
 };
-pub use parameter::{Parameter, ParameterContainer, PoseWeight};
-pub use state::State;
-pub use transition::Transition;
+pub use parameter::{
+    ComputedParameterDefinition, Parameter, ParameterContainer, ParameterSnapshot, PoseWeight,
+};
+pub use state::{State, StateMetadata};
+pub use transition::{Transition, TransitionCondition, TransitionInterruptSource, TransitionTime};
 
 pub mod event;
+pub mod expression;
 pub mod layer;
 pub mod mask;
 pub mod node;
@@ -34,6 +42,19 @@ pub mod parameter;
 pub mod state;
 pub mod transition;
 
+/// A point of the machine evaluation, at which a pose is passed to the injection callback of
+/// [`Machine::evaluate_pose_with`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PoseInjectionPoint {
+    /// The pose of a layer with the given index was blended into the pose of the machine. The callback receives
+    /// the accumulated pose of the machine (all the layers up to this one), the following layers are blended
+    /// on top of the modified pose.
+    Layer(usize),
+    /// All the layers were blended and the pose is ready to be applied. The callback receives the final pose
+    /// of the machine.
+    Final,
+}
+
 /// Animation blending state machine is used to blend multiple animation as well as perform automatic smooth transitions
 /// between states.
 ///
@@ -167,16 +188,95 @@ pub mod transition;
 /// locomotion machine will take control over lower body and combat machine will control upper body.
 ///
 /// Complex state machines quite hard to create from code, you should use ABSM editor instead whenever possible.
-#[derive(Default, Debug, Visit, Reflect, Clone, PartialEq)]
+///
+/// # Serialization and versioning
+///
+/// Machines and their layers are serialized with an explicit version, the data saved by older versions of the engine
+/// is upgraded on load. Version history of machines:
+///
+/// - `1` - the data without explicit version. Very old machines (before layers were added) stored their states, nodes
+/// and transitions directly in the machine, such machines are converted to a machine with a single layer.
+/// - `2` - explicit version was added.
+///
+/// Layers and transitions have their own versions, so the data added to them does not change the version of machines.
+///
+/// When the layout of the data changes, the version must be increased and the code that converts the data of the previous
+/// version must be added to respective `Visit` implementation, so no content is lost on engine upgrades.
+///
+/// # Reduced update rate
+///
+/// Evaluating a machine every frame could be expensive when there are hundreds of animated characters. Machines of distant
+/// or unimportant characters could be evaluated at a lower rate using [`Machine::set_update_rate`] (for example, by a
+/// distance-based LOD system of your game). In this case the machine evaluates its layers only a few times per second and
+/// the final pose is interpolated between two last evaluated poses every frame, so the motion stays smooth.
+///
+/// The tradeoff is latency and precision: the final pose lags behind the actual animations for up to one update interval,
+/// fast motions are smoothed out and root motion is interpolated as well. Layer events (state and transition changes) are
+/// not lost when the updates are skipped, the time of the skipped frames is accumulated and the layers are advanced by
+/// whole update intervals (the rest of the time is kept for the next evaluation), so the events are produced by that
+/// evaluation, up to one update interval later than usual. Animations themselves are
+/// updated by their owner (for example, animation player), so their own events are not affected.
+#[derive(Default, Debug, Reflect, Clone, PartialEq)]
 pub struct Machine<T: EntityId> {
     parameters: ParameterContainer,
 
-    #[visit(optional)]
     layers: Vec<MachineLayer<T>>,
 
-    #[visit(skip)]
     #[reflect(hidden)]
     final_pose: AnimationPose<T>,
+
+    #[reflect(hidden)]
+    update_rate: Option<f32>,
+
+    #[reflect(hidden)]
+    sampling: PoseSampling<T>,
+}
+
+// Two last evaluated poses of a machine with reduced update rate and the time passed since the last evaluation.
+#[derive(Default, Debug, Clone, PartialEq)]
+struct PoseSampling<T: EntityId> {
+    prev_pose: AnimationPose<T>,
+    next_pose: AnimationPose<T>,
+    elapsed: f32,
+    evaluated: bool,
+}
+
+const VERSION: u8 = 2;
+
+// Manual implementation of the trait to be able to upgrade the data saved by older versions.
+impl<T: EntityId> Visit for Machine<T> {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        // The data without version is the data of the first version.
+        let mut version = if region.is_reading() { 1 } else { VERSION };
+        let _ = version.visit("Version", &mut region);
+
+        match version {
+            1 => {
+                self.parameters.visit("Parameters", &mut region)?;
+                if self.layers.visit("Layers", &mut region).is_err() {
+                    // The machine was saved before layers were added, its graph is stored directly in the
+                    // machine and it becomes the only layer.
+                    let mut layer = MachineLayer::new();
+                    if layer.visit_graph(&mut region).is_ok() {
+                        self.layers = vec![layer];
+                    }
+                }
+            }
+            VERSION => {
+                self.parameters.visit("Parameters", &mut region)?;
+                self.layers.visit("Layers", &mut region)?;
+            }
+            _ => {
+                return Err(VisitError::User(format!(
+                    "Unsupported machine version {version}!"
+                )))
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<T: EntityId> Machine<T> {
@@ -187,6 +287,8 @@ impl<T: EntityId> Machine<T> {
             parameters: Default::default(),
             layers: vec![MachineLayer::new()],
             final_pose: Default::default(),
+            update_rate: None,
+            sampling: Default::default(),
         }
     }
 
@@ -229,6 +331,29 @@ impl<T: EntityId> Machine<T> {
         &mut self.parameters
     }
 
+    /// Restores default values of all parameters of the machine. See [`ParameterContainer::reset_to_defaults`]
+    /// for more info.
+    #[inline]
+    pub fn reset_parameters(&mut self) {
+        self.parameters.reset_to_defaults();
+    }
+
+    /// Resets every layer of the machine (see [`MachineLayer::reset`]) and, optionally, restores default values
+    /// of all parameters (see [`Self::reset_parameters`]). It could be used to reuse an entity with the machine
+    /// (for example, from a pool of entities) without rebuilding the machine.
+    #[inline]
+    pub fn reset(&mut self, reset_parameters: bool) {
+        for layer in self.layers.iter_mut() {
+            layer.reset();
+        }
+
+        if reset_parameters {
+            self.reset_parameters();
+        }
+
+        self.sampling = Default::default();
+    }
+
     /// Adds a new layer to the animation blending state machine.
     #[inline]
     pub fn add_layer(&mut self, layer: MachineLayer<T>) {
@@ -283,28 +408,455 @@ impl<T: EntityId> Machine<T> {
         find_by_name_mut(self.layers.iter_mut().enumerate(), name)
     }
 
+    /// Checks every layer of the machine for common authoring problems, see [`MachineLayer::validate`] for more info.
+    /// Returns a list of pairs `(layer index, issue)`, an empty list means that no problems were found.
+    pub fn validate(&self) -> Vec<(usize, MachineValidationIssue<T>)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(index, layer)| {
+                layer
+                    .validate(&self.parameters)
+                    .into_iter()
+                    .map(move |issue| (index, issue))
+            })
+            .collect()
+    }
+
+    /// Calls the given closure for every pending event of every layer of the machine, the first argument of the
+    /// closure is the index of the layer that emitted the event. Layers are processed in order and the events of
+    /// each layer are passed in the order they were emitted. The events are removed from the queues of the layers.
+    /// It is a convenient alternative to calling [`MachineLayer::pop_event`] for each layer.
+    ///
+    /// ```rust
+    /// use fyrox_animation::machine::{Event, Machine};
+    /// use fyrox_core::pool::ErasedHandle;
+    ///
+    /// fn handle_events(machine: &mut Machine<ErasedHandle>) {
+    ///     let mut entered_states = Vec::new();
+    ///     machine.poll_events(|layer_index, event| {
+    ///         if let Event::StateEnter(state) = event {
+    ///             // Play footstep sounds, etc.
+    ///             entered_states.push((layer_index, state));
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn poll_events<F>(&mut self, mut func: F)
+    where
+        F: FnMut(usize, Event<T>),
+    {
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            for event in layer.drain_events() {
+                func(index, event);
+            }
+        }
+    }
+
     /// Returns final pose of the machine.
     #[inline]
     pub fn pose(&self) -> &AnimationPose<T> {
         &self.final_pose
     }
 
-    /// Computes final animation pose that could be then applied to a set of entities graph.
+    /// Returns the root motion of the final pose of the machine (if any). It is the motion of the root node between
+    /// the last two evaluations of the machine, blended across the layers (according to their weights) and the active
+    /// transitions of the layers. See [`MachineLayer::set_root_motion_settings`] for more info.
+    #[inline]
+    pub fn root_motion(&self) -> Option<&RootMotion> {
+        self.final_pose.root_motion()
+    }
+
+    /// Sets the rate (in evaluations per second) at which the machine evaluates its layers, [`None`] means that the layers
+    /// are evaluated on every call of [`Self::evaluate_pose`] (default). Non-positive rates are treated as [`None`]. See
+    /// "Reduced update rate" section of [`Machine`] docs for more info. Returns the previous rate.
+    ///
+    /// ```rust
+    /// use fyrox_animation::machine::Machine;
+    /// use fyrox_core::pool::ErasedHandle;
+    ///
+    /// let mut machine = Machine::<ErasedHandle>::new();
+    ///
+    /// // A distant character, 10 evaluations per second is more than enough.
+    /// machine.set_update_rate(Some(10.0));
+    /// ```
+    #[inline]
+    pub fn set_update_rate(&mut self, rate: Option<f32>) -> Option<f32> {
+        let rate = rate.filter(|rate| *rate > 0.0);
+        if rate.is_some() && self.update_rate.is_none() {
+            // Start from scratch, the previously evaluated poses are outdated.
+            self.sampling.evaluated = false;
+        }
+        std::mem::replace(&mut self.update_rate, rate)
+    }
+
+    /// Returns current update rate of the machine. See [`Self::set_update_rate`] for more info.
+    #[inline]
+    pub fn update_rate(&self) -> Option<f32> {
+        self.update_rate
+    }
+
+    /// Computes final animation pose that could be then applied to a set of entities graph. If the machine has reduced
+    /// update rate (see [`Self::set_update_rate`]), the layers are evaluated only when needed and the final pose is
+    /// interpolated between two last evaluated poses.
     #[inline]
     pub fn evaluate_pose(
         &mut self,
         animations: &mut AnimationContainer<T>,
         dt: f32,
     ) -> &AnimationPose<T> {
-        self.final_pose.reset();
+        self.evaluate_pose_with(animations, dt, |_, _| {})
+    }
 
-        for layer in self.layers.iter_mut() {
-            let weight = layer.weight();
-            let pose = layer.evaluate_pose(animations, &self.parameters, dt);
+    /// Same as [`Self::evaluate_pose`], but allows to modify the pose while it is being computed. It could be used
+    /// to integrate procedural animation (IK, physics-based animation, etc.) with the machine, so the layers are
+    /// blended with the corrected pose instead of the one computed by the machine.
+    ///
+    /// # Ordering
+    ///
+    /// The callback is called with the following injection points (see [`PoseInjectionPoint`]):
+    ///
+    /// 1. [`PoseInjectionPoint::Layer`] - for every layer, in the order of layers, right after the pose of the layer
+    /// was blended (according to its weight) into the pose of the machine. The following layers are blended on top of
+    /// the modified pose. If the machine has reduced update rate (see [`Self::set_update_rate`]), these points are
+    /// passed only when the layers are actually evaluated.
+    /// 2. [`PoseInjectionPoint::Final`] - once per call, after all the layers were blended (and interpolated, in case
+    /// of reduced update rate). Changes made here affect only the returned pose, they're not seen by the layers.
+    ///
+    /// ```rust
+    /// use fyrox_animation::{
+    ///     machine::{Machine, PoseInjectionPoint},
+    ///     AnimationContainer,
+    /// };
+    /// use fyrox_core::pool::ErasedHandle;
+    ///
+    /// fn evaluate(machine: &mut Machine<ErasedHandle>, animations: &mut AnimationContainer<ErasedHandle>) {
+    ///     machine.evaluate_pose_with(animations, 1.0 / 60.0, |point, pose| {
+    ///         // Correct the pose of the base layer, so the upper-body layer is blended on top of it.
+    ///         if point == PoseInjectionPoint::Layer(0) {
+    ///             // Run IK here and modify the pose.
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn evaluate_pose_with<F>(
+        &mut self,
+        animations: &mut AnimationContainer<T>,
+        dt: f32,
+        mut injection: F,
+    ) -> &AnimationPose<T>
+    where
+        F: FnMut(PoseInjectionPoint, &mut AnimationPose<T>),
+    {
+        let Some(rate) = self.update_rate else {
+            Self::evaluate_layers(
+                &mut self.layers,
+                &mut self.parameters,
+                animations,
+                dt,
+                &mut self.final_pose,
+                &mut injection,
+            );
+            injection(PoseInjectionPoint::Final, &mut self.final_pose);
+            return &self.final_pose;
+        };
+
+        let interval = rate.recip();
+        let sampling = &mut self.sampling;
+        sampling.elapsed += dt;
+
+        if !sampling.evaluated || sampling.elapsed >= interval {
+            // The layers are advanced by whole intervals, the rest of the time is kept for the next evaluation,
+            // so the layers are not slowed down when the frame time is not a multiple of the interval.
+            let step = if sampling.evaluated {
+                (sampling.elapsed / interval).floor() * interval
+            } else {
+                sampling.elapsed
+            };
 
-            self.final_pose.blend_with(pose, weight);
+            std::mem::swap(&mut sampling.prev_pose, &mut sampling.next_pose);
+            Self::evaluate_layers(
+                &mut self.layers,
+                &mut self.parameters,
+                animations,
+                step,
+                &mut sampling.next_pose,
+                &mut injection,
+            );
+            if !sampling.evaluated {
+                sampling.next_pose.clone_into(&mut sampling.prev_pose);
+                sampling.evaluated = true;
+            }
+            sampling.elapsed = (sampling.elapsed - step).max(0.0);
         }
 
+        let t = (sampling.elapsed / interval).min(1.0);
+        sampling.prev_pose.clone_into(&mut self.final_pose);
+        self.final_pose.blend_with(&sampling.next_pose, t);
+        injection(PoseInjectionPoint::Final, &mut self.final_pose);
+
         &self.final_pose
     }
+
+    fn evaluate_layers<F>(
+        layers: &mut [MachineLayer<T>],
+        parameters: &mut ParameterContainer,
+        animations: &mut AnimationContainer<T>,
+        dt: f32,
+        pose: &mut AnimationPose<T>,
+        injection: &mut F,
+    ) where
+        F: FnMut(PoseInjectionPoint, &mut AnimationPose<T>),
+    {
+        pose.reset();
+
+        parameters.update_computed();
+
+        for (index, layer) in layers.iter_mut().enumerate() {
+            let weight = layer.weight();
+            let layer_pose = layer.evaluate_pose(animations, parameters, dt);
+
+            pose.blend_with(layer_pose, weight);
+
+            injection(PoseInjectionPoint::Layer(index), pose);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            pool::{ErasedHandle, Handle, Pool},
+            visitor::prelude::*,
+        },
+        machine::{
+            Event, Machine, MachineLayer, Parameter, ParameterContainer, PlayAnimation,
+            PoseInjectionPoint, PoseNode, State, Transition,
+        },
+        AnimationContainer, RootMotion,
+    };
+    use fyrox_core::algebra::Vector3;
+
+    // Layout of the first version of a machine, that was saved before layers were added.
+    #[derive(Default, Visit)]
+    struct MachineV1 {
+        parameters: ParameterContainer,
+        nodes: Pool<PoseNode<ErasedHandle>>,
+        transitions: Pool<Transition<ErasedHandle>>,
+        states: Pool<State<ErasedHandle>>,
+        active_state: Handle<State<ErasedHandle>>,
+        entry_state: Handle<State<ErasedHandle>>,
+        active_transition: Handle<Transition<ErasedHandle>>,
+    }
+
+    fn save_and_load<S: Visit>(data: &mut S) -> Machine<ErasedHandle> {
+        let mut visitor = Visitor::new();
+        data.visit("Machine", &mut visitor).unwrap();
+        let bytes = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&bytes).unwrap();
+        let mut machine = Machine::default();
+        machine.visit("Machine", &mut visitor).unwrap();
+        machine
+    }
+
+    #[test]
+    fn test_machine_v1_upgrade() {
+        let mut old = MachineV1::default();
+        old.parameters.add("Run", Parameter::Rule(true));
+        let idle_node = old
+            .nodes
+            .spawn(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let run_node = old
+            .nodes
+            .spawn(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let idle = old.states.spawn(State::new("Idle", idle_node));
+        let run = old.states.spawn(State::new("Run", run_node));
+        let idle_to_run =
+            old.transitions
+                .spawn(Transition::new("Idle->Run", idle, run, 0.5, "Run"));
+        old.entry_state = idle;
+        old.active_state = idle;
+
+        let machine = save_and_load(&mut old);
+
+        assert_eq!(
+            machine.parameters().get("Run"),
+            Some(&Parameter::Rule(true))
+        );
+        assert_eq!(machine.layers().len(), 1);
+        let layer = &machine.layers()[0];
+        assert_eq!(layer.entry_state(), idle);
+        assert_eq!(layer.states().alive_count(), 2);
+        assert_eq!(layer.nodes().alive_count(), 2);
+        assert_eq!(layer.transitions()[idle_to_run].name(), "Idle->Run");
+        assert_eq!(layer.states()[run].name, "Run");
+
+        // The upgraded machine must be saved in the current format without any losses.
+        let mut upgraded = machine.clone();
+        let reloaded = save_and_load(&mut upgraded);
+        assert_eq!(reloaded.parameters(), machine.parameters());
+        assert_eq!(reloaded.layers().len(), 1);
+        assert_eq!(reloaded.layers()[0].entry_state(), idle);
+        assert_eq!(reloaded.layers()[0].states(), layer.states());
+        assert_eq!(reloaded.layers()[0].transitions(), layer.transitions());
+    }
+
+    #[test]
+    fn test_pose_injection_order() {
+        // The machine has one layer already.
+        let mut machine = Machine::<ErasedHandle>::new();
+        machine.add_layer(MachineLayer::new());
+
+        let mut animations = AnimationContainer::new();
+        let mut points = Vec::new();
+        machine.evaluate_pose_with(&mut animations, 0.1, |point, _| points.push(point));
+        assert_eq!(
+            points,
+            [
+                PoseInjectionPoint::Layer(0),
+                PoseInjectionPoint::Layer(1),
+                PoseInjectionPoint::Final
+            ]
+        );
+
+        // Layers are evaluated only once per interval with reduced update rate.
+        machine.set_update_rate(Some(10.0));
+        points.clear();
+        machine.evaluate_pose_with(&mut animations, 0.01, |point, _| points.push(point));
+        machine.evaluate_pose_with(&mut animations, 0.01, |point, _| points.push(point));
+        assert_eq!(
+            points,
+            [
+                PoseInjectionPoint::Layer(0),
+                PoseInjectionPoint::Layer(1),
+                PoseInjectionPoint::Final,
+                PoseInjectionPoint::Final
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_rate() {
+        let mut machine = Machine::<ErasedHandle>::new();
+        let mut animations = AnimationContainer::new();
+
+        // Exact binary fractions are used to avoid rounding errors: the interval is 4/32 s, the frame time
+        // is 3/32 s. Every evaluation sets the root motion to the number of evaluations so far.
+        machine.set_update_rate(Some(8.0));
+        let mut evaluations = 0;
+        let mut frame = |machine: &mut Machine<ErasedHandle>| {
+            machine
+                .evaluate_pose_with(&mut animations, 3.0 / 32.0, |point, pose| {
+                    if point == PoseInjectionPoint::Layer(0) {
+                        evaluations += 1;
+                        pose.set_root_motion(Some(RootMotion {
+                            delta_position: Vector3::new(evaluations as f32, 0.0, 0.0),
+                            ..Default::default()
+                        }));
+                    }
+                })
+                .root_motion()
+                .unwrap()
+                .delta_position
+                .x
+        };
+
+        // The first call always evaluates the layers.
+        assert_eq!(frame(&mut machine), 1.0);
+        // 3/32 s elapsed, no evaluation.
+        assert_eq!(frame(&mut machine), 1.0);
+        // 6/32 s elapsed, the layers are advanced by 4/32 s, the rest is kept, so the pose is halfway
+        // between the two last evaluated poses.
+        assert_eq!(frame(&mut machine), 1.5);
+        // 5/32 s elapsed.
+        assert_eq!(frame(&mut machine), 2.25);
+        // 4/32 s elapsed.
+        assert_eq!(frame(&mut machine), 3.0);
+        // 3/32 s elapsed, no evaluation.
+        assert_eq!(frame(&mut machine), 3.75);
+        drop(frame);
+        assert_eq!(evaluations, 4);
+    }
+
+    #[test]
+    fn test_poll_events() {
+        // The machine has one layer already, add the second one.
+        let mut machine = Machine::<ErasedHandle>::new();
+        machine.add_layer(MachineLayer::new());
+        let mut states = Vec::new();
+        for layer in machine.layers_mut() {
+            let state = layer.add_state(State::new("State", Handle::NONE));
+            layer.set_active_state(state);
+            states.push(state);
+        }
+
+        let mut events = Vec::new();
+        machine.poll_events(|index, event| events.push((index, event)));
+        assert_eq!(
+            events,
+            [
+                (0, Event::StateEnter(states[0])),
+                (
+                    0,
+                    Event::ActiveStateChanged {
+                        prev: Handle::NONE,
+                        new: states[0]
+                    }
+                ),
+                (1, Event::StateEnter(states[1])),
+                (
+                    1,
+                    Event::ActiveStateChanged {
+                        prev: Handle::NONE,
+                        new: states[1]
+                    }
+                ),
+            ]
+        );
+
+        // The queues are empty now.
+        assert!(machine.layers_mut()[0].pop_event().is_none());
+        assert_eq!(machine.layers_mut()[1].drain_events().count(), 0);
+    }
+
+    #[test]
+    fn test_reset_parameters() {
+        let mut machine = Machine::<ErasedHandle>::new();
+        machine.set_parameter("Run", Parameter::Rule(false));
+
+        let layer = &mut machine.layers_mut()[0];
+        let idle = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let idle_state = layer.add_state(State::new("Idle", idle));
+        let run = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(Handle::NONE)));
+        let run_state = layer.add_state(State::new("Run", run));
+        layer.add_transition(Transition::new(
+            "Idle->Run",
+            idle_state,
+            run_state,
+            0.1,
+            "Run",
+        ));
+        layer.set_entry_state(idle_state);
+
+        let mut animations = AnimationContainer::new();
+        machine.set_parameter("Run", Parameter::Rule(true));
+        for _ in 0..5 {
+            machine.evaluate_pose(&mut animations, 0.1);
+        }
+        assert_eq!(machine.layers()[0].active_state(), run_state);
+
+        machine.reset(true);
+        assert_eq!(machine.parameters().get_rule("Run"), Some(false));
+        machine.evaluate_pose(&mut animations, 0.1);
+        assert_eq!(machine.layers()[0].active_state(), idle_state);
+
+        // Defaults survive serialization, the value at the moment of saving is not a default.
+        machine.set_parameter("Run", Parameter::Rule(true));
+        let mut loaded = save_and_load(&mut machine);
+        assert_eq!(loaded.parameters().get_rule("Run"), Some(true));
+        loaded.reset_parameters();
+        assert_eq!(loaded.parameters().get_rule("Run"), Some(false));
+    }
 }
@@ -73,6 +73,15 @@ impl<T: EntityId> BlendPose<T> {
 /// Weights can be parametrized, which means that you can dynamically change them in runtime. In our example we
 /// can decrease weight of hit animation over time and increase weight of run animation, so character will recover
 /// from his wounds.
+///
+/// # Inactive inputs
+///
+/// Input poses with weights less or equal to [`Self::INACTIVE_WEIGHT_EPSILON`] are considered inactive and are not
+/// evaluated at all, which saves a lot of CPU time for large blend trees with many mostly-inactive inputs. Since
+/// every input is blended into the output pose proportionally to its weight, inactive inputs do not contribute to
+/// the final pose, with one exception: if an inactive input is the first one, that has a pose of some scene node,
+/// the pose is added as is. Set [`Self::evaluate_inactive_inputs`] to `true` to disable the optimization if you
+/// rely on this behavior.
 #[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
 pub struct BlendAnimations<T: EntityId> {
     /// Base node.
@@ -81,6 +90,11 @@ pub struct BlendAnimations<T: EntityId> {
     /// A list of pose sources. See [`BlendPose`] docs for more info.
     pub pose_sources: Vec<BlendPose<T>>,
 
+    /// If `true`, then every input pose will be evaluated, even if its weight is zero. Default is `false`. See
+    /// [`Self`] docs for more info.
+    #[visit(optional)]
+    pub evaluate_inactive_inputs: bool,
+
     /// Output pose of the node, contains final result of blending all input poses.
     #[visit(skip)]
     #[reflect(hidden)]
@@ -107,10 +121,15 @@ impl<T: EntityId> BlendAnimations<T> {
         Self {
             base: Default::default(),
             pose_sources: poses,
+            evaluate_inactive_inputs: false,
             output_pose: Default::default(),
         }
     }
 
+    /// Input poses with weights less or equal to this value are not evaluated, unless
+    /// [`Self::evaluate_inactive_inputs`] is set.
+    pub const INACTIVE_WEIGHT_EPSILON: f32 = 1.0e-6;
+
     /// Returns a set of handles to children pose nodes.
     pub fn children(&self) -> Vec<Handle<PoseNode<T>>> {
         self.pose_sources.iter().map(|s| s.pose_source).collect()
@@ -138,6 +157,10 @@ impl<T: EntityId> AnimationPoseSource<T> for BlendAnimations<T> {
                 }
             };
 
+            if !self.evaluate_inactive_inputs && weight.abs() <= Self::INACTIVE_WEIGHT_EPSILON {
+                continue;
+            }
+
             if let Some(pose_source) = nodes
                 .try_borrow(blend_pose.pose_source)
                 .map(|pose_source| pose_source.eval_pose(nodes, params, animations, dt))
@@ -428,3 +451,228 @@ impl<T: EntityId> AnimationPoseSource<T> for BlendAnimationsByIndex<T> {
         Default::default()
     }
 }
+
+/// Additive blend node. It adds the difference between an additive pose and a reference pose, scaled by a weight,
+/// to a base pose: `base + (additive - reference) * weight` for every animated value (rotations are combined by
+/// multiplication). It is used to layer an animation on top of some other animation, that could be anything - for
+/// example, an aim offset or a breathing animation on top of locomotion animations.
+///
+/// The reference pose is usually the first frame of the additive animation (for example, a paused
+/// [`super::PlayAnimation`] node with the same animation), in this case only the motion of the additive animation
+/// is added to the base pose. If the reference pose source is not set, the additive pose is added as is, which is
+/// useful if the additive animation already contains differences (zero offsets and identity rotations mean "no
+/// change"). Only the values of the base pose are affected, the values that are only in the additive pose are ignored.
+#[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct AdditiveBlend<T: EntityId> {
+    /// Base node.
+    pub base: BasePoseNode<T>,
+
+    /// A source of the base pose.
+    #[reflect(hidden)]
+    pub base_pose_source: Handle<PoseNode<T>>,
+
+    /// A source of the additive pose.
+    #[reflect(hidden)]
+    pub additive_pose_source: Handle<PoseNode<T>>,
+
+    /// A source of the reference pose. The difference between the additive and the reference pose is added to the
+    /// base pose. If not set, the additive pose is added as is.
+    #[reflect(hidden)]
+    pub reference_pose_source: Handle<PoseNode<T>>,
+
+    /// Weight of the additive pose.
+    pub weight: PoseWeight,
+
+    /// Output pose of the node, contains final result of the blending.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub output_pose: RefCell<AnimationPose<T>>,
+}
+
+impl<T: EntityId> Deref for AdditiveBlend<T> {
+    type Target = BasePoseNode<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<T: EntityId> DerefMut for AdditiveBlend<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl<T: EntityId> AdditiveBlend<T> {
+    /// Creates new additive blend node with the given pose sources and weight.
+    pub fn new(
+        base_pose_source: Handle<PoseNode<T>>,
+        additive_pose_source: Handle<PoseNode<T>>,
+        reference_pose_source: Handle<PoseNode<T>>,
+        weight: PoseWeight,
+    ) -> Self {
+        Self {
+            base: Default::default(),
+            base_pose_source,
+            additive_pose_source,
+            reference_pose_source,
+            weight,
+            output_pose: Default::default(),
+        }
+    }
+
+    /// Returns a set of handles to children pose nodes in the following order: base, additive, reference.
+    pub fn children(&self) -> Vec<Handle<PoseNode<T>>> {
+        vec![
+            self.base_pose_source,
+            self.additive_pose_source,
+            self.reference_pose_source,
+        ]
+    }
+}
+
+impl<T: EntityId> AnimationPoseSource<T> for AdditiveBlend<T> {
+    fn eval_pose(
+        &self,
+        nodes: &Pool<PoseNode<T>>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+        dt: f32,
+    ) -> Ref<AnimationPose<T>> {
+        let mut output_pose = self.output_pose.borrow_mut();
+        output_pose.reset();
+
+        if let Some(base) = nodes.try_borrow(self.base_pose_source) {
+            base.eval_pose(nodes, params, animations, dt)
+                .clone_into(&mut output_pose);
+        }
+
+        let weight = self.weight.value(params).unwrap_or_default();
+        if weight.abs() > BlendAnimations::<T>::INACTIVE_WEIGHT_EPSILON {
+            // The reference pose is copied, because it could be the same node as the additive one.
+            let reference_pose = nodes
+                .try_borrow(self.reference_pose_source)
+                .map(|reference| reference.eval_pose(nodes, params, animations, dt).clone());
+
+            if let Some(additive) = nodes.try_borrow(self.additive_pose_source) {
+                output_pose.add_difference(
+                    &additive.eval_pose(nodes, params, animations, dt),
+                    reference_pose.as_ref(),
+                    weight,
+                );
+            }
+        }
+
+        drop(output_pose);
+
+        self.output_pose.borrow()
+    }
+
+    fn pose(&self) -> Ref<AnimationPose<T>> {
+        self.output_pose.borrow()
+    }
+
+    fn collect_animation_events(
+        &self,
+        nodes: &Pool<PoseNode<T>>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+        strategy: AnimationEventCollectionStrategy,
+    ) -> Vec<(Handle<Animation<T>>, AnimationEvent)> {
+        let weight = self.weight.value(params).unwrap_or_default();
+
+        // The base pose is always fully applied, so its weight is one.
+        let sources = match strategy {
+            AnimationEventCollectionStrategy::All => {
+                vec![self.base_pose_source, self.additive_pose_source]
+            }
+            AnimationEventCollectionStrategy::MaxWeight => {
+                if weight > 1.0 {
+                    vec![self.additive_pose_source]
+                } else {
+                    vec![self.base_pose_source]
+                }
+            }
+            AnimationEventCollectionStrategy::MinWeight => {
+                if weight < 1.0 {
+                    vec![self.additive_pose_source]
+                } else {
+                    vec![self.base_pose_source]
+                }
+            }
+        };
+
+        let mut events = Vec::new();
+        for source in sources {
+            if let Some(source) = nodes.try_borrow(source) {
+                events.extend(source.collect_animation_events(nodes, params, animations, strategy));
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::Vector3,
+            pool::{ErasedHandle, Pool},
+        },
+        machine::{
+            node::{
+                blend::{BlendAnimations, BlendPose},
+                play::PlayAnimation,
+                PoseNode,
+            },
+            AnimationPoseSource, ParameterContainer,
+        },
+        pose::NodePose,
+        value::{BoundValue, BoundValueCollection, TrackValue, ValueBinding},
+        Animation, AnimationContainer,
+    };
+
+    #[test]
+    fn test_inactive_inputs_are_not_evaluated() {
+        let node = ErasedHandle::new(1, 1);
+        let mut animation = Animation::default();
+        animation.pose.poses_mut().insert(
+            node,
+            NodePose {
+                node,
+                values: BoundValueCollection {
+                    values: vec![BoundValue {
+                        binding: ValueBinding::Position,
+                        value: TrackValue::Vector3(Vector3::new(1.0, 2.0, 3.0)),
+                    }],
+                },
+            },
+        );
+        let mut animations = AnimationContainer::new();
+        let animation = animations.add(animation);
+
+        let mut nodes = Pool::new();
+        let active = nodes.spawn(PoseNode::PlayAnimation(PlayAnimation::new(animation)));
+        let inactive = nodes.spawn(PoseNode::PlayAnimation(PlayAnimation::new(animation)));
+
+        let mut blend = BlendAnimations::new(vec![
+            BlendPose::with_constant_weight(1.0, active),
+            BlendPose::with_constant_weight(0.0, inactive),
+        ]);
+        let params = ParameterContainer::default();
+
+        assert_eq!(
+            blend
+                .eval_pose(&nodes, &params, &animations, 0.0)
+                .poses()
+                .len(),
+            1
+        );
+        assert_eq!(nodes[active].pose().poses().len(), 1);
+        assert!(nodes[inactive].pose().poses().is_empty());
+
+        blend.evaluate_inactive_inputs = true;
+        blend.eval_pose(&nodes, &params, &animations, 0.0);
+        assert_eq!(nodes[inactive].pose().poses().len(), 1);
+    }
+}
@@ -63,6 +63,18 @@ pub struct BlendSpace<T: EntityId> {
     #[reflect(setter = "set_sampling_parameter")]
     sampling_parameter: String,
 
+    /// Name of a `Weight` parameter, that is used as X coordinate of the sampling point, when the sampling
+    /// parameter is not found.
+    #[visit(optional)]
+    #[reflect(setter = "set_x_parameter")]
+    x_parameter: String,
+
+    /// Name of a `Weight` parameter, that is used as Y coordinate of the sampling point, when the sampling
+    /// parameter is not found.
+    #[visit(optional)]
+    #[reflect(setter = "set_y_parameter")]
+    y_parameter: String,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pose: RefCell<AnimationPose<T>>,
@@ -80,6 +92,8 @@ impl<T: EntityId> Default for BlendSpace<T> {
             max_values: Vector2::new(1.0, 1.0),
             snap_step: Vector2::new(0.1, 0.1),
             sampling_parameter: Default::default(),
+            x_parameter: Default::default(),
+            y_parameter: Default::default(),
             pose: Default::default(),
         }
     }
@@ -111,9 +125,8 @@ impl<T: EntityId> AnimationPoseSource<T> for BlendSpace<T> {
 
         pose.reset();
 
-        if let Some(Parameter::SamplingPoint(sampling_point)) = params.get(&self.sampling_parameter)
-        {
-            if let Some(weights) = self.fetch_weights(*sampling_point) {
+        if let Some(sampling_point) = self.fetch_sampling_point(params) {
+            if let Some(weights) = self.fetch_weights(sampling_point) {
                 let (ia, wa) = weights[0];
                 let (ib, wb) = weights[1];
                 let (ic, wc) = weights[2];
@@ -146,9 +159,8 @@ impl<T: EntityId> AnimationPoseSource<T> for BlendSpace<T> {
         animations: &AnimationContainer<T>,
         strategy: AnimationEventCollectionStrategy,
     ) -> Vec<(Handle<Animation<T>>, AnimationEvent)> {
-        if let Some(Parameter::SamplingPoint(sampling_point)) = params.get(&self.sampling_parameter)
-        {
-            if let Some(weights) = self.fetch_weights(*sampling_point) {
+        if let Some(sampling_point) = self.fetch_sampling_point(params) {
+            if let Some(weights) = self.fetch_weights(sampling_point) {
                 let (ia, wa) = weights[0];
                 let (ib, wb) = weights[1];
                 let (ic, wc) = weights[2];
@@ -294,6 +306,46 @@ impl<T: EntityId> BlendSpace<T> {
         &self.sampling_parameter
     }
 
+    /// Sets the name of a `Weight` parameter, that is used as X coordinate of the sampling point when the sampling
+    /// parameter (see [`Self::set_sampling_parameter`]) is not found. It allows to drive the blend space with two
+    /// independent parameters (for example, speed on X axis and direction on Y axis).
+    pub fn set_x_parameter(&mut self, parameter: String) {
+        self.x_parameter = parameter;
+    }
+
+    /// Returns the name of a `Weight` parameter, that is used as X coordinate of the sampling point. See
+    /// [`Self::set_x_parameter`] for more info.
+    pub fn x_parameter(&self) -> &str {
+        &self.x_parameter
+    }
+
+    /// Sets the name of a `Weight` parameter, that is used as Y coordinate of the sampling point when the sampling
+    /// parameter (see [`Self::set_sampling_parameter`]) is not found. See [`Self::set_x_parameter`] for more info.
+    pub fn set_y_parameter(&mut self, parameter: String) {
+        self.y_parameter = parameter;
+    }
+
+    /// Returns the name of a `Weight` parameter, that is used as Y coordinate of the sampling point. See
+    /// [`Self::set_y_parameter`] for more info.
+    pub fn y_parameter(&self) -> &str {
+        &self.y_parameter
+    }
+
+    /// Returns the sampling point of the blend space. It is taken from the `SamplingPoint` parameter (see
+    /// [`Self::set_sampling_parameter`]) if it exists, otherwise it is composed of two `Weight` parameters (see
+    /// [`Self::set_x_parameter`] and [`Self::set_y_parameter`]).
+    pub fn fetch_sampling_point(&self, params: &ParameterContainer) -> Option<Vector2<f32>> {
+        if let Some(Parameter::SamplingPoint(sampling_point)) = params.get(&self.sampling_parameter)
+        {
+            return Some(*sampling_point);
+        }
+
+        match (params.get(&self.x_parameter), params.get(&self.y_parameter)) {
+            (Some(Parameter::Weight(x)), Some(Parameter::Weight(y))) => Some(Vector2::new(*x, *y)),
+            _ => None,
+        }
+    }
+
     pub fn set_x_axis_name(&mut self, name: String) -> String {
         std::mem::replace(&mut self.x_axis_name, name)
     }
@@ -433,7 +485,10 @@ impl<T: EntityId> BlendSpace<T> {
 mod test {
     use crate::{
         core::{algebra::Vector2, math::TriangleDefinition},
-        machine::node::blendspace::{BlendSpace, BlendSpacePoint},
+        machine::{
+            node::blendspace::{BlendSpace, BlendSpacePoint},
+            Parameter, ParameterContainer,
+        },
     };
     use fyrox_core::pool::ErasedHandle;
 
@@ -520,4 +575,29 @@ mod test {
             Some([(0, 0.0), (1, 1.0), (0, 0.0)])
         );
     }
+
+    #[test]
+    fn test_blend_space_weight_parameters() {
+        let mut blend_space = BlendSpace::<ErasedHandle>::default();
+        blend_space.set_x_parameter("Speed".to_string());
+        blend_space.set_y_parameter("Direction".to_string());
+
+        let mut params = ParameterContainer::default();
+        assert_eq!(blend_space.fetch_sampling_point(&params), None);
+
+        params.add("Speed", Parameter::Weight(0.25));
+        params.add("Direction", Parameter::Weight(0.75));
+        assert_eq!(
+            blend_space.fetch_sampling_point(&params),
+            Some(Vector2::new(0.25, 0.75))
+        );
+
+        // Sampling point parameter has priority.
+        blend_space.set_sampling_parameter("Point".to_string());
+        params.add("Point", Parameter::SamplingPoint(Vector2::new(1.0, 0.0)));
+        assert_eq!(
+            blend_space.fetch_sampling_point(&params),
+            Some(Vector2::new(1.0, 0.0))
+        );
+    }
 }
@@ -0,0 +1,333 @@
+//! Mirror node produces a mirrored version of an input pose. See [`MirrorPose`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector3},
+        pool::{Handle, Pool},
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    machine::{
+        node::AnimationEventCollectionStrategy, node::BasePoseNode, AnimationPoseSource,
+        ParameterContainer, PoseNode,
+    },
+    value::{BoundValueCollection, TrackValue, ValueBinding},
+    Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId,
+};
+use fyrox_core::{
+    uuid::{uuid, Uuid},
+    TypeUuidProvider,
+};
+use std::{
+    cell::{Ref, RefCell},
+    ops::{Deref, DerefMut},
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// An axis, that is perpendicular to the mirror plane. For example, [`MirrorAxis::X`] mirrors the pose across
+/// `YZ` plane, which is the usual case for characters that are facing `Z` axis.
+#[derive(
+    Default, Debug, Clone, Copy, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum MirrorAxis {
+    /// Mirrors across `YZ` plane.
+    #[default]
+    X,
+    /// Mirrors across `XZ` plane.
+    Y,
+    /// Mirrors across `XY` plane.
+    Z,
+}
+
+impl TypeUuidProvider for MirrorAxis {
+    fn type_uuid() -> Uuid {
+        uuid!("0f3f4b77-2f5e-4a7e-b5a4-6a3d1f0e9c52")
+    }
+}
+
+impl MirrorAxis {
+    fn index(self) -> usize {
+        match self {
+            MirrorAxis::X => 0,
+            MirrorAxis::Y => 1,
+            MirrorAxis::Z => 2,
+        }
+    }
+
+    /// Reflects the given position across the mirror plane.
+    pub fn mirror_position(self, position: &Vector3<f32>) -> Vector3<f32> {
+        let mut mirrored = *position;
+        mirrored[self.index()] = -mirrored[self.index()];
+        mirrored
+    }
+
+    /// Reflects the given rotation across the mirror plane. The rotation axis is reflected as a pseudo-vector,
+    /// which means that its component along the mirror axis is kept and the other components are negated.
+    pub fn mirror_rotation(self, rotation: &UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+        let mut coords = rotation.into_inner().coords;
+        // The first three components are the imaginary part of the quaternion.
+        for (i, component) in coords.iter_mut().take(3).enumerate() {
+            if i != self.index() {
+                *component = -*component;
+            }
+        }
+        UnitQuaternion::new_unchecked(Quaternion::from(coords))
+    }
+
+    fn mirror_values(self, values: &mut BoundValueCollection) {
+        for value in values.values.iter_mut() {
+            match (&value.binding, &mut value.value) {
+                (ValueBinding::Position, TrackValue::Vector3(position)) => {
+                    *position = self.mirror_position(position);
+                }
+                (ValueBinding::Rotation, TrackValue::UnitQuaternion(rotation)) => {
+                    *rotation = self.mirror_rotation(rotation);
+                }
+                // Scale and arbitrary properties are not affected.
+                _ => (),
+            }
+        }
+    }
+}
+
+/// A pair of symmetric bones (for example, left and right hands), which poses are swapped by [`MirrorPose`] node.
+#[derive(Default, Debug, Visit, Clone, Reflect, PartialEq, Eq)]
+pub struct BonePair<T: EntityId> {
+    /// A handle of the left bone.
+    pub left: T,
+    /// A handle of the right bone.
+    pub right: T,
+}
+
+impl<T: EntityId> TypeUuidProvider for BonePair<T> {
+    fn type_uuid() -> Uuid {
+        uuid!("6a5f7b0b-1c3e-4a5b-8f52-2d8e7b9c4e10")
+    }
+}
+
+/// Mirror node takes a pose from its input and mirrors it across a plane, defined by [`MirrorAxis`]. Poses of each
+/// pair of symmetric bones are swapped and then positions and rotations of all bones are reflected across the plane.
+/// It could be used to get left-handed versions of right-handed animations (or vice versa) without duplicating
+/// animation assets. Root motion is mirrored too.
+///
+/// The table of bone pairs is usually authored once per skeleton. Bones without a pair (for example, spine bones)
+/// are mirrored in place. The node works with local transforms of the bones, so the skeleton must be symmetric:
+/// bind poses of paired bones must be mirror images of each other and local axes of the bones in the middle must be
+/// symmetric relative to the mirror plane.
+#[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct MirrorPose<T: EntityId> {
+    /// Base node.
+    pub base: BasePoseNode<T>,
+
+    /// A source of the pose to mirror.
+    #[reflect(hidden)]
+    pub pose_source: Handle<PoseNode<T>>,
+
+    /// Pairs of symmetric bones, which poses are swapped.
+    pub bone_pairs: Vec<BonePair<T>>,
+
+    /// An axis, that is perpendicular to the mirror plane.
+    pub axis: MirrorAxis,
+
+    /// Output pose of the node, contains the mirrored pose.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub output_pose: RefCell<AnimationPose<T>>,
+}
+
+impl<T: EntityId> Deref for MirrorPose<T> {
+    type Target = BasePoseNode<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<T: EntityId> DerefMut for MirrorPose<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl<T: EntityId> MirrorPose<T> {
+    /// Creates new mirror node with the given pose source, pairs of symmetric bones and mirror axis.
+    pub fn new(
+        pose_source: Handle<PoseNode<T>>,
+        bone_pairs: Vec<BonePair<T>>,
+        axis: MirrorAxis,
+    ) -> Self {
+        Self {
+            base: Default::default(),
+            pose_source,
+            bone_pairs,
+            axis,
+            output_pose: Default::default(),
+        }
+    }
+
+    /// Returns a set of handles to children pose nodes.
+    pub fn children(&self) -> Vec<Handle<PoseNode<T>>> {
+        vec![self.pose_source]
+    }
+
+    /// Mirrors the given pose in-place. See [`MirrorPose`] docs for more info.
+    pub fn mirror(&self, pose: &mut AnimationPose<T>) {
+        let poses = pose.poses_mut();
+        for pair in self.bone_pairs.iter() {
+            let left = poses.remove(&pair.left);
+            let right = poses.remove(&pair.right);
+
+            if let Some(mut left) = left {
+                left.node = pair.right;
+                poses.insert(pair.right, left);
+            }
+            if let Some(mut right) = right {
+                right.node = pair.left;
+                poses.insert(pair.left, right);
+            }
+        }
+
+        for node_pose in poses.values_mut() {
+            self.axis.mirror_values(&mut node_pose.values);
+        }
+
+        if let Some(mut root_motion) = pose.root_motion().cloned() {
+            root_motion.delta_position = self.axis.mirror_position(&root_motion.delta_position);
+            root_motion.delta_rotation = self.axis.mirror_rotation(&root_motion.delta_rotation);
+            pose.set_root_motion(Some(root_motion));
+        }
+    }
+}
+
+impl<T: EntityId> AnimationPoseSource<T> for MirrorPose<T> {
+    fn eval_pose(
+        &self,
+        nodes: &Pool<PoseNode<T>>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+        dt: f32,
+    ) -> Ref<AnimationPose<T>> {
+        let mut output_pose = self.output_pose.borrow_mut();
+        output_pose.reset();
+
+        if let Some(pose_source) = nodes.try_borrow(self.pose_source) {
+            pose_source
+                .eval_pose(nodes, params, animations, dt)
+                .clone_into(&mut output_pose);
+        }
+
+        self.mirror(&mut output_pose);
+
+        drop(output_pose);
+
+        self.output_pose.borrow()
+    }
+
+    fn pose(&self) -> Ref<AnimationPose<T>> {
+        self.output_pose.borrow()
+    }
+
+    fn collect_animation_events(
+        &self,
+        nodes: &Pool<PoseNode<T>>,
+        params: &ParameterContainer,
+        animations: &AnimationContainer<T>,
+        strategy: AnimationEventCollectionStrategy,
+    ) -> Vec<(Handle<Animation<T>>, AnimationEvent)> {
+        nodes
+            .try_borrow(self.pose_source)
+            .map(|pose_source| {
+                pose_source.collect_animation_events(nodes, params, animations, strategy)
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            pool::ErasedHandle,
+        },
+        machine::node::mirror::{BonePair, MirrorAxis, MirrorPose},
+        pose::{AnimationPose, NodePose},
+        value::{BoundValue, BoundValueCollection, TrackValue, ValueBinding},
+    };
+
+    fn node_pose(
+        node: ErasedHandle,
+        position: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+    ) -> NodePose<ErasedHandle> {
+        NodePose {
+            node,
+            values: BoundValueCollection {
+                values: vec![
+                    BoundValue {
+                        binding: ValueBinding::Position,
+                        value: TrackValue::Vector3(position),
+                    },
+                    BoundValue {
+                        binding: ValueBinding::Rotation,
+                        value: TrackValue::UnitQuaternion(rotation),
+                    },
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn test_mirror_pose() {
+        let left = ErasedHandle::new(1, 1);
+        let right = ErasedHandle::new(2, 1);
+        let spine = ErasedHandle::new(3, 1);
+
+        let rotation = UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3);
+        let mut pose = AnimationPose::default();
+        pose.poses_mut()
+            .insert(left, node_pose(left, Vector3::new(1.0, 2.0, 3.0), rotation));
+        pose.poses_mut().insert(
+            spine,
+            node_pose(spine, Vector3::new(1.0, 0.0, 0.0), rotation),
+        );
+
+        let mirror = MirrorPose::new(
+            Default::default(),
+            vec![BonePair { left, right }],
+            MirrorAxis::X,
+        );
+        mirror.mirror(&mut pose);
+
+        // The pose of the left bone is moved to the right one.
+        assert!(!pose.poses().contains_key(&left));
+        let right_pose = &pose.poses()[&right];
+        assert_eq!(right_pose.node, right);
+        assert_eq!(
+            right_pose.values.values[0].value,
+            TrackValue::Vector3(Vector3::new(-1.0, 2.0, 3.0))
+        );
+
+        // Mirrored rotation is the same as the rotation conjugated by the reflection.
+        let TrackValue::UnitQuaternion(mirrored) = right_pose.values.values[1].value else {
+            unreachable!()
+        };
+        let reflection = Vector3::new(-1.0, 1.0, 1.0);
+        let vector = Vector3::new(0.3, -0.5, 0.7);
+        let expected = (rotation * vector.component_mul(&reflection)).component_mul(&reflection);
+        assert!((mirrored * vector - expected).norm() < 1.0e-5);
+
+        // Bones without a pair are mirrored in place.
+        assert_eq!(
+            pose.poses()[&spine].values.values[0].value,
+            TrackValue::Vector3(Vector3::new(-1.0, 0.0, 0.0))
+        );
+
+        // Mirroring twice gives the original pose.
+        mirror.mirror(&mut pose);
+        assert_eq!(
+            pose.poses()[&left].values.values[0].value,
+            TrackValue::Vector3(Vector3::new(1.0, 2.0, 3.0))
+        );
+    }
+}
@@ -9,8 +9,14 @@ use crate::{
         visitor::prelude::*,
     },
     machine::{
-        node::{blend::BlendAnimations, blendspace::BlendSpace, play::PlayAnimation},
-        BlendAnimationsByIndex, BlendPose, IndexedBlendInput, ParameterContainer, State,
+        node::{
+            blend::{AdditiveBlend, BlendAnimations},
+            blendspace::BlendSpace,
+            mirror::{BonePair, MirrorAxis, MirrorPose},
+            play::PlayAnimation,
+        },
+        BlendAnimationsByIndex, BlendPose, IndexedBlendInput, ParameterContainer, PoseWeight,
+        State,
     },
     Animation, AnimationContainer, AnimationEvent, AnimationPose, EntityId,
 };
@@ -21,6 +27,7 @@ use std::{
 
 pub mod blend;
 pub mod blendspace;
+pub mod mirror;
 pub mod play;
 
 /// A set of common data fields that is used in every node.
@@ -48,6 +55,12 @@ pub enum PoseNode<T: EntityId> {
 
     /// See doc for [`BlendSpace`]
     BlendSpace(BlendSpace<T>),
+
+    /// See docs for [`AdditiveBlend`].
+    AdditiveBlend(AdditiveBlend<T>),
+
+    /// See docs for [`MirrorPose`].
+    MirrorPose(MirrorPose<T>),
 }
 
 impl<T: EntityId> Default for PoseNode<T> {
@@ -67,6 +80,31 @@ impl<T: EntityId> PoseNode<T> {
         Self::BlendAnimations(BlendAnimations::new(poses))
     }
 
+    /// Creates new node that adds the difference between the additive and the reference poses to the base pose.
+    /// See [`AdditiveBlend`] docs for more info.
+    pub fn make_additive_blend(
+        base_pose_source: Handle<PoseNode<T>>,
+        additive_pose_source: Handle<PoseNode<T>>,
+        reference_pose_source: Handle<PoseNode<T>>,
+        weight: PoseWeight,
+    ) -> Self {
+        Self::AdditiveBlend(AdditiveBlend::new(
+            base_pose_source,
+            additive_pose_source,
+            reference_pose_source,
+            weight,
+        ))
+    }
+
+    /// Creates new node that mirrors a pose of the given pose source. See [`MirrorPose`] docs for more info.
+    pub fn make_mirror_pose(
+        pose_source: Handle<PoseNode<T>>,
+        bone_pairs: Vec<BonePair<T>>,
+        axis: MirrorAxis,
+    ) -> Self {
+        Self::MirrorPose(MirrorPose::new(pose_source, bone_pairs, axis))
+    }
+
     /// Creates new node that switches between given animations using index and smoothly blends from
     /// one animation to another while switching.
     pub fn make_blend_animations_by_index(
@@ -86,6 +124,44 @@ impl<T: EntityId> PoseNode<T> {
             Self::BlendAnimations(blend_animations) => blend_animations.children(),
             Self::BlendAnimationsByIndex(blend_by_index) => blend_by_index.children(),
             Self::BlendSpace(blend_space) => blend_space.children(),
+            Self::AdditiveBlend(additive_blend) => additive_blend.children(),
+            Self::MirrorPose(mirror_pose) => mirror_pose.children(),
+        }
+    }
+
+    /// Replaces the handle of every child pose node with the handle returned by the given function. It
+    /// is used to fix internal references of nodes, that were copied from one graph to another.
+    pub fn remap_children<F>(&mut self, mut remap: F)
+    where
+        F: FnMut(Handle<PoseNode<T>>) -> Handle<PoseNode<T>>,
+    {
+        match self {
+            Self::PlayAnimation(_) => {
+                // No children nodes.
+            }
+            Self::BlendAnimations(blend_animations) => {
+                for pose in blend_animations.pose_sources.iter_mut() {
+                    pose.pose_source = remap(pose.pose_source);
+                }
+            }
+            Self::BlendAnimationsByIndex(blend_by_index) => {
+                for input in blend_by_index.inputs.iter_mut() {
+                    input.pose_source = remap(input.pose_source);
+                }
+            }
+            Self::BlendSpace(blend_space) => {
+                for point in blend_space.points_mut().iter_mut() {
+                    point.pose_source = remap(point.pose_source);
+                }
+            }
+            Self::AdditiveBlend(additive_blend) => {
+                additive_blend.base_pose_source = remap(additive_blend.base_pose_source);
+                additive_blend.additive_pose_source = remap(additive_blend.additive_pose_source);
+                additive_blend.reference_pose_source = remap(additive_blend.reference_pose_source);
+            }
+            Self::MirrorPose(mirror_pose) => {
+                mirror_pose.pose_source = remap(mirror_pose.pose_source);
+            }
         }
     }
 }
@@ -97,6 +173,8 @@ macro_rules! static_dispatch {
             PoseNode::BlendAnimations(v) => v.$func($($args),*),
             PoseNode::BlendAnimationsByIndex(v) => v.$func($($args),*),
             PoseNode::BlendSpace(v) => v.$func($($args),*),
+            PoseNode::AdditiveBlend(v) => v.$func($($args),*),
+            PoseNode::MirrorPose(v) => v.$func($($args),*),
         }
     };
 }
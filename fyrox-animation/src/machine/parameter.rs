@@ -1,7 +1,10 @@
 //! Parameter is a name variable of a fixed type. See [`Parameter`] docs for more info.
 
-use crate::core::{algebra::Vector2, reflect::prelude::*, visitor::prelude::*};
-use fxhash::FxHashMap;
+use crate::{
+    core::{algebra::Vector2, log::Log, reflect::prelude::*, visitor::prelude::*},
+    machine::expression::{ExpressionError, ParameterExpression},
+};
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_core::uuid_provider;
 use std::{
     cell::{Cell, RefCell},
@@ -81,10 +84,71 @@ pub struct ParameterDefinition {
 
 uuid_provider!(ParameterDefinition = "6cbba5c9-8daf-4f4c-a920-9716ed233d89");
 
+/// A parameter, which value is computed from other parameters. See [`ParameterContainer::add_computed`] docs
+/// for more info.
+#[derive(Reflect, Visit, Default, Debug, Clone, PartialEq)]
+pub struct ComputedParameterDefinition {
+    /// Name of the parameter.
+    pub name: String,
+
+    /// An expression that is used to compute value of the parameter.
+    pub expression: ParameterExpression,
+}
+
+uuid_provider!(ComputedParameterDefinition = "0b0ce3a5-4cf9-4d4e-b3e2-8a7d4df0a1c3");
+
+/// A compact copy of parameter values of a [`ParameterContainer`] at some moment in time. It could be used to
+/// find which parameters were changed since the snapshot was made, see [`ParameterContainer::diff_since`] for
+/// more info. Computed parameter definitions are not included in snapshots, only their values are.
+#[derive(Visit, Default, Debug, Clone, PartialEq)]
+pub struct ParameterSnapshot {
+    parameters: Vec<ParameterDefinition>,
+}
+
+impl ParameterSnapshot {
+    /// Tries to fetch a value of a parameter with the given name. The method has O(n) complexity.
+    pub fn get(&self, name: &str) -> Option<&Parameter> {
+        self.parameters
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| &p.value)
+    }
+
+    /// Returns an iterator over all parameters in the snapshot.
+    pub fn iter(&self) -> impl Iterator<Item = &ParameterDefinition> {
+        self.parameters.iter()
+    }
+
+    /// Returns total amount of parameters in the snapshot.
+    pub fn len(&self) -> usize {
+        self.parameters.len()
+    }
+
+    /// Returns `true` if the snapshot is empty, `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+}
+
+impl Parameter {
+    fn as_number(&self) -> Option<f32> {
+        match self {
+            Parameter::Weight(weight) => Some(*weight),
+            Parameter::Rule(rule) => Some(if *rule { 1.0 } else { 0.0 }),
+            Parameter::Index(index) => Some(*index as f32),
+            Parameter::SamplingPoint(_) => None,
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 struct Wrapper {
     parameters: Vec<ParameterDefinition>,
     dirty: Cell<bool>,
+    // `true` if some parameter was changed since the last update of computed parameters.
+    changed: bool,
+    // Values of the parameters at the moment when they were added.
+    defaults: FxHashMap<String, Parameter>,
 }
 
 impl PartialEq for Wrapper {
@@ -95,7 +159,25 @@ impl PartialEq for Wrapper {
 
 impl Visit for Wrapper {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
-        self.parameters.visit(name, visitor)
+        self.parameters.visit(name, visitor)?;
+
+        if visitor.is_reading() {
+            self.defaults.clear();
+        }
+        let _ = self.defaults.visit(&format!("{name}Defaults"), visitor);
+
+        if visitor.is_reading() {
+            self.dirty.set(true);
+            self.changed = true;
+            // Older files do not have defaults, current values of the parameters are used instead.
+            for parameter in self.parameters.iter() {
+                self.defaults
+                    .entry(parameter.name.clone())
+                    .or_insert(parameter.value);
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -110,6 +192,7 @@ impl Deref for Wrapper {
 impl DerefMut for Wrapper {
     fn deref_mut(&mut self) -> &mut Self::Target {
         self.dirty.set(true);
+        self.changed = true;
         &mut self.parameters
     }
 }
@@ -120,14 +203,24 @@ pub struct ParameterContainer {
     #[reflect(deref)]
     parameters: Wrapper,
 
+    #[reflect(hidden)]
+    #[visit(optional)]
+    computed: Vec<ComputedParameterDefinition>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     lookup: RefCell<FxHashMap<String, usize>>,
+
+    // Names of the computed parameters, which errors were written to the log. An error is reported only
+    // once, until the parameter is computed successfully.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    reported_errors: FxHashSet<String>,
 }
 
 impl PartialEq for ParameterContainer {
     fn eq(&self, other: &Self) -> bool {
-        self.parameters == other.parameters
+        self.parameters == other.parameters && self.computed == other.computed
     }
 }
 
@@ -135,7 +228,9 @@ impl Clone for ParameterContainer {
     fn clone(&self) -> Self {
         Self {
             parameters: self.parameters.clone(),
+            computed: self.computed.clone(),
             lookup: RefCell::new(self.lookup.borrow().clone()),
+            reported_errors: self.reported_errors.clone(),
         }
     }
 }
@@ -154,8 +249,13 @@ impl ParameterContainer {
         }
     }
 
-    /// Adds a new parameter with a given name and value to the container.
+    /// Adds a new parameter with a given name and value to the container. The value is also remembered as
+    /// the default value of the parameter, see [`Self::reset_to_defaults`] for more info.
     pub fn add(&mut self, name: &str, value: Parameter) {
+        self.parameters
+            .defaults
+            .entry(name.to_string())
+            .or_insert(value);
         self.parameters.push(ParameterDefinition {
             name: name.to_string(),
             value,
@@ -171,12 +271,589 @@ impl ParameterContainer {
             .and_then(|i| self.parameters.parameters.get(*i).map(|d| &d.value))
     }
 
+    /// Sets a new value of a parameter with the given name. If there's no such parameter, adds a new one.
+    pub fn set(&mut self, name: &str, value: Parameter) {
+        match self.get_mut(name) {
+            Some(parameter) => *parameter = value,
+            None => self.add(name, value),
+        }
+    }
+
+    /// Removes a parameter with the given name and returns its value.
+    pub fn remove(&mut self, name: &str) -> Option<Parameter> {
+        let index = self.parameters.iter().position(|p| p.name == name)?;
+        self.parameters.defaults.remove(name);
+        Some(self.parameters.remove(index).value)
+    }
+
+    /// Returns a default value of a parameter with the given name. The default value is the value the parameter
+    /// had when it was added to the container or when the container was loaded.
+    pub fn default_value(&self, name: &str) -> Option<&Parameter> {
+        self.parameters.defaults.get(name)
+    }
+
+    /// Restores the default value of every parameter in the container. The default value is captured when a
+    /// parameter is added to the container (see [`Self::add`]) or when the container is loaded, so for machines
+    /// created in the editor it is the value set in the editor. Parameters without a default value (for
+    /// example, the ones that were added using reflection) keep their current values. It could be useful to
+    /// reuse an entity with an animation blending state machine without rebuilding the machine.
+    pub fn reset_to_defaults(&mut self) {
+        // Modify the inner storage directly, the layout is not changed.
+        let Wrapper {
+            parameters,
+            defaults,
+            changed,
+            ..
+        } = &mut self.parameters;
+        *changed = true;
+        for definition in parameters.iter_mut() {
+            if let Some(default) = defaults.get(&definition.name) {
+                definition.value = *default;
+            }
+        }
+    }
+
+    /// Returns total amount of parameters in the container.
+    pub fn len(&self) -> usize {
+        self.parameters.len()
+    }
+
+    /// Returns `true` if the container is empty, `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty()
+    }
+
+    /// Captures current values of all parameters of the container. The snapshot could be used later to find
+    /// which parameters were changed, see [`Self::diff_since`] for more info.
+    pub fn snapshot(&self) -> ParameterSnapshot {
+        ParameterSnapshot {
+            parameters: self.parameters.parameters.clone(),
+        }
+    }
+
+    /// Returns a list of parameters (with their current values), that were added or changed since the given
+    /// snapshot was made. Parameters that were removed from the container are not reported. It could be used
+    /// to send only changed parameters over the network, for example:
+    ///
+    /// ```rust
+    /// # use fyrox_animation::machine::{Parameter, ParameterContainer};
+    /// let mut parameters = ParameterContainer::default();
+    /// parameters.add("Run", Parameter::Rule(false));
+    /// parameters.add("Speed", Parameter::Weight(0.0));
+    ///
+    /// let snapshot = parameters.snapshot();
+    ///
+    /// parameters.set("Run", Parameter::Rule(true));
+    ///
+    /// assert_eq!(
+    ///     parameters.diff_since(&snapshot),
+    ///     vec![("Run".to_string(), Parameter::Rule(true))]
+    /// );
+    /// ```
+    ///
+    /// The method has O(n) complexity if the set of parameters has not changed since the snapshot was made,
+    /// and O(n*m) in the worst case.
+    pub fn diff_since(&self, previous: &ParameterSnapshot) -> Vec<(String, Parameter)> {
+        self.parameters
+            .iter()
+            .enumerate()
+            .filter(|(index, definition)| {
+                let previous_value = match previous.parameters.get(*index) {
+                    // Fast path - parameters usually stay at the same positions.
+                    Some(previous) if previous.name == definition.name => Some(&previous.value),
+                    _ => previous.get(&definition.name),
+                };
+                previous_value != Some(&definition.value)
+            })
+            .map(|(_, definition)| (definition.name.clone(), definition.value))
+            .collect()
+    }
+
+    /// Fills the container with parameters of `base` container overridden by parameters of `overrides`
+    /// container. Parameters of `overrides` that do not exist in `base` are added to the end. The lookup
+    /// table is rebuilt only if the set of parameters has changed. Computed parameters of `base` are copied
+    /// as well (except the overridden ones), so they could be re-calculated from the overridden values.
+    pub(crate) fn overlay(&mut self, base: &ParameterContainer, overrides: &ParameterContainer) {
+        let computed = base
+            .computed
+            .iter()
+            .filter(|c| overrides.get(&c.name).is_none());
+        if !self.computed.iter().eq(computed.clone()) {
+            self.computed = computed.cloned().collect();
+            self.parameters.changed = true;
+        }
+
+        // Modify the inner storage directly to not mark the lookup table dirty on every call.
+        let target = &mut self.parameters.parameters;
+        let mut layout_changed = false;
+        let mut count = 0;
+
+        for definition in base.parameters.iter().chain(
+            overrides
+                .parameters
+                .iter()
+                .filter(|p| base.get(&p.name).is_none()),
+        ) {
+            let value = overrides.get(&definition.name).unwrap_or(&definition.value);
+
+            match target.get_mut(count) {
+                Some(existing) if existing.name == definition.name => {
+                    if existing.value != *value {
+                        existing.value = *value;
+                        self.parameters.changed = true;
+                    }
+                }
+                _ => {
+                    target.truncate(count);
+                    target.push(ParameterDefinition {
+                        name: definition.name.clone(),
+                        value: *value,
+                    });
+                    layout_changed = true;
+                }
+            }
+
+            count += 1;
+        }
+
+        if target.len() != count {
+            target.truncate(count);
+            layout_changed = true;
+        }
+
+        if layout_changed {
+            self.parameters.dirty.set(true);
+            self.parameters.changed = true;
+        }
+    }
+
     /// Tries to borrow a parameter by its name. The method has O(1) complexity.
     pub fn get_mut(&mut self, name: &str) -> Option<&mut Parameter> {
         self.update_index();
+        self.parameters.changed = true;
         self.lookup
             .borrow()
             .get(name)
             .and_then(|i| self.parameters.parameters.get_mut(*i).map(|d| &mut d.value))
     }
+
+    /// Returns a value of a [`Parameter::Rule`] parameter with the given name. Returns `None` if there's
+    /// no such parameter or it has some other type.
+    pub fn get_rule(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            Parameter::Rule(rule) => Some(*rule),
+            _ => None,
+        }
+    }
+
+    /// Returns a value of a [`Parameter::Weight`] parameter with the given name. Returns `None` if there's
+    /// no such parameter or it has some other type.
+    pub fn get_weight(&self, name: &str) -> Option<f32> {
+        match self.get(name)? {
+            Parameter::Weight(weight) => Some(*weight),
+            _ => None,
+        }
+    }
+
+    /// Returns a value of a [`Parameter::Index`] parameter with the given name. Returns `None` if there's
+    /// no such parameter or it has some other type.
+    pub fn get_index(&self, name: &str) -> Option<u32> {
+        match self.get(name)? {
+            Parameter::Index(index) => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// Sets a new value of a [`Parameter::Rule`] parameter with the given name. If there's no such parameter,
+    /// adds a new one. See [`Self::set`] for more info.
+    pub fn set_rule(&mut self, name: &str, value: bool) {
+        self.set(name, Parameter::Rule(value))
+    }
+
+    /// Sets a new value of a [`Parameter::Weight`] parameter with the given name. If there's no such parameter,
+    /// adds a new one. See [`Self::set`] for more info.
+    pub fn set_weight(&mut self, name: &str, value: f32) {
+        self.set(name, Parameter::Weight(value))
+    }
+
+    /// Sets a new value of a [`Parameter::Index`] parameter with the given name. If there's no such parameter,
+    /// adds a new one. See [`Self::set`] for more info.
+    pub fn set_index(&mut self, name: &str, value: u32) {
+        self.set(name, Parameter::Index(value))
+    }
+
+    /// Adds a computed parameter with the given name. Value of the parameter is calculated from the given
+    /// expression (see [`ParameterExpression`] docs for syntax) over other parameters of the container, and
+    /// stored as [`Parameter::Weight`], so it can be used as any other weight parameter. If there's already
+    /// a computed parameter with the same name, its expression is replaced.
+    ///
+    /// Computed parameters are evaluated lazily: [`super::Machine::evaluate_pose`] updates them before any
+    /// layer is evaluated, so their values are always consistent with the rest of parameters, but only if
+    /// some parameter was changed since the last update. Call [`Self::update_computed`] to get up-to-date
+    /// values right after changing the parameters. Layers with local
+    /// parameters (see [`super::MachineLayer::set_local_parameter`]) re-calculate computed parameters using
+    /// the local values, a local parameter with the same name as a computed one shadows it. Computed parameters
+    /// may depend on other computed parameters, but they must not form a cycle - in this case the method
+    /// returns [`ExpressionError::Cycle`] with the names of the parameters in the cycle and the container
+    /// is left unchanged.
+    pub fn add_computed(&mut self, name: &str, expression: &str) -> Result<(), ExpressionError> {
+        let definition = ComputedParameterDefinition {
+            name: name.to_string(),
+            expression: ParameterExpression::parse(expression)?,
+        };
+
+        let previous = match self.computed.iter_mut().find(|c| c.name == name) {
+            Some(existing) => Some(std::mem::replace(existing, definition)),
+            None => {
+                self.computed.push(definition);
+                None
+            }
+        };
+
+        if let Some(cycle) = self.find_cycle() {
+            match previous {
+                Some(previous) => {
+                    if let Some(existing) = self.computed.iter_mut().find(|c| c.name == name) {
+                        *existing = previous;
+                    }
+                }
+                None => {
+                    self.computed.pop();
+                }
+            }
+            return Err(ExpressionError::Cycle(cycle));
+        }
+
+        if self.get(name).is_none() {
+            self.add(name, Parameter::Weight(0.0));
+        }
+        self.reported_errors.remove(name);
+        self.parameters.changed = true;
+
+        Ok(())
+    }
+
+    /// Removes a computed parameter with the given name and returns its definition. The parameter itself
+    /// stays in the container with its last computed value.
+    pub fn remove_computed(&mut self, name: &str) -> Option<ComputedParameterDefinition> {
+        let index = self.computed.iter().position(|c| c.name == name)?;
+        self.reported_errors.remove(name);
+        Some(self.computed.remove(index))
+    }
+
+    /// Returns a slice with all computed parameter definitions.
+    pub fn computed(&self) -> &[ComputedParameterDefinition] {
+        &self.computed
+    }
+
+    fn find_cycle(&self) -> Option<Vec<String>> {
+        fn visit<'a>(
+            computed: &'a [ComputedParameterDefinition],
+            name: &'a str,
+            stack: &mut Vec<&'a str>,
+            finished: &mut FxHashSet<&'a str>,
+        ) -> Option<Vec<String>> {
+            if finished.contains(name) {
+                return None;
+            }
+            if let Some(position) = stack.iter().position(|n| *n == name) {
+                let mut cycle = stack[position..]
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>();
+                cycle.push(name.to_string());
+                return Some(cycle);
+            }
+            if let Some(definition) = computed.iter().find(|c| c.name == name) {
+                stack.push(name);
+                for dependency in definition.expression.dependencies() {
+                    if let Some(cycle) = visit(computed, dependency, stack, finished) {
+                        return Some(cycle);
+                    }
+                }
+                stack.pop();
+            }
+            finished.insert(name);
+            None
+        }
+
+        let mut finished = FxHashSet::default();
+        self.computed.iter().find_map(|definition| {
+            visit(
+                &self.computed,
+                &definition.name,
+                &mut Vec::new(),
+                &mut finished,
+            )
+        })
+    }
+
+    /// Re-calculates values of all computed parameters, if some parameter was changed since the last call.
+    /// Usually there's no need to call this method manually, it is called automatically by
+    /// [`super::Machine::evaluate_pose`]. Errors (such as unknown parameters in expressions) are written to
+    /// the log once per parameter (until it is computed successfully) and the respective parameters keep
+    /// their previous values.
+    pub fn update_computed(&mut self) {
+        if !self.parameters.changed {
+            return;
+        }
+
+        fn evaluate(
+            container: &mut ParameterContainer,
+            index: usize,
+            evaluated: &mut [bool],
+            stack: &mut Vec<usize>,
+        ) -> Result<f32, ExpressionError> {
+            let name = container.computed[index].name.clone();
+
+            if evaluated[index] {
+                return container
+                    .get(&name)
+                    .and_then(|p| p.as_number())
+                    .ok_or(ExpressionError::UnknownParameter(name));
+            }
+
+            if let Some(position) = stack.iter().position(|i| *i == index) {
+                let mut cycle = stack[position..]
+                    .iter()
+                    .map(|i| container.computed[*i].name.clone())
+                    .collect::<Vec<_>>();
+                cycle.push(name);
+                return Err(ExpressionError::Cycle(cycle));
+            }
+
+            stack.push(index);
+            let expression = container.computed[index].expression.clone();
+            let result = expression.evaluate(|dependency| {
+                match container.computed.iter().position(|c| c.name == dependency) {
+                    Some(dependency_index) => {
+                        evaluate(container, dependency_index, evaluated, stack)
+                    }
+                    None => container
+                        .get(dependency)
+                        .and_then(|p| p.as_number())
+                        .ok_or_else(|| ExpressionError::UnknownParameter(dependency.to_string())),
+                }
+            });
+            stack.pop();
+
+            let value = result?;
+            container.set(&name, Parameter::Weight(value));
+            container.reported_errors.remove(&name);
+            evaluated[index] = true;
+            Ok(value)
+        }
+
+        let mut evaluated = vec![false; self.computed.len()];
+        let mut stack = Vec::new();
+        for index in 0..self.computed.len() {
+            if !evaluated[index] {
+                if let Err(err) = evaluate(self, index, &mut evaluated, &mut stack) {
+                    let name = &self.computed[index].name;
+                    if !self.reported_errors.contains(name) {
+                        Log::err(format!(
+                            "Unable to compute {} parameter. Reason: {}",
+                            name, err
+                        ));
+                        self.reported_errors.insert(name.clone());
+                    }
+                    evaluated[index] = true;
+                }
+            }
+        }
+
+        self.parameters.changed = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::machine::{expression::ExpressionError, Parameter, ParameterContainer};
+
+    #[test]
+    fn test_diff_since_snapshot() {
+        let mut parameters = ParameterContainer::default();
+        parameters.add("speed", Parameter::Weight(2.0));
+        parameters.add("running", Parameter::Rule(false));
+        parameters.add("index", Parameter::Index(1));
+
+        let snapshot = parameters.snapshot();
+        assert!(parameters.diff_since(&snapshot).is_empty());
+
+        parameters.set("running", Parameter::Rule(true));
+
+        assert_eq!(
+            parameters.diff_since(&snapshot),
+            vec![("running".to_string(), Parameter::Rule(true))]
+        );
+    }
+
+    #[test]
+    fn test_computed_parameters() {
+        let mut parameters = ParameterContainer::default();
+        parameters.add("speed", Parameter::Weight(2.0));
+        parameters.add("max_speed", Parameter::Weight(4.0));
+        parameters.add("running", Parameter::Rule(true));
+
+        parameters
+            .add_computed("doubled", "normalized * 2")
+            .unwrap();
+        parameters
+            .add_computed("normalized", "speed / max_speed * running")
+            .unwrap();
+
+        parameters.update_computed();
+
+        assert_eq!(parameters.get("normalized"), Some(&Parameter::Weight(0.5)));
+        assert_eq!(parameters.get("doubled"), Some(&Parameter::Weight(1.0)));
+    }
+
+    #[test]
+    fn test_computed_parameters_cycle() {
+        let mut parameters = ParameterContainer::default();
+        parameters.add_computed("a", "b + 1").unwrap();
+        parameters.add_computed("b", "c + 1").unwrap();
+
+        assert_eq!(
+            parameters.add_computed("c", "a + 1"),
+            Err(ExpressionError::Cycle(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "a".to_string()
+            ]))
+        );
+        assert_eq!(parameters.computed().len(), 2);
+        assert!(parameters.get("c").is_none());
+    }
+
+    #[test]
+    fn test_typed_accessors() {
+        let mut parameters = ParameterContainer::default();
+        parameters.set_rule("running", true);
+        parameters.set_weight("speed", 2.0);
+        parameters.set_index("pose", 3);
+
+        assert_eq!(parameters.get_rule("running"), Some(true));
+        assert_eq!(parameters.get_weight("speed"), Some(2.0));
+        assert_eq!(parameters.get_index("pose"), Some(3));
+
+        // Type mismatch or unknown parameter.
+        assert_eq!(parameters.get_weight("running"), None);
+        assert_eq!(parameters.get_rule("jumping"), None);
+
+        parameters.set_rule("running", false);
+        assert_eq!(parameters.get_rule("running"), Some(false));
+        assert_eq!(parameters.len(), 3);
+    }
+
+    #[test]
+    fn test_reset_to_defaults() {
+        let mut parameters = ParameterContainer::default();
+        parameters.add("running", Parameter::Rule(false));
+        parameters.add("speed", Parameter::Weight(1.0));
+
+        parameters.set_rule("running", true);
+        parameters.set_weight("speed", 5.0);
+        // Added by `set`, the first value is the default.
+        parameters.set_index("pose", 2);
+        parameters.set_index("pose", 4);
+        assert_eq!(
+            parameters.default_value("speed"),
+            Some(&Parameter::Weight(1.0))
+        );
+
+        parameters.reset_to_defaults();
+        assert_eq!(parameters.get_rule("running"), Some(false));
+        assert_eq!(parameters.get_weight("speed"), Some(1.0));
+        assert_eq!(parameters.get_index("pose"), Some(2));
+    }
+
+    #[test]
+    fn test_overlay() {
+        let mut base = ParameterContainer::default();
+        base.add("speed", Parameter::Weight(1.0));
+        base.add("running", Parameter::Rule(false));
+        base.add_computed("doubled", "speed * 2").unwrap();
+
+        let mut overrides = ParameterContainer::default();
+        overrides.add("running", Parameter::Rule(true));
+        overrides.add("local", Parameter::Index(3));
+
+        let mut merged = ParameterContainer::default();
+        merged.overlay(&base, &overrides);
+        assert_eq!(
+            merged
+                .parameters
+                .iter()
+                .map(|p| (p.name.as_str(), p.value))
+                .collect::<Vec<_>>(),
+            [
+                ("speed", Parameter::Weight(1.0)),
+                ("running", Parameter::Rule(true)),
+                ("doubled", Parameter::Weight(0.0)),
+                ("local", Parameter::Index(3)),
+            ]
+        );
+        assert_eq!(merged.computed(), base.computed());
+
+        // Values are updated in place, removed parameters are removed from the merged container.
+        base.set_weight("speed", 2.0);
+        overrides.remove("local");
+        merged.overlay(&base, &overrides);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.get_weight("speed"), Some(2.0));
+        assert_eq!(merged.get_rule("running"), Some(true));
+        assert_eq!(merged.get_index("local"), None);
+
+        // Local parameters shadow computed ones.
+        overrides.set_weight("doubled", 5.0);
+        merged.overlay(&base, &overrides);
+        merged.update_computed();
+        assert!(merged.computed().is_empty());
+        assert_eq!(merged.get_weight("doubled"), Some(5.0));
+    }
+
+    #[test]
+    fn test_computed_parameters_are_lazy() {
+        let mut parameters = ParameterContainer::default();
+        parameters.add("speed", Parameter::Weight(2.0));
+        parameters.add_computed("doubled", "speed * 2").unwrap();
+
+        parameters.update_computed();
+        assert_eq!(parameters.get_weight("doubled"), Some(4.0));
+
+        // Nothing was changed, so the computed value is not touched. Modify the storage directly to
+        // check it, the public API marks the container as changed.
+        parameters.parameters.parameters[1].value = Parameter::Weight(100.0);
+        parameters.update_computed();
+        assert_eq!(parameters.get_weight("doubled"), Some(100.0));
+
+        parameters.set_weight("speed", 3.0);
+        parameters.update_computed();
+        assert_eq!(parameters.get_weight("doubled"), Some(6.0));
+    }
+
+    #[test]
+    fn test_computed_parameter_errors_are_reported_once() {
+        let mut parameters = ParameterContainer::default();
+        parameters.add("speed", Parameter::Weight(2.0));
+        parameters
+            .add_computed("ratio", "speed / max_speed")
+            .unwrap();
+
+        parameters.update_computed();
+        assert!(parameters.reported_errors.contains("ratio"));
+
+        parameters.set_weight("speed", 3.0);
+        parameters.update_computed();
+        assert_eq!(parameters.reported_errors.len(), 1);
+        assert_eq!(parameters.get_weight("ratio"), Some(0.0));
+
+        // The error is reported again only after successful computation.
+        parameters.set_weight("max_speed", 6.0);
+        parameters.update_computed();
+        assert!(parameters.reported_errors.is_empty());
+        assert_eq!(parameters.get_weight("ratio"), Some(0.5));
+    }
 }
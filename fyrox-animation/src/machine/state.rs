@@ -3,6 +3,7 @@
 use crate::{
     core::{
         algebra::Vector2,
+        color::Color,
         pool::{Handle, Pool},
         rand::{self, seq::IteratorRandom},
         reflect::prelude::*,
@@ -12,7 +13,7 @@ use crate::{
     Animation, AnimationContainer, AnimationPose, EntityId,
 };
 use fyrox_core::uuid::{uuid, Uuid};
-use fyrox_core::{NameProvider, TypeUuidProvider};
+use fyrox_core::{uuid_provider, NameProvider, TypeUuidProvider};
 use std::{
     cell::Ref,
     ops::{Deref, DerefMut},
@@ -104,6 +105,19 @@ impl<T: EntityId> StateAction<T> {
     }
 }
 
+/// Optional editor-specific data of a state. It is not used at runtime in any way and exists only to be
+/// persisted together with the state.
+#[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct StateMetadata {
+    /// Color of the state in the graph view.
+    pub color: Color,
+
+    /// Arbitrary notes about the state.
+    pub comment: String,
+}
+
+uuid_provider!(StateMetadata = "0f7c2b9e-4a51-4d86-b3c3-5e2a9d8f61a7");
+
 /// State is a final "container" for animation pose. It has backing pose node which provides a set of values.
 /// States can be connected with each other using _transitions_, states with transitions form a state graph.
 #[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
@@ -125,6 +139,34 @@ pub struct State<T: EntityId> {
     /// Root node of the state that provides the state with animation data.
     #[reflect(read_only)]
     pub root: Handle<PoseNode<T>>,
+
+    /// A name of a synchronization group of the state. Animations of the states that share the same
+    /// group are played with the same normalized time (phase), which keeps them aligned while blending
+    /// (for example, feet of walk and run animations of different length). Empty string means that the
+    /// state does not belong to any group. See [`crate::machine::MachineLayer`] docs for more info.
+    #[visit(optional)]
+    #[reflect(
+        description = "A name of a synchronization group of the state. Animations of the states \
+        that share the same group are played with the same normalized time."
+    )]
+    pub sync_group: String,
+
+    /// Optional looping override for the animations of the state. When set, looping flag of every animation
+    /// used by the state is replaced with this value while the state is active (or participates in a
+    /// transition) and restored back when it is not. It allows to use the same animation as a looping one
+    /// in one state and as a play-once one in another. When a state with `Some(false)` override plays all its
+    /// animations to the end, the layer emits [`crate::machine::Event::StatePlaybackEnded`] event.
+    #[visit(optional)]
+    #[reflect(
+        description = "Optional looping override for the animations of the state. If not set, \
+        looping flags of the animations are used as is."
+    )]
+    pub looping: Option<bool>,
+
+    /// Optional editor-specific data of the state (in addition to [`Self::position`]). It is [`None`] by
+    /// default, so runtime-only machines do not pay anything for it.
+    #[visit(optional)]
+    pub metadata: Option<StateMetadata>,
 }
 
 impl<T: EntityId> NameProvider for State<T> {
@@ -142,9 +184,43 @@ impl<T: EntityId> State<T> {
             on_enter_actions: Default::default(),
             on_leave_actions: Default::default(),
             root,
+            sync_group: Default::default(),
+            looping: None,
+            metadata: None,
         }
     }
 
+    /// Sets a new looping override for the animations of the state. See [`Self::looping`] docs for more info.
+    pub fn set_looping(&mut self, looping: Option<bool>) {
+        self.looping = looping;
+    }
+
+    /// Sets a new name of the state.
+    pub fn set_name<S: AsRef<str>>(&mut self, name: S) {
+        name.as_ref().clone_into(&mut self.name);
+    }
+
+    /// Returns a reference to editor-specific data of the state, if any.
+    pub fn metadata(&self) -> Option<&StateMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Returns a reference to editor-specific data of the state, creating default data if there's none.
+    pub fn metadata_mut(&mut self) -> &mut StateMetadata {
+        self.metadata.get_or_insert_with(Default::default)
+    }
+
+    /// Sets a new synchronization group of the state. Empty string removes the state from any group.
+    pub fn set_sync_group<S: AsRef<str>>(&mut self, sync_group: S) {
+        sync_group.as_ref().clone_into(&mut self.sync_group);
+    }
+
+    /// Returns a name of synchronization group of the state. Empty string means that the state does not
+    /// belong to any group.
+    pub fn sync_group(&self) -> &str {
+        &self.sync_group
+    }
+
     /// Returns a final pose of the state.
     pub fn pose<'a>(&self, nodes: &'a Pool<PoseNode<T>>) -> Option<Ref<'a, AnimationPose<T>>> {
         nodes.try_borrow(self.root).map(|root| root.pose())
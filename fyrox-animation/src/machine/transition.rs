@@ -7,7 +7,11 @@ use crate::{
 };
 use fyrox_core::uuid::{uuid, Uuid};
 use fyrox_core::{NameProvider, TypeUuidProvider};
-use std::any::{type_name, Any, TypeId};
+use std::{
+    any::{type_name, Any, TypeId},
+    fmt::{Display, Formatter},
+    ops::Not,
+};
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 macro_rules! define_two_args_node {
@@ -338,6 +342,28 @@ impl<T: EntityId> Default for LogicNode<T> {
 }
 
 impl<T: EntityId> LogicNode<T> {
+    /// Returns names of all the `Rule` parameters used by the logic node and its descendants, in the order of
+    /// appearance. The same name could be returned multiple times.
+    pub fn rule_names(&self) -> Vec<&str> {
+        fn collect<'a, T: EntityId>(node: &'a LogicNode<T>, names: &mut Vec<&'a str>) {
+            match node {
+                LogicNode::Parameter(rule_name) => names.push(rule_name),
+                LogicNode::And(AndNode { lhs, rhs })
+                | LogicNode::Or(OrNode { lhs, rhs })
+                | LogicNode::Xor(XorNode { lhs, rhs }) => {
+                    collect(lhs, names);
+                    collect(rhs, names);
+                }
+                LogicNode::Not(NotNode { lhs }) => collect(lhs, names),
+                LogicNode::IsAnimationEnded(_) => {}
+            }
+        }
+
+        let mut names = Vec::new();
+        collect(self, &mut names);
+        names
+    }
+
     /// Calculates final value of the logic node.
     pub fn calculate_value(
         &self,
@@ -375,6 +401,190 @@ impl<T: EntityId> LogicNode<T> {
     }
 }
 
+impl<T: EntityId> Display for LogicNode<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogicNode::Parameter(rule_name) => write!(f, "{rule_name}"),
+            LogicNode::And(and) => write!(f, "({} && {})", and.lhs, and.rhs),
+            LogicNode::Or(or) => write!(f, "({} || {})", or.lhs, or.rhs),
+            LogicNode::Xor(xor) => write!(f, "({} ^ {})", xor.lhs, xor.rhs),
+            LogicNode::Not(not) => write!(f, "!{}", not.lhs),
+            LogicNode::IsAnimationEnded(animation) => write!(f, "IsAnimationEnded({animation})"),
+        }
+    }
+}
+
+/// A builder for transition conditions, that allows to combine any number of `Rule` parameters using boolean logic
+/// without creating the logic tree manually. The result could be converted into [`LogicNode`] and used as a condition
+/// of a transition (see [`Transition::set_condition`]).
+///
+/// # Examples
+///
+/// ```rust
+/// use fyrox_animation::AnimationContainer;
+/// use fyrox_animation::machine::{
+///     transition::{LogicNode, TransitionCondition},
+///     Parameter, ParameterContainer,
+/// };
+/// use fyrox_core::pool::ErasedHandle;
+///
+/// let mut parameters = ParameterContainer::default();
+/// parameters.add("Grounded", Parameter::Rule(true));
+/// parameters.add("Attacking", Parameter::Rule(false));
+///
+/// // Grounded && !Attacking
+/// let condition: LogicNode<ErasedHandle> = TransitionCondition::rule("Grounded")
+///     .and(!TransitionCondition::rule("Attacking"))
+///     .build();
+///
+/// assert!(condition.calculate_value(&parameters, &AnimationContainer::default()));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionCondition<T: EntityId> {
+    node: LogicNode<T>,
+}
+
+impl<T: EntityId> TransitionCondition<T> {
+    /// Creates a condition that is `true` iff the `Rule` parameter with the given name is `true`.
+    pub fn rule(name: &str) -> Self {
+        Self {
+            node: LogicNode::Parameter(name.to_owned()),
+        }
+    }
+
+    /// Creates a condition that is `true` iff the given animation has ended.
+    pub fn animation_ended(animation: Handle<Animation<T>>) -> Self {
+        Self {
+            node: LogicNode::IsAnimationEnded(animation),
+        }
+    }
+
+    /// Combines the condition with the other one using logical AND.
+    pub fn and(self, other: Self) -> Self {
+        Self {
+            node: LogicNode::And(AndNode {
+                lhs: Box::new(self.node),
+                rhs: Box::new(other.node),
+            }),
+        }
+    }
+
+    /// Combines the condition with the other one using logical OR.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            node: LogicNode::Or(OrNode {
+                lhs: Box::new(self.node),
+                rhs: Box::new(other.node),
+            }),
+        }
+    }
+
+    /// Combines the condition with the other one using logical XOR.
+    pub fn xor(self, other: Self) -> Self {
+        Self {
+            node: LogicNode::Xor(XorNode {
+                lhs: Box::new(self.node),
+                rhs: Box::new(other.node),
+            }),
+        }
+    }
+
+    /// Finishes the condition and returns its logic tree.
+    pub fn build(self) -> LogicNode<T> {
+        self.node
+    }
+}
+
+impl<T: EntityId> Not for TransitionCondition<T> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self {
+            node: LogicNode::Not(NotNode {
+                lhs: Box::new(self.node),
+            }),
+        }
+    }
+}
+
+impl<T: EntityId> From<TransitionCondition<T>> for LogicNode<T> {
+    fn from(condition: TransitionCondition<T>) -> Self {
+        condition.build()
+    }
+}
+
+/// Defines the amount of time to transition from the source to the destination state.
+#[derive(Debug, Clone, Copy, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum TransitionTime {
+    /// Fixed amount of time in seconds.
+    Absolute(f32),
+    /// A fraction of the duration of the first animation of the destination state. For example, `0.25` means that
+    /// the transition takes a quarter of the animation duration. The actual time is calculated when the transition
+    /// is activated, so changes of the animation length (or speed) are taken into account automatically. If the
+    /// destination state has no animations, the transition is done instantly.
+    RelativeToTarget(f32),
+}
+
+impl TypeUuidProvider for TransitionTime {
+    fn type_uuid() -> Uuid {
+        uuid!("9702260f-b198-4080-8ce3-f1922bf9c3b6")
+    }
+}
+
+impl Default for TransitionTime {
+    fn default() -> Self {
+        Self::Absolute(0.0)
+    }
+}
+
+impl Display for TransitionTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransitionTime::Absolute(time) => write!(f, "{time}s"),
+            TransitionTime::RelativeToTarget(fraction) => {
+                write!(f, "{}% of target", fraction * 100.0)
+            }
+        }
+    }
+}
+
+/// Defines which transitions could interrupt an active interruptible transition. See
+/// [`Transition::set_interruptible`] for more info.
+#[derive(
+    Default, Debug, Clone, Copy, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum TransitionInterruptSource {
+    /// Transitions from the destination state of the active transition.
+    #[default]
+    Destination,
+    /// Transitions from the source state of the active transition, that were added before the active transition
+    /// (i.e. transitions with higher priority).
+    Source,
+    /// Transitions from the destination state are checked first, then the transitions from the source state.
+    DestinationThenSource,
+    /// Transitions from the source state are checked first, then the transitions from the destination state.
+    SourceThenDestination,
+}
+
+impl TypeUuidProvider for TransitionInterruptSource {
+    fn type_uuid() -> Uuid {
+        uuid!("4a0d8c0e-5c47-4b53-9a43-2f1f3f6b8d21")
+    }
+}
+
+impl TransitionTime {
+    /// Calculates the amount of time (in seconds) using the given duration of the target animation (if any).
+    pub fn resolve(&self, target_duration: Option<f32>) -> f32 {
+        match self {
+            TransitionTime::Absolute(time) => *time,
+            TransitionTime::RelativeToTarget(fraction) => {
+                target_duration.map_or(0.0, |duration| duration * fraction)
+            }
+        }
+        .max(0.0)
+    }
+}
+
 /// Transition is a connection between two states with a rule that defines possibility of actual transition with blending.
 #[derive(Default, Debug, Clone, Reflect, PartialEq)]
 pub struct Transition<T: EntityId> {
@@ -382,9 +592,16 @@ pub struct Transition<T: EntityId> {
     #[reflect(description = "The name of the transition, it is used for debug output.")]
     pub(crate) name: String,
 
-    /// Total amount of time to transition from `src` to `dst` state.
-    #[reflect(description = "Total amount of time (in seconds) to transition \
-        from source to destination state")]
+    /// Amount of time to transition from `src` to `dst` state.
+    #[reflect(
+        description = "Amount of time to transition from source to destination state, \
+        either in seconds or relative to the duration of the destination state animation."
+    )]
+    pub(crate) time: TransitionTime,
+
+    /// Actual amount of time (in seconds) to transition from `src` to `dst` state, it is calculated from `time`
+    /// when the transition is activated.
+    #[reflect(hidden)]
     pub(crate) transition_time: f32,
 
     pub(crate) elapsed_time: f32,
@@ -402,37 +619,113 @@ pub struct Transition<T: EntityId> {
 
     /// 0 - evaluates `src` pose, 1 - `dest`, 0..1 - blends `src` and `dest`
     pub(crate) blend_factor: f32,
+
+    /// Minimum amount of time (in seconds) the source state must be active before the transition can
+    /// be activated.
+    #[reflect(
+        description = "Minimum amount of time (in seconds) the source state must be active \
+        before the transition can be activated.",
+        min_value = 0.0
+    )]
+    pub(crate) min_source_time: f32,
+
+    /// An optional weight of the transition, that is used to pick one of the transitions that could be activated
+    /// at the same time.
+    #[reflect(
+        description = "An optional weight of the transition, that is used to randomly pick one of the \
+        transitions from the same state that could be activated at the same time.",
+        min_value = 0.0
+    )]
+    pub(crate) selection_weight: Option<f32>,
+
+    /// Whether the transition could be interrupted by other transitions while it is active.
+    #[reflect(
+        description = "Whether the transition could be interrupted by other transitions while it is active."
+    )]
+    pub(crate) interruptible: bool,
+
+    /// Defines which transitions could interrupt the transition.
+    #[reflect(description = "Defines which transitions could interrupt the transition.")]
+    pub(crate) interrupt_source: TransitionInterruptSource,
+
+    /// An optional condition, that must be true to allow the transition to be interrupted.
+    #[reflect(
+        description = "An optional condition, that must be true to allow the transition to be interrupted."
+    )]
+    pub(crate) interrupt_rules: Option<LogicNode<T>>,
 }
 
+// Version history of the serialized data of the transition:
+//
+// 1 - the data without explicit version. Very old transitions stored an absolute transition time and a single rule
+//     instead of a condition.
+// 2 - explicit version was added along with the minimum source time, the selection weight and the interruption
+//     settings.
+const VERSION: u8 = 2;
+
+// Manual implementation of the trait to be able to upgrade the data saved by older versions.
 impl<T: EntityId> Visit for Transition<T> {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut guard = visitor.enter_region(name)?;
 
-        self.name.visit("Name", &mut guard)?;
-        self.transition_time.visit("TransitionTime", &mut guard)?;
-        self.source.visit("Source", &mut guard)?;
-        self.dest.visit("Dest", &mut guard)?;
-        self.blend_factor.visit("BlendFactor", &mut guard)?;
+        // The data without version is the data of the first version.
+        let mut version = if guard.is_reading() { 1 } else { VERSION };
+        let _ = version.visit("Version", &mut guard);
 
-        if guard.is_reading() {
-            if self.condition.visit("Condition", &mut guard).is_err() {
-                // Try to convert the old version.
-                let mut invert_rule = false;
-                let mut rule: String = Default::default();
-
-                invert_rule.visit("InvertRule", &mut guard)?;
-                rule.visit("Rule", &mut guard)?;
-
-                if invert_rule {
-                    self.condition = LogicNode::Not(NotNode {
-                        lhs: Box::new(LogicNode::Parameter(rule)),
-                    });
-                } else {
-                    self.condition = LogicNode::Parameter(rule);
+        match version {
+            1 => {
+                self.name.visit("Name", &mut guard)?;
+
+                if self.time.visit("Time", &mut guard).is_err() {
+                    // Try to convert the old version.
+                    let mut transition_time = 0.0f32;
+                    transition_time.visit("TransitionTime", &mut guard)?;
+                    self.time = TransitionTime::Absolute(transition_time);
+                }
+
+                self.source.visit("Source", &mut guard)?;
+                self.dest.visit("Dest", &mut guard)?;
+                self.blend_factor.visit("BlendFactor", &mut guard)?;
+
+                if self.condition.visit("Condition", &mut guard).is_err() {
+                    // Try to convert the old version.
+                    let mut invert_rule = false;
+                    let mut rule: String = Default::default();
+
+                    invert_rule.visit("InvertRule", &mut guard)?;
+                    rule.visit("Rule", &mut guard)?;
+
+                    if invert_rule {
+                        self.condition = LogicNode::Not(NotNode {
+                            lhs: Box::new(LogicNode::Parameter(rule)),
+                        });
+                    } else {
+                        self.condition = LogicNode::Parameter(rule);
+                    }
                 }
             }
-        } else {
-            self.condition.visit("Condition", &mut guard)?;
+            VERSION => {
+                self.name.visit("Name", &mut guard)?;
+                self.time.visit("Time", &mut guard)?;
+                self.source.visit("Source", &mut guard)?;
+                self.dest.visit("Dest", &mut guard)?;
+                self.blend_factor.visit("BlendFactor", &mut guard)?;
+                self.condition.visit("Condition", &mut guard)?;
+                self.min_source_time.visit("MinSourceTime", &mut guard)?;
+                self.selection_weight.visit("SelectionWeight", &mut guard)?;
+                self.interruptible.visit("Interruptible", &mut guard)?;
+                self.interrupt_source.visit("InterruptSource", &mut guard)?;
+                self.interrupt_rules.visit("InterruptRules", &mut guard)?;
+            }
+            _ => {
+                return Err(VisitError::User(format!(
+                    "Unsupported transition version {version}!"
+                )))
+            }
+        }
+
+        if guard.is_reading() {
+            self.transition_time = self.time.resolve(None);
         }
 
         Ok(())
@@ -457,12 +750,18 @@ impl<T: EntityId> Transition<T> {
     ) -> Transition<T> {
         Self {
             name: name.to_owned(),
+            time: TransitionTime::Absolute(time),
             transition_time: time,
             elapsed_time: 0.0,
             source: src,
             dest,
             blend_factor: 0.0,
             condition: LogicNode::Parameter(rule.to_owned()),
+            min_source_time: 0.0,
+            selection_weight: None,
+            interruptible: false,
+            interrupt_source: Default::default(),
+            interrupt_rules: None,
         }
     }
 
@@ -473,11 +772,28 @@ impl<T: EntityId> Transition<T> {
     }
 
     /// Returns the amount of time required to perform a transition from source to destination state, in seconds.
+    /// For relative times (see [`TransitionTime::RelativeToTarget`]) it is the time, that was calculated when the
+    /// transition was activated the last time.
     #[inline]
     pub fn transition_time(&self) -> f32 {
         self.transition_time
     }
 
+    /// Sets the amount of time to transition from source to destination state. See [`TransitionTime`] docs for
+    /// more info.
+    #[inline]
+    pub fn set_time(&mut self, time: TransitionTime) {
+        self.time = time;
+        self.transition_time = time.resolve(None);
+    }
+
+    /// Returns the amount of time to transition from source to destination state. See [`TransitionTime`] docs for
+    /// more info.
+    #[inline]
+    pub fn time(&self) -> TransitionTime {
+        self.time
+    }
+
     /// Returns a handle to source state.
     #[inline]
     pub fn source(&self) -> Handle<State<T>> {
@@ -490,11 +806,87 @@ impl<T: EntityId> Transition<T> {
         self.dest
     }
 
+    /// Sets minimum amount of time (in seconds) the source state must be active before the transition
+    /// can be activated (see [`crate::machine::MachineLayer::active_state_time`]). It could be used to
+    /// prevent rapid switching between two states, which conditions are toggling near some threshold.
+    /// Default value is zero, which means that the transition could be activated at any time.
+    pub fn set_min_source_time(&mut self, time: f32) {
+        self.min_source_time = time.max(0.0);
+    }
+
+    /// Returns minimum amount of time (in seconds) the source state must be active before the transition
+    /// can be activated.
+    pub fn min_source_time(&self) -> f32 {
+        self.min_source_time
+    }
+
+    /// Sets an optional selection weight of the transition. When multiple weighted transitions from the same
+    /// state could be activated at the same time, one of them is picked randomly with the probability proportional
+    /// to its weight (see [`crate::machine::MachineLayer::set_rng_seed`]). It could be used to add variations, for
+    /// example "from Idle, 70% go to IdleScratch, 30% to IdleLookAround". Transitions without a weight are picked
+    /// in the order of their creation (the first one that could be activated is used).
+    pub fn set_selection_weight(&mut self, weight: Option<f32>) {
+        self.selection_weight = weight.map(|w| w.max(0.0));
+    }
+
+    /// Returns an optional selection weight of the transition.
+    pub fn selection_weight(&self) -> Option<f32> {
+        self.selection_weight
+    }
+
+    /// Defines whether the transition could be interrupted by other transitions while it is active. By default,
+    /// transitions are not interruptible and once a transition is activated, it is always finished. An active
+    /// interruptible transition checks the transitions defined by [`Self::set_interrupt_source`] every update
+    /// and if the condition of any of them is true (and the interrupt rules allow it, see
+    /// [`Self::set_interrupt_rules`]), the new transition is activated. The new transition blends from the pose
+    /// at the moment of interruption, so there's no pose pop even if the interrupted transition was
+    /// partially completed. It could be used to make responsive controls, for example to cancel an attack
+    /// wind-up with a dodge.
+    ///
+    /// Transitions of interrupts (see [`crate::machine::MachineLayer::add_interrupt`]) cannot be interrupted.
+    pub fn set_interruptible(&mut self, interruptible: bool) {
+        self.interruptible = interruptible;
+    }
+
+    /// Returns `true` if the transition could be interrupted by other transitions, `false` - otherwise.
+    pub fn is_interruptible(&self) -> bool {
+        self.interruptible
+    }
+
+    /// Sets which transitions could interrupt the transition. See [`TransitionInterruptSource`] docs for more
+    /// info.
+    pub fn set_interrupt_source(&mut self, source: TransitionInterruptSource) {
+        self.interrupt_source = source;
+    }
+
+    /// Returns which transitions could interrupt the transition.
+    pub fn interrupt_source(&self) -> TransitionInterruptSource {
+        self.interrupt_source
+    }
+
+    /// Sets an optional condition, that must be true to allow the transition to be interrupted. If there's no
+    /// condition, the transition could be interrupted at any time.
+    pub fn set_interrupt_rules(&mut self, rules: Option<LogicNode<T>>) {
+        self.interrupt_rules = rules;
+    }
+
+    /// Returns a reference to the optional interrupt condition of the transition.
+    pub fn interrupt_rules(&self) -> Option<&LogicNode<T>> {
+        self.interrupt_rules.as_ref()
+    }
+
     /// Sets new condition for the transition.
     pub fn set_condition(&mut self, condition: LogicNode<T>) {
         self.condition = condition;
     }
 
+    /// Sets new condition for the transition. Could be used to replace the single rule passed to [`Self::new`] with
+    /// a compound condition built with [`TransitionCondition`].
+    pub fn with_condition(mut self, condition: impl Into<LogicNode<T>>) -> Self {
+        self.condition = condition.into();
+        self
+    }
+
     /// Returns a reference to the current condition of the transition.
     pub fn condition(&self) -> &LogicNode<T> {
         &self.condition
@@ -517,11 +909,20 @@ impl<T: EntityId> Transition<T> {
         self.blend_factor = 0.0;
     }
 
+    // Calculates the actual transition time using the duration of the destination state animation (if any).
+    pub(super) fn resolve_time(&mut self, target_duration: Option<f32>) {
+        self.transition_time = self.time.resolve(target_duration);
+    }
+
     pub(super) fn update(&mut self, dt: f32) {
         self.elapsed_time += dt;
         if self.elapsed_time > self.transition_time {
             self.elapsed_time = self.transition_time;
         }
-        self.blend_factor = self.elapsed_time / self.transition_time;
+        self.blend_factor = if self.transition_time > 0.0 {
+            self.elapsed_time / self.transition_time
+        } else {
+            1.0
+        };
     }
 }
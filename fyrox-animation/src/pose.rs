@@ -67,6 +67,27 @@ impl<T: EntityId> AnimationPose<T> {
             .blend_with(&other.root_motion.clone().unwrap_or_default(), weight);
     }
 
+    /// Adds the difference between the `additive` and the `reference` poses scaled by the given weight to the current
+    /// pose. Node poses, that are not in the current pose, are ignored. Root motion of the current pose is not changed.
+    /// See [`crate::value::TrackValue::add_difference`] docs for more info.
+    pub fn add_difference(
+        &mut self,
+        additive: &AnimationPose<T>,
+        reference: Option<&AnimationPose<T>>,
+        weight: f32,
+    ) {
+        for (handle, current_pose) in self.poses.iter_mut() {
+            if let Some(additive_pose) = additive.poses.get(handle) {
+                let reference_values = reference
+                    .and_then(|reference| reference.poses.get(handle))
+                    .map(|pose| &pose.values);
+                current_pose
+                    .values
+                    .add_difference(&additive_pose.values, reference_values, weight);
+            }
+        }
+    }
+
     fn add_node_pose(&mut self, local_pose: NodePose<T>) {
         self.poses.insert(local_pose.node, local_pose);
     }
@@ -87,9 +108,10 @@ impl<T: EntityId> AnimationPose<T> {
         }
     }
 
-    /// Clears the pose.
+    /// Clears the pose, including its root motion.
     pub fn reset(&mut self) {
         self.poses.clear();
+        self.root_motion = None;
     }
 
     /// Returns a reference to inner node pose map.
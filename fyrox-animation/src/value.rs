@@ -153,6 +153,57 @@ impl TrackValue {
         }
     }
 
+    /// Adds the difference between the `additive` and the `reference` values scaled by the given weight to the current
+    /// value (`self + (additive - reference) * weight`). Rotations are combined by multiplication, the difference of
+    /// rotations is `reference.inverse() * additive`. Missing reference (or the reference of a different type) is
+    /// treated as zero (or as identity rotation). Adding is possible only if the types are the same.
+    pub fn add_difference(&mut self, additive: &Self, reference: Option<&Self>, weight: f32) {
+        match (self, additive) {
+            (Self::Real(a), Self::Real(b)) => {
+                let r = if let Some(Self::Real(r)) = reference {
+                    *r
+                } else {
+                    0.0
+                };
+                *a += (*b - r) * weight;
+            }
+            (Self::Vector2(a), Self::Vector2(b)) => {
+                let r = if let Some(Self::Vector2(r)) = reference {
+                    *r
+                } else {
+                    Vector2::zeros()
+                };
+                *a += (*b - r).scale(weight);
+            }
+            (Self::Vector3(a), Self::Vector3(b)) => {
+                let r = if let Some(Self::Vector3(r)) = reference {
+                    *r
+                } else {
+                    Vector3::zeros()
+                };
+                *a += (*b - r).scale(weight);
+            }
+            (Self::Vector4(a), Self::Vector4(b)) => {
+                let r = if let Some(Self::Vector4(r)) = reference {
+                    *r
+                } else {
+                    Vector4::zeros()
+                };
+                *a += (*b - r).scale(weight);
+            }
+            (Self::UnitQuaternion(a), Self::UnitQuaternion(b)) => {
+                let r = if let Some(Self::UnitQuaternion(r)) = reference {
+                    *r
+                } else {
+                    UnitQuaternion::identity()
+                };
+                let delta = r.inverse() * b;
+                *a *= nlerp(UnitQuaternion::identity(), &delta, weight);
+            }
+            _ => (),
+        }
+    }
+
     /// Tries to perform a numeric type casting of the current value to some other and returns a boxed value, that can
     /// be used to set the value using reflection.
     pub fn numeric_type_cast(&self, value_type: ValueType) -> Option<Box<dyn Reflect>> {
@@ -356,6 +407,28 @@ impl BoundValueCollection {
             }
         }
     }
+
+    /// Adds the difference between each value of the `additive` collection and a respective (by binding) value in the
+    /// `reference` collection to a respective value of the current collection. Values, that are not in the current
+    /// collection, are ignored. See [`TrackValue::add_difference`] docs for more info.
+    pub fn add_difference(&mut self, additive: &Self, reference: Option<&Self>, weight: f32) {
+        for value in self.values.iter_mut() {
+            if let Some(additive_value) =
+                additive.values.iter().find(|v| v.binding == value.binding)
+            {
+                let reference_value = reference.and_then(|reference| {
+                    reference
+                        .values
+                        .iter()
+                        .find(|v| v.binding == value.binding)
+                        .map(|v| &v.value)
+                });
+                value
+                    .value
+                    .add_difference(&additive_value.value, reference_value, weight);
+            }
+        }
+    }
 }
 
 /// Interpolates from `a` to `b` using nlerp, including an additional check to ensure
@@ -371,3 +444,40 @@ pub fn nlerp(mut a: UnitQuaternion<f32>, b: &UnitQuaternion<f32>, w: f32) -> Uni
 pub fn negate_unit_quaternion(a: &UnitQuaternion<f32>) -> UnitQuaternion<f32> {
     Unit::new_unchecked(-a.as_ref())
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector3},
+        value::TrackValue,
+    };
+
+    #[test]
+    fn test_add_difference() {
+        let mut position = TrackValue::Vector3(Vector3::new(1.0, 2.0, 3.0));
+        position.add_difference(
+            &TrackValue::Vector3(Vector3::new(2.0, 1.0, 0.0)),
+            Some(&TrackValue::Vector3(Vector3::new(1.0, 1.0, 0.0))),
+            0.5,
+        );
+        assert_eq!(position, TrackValue::Vector3(Vector3::new(1.5, 2.0, 3.0)));
+
+        // Missing reference is zero.
+        position.add_difference(&TrackValue::Vector3(Vector3::new(0.5, 0.0, 0.0)), None, 1.0);
+        assert_eq!(position, TrackValue::Vector3(Vector3::new(2.0, 2.0, 3.0)));
+
+        let base = UnitQuaternion::from_euler_angles(0.0, 0.5, 0.0);
+        let reference = UnitQuaternion::from_euler_angles(0.0, 0.0, 0.25);
+        let additive = reference * UnitQuaternion::from_euler_angles(0.0, 0.3, 0.0);
+        let mut rotation = TrackValue::UnitQuaternion(base);
+        rotation.add_difference(
+            &TrackValue::UnitQuaternion(additive),
+            Some(&TrackValue::UnitQuaternion(reference)),
+            1.0,
+        );
+        let TrackValue::UnitQuaternion(rotation) = rotation else {
+            unreachable!()
+        };
+        assert!(rotation.angle_to(&UnitQuaternion::from_euler_angles(0.0, 0.8, 0.0)) < 1.0e-5);
+    }
+}
@@ -5,9 +5,10 @@ use crate::{
     visitor::{Visit, VisitResult, Visitor},
 };
 use num_traits::Zero;
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
-#[derive(Copy, Clone, Debug, PartialOrd, PartialEq, Eq, Visit, Reflect)]
+#[derive(Copy, Clone, Debug, PartialOrd, PartialEq, Eq, Serialize, Deserialize, Visit, Reflect)]
 #[repr(C)]
 pub struct Color {
     // Do not change order! OpenGL requires this order!
@@ -1935,6 +1935,7 @@ impl Engine {
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
         self.resource_manager.state().update(dt);
+        self.sound_engine.update_device();
         self.handle_model_events();
 
         let window_size = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
@@ -0,0 +1,300 @@
+//! JSON representation of material property values. It is intended for interop with external (non-Rust)
+//! tools that need to read or write material properties without going through the binary visitor format.
+//!
+//! # Schema
+//!
+//! Each value is stored as a tagged JSON object `{"type": <variant name>, "value": <payload>}`, where the
+//! variant name is exactly the name of the respective [`PropertyValue`] variant. Payloads are:
+//!
+//! | Variant                          | Payload                                                                            |
+//! |----------------------------------|------------------------------------------------------------------------------------|
+//! | `Float`, `Double`, `Int`, `UInt` | a number                                                                           |
+//! | `Bool`                           | a boolean                                                                          |
+//! | `Vector2`, `Vector3`, `Vector4`  | an array of 2, 3 or 4 numbers - `[x, y, z, w]`                                     |
+//! | `Matrix2`, `Matrix3`, `Matrix4`  | an array of rows (row-major) - `[[m00, m01], [m10, m11]]`                          |
+//! | `Color`                          | an array of 4 integers in `0..=255` range - `[r, g, b, a]`                         |
+//! | `*Array`                         | an array of payloads of the respective scalar variant                              |
+//! | `Sampler`                        | an object `{"path": <string or null>, "fallback": <string>, "priority": <string>}` |
+//!
+//! Sampler fallback is one of `"White"`, `"Normal"` or `"Black"`. Sampler priority is one of `"Low"`, `"Normal"`
+//! or `"High"`, it is optional and defaults to `"Normal"`. Sampler path is a path of the texture resource, embedded
+//! textures cannot be referenced by a path and are written as `null`.
+//!
+//! For example, `PropertyValue::Vector3(Vector3::new(1.0, 2.0, 3.0))` is written as
+//! `{"type":"Vector3","value":[1.0,2.0,3.0]}`.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{
+        algebra::{Matrix2, Matrix3, Matrix4, Vector2, Vector3, Vector4},
+        color::Color,
+    },
+    material::{shader::SamplerFallback, PropertyValue, TextureLoadPriority},
+    resource::texture::Texture,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+type Rows<const N: usize> = [[f32; N]; N];
+
+#[derive(Serialize, Deserialize)]
+struct SamplerJson {
+    path: Option<PathBuf>,
+    fallback: SamplerFallback,
+    #[serde(default)]
+    priority: TextureLoadPriority,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+enum PropertyValueJson {
+    Float(f32),
+    FloatArray(Vec<f32>),
+    Int(i32),
+    IntArray(Vec<i32>),
+    UInt(u32),
+    UIntArray(Vec<u32>),
+    Vector2([f32; 2]),
+    Vector2Array(Vec<[f32; 2]>),
+    Vector3([f32; 3]),
+    Vector3Array(Vec<[f32; 3]>),
+    Vector4([f32; 4]),
+    Vector4Array(Vec<[f32; 4]>),
+    Matrix2(Rows<2>),
+    Matrix2Array(Vec<Rows<2>>),
+    Matrix3(Rows<3>),
+    Matrix3Array(Vec<Rows<3>>),
+    Matrix4(Rows<4>),
+    Matrix4Array(Vec<Rows<4>>),
+    Bool(bool),
+    Color([u8; 4]),
+    Sampler(SamplerJson),
+    Double(f64),
+    DoubleArray(Vec<f64>),
+    ColorArray(Vec<[u8; 4]>),
+}
+
+macro_rules! define_matrix_conversions {
+    ($to:ident, $from:ident, $ty:ty, $n:literal) => {
+        fn $to(m: &$ty) -> Rows<$n> {
+            std::array::from_fn(|row| std::array::from_fn(|column| m[(row, column)]))
+        }
+
+        fn $from(rows: &Rows<$n>) -> $ty {
+            <$ty>::from_fn(|row, column| rows[row][column])
+        }
+    };
+}
+
+define_matrix_conversions!(matrix2_to_rows, matrix2_from_rows, Matrix2<f32>, 2);
+define_matrix_conversions!(matrix3_to_rows, matrix3_from_rows, Matrix3<f32>, 3);
+define_matrix_conversions!(matrix4_to_rows, matrix4_from_rows, Matrix4<f32>, 4);
+
+impl From<&PropertyValue> for PropertyValueJson {
+    fn from(value: &PropertyValue) -> Self {
+        match value {
+            PropertyValue::Float(v) => Self::Float(*v),
+            PropertyValue::FloatArray(v) => Self::FloatArray(v.clone()),
+            PropertyValue::Int(v) => Self::Int(*v),
+            PropertyValue::IntArray(v) => Self::IntArray(v.clone()),
+            PropertyValue::UInt(v) => Self::UInt(*v),
+            PropertyValue::UIntArray(v) => Self::UIntArray(v.clone()),
+            PropertyValue::Vector2(v) => Self::Vector2((*v).into()),
+            PropertyValue::Vector2Array(v) => {
+                Self::Vector2Array(v.iter().map(|v| (*v).into()).collect())
+            }
+            PropertyValue::Vector3(v) => Self::Vector3((*v).into()),
+            PropertyValue::Vector3Array(v) => {
+                Self::Vector3Array(v.iter().map(|v| (*v).into()).collect())
+            }
+            PropertyValue::Vector4(v) => Self::Vector4((*v).into()),
+            PropertyValue::Vector4Array(v) => {
+                Self::Vector4Array(v.iter().map(|v| (*v).into()).collect())
+            }
+            PropertyValue::Matrix2(v) => Self::Matrix2(matrix2_to_rows(v)),
+            PropertyValue::Matrix2Array(v) => {
+                Self::Matrix2Array(v.iter().map(matrix2_to_rows).collect())
+            }
+            PropertyValue::Matrix3(v) => Self::Matrix3(matrix3_to_rows(v)),
+            PropertyValue::Matrix3Array(v) => {
+                Self::Matrix3Array(v.iter().map(matrix3_to_rows).collect())
+            }
+            PropertyValue::Matrix4(v) => Self::Matrix4(matrix4_to_rows(v)),
+            PropertyValue::Matrix4Array(v) => {
+                Self::Matrix4Array(v.iter().map(matrix4_to_rows).collect())
+            }
+            PropertyValue::Bool(v) => Self::Bool(*v),
+            PropertyValue::Color(v) => Self::Color([v.r, v.g, v.b, v.a]),
+            PropertyValue::Sampler {
+                value,
+                fallback,
+                priority,
+            } => Self::Sampler(SamplerJson {
+                path: value
+                    .as_ref()
+                    .and_then(|texture| texture.kind().into_path()),
+                fallback: *fallback,
+                priority: *priority,
+            }),
+            PropertyValue::Double(v) => Self::Double(*v),
+            PropertyValue::DoubleArray(v) => Self::DoubleArray(v.clone()),
+            PropertyValue::ColorArray(v) => {
+                Self::ColorArray(v.iter().map(|v| [v.r, v.g, v.b, v.a]).collect())
+            }
+        }
+    }
+}
+
+impl PropertyValueJson {
+    fn into_property_value(self, resource_manager: Option<&ResourceManager>) -> PropertyValue {
+        match self {
+            Self::Float(v) => PropertyValue::Float(v),
+            Self::FloatArray(v) => PropertyValue::FloatArray(v),
+            Self::Int(v) => PropertyValue::Int(v),
+            Self::IntArray(v) => PropertyValue::IntArray(v),
+            Self::UInt(v) => PropertyValue::UInt(v),
+            Self::UIntArray(v) => PropertyValue::UIntArray(v),
+            Self::Vector2(v) => PropertyValue::Vector2(v.into()),
+            Self::Vector2Array(v) => {
+                PropertyValue::Vector2Array(v.into_iter().map(Vector2::from).collect())
+            }
+            Self::Vector3(v) => PropertyValue::Vector3(v.into()),
+            Self::Vector3Array(v) => {
+                PropertyValue::Vector3Array(v.into_iter().map(Vector3::from).collect())
+            }
+            Self::Vector4(v) => PropertyValue::Vector4(v.into()),
+            Self::Vector4Array(v) => {
+                PropertyValue::Vector4Array(v.into_iter().map(Vector4::from).collect())
+            }
+            Self::Matrix2(v) => PropertyValue::Matrix2(matrix2_from_rows(&v)),
+            Self::Matrix2Array(v) => {
+                PropertyValue::Matrix2Array(v.iter().map(matrix2_from_rows).collect())
+            }
+            Self::Matrix3(v) => PropertyValue::Matrix3(matrix3_from_rows(&v)),
+            Self::Matrix3Array(v) => {
+                PropertyValue::Matrix3Array(v.iter().map(matrix3_from_rows).collect())
+            }
+            Self::Matrix4(v) => PropertyValue::Matrix4(matrix4_from_rows(&v)),
+            Self::Matrix4Array(v) => {
+                PropertyValue::Matrix4Array(v.iter().map(matrix4_from_rows).collect())
+            }
+            Self::Bool(v) => PropertyValue::Bool(v),
+            Self::Color([r, g, b, a]) => PropertyValue::Color(Color::from_rgba(r, g, b, a)),
+            Self::Sampler(SamplerJson {
+                path,
+                fallback,
+                priority,
+            }) => PropertyValue::Sampler {
+                value: path.and_then(|path| resource_manager.map(|rm| rm.request::<Texture>(path))),
+                fallback,
+                priority,
+            },
+            Self::Double(v) => PropertyValue::Double(v),
+            Self::DoubleArray(v) => PropertyValue::DoubleArray(v),
+            Self::ColorArray(v) => PropertyValue::ColorArray(
+                v.into_iter()
+                    .map(|[r, g, b, a]| Color::from_rgba(r, g, b, a))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl PropertyValue {
+    /// Serializes the property value into a JSON string. See [module docs](self) for the schema description.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&PropertyValueJson::from(self))
+    }
+
+    /// Deserializes the property value from a JSON string. See [module docs](self) for the schema description.
+    /// Sampler textures are requested from the given resource manager (if any), if there's no resource
+    /// manager, samplers will have no texture and will use their fallback value.
+    pub fn from_json(
+        json: &str,
+        resource_manager: Option<&ResourceManager>,
+    ) -> Result<Self, serde_json::Error> {
+        serde_json::from_str::<PropertyValueJson>(json)
+            .map(|value| value.into_property_value(resource_manager))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix2, Matrix3, Matrix4, Vector2, Vector3, Vector4},
+            color::Color,
+        },
+        material::{shader::SamplerFallback, PropertyValue, TextureLoadPriority},
+    };
+
+    fn assert_round_trip(value: PropertyValue) {
+        let json = value.to_json().unwrap();
+        let restored = PropertyValue::from_json(&json, None).unwrap();
+        assert_eq!(format!("{value:?}"), format!("{restored:?}"), "{json}");
+    }
+
+    #[test]
+    fn test_scalars_round_trip() {
+        assert_round_trip(PropertyValue::Float(1.5));
+        assert_round_trip(PropertyValue::FloatArray(vec![1.0, -2.0, 3.5]));
+        assert_round_trip(PropertyValue::Int(-3));
+        assert_round_trip(PropertyValue::IntArray(vec![1, -2, 3]));
+        assert_round_trip(PropertyValue::UInt(7));
+        assert_round_trip(PropertyValue::UIntArray(vec![1, 2, 3]));
+        assert_round_trip(PropertyValue::Bool(true));
+        assert_round_trip(PropertyValue::Color(Color::from_rgba(1, 2, 3, 4)));
+        assert_round_trip(PropertyValue::Double(0.1));
+        assert_round_trip(PropertyValue::DoubleArray(vec![1.0e-12, -2.5, 3.0e300]));
+        assert_round_trip(PropertyValue::ColorArray(vec![Color::RED, Color::GREEN]));
+    }
+
+    #[test]
+    fn test_vectors_round_trip() {
+        assert_round_trip(PropertyValue::Vector2(Vector2::new(1.0, 2.0)));
+        assert_round_trip(PropertyValue::Vector2Array(vec![
+            Vector2::new(1.0, 2.0),
+            Vector2::new(3.0, 4.0),
+        ]));
+        assert_round_trip(PropertyValue::Vector3(Vector3::new(1.0, 2.0, 3.0)));
+        assert_round_trip(PropertyValue::Vector3Array(vec![Vector3::new(
+            1.0, 2.0, 3.0,
+        )]));
+        assert_round_trip(PropertyValue::Vector4(Vector4::new(1.0, 2.0, 3.0, 4.0)));
+        assert_round_trip(PropertyValue::Vector4Array(vec![Vector4::new(
+            1.0, 2.0, 3.0, 4.0,
+        )]));
+    }
+
+    #[test]
+    fn test_matrices_round_trip() {
+        let m2 = Matrix2::new(1.0, 2.0, 3.0, 4.0);
+        let m3 = Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0);
+        let m4 = Matrix4::new(
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+        );
+        assert_round_trip(PropertyValue::Matrix2(m2));
+        assert_round_trip(PropertyValue::Matrix2Array(vec![m2, m2.transpose()]));
+        assert_round_trip(PropertyValue::Matrix3(m3));
+        assert_round_trip(PropertyValue::Matrix3Array(vec![m3, m3.transpose()]));
+        assert_round_trip(PropertyValue::Matrix4(m4));
+        assert_round_trip(PropertyValue::Matrix4Array(vec![m4, m4.transpose()]));
+    }
+
+    #[test]
+    fn test_sampler_round_trip() {
+        assert_round_trip(PropertyValue::Sampler {
+            value: None,
+            fallback: SamplerFallback::Normal,
+            priority: TextureLoadPriority::High,
+        });
+    }
+
+    #[test]
+    fn test_matrix_layout_is_row_major() {
+        let json = PropertyValue::Matrix2(Matrix2::new(1.0, 2.0, 3.0, 4.0))
+            .to_json()
+            .unwrap();
+        assert_eq!(json, r#"{"type":"Matrix2","value":[[1.0,2.0],[3.0,4.0]]}"#);
+    }
+}
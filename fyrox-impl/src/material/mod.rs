@@ -5,7 +5,7 @@
 #![warn(missing_docs)]
 
 use crate::{
-    asset::{io::ResourceIo, manager::ResourceManager, Resource, ResourceData},
+    asset::{io::ResourceIo, manager::ResourceManager, Resource, ResourceData, TypedResourceData},
     core::{
         algebra::{Matrix2, Matrix3, Matrix4, Vector2, Vector3, Vector4},
         color::Color,
@@ -13,29 +13,38 @@ use crate::{
         log::Log,
         parking_lot::Mutex,
         reflect::prelude::*,
+        sparse::AtomicIndex,
         sstorage::ImmutableString,
         uuid::{uuid, Uuid},
         visitor::{prelude::*, RegionGuard},
         TypeUuidProvider,
     },
-    material::shader::{PropertyKind, SamplerFallback, ShaderResource, ShaderResourceExtension},
-    resource::texture::{Texture, TextureResource},
+    material::shader::{
+        PropertyDefinition, PropertyKind, RenderQueue, SamplerFallback, ShaderResource,
+        ShaderResourceExtension, TextureFormatRequirement,
+    },
+    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet, FxHasher};
 use fyrox_resource::state::ResourceState;
 use fyrox_resource::untyped::ResourceKind;
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::{
     any::Any,
-    fmt::{Display, Formatter},
-    ops::Deref,
+    fmt::{Debug, Display, Formatter},
+    hash::{Hash, Hasher},
+    ops::{Deref, Range},
     path::Path,
     sync::Arc,
 };
 
+#[cfg(feature = "serde_json")]
+pub mod json;
 pub mod loader;
 pub mod shader;
+pub mod text;
 
 /// A value of a property that will be used for rendering with a shader.
 ///
@@ -133,7 +142,27 @@ pub enum PropertyValue {
 
         /// Sampler fallback value.
         fallback: SamplerFallback,
+
+        /// Loading priority of the texture. It belongs to the binding, so [`Material::set_property`] keeps
+        /// the priority of the existing binding, use [`Material::set_texture_priority`] to change it.
+        #[visit(optional)]
+        priority: TextureLoadPriority,
     },
+
+    /// Double-precision real number.
+    ///
+    /// # Precision
+    ///
+    /// Not every GPU (and graphics API) supports double-precision uniforms, the renderer down-converts the
+    /// value to single precision (and logs a warning) in this case.
+    Double(f64),
+
+    /// Double-precision real number array. See [`Self::Double`] for more info about precision.
+    DoubleArray(Vec<f64>),
+
+    /// An array of sRGB colors. Every color of the array is **converted to linear color space** before it
+    /// passed to shader, the same as [`Self::Color`].
+    ColorArray(Vec<Color>),
 }
 
 macro_rules! define_as {
@@ -190,6 +219,7 @@ impl PropertyValue {
                     .as_ref()
                     .and_then(|path| resource_manager.map(|rm| rm.request::<Texture>(path))),
                 fallback: *usage,
+                priority: Default::default(),
             },
             PropertyKind::FloatArray(value) => PropertyValue::FloatArray(value.clone()),
             PropertyKind::IntArray(value) => PropertyValue::IntArray(value.clone()),
@@ -200,6 +230,9 @@ impl PropertyValue {
             PropertyKind::Matrix2Array(value) => PropertyValue::Matrix2Array(value.clone()),
             PropertyKind::Matrix3Array(value) => PropertyValue::Matrix3Array(value.clone()),
             PropertyKind::Matrix4Array(value) => PropertyValue::Matrix4Array(value.clone()),
+            PropertyKind::Double(value) => PropertyValue::Double(*value),
+            PropertyKind::DoubleArray(value) => PropertyValue::DoubleArray(value.clone()),
+            PropertyKind::ColorArray(value) => PropertyValue::ColorArray(value.clone()),
         }
     }
 
@@ -235,6 +268,10 @@ impl PropertyValue {
         /// Tries to unwrap property value as color.
         as_color = Color -> Color
     );
+    define_as_ref!(
+        /// Tries to unwrap property value as color array.
+        as_color_array = ColorArray -> [Color]
+    );
     define_as!(
         /// Tries to unwrap property value as two-dimensional vector.
         as_vector2 = Vector2 -> Vector2<f32>
@@ -283,6 +320,14 @@ impl PropertyValue {
         /// Tries to unwrap property value as 4x4 matrix array.
         as_matrix4_array = Matrix4Array -> [Matrix4<f32>]
     );
+    define_as!(
+        /// Tries to unwrap property value as double-precision real number.
+        as_double = Double -> f64
+    );
+    define_as_ref!(
+        /// Tries to unwrap property value as double-precision real number array.
+        as_double_array = DoubleArray -> [f64]
+    );
 
     /// Tries to unwrap property value as texture.
     pub fn as_sampler(&self) -> Option<TextureResource> {
@@ -292,6 +337,16 @@ impl PropertyValue {
             None
         }
     }
+
+    /// Tries to unwrap property value as cube texture. Returns [`None`] if the property is not a sampler, or
+    /// if the texture is not loaded (yet) or it is not a cube texture. See [`Material::set_cube_texture`].
+    pub fn as_cube_texture(&self) -> Option<TextureResource> {
+        self.as_sampler().filter(|texture| {
+            texture.state().data().map_or(false, |data| {
+                matches!(data.kind(), TextureKind::Cube { .. })
+            })
+        })
+    }
 }
 
 impl Default for PropertyValue {
@@ -351,7 +406,8 @@ impl Default for PropertyValue {
 ///         &ImmutableString::new("diffuseTexture"),
 ///         PropertyValue::Sampler {
 ///             value: Some(resource_manager.request::<Texture>("Brick_DiffuseTexture.jpg")),
-///             fallback: SamplerFallback::White
+///             fallback: SamplerFallback::White,
+///             priority: Default::default(),
 ///         })
 ///         .unwrap();
 ///
@@ -400,6 +456,360 @@ impl Default for PropertyValue {
 pub struct Material {
     shader: ShaderResource,
     properties: FxHashMap<ImmutableString, PropertyValue>,
+    #[reflect(hidden)]
+    texture_swizzles: FxHashMap<ImmutableString, TextureSwizzle>,
+    #[reflect(hidden)]
+    render_queue: Option<RenderQueue>,
+    #[reflect(hidden)]
+    sort_bias: i32,
+    #[reflect(hidden)]
+    two_sided: bool,
+    #[reflect(hidden)]
+    defines: FxHashMap<ImmutableString, ShaderDefineValue>,
+    // `#define` directives built from the defines, sorted by name. It is used as a key of shader
+    // permutations, so it is rebuilt only when the defines change.
+    #[reflect(hidden)]
+    define_directives: String,
+    #[reflect(hidden)]
+    fallback_shader: Option<ShaderResource>,
+    // A material, which properties are used when the material does not have its own values. See
+    // `Material::from_base` for more info.
+    #[reflect(hidden)]
+    base: Option<MaterialResource>,
+    #[reflect(hidden)]
+    data_version: u64,
+    #[reflect(hidden)]
+    batching: bool,
+    #[reflect(hidden)]
+    batch_modified: bool,
+    #[reflect(hidden)]
+    property_schema: PropertySchemaCache,
+    #[reflect(hidden)]
+    snapshot: SnapshotCache,
+    // Names of the properties changed since the last `take_dirty_properties` call. See the method docs for more
+    // info.
+    #[reflect(hidden)]
+    dirty_properties: FxHashSet<ImmutableString>,
+    #[reflect(hidden)]
+    all_properties_dirty: bool,
+    #[reflect(hidden)]
+    pending_defaults: Option<PendingDefaults>,
+}
+
+// A material, that was created while its shader was loading, keeps the resource manager, that was passed
+// to its constructor, to populate the default values of the properties once the shader is loaded. See
+// `Material::populate_pending_defaults` for more info.
+#[derive(Clone)]
+struct PendingDefaults(Option<ResourceManager>);
+
+impl Debug for PendingDefaults {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PendingDefaults")
+    }
+}
+
+// Property definitions of the shader, that were used to build the cache, are identified by the cache
+// index of the shader instance. It is unique for every shader instance (reloading creates a new
+// instance) and it is kept alive by the cache, so it cannot be reused by some other shader.
+#[derive(Default)]
+struct PropertySchemaCache(Mutex<Option<(Arc<AtomicIndex>, Arc<[PropertyDefinition]>)>>);
+
+impl Clone for PropertySchemaCache {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().clone()))
+    }
+}
+
+impl Debug for PropertySchemaCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PropertySchemaCache")
+    }
+}
+
+// The latest snapshot of the material. Clones of the material start with the same snapshot, but have their
+// own caches, because their versions are changed independently.
+#[derive(Default)]
+struct SnapshotCache(Mutex<Option<Arc<MaterialSnapshot>>>);
+
+impl Clone for SnapshotCache {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().clone()))
+    }
+}
+
+impl Debug for SnapshotCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SnapshotCache")
+    }
+}
+
+/// An immutable copy of the data of a [`Material`] at some version (see [`Material::data_version`]). See
+/// [`Material::snapshot`] for more info.
+#[derive(Debug)]
+pub struct MaterialSnapshot {
+    version: u64,
+    // Data version of the base material at which the snapshot was taken, if any.
+    base_version: Option<u64>,
+    shader: ShaderResource,
+    properties: FxHashMap<ImmutableString, PropertyValue>,
+    texture_swizzles: FxHashMap<ImmutableString, TextureSwizzle>,
+    render_queue: Option<RenderQueue>,
+    sort_bias: i32,
+    two_sided: bool,
+    define_directives: String,
+}
+
+impl MaterialSnapshot {
+    /// Returns the data version of the material, at which the snapshot was taken.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns a reference to the shader of the material. See [`Material::shader`].
+    pub fn shader(&self) -> &ShaderResource {
+        &self.shader
+    }
+
+    /// Returns a reference to the properties of the material. See [`Material::properties`].
+    pub fn properties(&self) -> &FxHashMap<ImmutableString, PropertyValue> {
+        &self.properties
+    }
+
+    /// Searches for a property with the given name. See [`Material::property_ref`].
+    pub fn property_ref(&self, name: &ImmutableString) -> Option<&PropertyValue> {
+        self.properties.get(name)
+    }
+
+    /// Returns texture swizzle of a sampler with the given name. See [`Material::texture_swizzle`].
+    pub fn texture_swizzle(&self, name: &ImmutableString) -> Option<TextureSwizzle> {
+        self.texture_swizzles.get(name).cloned()
+    }
+
+    /// Returns the render queue override of the material. See [`Material::render_queue_override`].
+    pub fn render_queue_override(&self) -> Option<RenderQueue> {
+        self.render_queue
+    }
+
+    /// Returns sort bias of the material. See [`Material::sort_bias`].
+    pub fn sort_bias(&self) -> i32 {
+        self.sort_bias
+    }
+
+    /// Returns `true` if the material is two-sided. See [`Material::is_two_sided`].
+    pub fn is_two_sided(&self) -> bool {
+        self.two_sided
+    }
+
+    /// Returns the `#define` directives of the material. See [`Material::define_directives`].
+    pub fn define_directives(&self) -> &str {
+        &self.define_directives
+    }
+}
+
+/// Loading priority of a texture bound to a material sampler. It is a hint for streaming systems,
+/// that tells in which order the textures of a material should be loaded to make the material look
+/// acceptable as soon as possible. For example, diffuse texture should usually have higher priority
+/// than detail maps.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Visit,
+    Reflect,
+)]
+#[repr(u32)]
+pub enum TextureLoadPriority {
+    /// Lowest priority, textures with this priority should be loaded last.
+    Low = 0,
+    /// Neutral priority. This is the default priority of every sampler.
+    #[default]
+    Normal = 1,
+    /// Highest priority, textures with this priority should be loaded first.
+    High = 2,
+}
+
+/// Defines what [`Material::set_property_checked`] does with values, that are out of range of a property.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OutOfRangePolicy {
+    /// Out-of-range values are clamped to the range. This is the default policy.
+    #[default]
+    Clamp,
+    /// Out-of-range values are rejected, the property is not modified.
+    Reject,
+}
+
+/// A value of a shader define of a material. See [`Material::set_define`] for more info.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Visit)]
+pub enum ShaderDefineValue {
+    /// A feature flag. `true` defines the name as `1`, `false` leaves it undefined, so the flag could
+    /// be checked with both `#ifdef` and `#if`.
+    Bool(bool),
+    /// An integer value, it could be used to select one of a few code paths with `#if`.
+    Int(i32),
+}
+
+impl Default for ShaderDefineValue {
+    fn default() -> Self {
+        Self::Bool(false)
+    }
+}
+
+impl From<bool> for ShaderDefineValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for ShaderDefineValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+/// A source of a channel of [`TextureSwizzle`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SwizzleSource {
+    /// Red channel of the texture.
+    Red,
+    /// Green channel of the texture.
+    Green,
+    /// Blue channel of the texture.
+    Blue,
+    /// Alpha channel of the texture.
+    Alpha,
+    /// Constant zero.
+    Zero,
+    /// Constant one.
+    One,
+}
+
+/// Texture swizzle defines which channels of a texture feed the channels, that a shader receives when sampling
+/// the texture. It allows to use packed textures (for example, a texture with roughness in green channel and
+/// metallic in blue channel) with shaders that expect the data in some other channel, without authoring new
+/// textures. See [`Material::set_texture_swizzle`] for more info.
+///
+/// # Format
+///
+/// Swizzle could be created from a string (see [`Self::parse`]) of exactly four characters, where the first
+/// character defines the source of the red channel the shader receives, the second - green, the third - blue and
+/// the fourth - alpha. Each character is one of `r`, `g`, `b`, `a` (respective channel of the texture), `0` or `1`
+/// (constant values), upper case letters are allowed as well. For example:
+///
+/// - `rgba` - identity, channels are passed as is.
+/// - `bbbb` - blue channel is passed to every channel, it could be used to read metallic from blue channel of a
+/// packed glTF metallic-roughness texture with a shader that expects metallic in red channel.
+/// - `ggg1` - green channel is passed to color channels, alpha is always one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextureSwizzle(pub [SwizzleSource; 4]);
+
+impl Default for TextureSwizzle {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl TextureSwizzle {
+    /// Identity swizzle, channels are passed as is.
+    pub const IDENTITY: Self = Self([
+        SwizzleSource::Red,
+        SwizzleSource::Green,
+        SwizzleSource::Blue,
+        SwizzleSource::Alpha,
+    ]);
+
+    /// Tries to parse a swizzle from a string, see [`Self`] docs for the format description. Returns [`None`]
+    /// if the string is malformed.
+    pub fn parse(str: &str) -> Option<Self> {
+        let mut channels = [SwizzleSource::Zero; 4];
+        let mut count = 0;
+        for char in str.chars() {
+            let source = match char.to_ascii_lowercase() {
+                'r' => SwizzleSource::Red,
+                'g' => SwizzleSource::Green,
+                'b' => SwizzleSource::Blue,
+                'a' => SwizzleSource::Alpha,
+                '0' => SwizzleSource::Zero,
+                '1' => SwizzleSource::One,
+                _ => return None,
+            };
+            *channels.get_mut(count)? = source;
+            count += 1;
+        }
+        (count == 4).then_some(Self(channels))
+    }
+}
+
+impl Display for TextureSwizzle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for source in self.0 {
+            let char = match source {
+                SwizzleSource::Red => 'r',
+                SwizzleSource::Green => 'g',
+                SwizzleSource::Blue => 'b',
+                SwizzleSource::Alpha => 'a',
+                SwizzleSource::Zero => '0',
+                SwizzleSource::One => '1',
+            };
+            write!(f, "{char}")?;
+        }
+        Ok(())
+    }
+}
+
+// Swizzles are stored as strings to keep them human-readable.
+impl Visit for TextureSwizzle {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut str = self.to_string();
+        str.visit(name, visitor)?;
+        if visitor.is_reading() {
+            *self = Self::parse(&str)
+                .ok_or_else(|| VisitError::User(format!("Invalid texture swizzle {str}!")))?;
+        }
+        Ok(())
+    }
+}
+
+// Does the same as `HashMap::visit`, but writes the entries sorted by their names. Iteration order of hash maps
+// is unspecified, so otherwise saving the same material could produce different files, which is bad for version
+// control.
+fn visit_sorted_map<V>(
+    map: &mut FxHashMap<ImmutableString, V>,
+    name: &str,
+    visitor: &mut Visitor,
+) -> VisitResult
+where
+    V: Visit + Default,
+{
+    if visitor.is_reading() {
+        return map.visit(name, visitor);
+    }
+
+    let mut region = visitor.enter_region(name)?;
+
+    let mut count = map.len() as u32;
+    count.visit("Count", &mut region)?;
+
+    let mut entries = map.iter_mut().collect::<Vec<_>>();
+    entries.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        let mut region = region.enter_region(&format!("Item{i}"))?;
+
+        let mut key = key.clone();
+        key.visit("Key", &mut region)?;
+
+        value.visit("Value", &mut region)?;
+    }
+
+    Ok(())
 }
 
 impl Visit for Material {
@@ -417,7 +827,21 @@ impl Visit for Material {
         };
         shader.visit("Shader", &mut region)?;
         self.shader = shader;
-        self.properties.visit("Properties", &mut region)?;
+        visit_sorted_map(&mut self.properties, "Properties", &mut region)?;
+        let _ = visit_sorted_map(&mut self.texture_swizzles, "TextureSwizzles", &mut region);
+        let _ = self.render_queue.visit("RenderQueue", &mut region);
+        let _ = self.sort_bias.visit("SortBias", &mut region);
+        let _ = self.two_sided.visit("TwoSided", &mut region);
+        let _ = visit_sorted_map(&mut self.defines, "Defines", &mut region);
+        let _ = self.fallback_shader.visit("FallbackShader", &mut region);
+        let _ = self.base.visit("Base", &mut region);
+
+        if region.is_reading() {
+            self.update_define_directives();
+            // The data was replaced without changing the version.
+            *self.snapshot.0.lock() = None;
+            self.all_properties_dirty = true;
+        }
 
         Ok(())
     }
@@ -479,6 +903,35 @@ pub enum MaterialError {
     },
     /// Unable to read data source.
     Visit(VisitError),
+    /// A texture bound to a sampler has a format that does not satisfy the requirement of the shader.
+    IncompatibleTextureFormat {
+        /// Name of the property.
+        property_name: String,
+        /// Format requirement declared in the shader.
+        required: TextureFormatRequirement,
+        /// Actual pixel format of the texture.
+        given: TexturePixelKind,
+    },
+    /// Attempt to set a value outside of the range of the property declared in the shader. See
+    /// [`Material::set_property_checked`].
+    OutOfRange {
+        /// Name of the property.
+        property_name: String,
+        /// Range of the property declared in the shader.
+        range: Range<f32>,
+        /// Given property value.
+        given: PropertyValue,
+    },
+    /// Attempt to bind a texture of wrong kind to a sampler, for example a 2D texture to a cube sampler.
+    /// See [`Material::set_cube_texture`].
+    IncompatibleTextureKind {
+        /// Name of the property.
+        property_name: String,
+        /// Kind of the given texture.
+        given: TextureKind,
+    },
+    /// Failed to parse a material in RON format. See [`text`] module docs for more info.
+    ParseError(ron::error::SpannedError),
 }
 
 impl From<VisitError> for MaterialError {
@@ -487,6 +940,12 @@ impl From<VisitError> for MaterialError {
     }
 }
 
+impl From<ron::error::SpannedError> for MaterialError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        Self::ParseError(value)
+    }
+}
+
 impl From<FileLoadError> for MaterialError {
     fn from(value: FileLoadError) -> Self {
         Self::Visit(VisitError::FileLoadError(value))
@@ -513,6 +972,41 @@ impl Display for MaterialError {
             MaterialError::Visit(e) => {
                 write!(f, "Failed to visit data source. Reason: {:?}", e)
             }
+            MaterialError::IncompatibleTextureFormat {
+                property_name,
+                required,
+                given,
+            } => {
+                write!(
+                    f,
+                    "Texture bound to {property_name} property has incompatible format. \
+                Required: {required:?}, given {given:?}"
+                )
+            }
+            MaterialError::OutOfRange {
+                property_name,
+                range,
+                given,
+            } => {
+                write!(
+                    f,
+                    "Value of {property_name} property is out of range. \
+                Range: {range:?}, given {given:?}"
+                )
+            }
+            MaterialError::IncompatibleTextureKind {
+                property_name,
+                given,
+            } => {
+                write!(
+                    f,
+                    "Texture bound to {property_name} property has incompatible kind. \
+                Expected a cube texture, given {given:?}"
+                )
+            }
+            MaterialError::ParseError(e) => {
+                write!(f, "Failed to parse material. Reason: {e:?}")
+            }
         }
     }
 }
@@ -571,6 +1065,48 @@ lazy_static! {
     );
 }
 
+/// Metallic-roughness material parameters as defined by glTF 2.0 specification. It is used to create a
+/// standard material with [`Material::from_gltf_pbr`]. Default values match the defaults of the
+/// specification.
+#[derive(Clone, Debug)]
+pub struct GltfPbrParameters {
+    /// Base color factor in **linear** color space (as stored in glTF files).
+    pub base_color_factor: [f32; 4],
+    /// Base color texture.
+    pub base_color_texture: Option<TextureResource>,
+    /// Metallic factor.
+    pub metallic_factor: f32,
+    /// Roughness factor.
+    pub roughness_factor: f32,
+    /// Packed metallic-roughness texture, roughness is stored in the green channel and metallic - in the
+    /// blue channel.
+    pub metallic_roughness_texture: Option<TextureResource>,
+    /// Tangent-space normal map.
+    pub normal_texture: Option<TextureResource>,
+    /// Ambient occlusion texture, occlusion is stored in the red channel.
+    pub occlusion_texture: Option<TextureResource>,
+    /// Emissive factor in linear color space.
+    pub emissive_factor: [f32; 3],
+    /// Emissive texture.
+    pub emissive_texture: Option<TextureResource>,
+}
+
+impl Default for GltfPbrParameters {
+    fn default() -> Self {
+        Self {
+            base_color_factor: [1.0; 4],
+            base_color_texture: None,
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_factor: [0.0; 3],
+            emissive_texture: None,
+        }
+    }
+}
+
 impl Material {
     /// Creates a new instance of material with the standard shader. For the full list
     /// of properties of the standard material see [shader module docs](self::shader).
@@ -593,7 +1129,8 @@ impl Material {
     ///         &ImmutableString::new("diffuseTexture"),
     ///         PropertyValue::Sampler {
     ///             value: Some(resource_manager.request::<Texture>("Brick_DiffuseTexture.jpg")),
-    ///             fallback: SamplerFallback::White
+    ///             fallback: SamplerFallback::White,
+    ///             priority: Default::default(),
     ///         })
     ///         .unwrap();
     ///
@@ -662,27 +1199,178 @@ impl Material {
     ///     material
     /// }
     /// ```
+    ///
+    /// # Async shader loading
+    ///
+    /// Default values of the properties can be populated only if the shader is fully loaded. If the shader
+    /// is still loading (or failed to load), the material will be created without any properties and the
+    /// defaults will be populated by the renderer, when it meets the material for the first time after the
+    /// shader was loaded. Properties, that were set before that, are kept as is. If the defaults are needed
+    /// earlier (for example, to read them right after awaiting the shader resource), call
+    /// [`Self::ensure_defaults`] manually, it is idempotent. Materials of a scene are also synced with their
+    /// shaders when the scene is loaded (see [`Self::sync_to_shader`]).
     pub fn from_shader(shader: ShaderResource, resource_manager: Option<ResourceManager>) -> Self {
-        let data = shader.data_ref();
+        Self::from_shader_internal(shader, resource_manager.as_ref())
+    }
+
+    /// Does the same as [`Self::from_shader`], but takes the resource manager by reference. Default values of
+    /// samplers, that are bound to textures in the shader definition, are requested through the given resource
+    /// manager, so the samplers of the new material will have the textures instead of [`None`].
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{
+    /// #     asset::manager::ResourceManager,
+    /// #     material::{shader::Shader, Material},
+    /// # };
+    /// async fn create_material(resource_manager: &ResourceManager) -> Material {
+    ///     let shader = resource_manager.request::<Shader>("my_shader.shader").await.unwrap();
+    ///     Material::from_shader_with_manager(shader, resource_manager)
+    /// }
+    /// ```
+    ///
+    /// See the "Async shader loading" section of [`Self::from_shader`] docs, it applies to this method as well.
+    pub fn from_shader_with_manager(
+        shader: ShaderResource,
+        resource_manager: &ResourceManager,
+    ) -> Self {
+        Self::from_shader_internal(shader, Some(resource_manager))
+    }
+
+    fn from_shader_internal(
+        shader: ShaderResource,
+        resource_manager: Option<&ResourceManager>,
+    ) -> Self {
+        let mut material = Self {
+            shader,
+            properties: Default::default(),
+            texture_swizzles: Default::default(),
+            render_queue: None,
+            sort_bias: 0,
+            two_sided: false,
+            defines: Default::default(),
+            define_directives: Default::default(),
+            fallback_shader: None,
+            base: None,
+            data_version: 0,
+            batching: false,
+            batch_modified: false,
+            property_schema: Default::default(),
+            snapshot: Default::default(),
+            dirty_properties: Default::default(),
+            all_properties_dirty: true,
+            pending_defaults: None,
+        };
 
-        let mut property_values = FxHashMap::default();
-        for property_definition in data.definition.properties.iter() {
-            let value = PropertyValue::from_property_kind(
-                &property_definition.kind,
-                resource_manager.as_ref(),
-            );
-            property_values.insert(ImmutableString::new(&property_definition.name), value);
+        if !material.ensure_defaults(resource_manager) {
+            material.pending_defaults = Some(PendingDefaults(resource_manager.cloned()));
         }
 
-        drop(data);
+        material
+    }
 
-        Self {
-            shader,
-            properties: property_values,
+    /// Creates a new material instance, that uses the given material as its base. The instance does not have
+    /// its own property values at first, every property is resolved through the base material (see
+    /// [`Self::resolved_property`]). [`Self::set_property`] stores the new value in the instance, leaving the
+    /// base intact, and [`Self::reset_property_override`] reverts the property to the value of the base. This
+    /// allows to have lots of variations of a material without duplicating all of its data.
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{
+    /// #     core::{color::Color, sstorage::ImmutableString},
+    /// #     material::{Material, MaterialResource, MaterialResourceExtension, PropertyValue},
+    /// # };
+    /// fn make_red_variant(base: &MaterialResource) -> MaterialResource {
+    ///     let mut material = Material::from_base(base.clone());
+    ///     material
+    ///         .set_property(
+    ///             &ImmutableString::new("diffuseColor"),
+    ///             PropertyValue::Color(Color::RED),
+    ///         )
+    ///         .unwrap();
+    ///     MaterialResource::new(material)
+    /// }
+    /// ```
+    ///
+    /// Only the properties are resolved through the base. The shader and the rest of the settings (texture swizzles
+    /// and priorities, render queue, sort bias, two-sided flag, defines, fallback shader) are copied from the base
+    /// material when the instance is created and belong to the instance since then. The base material is serialized
+    /// as a reference (external resources are saved as paths), along with the overridden properties.
+    ///
+    /// # Changes of the base
+    ///
+    /// Property values of the base material are resolved every time they're requested, so any changes of the base
+    /// (including hot reloading) are visible by its instances immediately, unless the property is overridden. If the
+    /// shader of the base material is changed, the instance keeps using the old one until [`Self::sync_to_base`] is
+    /// called (it is done automatically when a scene is loaded). Overridden properties, that do not exist in the
+    /// new shader, are ignored by the renderer. If the base material is not loaded (yet) or failed to load, only the
+    /// overridden properties are used. Bases could be chained (an instance of an instance), but cycles are not
+    /// allowed and will cause a deadlock.
+    pub fn from_base(base: MaterialResource) -> Self {
+        let mut base_state = base.state();
+        let mut material = match base_state.data() {
+            Some(base_material) => {
+                let mut material = base_material.clone();
+                material.properties.clear();
+                material.batching = false;
+                material.batch_modified = false;
+                material.snapshot = Default::default();
+                material.all_properties_dirty = true;
+                material
+            }
+            None => Self::from_shader(ShaderResource::default(), None),
+        };
+        drop(base_state);
+
+        material.base = Some(base);
+        material.pending_defaults = None;
+        material
+    }
+
+    /// Fills every property that is declared in the shader, but missing in the material, with its default
+    /// value from the shader definition. Existing properties are never modified, so the method is idempotent
+    /// and could be called any amount of times. It is useful when the material was created (see
+    /// [`Self::from_shader`]) while the shader was still loading and the defaults are needed right after the
+    /// shader was loaded, before the renderer populates them (see the "Async shader loading" section of
+    /// [`Self::from_shader`] docs). Returns `true` if the shader is loaded and the defaults were populated,
+    /// `false` - otherwise.
+    pub fn ensure_defaults(&mut self, resource_manager: Option<&ResourceManager>) -> bool {
+        let mut shader_state = self.shader.state();
+        let Some(shader) = shader_state.data() else {
+            return false;
+        };
+        self.pending_defaults = None;
+
+        let mut modified = false;
+        for property_definition in shader.definition.properties.iter() {
+            let name = ImmutableString::new(&property_definition.name);
+            if !self.properties.contains_key(&name) {
+                let value =
+                    PropertyValue::from_property_kind(&property_definition.kind, resource_manager);
+                self.dirty_properties.insert(name.clone());
+                self.properties.insert(name, value);
+                modified = true;
+            }
+        }
+        drop(shader_state);
+
+        if modified {
+            self.mark_modified();
+        }
+
+        true
+    }
+
+    /// Populates the default values of the properties (see [`Self::ensure_defaults`]), if the material was
+    /// created while its shader was loading and the shader is loaded now. The defaults are populated only
+    /// once, the subsequent calls do nothing. The renderer calls this method for every material it renders.
+    pub(crate) fn populate_pending_defaults(&mut self) {
+        if let Some(PendingDefaults(resource_manager)) = self.pending_defaults.clone() {
+            self.ensure_defaults(resource_manager.as_ref());
         }
     }
 
-    /// Loads a material from file.
+    /// Loads a material from file. Both binary and text (RON, see [`text`] module docs) formats are supported,
+    /// the format is detected by the content of the file.
     pub async fn from_file<P>(
         path: P,
         io: &dyn ResourceIo,
@@ -692,9 +1380,29 @@ impl Material {
         P: AsRef<Path>,
     {
         let content = io.load_file(path.as_ref()).await?;
+        if !content.starts_with(Visitor::MAGIC.as_bytes()) {
+            return Self::from_ron_str(&String::from_utf8_lossy(&content), &resource_manager);
+        }
+
         let mut material = Material {
             shader: Default::default(),
             properties: Default::default(),
+            texture_swizzles: Default::default(),
+            render_queue: None,
+            sort_bias: 0,
+            two_sided: false,
+            defines: Default::default(),
+            define_directives: Default::default(),
+            fallback_shader: None,
+            base: None,
+            data_version: 0,
+            batching: false,
+            batch_modified: false,
+            property_schema: Default::default(),
+            snapshot: Default::default(),
+            dirty_properties: Default::default(),
+            all_properties_dirty: true,
+            pending_defaults: None,
         };
         let mut visitor = Visitor::load_from_memory(&content)?;
         visitor.blackboard.register(Arc::new(resource_manager));
@@ -718,10 +1426,103 @@ impl Material {
     ///
     /// let color = material.property_ref(&ImmutableString::new("diffuseColor")).unwrap().as_color();
     /// ```
+    ///
+    /// Material instances (see [`Self::from_base`]) return only their own (overridden) values here, use
+    /// [`Self::resolved_property`] to get the values inherited from the base material as well.
     pub fn property_ref(&self, name: &ImmutableString) -> Option<&PropertyValue> {
         self.properties.get(name)
     }
 
+    /// Returns a value of the property with the given name. Unlike [`Self::property_ref`], it resolves the
+    /// value through the base material (see [`Self::from_base`]): own value of the material is returned, if
+    /// any, otherwise the value of the base material (which, in its turn, could come from its own base).
+    pub fn resolved_property(&self, name: &ImmutableString) -> Option<PropertyValue> {
+        if let Some(value) = self.properties.get(name) {
+            return Some(value.clone());
+        }
+
+        self.base
+            .as_ref()?
+            .state()
+            .data()
+            .and_then(|base| base.resolved_property(name))
+    }
+
+    /// Calls the given function for every property of the material, resolved through the base material (see
+    /// [`Self::resolved_property`]). Every property is passed only once, own values of the material are passed
+    /// first.
+    pub fn for_each_resolved_property<F>(&self, mut func: F)
+    where
+        F: FnMut(&ImmutableString, &PropertyValue),
+    {
+        self.for_each_resolved_property_internal(&|_| false, &mut func)
+    }
+
+    fn for_each_resolved_property_internal(
+        &self,
+        is_overridden: &dyn Fn(&ImmutableString) -> bool,
+        func: &mut dyn FnMut(&ImmutableString, &PropertyValue),
+    ) {
+        for (name, value) in self.properties.iter() {
+            if !is_overridden(name) {
+                func(name, value);
+            }
+        }
+
+        if let Some(base) = self.base.as_ref() {
+            if let Some(base) = base.state().data() {
+                base.for_each_resolved_property_internal(
+                    &|name| is_overridden(name) || self.properties.contains_key(name),
+                    func,
+                );
+            }
+        }
+    }
+
+    /// Removes own value of the property with the given name from a material instance (see [`Self::from_base`]),
+    /// so the value of the base material will be used. Returns the removed value. Does nothing for materials
+    /// without a base, because all their properties are required by the shader.
+    pub fn reset_property_override(&mut self, name: &ImmutableString) -> Option<PropertyValue> {
+        if self.base.is_none() {
+            return None;
+        }
+
+        let value = self.properties.remove(name);
+        if value.is_some() {
+            self.dirty_properties.insert(name.clone());
+            self.mark_modified();
+        }
+        value
+    }
+
+    /// Returns the base material of a material instance, if any. See [`Self::from_base`] for more info.
+    pub fn base(&self) -> Option<&MaterialResource> {
+        self.base.as_ref()
+    }
+
+    /// Updates the shader of a material instance to the shader of its base material, if it has changed.
+    /// Returns `true` if the base material is loaded, `false` - otherwise (or if the material has no base).
+    /// See [`Self::from_base`] for more info.
+    pub fn sync_to_base(&mut self) -> bool {
+        let Some(base) = self.base.as_ref() else {
+            return false;
+        };
+
+        let mut base_state = base.state();
+        let Some(base) = base_state.data() else {
+            return false;
+        };
+
+        if base.shader != self.shader {
+            self.shader = base.shader.clone();
+            drop(base_state);
+            self.all_properties_dirty = true;
+            self.mark_modified();
+        }
+
+        true
+    }
+
     /// Sets new value of the property with given name.
     ///
     /// # Type checking
@@ -747,14 +1548,33 @@ impl Material {
         name: &ImmutableString,
         new_value: PropertyValue,
     ) -> Result<(), MaterialError> {
+        if self.base.is_some() && !self.properties.contains_key(name) {
+            // Material instances store only overridden values. The new value is checked against the value
+            // of the base material.
+            let Some(base_value) = self.resolved_property(name) else {
+                return Err(MaterialError::NoSuchProperty {
+                    property_name: name.deref().to_owned(),
+                });
+            };
+            self.properties.insert(name.clone(), base_value);
+            let result = self.set_property(name, new_value);
+            if result.is_err() {
+                self.properties.remove(name);
+            }
+            return result;
+        }
+
         if let Some(value) = self.properties.get_mut(name) {
             match (value, new_value) {
                 (
                     PropertyValue::Sampler {
                         value: old_value,
                         fallback: old_fallback,
+                        ..
+                    },
+                    PropertyValue::Sampler {
+                        value, fallback, ..
                     },
-                    PropertyValue::Sampler { value, fallback },
                 ) => {
                     *old_value = value;
                     *old_fallback = fallback;
@@ -819,6 +1639,15 @@ impl Material {
                 (PropertyValue::Color(old_value), PropertyValue::Color(value)) => {
                     *old_value = value;
                 }
+                (PropertyValue::Double(old_value), PropertyValue::Double(value)) => {
+                    *old_value = value;
+                }
+                (PropertyValue::DoubleArray(old_value), PropertyValue::DoubleArray(value)) => {
+                    *old_value = value;
+                }
+                (PropertyValue::ColorArray(old_value), PropertyValue::ColorArray(value)) => {
+                    *old_value = value;
+                }
                 (value, new_value) => {
                     return Err(MaterialError::TypeMismatch {
                         property_name: name.deref().to_owned(),
@@ -828,6 +1657,9 @@ impl Material {
                 }
             }
 
+            self.dirty_properties.insert(name.clone());
+            self.mark_modified();
+
             Ok(())
         } else {
             Err(MaterialError::NoSuchProperty {
@@ -836,6 +1668,51 @@ impl Material {
         }
     }
 
+    /// Sets new values of multiple properties at once. Every value is set using [`Self::set_property`], so
+    /// the same type checking is applied. Unlike a chain of [`Self::set_property`] calls, it does not stop on
+    /// the first error: all valid values are applied and the errors for the rest are returned in the order
+    /// of the input. All the changes are counted as a single modification (see [`Self::edit`]).
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{
+    /// #     core::{color::Color, sstorage::ImmutableString},
+    /// #     material::{Material, PropertyValue},
+    /// # };
+    /// let mut material = Material::standard();
+    ///
+    /// if let Err(errors) = material.set_properties([
+    ///     (
+    ///         ImmutableString::new("diffuseColor"),
+    ///         PropertyValue::Color(Color::RED),
+    ///     ),
+    ///     (
+    ///         ImmutableString::new("emissionStrength"),
+    ///         PropertyValue::Float(1.0),
+    ///     ),
+    /// ]) {
+    ///     for error in errors {
+    ///         eprintln!("{error}");
+    ///     }
+    /// }
+    /// ```
+    pub fn set_properties<I>(&mut self, properties: I) -> Result<(), Vec<MaterialError>>
+    where
+        I: IntoIterator<Item = (ImmutableString, PropertyValue)>,
+    {
+        let errors = self.edit(|material| {
+            properties
+                .into_iter()
+                .filter_map(|(name, value)| material.set_property(&name, value).err())
+                .collect::<Vec<_>>()
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Sets a value for sampler at the given name. It is a shortcut for [`Self::set_property`]
     /// method with [`PropertyValue::Sampler`] and [`SamplerFallback::White`].
     pub fn set_texture(
@@ -848,15 +1725,235 @@ impl Material {
             PropertyValue::Sampler {
                 value: texture,
                 fallback: SamplerFallback::White,
+                priority: Default::default(),
             },
         )
     }
 
+    /// Sets the fallback value of a sampler with the given name, keeping its texture. Returns the previous
+    /// fallback value. Every sampler of a material has its own fallback value (initialized from the shader
+    /// definition), so the same sampler could fall back to black in one material and to white in another.
+    /// See [`SamplerFallback`] docs for more info.
+    pub fn set_texture_fallback(
+        &mut self,
+        name: &ImmutableString,
+        fallback: SamplerFallback,
+    ) -> Result<SamplerFallback, MaterialError> {
+        match self.resolved_property(name) {
+            Some(PropertyValue::Sampler {
+                value,
+                fallback: prev,
+                priority,
+            }) => {
+                self.set_property(
+                    name,
+                    PropertyValue::Sampler {
+                        value,
+                        fallback,
+                        priority,
+                    },
+                )?;
+                Ok(prev)
+            }
+            Some(expected) => Err(MaterialError::TypeMismatch {
+                property_name: name.deref().to_owned(),
+                expected,
+                given: PropertyValue::Sampler {
+                    value: None,
+                    fallback,
+                    priority: Default::default(),
+                },
+            }),
+            None => Err(MaterialError::NoSuchProperty {
+                property_name: name.deref().to_owned(),
+            }),
+        }
+    }
+
+    /// Returns the fallback value of a sampler with the given name, [`None`] if there's no such sampler. See
+    /// [`Self::set_texture_fallback`] for more info.
+    pub fn texture_fallback(&self, name: &ImmutableString) -> Option<SamplerFallback> {
+        match self.resolved_property(name)? {
+            PropertyValue::Sampler { fallback, .. } => Some(fallback),
+            _ => None,
+        }
+    }
+
+    /// Binds a cube texture (environment map, skybox, etc.) to a sampler with the given name. It is a shortcut for
+    /// [`Self::set_texture`], that additionally checks that the texture is a cube texture (see [`TextureKind::Cube`]),
+    /// the check is skipped if the texture is not loaded yet. In the shader, the property must be declared as a
+    /// sampler and used as `samplerCube` uniform:
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{
+    /// #     core::sstorage::ImmutableString,
+    /// #     material::Material,
+    /// #     resource::texture::TextureResource,
+    /// # };
+    /// fn set_environment_map(material: &mut Material, environment_map: TextureResource) {
+    ///     // uniform samplerCube environmentMap;
+    ///     material
+    ///         .set_cube_texture(&ImmutableString::new("environmentMap"), Some(environment_map))
+    ///         .unwrap();
+    /// }
+    /// ```
+    ///
+    /// Sampler fallbacks (see [`SamplerFallback`]) are 2D textures, so a cube sampler without a texture (or with
+    /// a texture that is still loading) reads black color.
+    pub fn set_cube_texture(
+        &mut self,
+        name: &ImmutableString,
+        texture: Option<TextureResource>,
+    ) -> Result<(), MaterialError> {
+        if let Some(texture) = texture.as_ref() {
+            if let Some(data) = texture.state().data() {
+                let kind = data.kind();
+                if !matches!(kind, TextureKind::Cube { .. }) {
+                    return Err(MaterialError::IncompatibleTextureKind {
+                        property_name: name.deref().to_owned(),
+                        given: kind,
+                    });
+                }
+            }
+        }
+
+        self.set_texture(name, texture)
+    }
+
+    /// Creates a new standard material (see [`Self::standard`]) from glTF metallic-roughness parameters. It
+    /// should be used by custom importers to get the same mapping as the engine uses. Missing textures are
+    /// replaced with fallback values of the standard shader, so the respective factors are used as is. The
+    /// following properties are set:
+    ///
+    /// - `diffuseColor` - `base_color_factor` converted from linear to sRGB color space, because color
+    /// properties are converted to linear color space before passing them to shaders.
+    /// - `diffuseTexture` - `base_color_texture`.
+    /// - `metallicTexture` and `roughnessTexture` - both are set to `metallic_roughness_texture`.
+    /// - `metallicFactor` and `roughnessFactor` - `metallic_factor` and `roughness_factor` respectively.
+    /// - `packedMetallicRoughness` - always `true`, it tells the shader to fetch metallic from the blue channel
+    /// and roughness from the green channel and to multiply them by the factors.
+    /// - `normalTexture` - `normal_texture`.
+    /// - `aoTexture` - `occlusion_texture`.
+    /// - `emissionTexture` - `emissive_texture`.
+    /// - `emissionStrength` - `emissive_factor`.
+    ///
+    /// Textures are used as is, base color and emissive textures are expected to be in sRGB color space,
+    /// other textures - in linear color space (as required by glTF specification).
+    pub fn from_gltf_pbr(params: GltfPbrParameters) -> Self {
+        let mut material = Self::standard();
+
+        let mut set = |name: &str, value: PropertyValue| {
+            Log::verify(material.set_property(&ImmutableString::new(name), value));
+        };
+
+        let sampler =
+            |texture: Option<TextureResource>, fallback: SamplerFallback| PropertyValue::Sampler {
+                value: texture,
+                fallback,
+                priority: Default::default(),
+            };
+
+        // Convert in floating point to not lose precision of dark colors.
+        let [r, g, b, a] = params.base_color_factor;
+        let gamma = 1.0 / 2.2;
+        set(
+            "diffuseColor",
+            PropertyValue::Color(Color::from(Vector4::new(
+                r.powf(gamma),
+                g.powf(gamma),
+                b.powf(gamma),
+                a,
+            ))),
+        );
+        set(
+            "diffuseTexture",
+            sampler(params.base_color_texture, SamplerFallback::White),
+        );
+        set(
+            "metallicTexture",
+            sampler(
+                params.metallic_roughness_texture.clone(),
+                SamplerFallback::White,
+            ),
+        );
+        set(
+            "roughnessTexture",
+            sampler(params.metallic_roughness_texture, SamplerFallback::White),
+        );
+        set(
+            "metallicFactor",
+            PropertyValue::Float(params.metallic_factor),
+        );
+        set(
+            "roughnessFactor",
+            PropertyValue::Float(params.roughness_factor),
+        );
+        set("packedMetallicRoughness", PropertyValue::Bool(true));
+        set(
+            "normalTexture",
+            sampler(params.normal_texture, SamplerFallback::Normal),
+        );
+        set(
+            "aoTexture",
+            sampler(params.occlusion_texture, SamplerFallback::White),
+        );
+        set(
+            "emissionTexture",
+            sampler(params.emissive_texture, SamplerFallback::Black),
+        );
+        set(
+            "emissionStrength",
+            PropertyValue::Vector3(Vector3::from(params.emissive_factor)),
+        );
+
+        material
+    }
+
+    /// Enables or disables multiplication of the base color by per-vertex color. It is a shortcut for
+    /// [`Self::set_property`] with `useVertexColors` property of the standard shaders (see
+    /// [`ShaderResourceExtension::standard`] and [`ShaderResourceExtension::standard_twosides`]), disabled
+    /// by default.
+    ///
+    /// Vertex colors are taken from the vertex attribute at shader location 7, so the vertex buffer of a
+    /// mesh must have a [`crate::scene::mesh::buffer::VertexAttributeUsage::Color`] attribute with this
+    /// location. Meshes without such attribute are rendered as if their vertex colors were white. FBX and
+    /// glTF importers add this attribute to the meshes that have vertex colors in the source file.
+    pub fn set_use_vertex_colors(&mut self, use_vertex_colors: bool) -> Result<(), MaterialError> {
+        self.set_property(
+            &ImmutableString::new("useVertexColors"),
+            PropertyValue::Bool(use_vertex_colors),
+        )
+    }
+
+    /// Sets a tint color of the material. It is a shortcut for [`Self::set_property`] with `tintColor` property
+    /// of the standard shaders (see [`ShaderResourceExtension::standard`] and
+    /// [`ShaderResourceExtension::standard_twosides`]), [`Color::WHITE`] (default) means no tint.
+    ///
+    /// The tint is multiplied with the `diffuseColor` property, the diffuse texture and the vertex colors (if
+    /// enabled), so the final base color is `tintColor * diffuseColor * texture * color`. Unlike `diffuseColor`,
+    /// that usually comes from the imported model, the tint is meant to be changed at runtime. Since it is a
+    /// property of the material, it affects every mesh that uses the material. It could be used, for example, to
+    /// flash every enemy, that shares a material, red on hit, without touching the diffuse color of the material.
+    pub fn set_tint(&mut self, tint: Color) -> Result<(), MaterialError> {
+        self.set_property(
+            &ImmutableString::new("tintColor"),
+            PropertyValue::Color(tint),
+        )
+    }
+
     /// Adds missing properties with default values, removes non-existent properties. Does not modify any existing
     /// properties. This method has limited usage, that is mostly related to shader hot reloading. Returns `true`
     /// if the syncing was successful, `false` - if the shader resource is not loaded.
+    ///
+    /// Material instances (see [`Self::from_base`]) are synced with their base material instead (see
+    /// [`Self::sync_to_base`]), because they store only overridden properties.
     pub fn sync_to_shader(&mut self, resource_manager: &ResourceManager) -> bool {
+        if self.base.is_some() {
+            return self.sync_to_base();
+        }
+
         let shader_kind = self.shader.kind().clone();
+        let property_count = self.properties.len();
         if let Some(shader) = self.shader.state().data() {
             if shader.definition.properties.len() > self.properties.len() {
                 // Some property was added to the shader, but missing in the material.
@@ -864,6 +1961,7 @@ impl Material {
                     let name = ImmutableString::new(&property_definition.name);
                     if !self.properties.contains_key(&name) {
                         // Add the property with default values.
+                        self.dirty_properties.insert(name.clone());
                         self.properties.insert(
                             name.clone(),
                             PropertyValue::from_property_kind(
@@ -889,6 +1987,7 @@ impl Material {
                         .all(|p| p.name != property_name.as_ref())
                     {
                         self.properties.remove(&property_name);
+                        self.dirty_properties.insert(property_name.clone());
 
                         Log::info(format!(
                             "Removing {} property from the material instance, since it does \
@@ -898,11 +1997,617 @@ impl Material {
                     }
                 }
             }
+        } else {
+            return false;
+        }
 
-            return true;
+        if self.properties.len() != property_count {
+            self.mark_modified();
         }
 
-        false
+        true
+    }
+
+    /// Returns current data version of the material. The version is increased every time when the data
+    /// that is passed to shaders (properties) is changed via methods of the material, so it could be used
+    /// by caches (for example, uniform buffers in renderers) to find out whether the data must be uploaded
+    /// to GPU again. Changes done via reflection (for example, by the editor) are not tracked. The version
+    /// is not serialized and starts from zero for every new (or loaded) material.
+    ///
+    /// Every mutation outside of [`Self::edit`] increases the version individually, use [`Self::edit`]
+    /// to change multiple properties at once.
+    pub fn data_version(&self) -> u64 {
+        self.data_version
+    }
+
+    /// Returns an immutable snapshot of the data of the material. A snapshot is a consistent view of the material at
+    /// some data version (see [`Self::data_version`]), that can be read without holding a lock of the material
+    /// resource. It is intended for systems that read a shared material while some other system edits it, for example
+    /// a renderer could take snapshots of every material of a frame and release the resources immediately.
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::material::MaterialResource;
+    /// fn read_material(material: &MaterialResource) {
+    ///     // Lock the resource only to take the snapshot.
+    ///     let Some(snapshot) = material.state().data().map(|m| m.snapshot()) else {
+    ///         return;
+    ///     };
+    ///
+    ///     // The snapshot stays the same, even if the material is modified meanwhile.
+    ///     for (name, value) in snapshot.properties() {
+    ///         println!("{name}: {value:?}");
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Versioning
+    ///
+    /// Snapshots are immutable, every snapshot is tied to the data version of the material at which it was taken.
+    /// Edits never modify existing snapshots. Instead, the first call of this method after an edit builds a new
+    /// snapshot (a copy of the data of the material) and replaces the cached one atomically, so the subsequent calls
+    /// return the new snapshot. Calls without edits in between return the same snapshot, so taking a snapshot is cheap
+    /// (just a reference counter increment) when the material is not changed. Readers that keep a snapshot for a frame
+    /// see the same data for the whole frame, regardless of edits made in the meantime. Edits done in
+    /// [`Self::edit`] are "committed" at once: if no snapshot was taken during the edit, no snapshot can ever see a
+    /// part of the edit.
+    ///
+    /// Changes that do not increase the data version (modifications via reflection, see [`Self::data_version`]) are
+    /// not detected, the snapshot will be updated only with the next tracked edit. Loading the material resets the
+    /// snapshot. Snapshots of material instances (see [`Self::from_base`]) contain resolved properties and are
+    /// rebuilt when the data version of the direct base material changes as well.
+    ///
+    /// # Memory
+    ///
+    /// Snapshots are reference counted. The material holds only the latest snapshot, older snapshots are freed as
+    /// soon as their last reader drops them. Keeping a snapshot for a long time keeps the copy of the data (but not the
+    /// textures, which are shared resources) alive, so readers should not store snapshots for longer than they
+    /// need. Every edit, that is followed by a snapshot request, copies the data of the material, so it is better to
+    /// coalesce multiple edits using [`Self::edit`].
+    pub fn snapshot(&self) -> Arc<MaterialSnapshot> {
+        let base_version = self
+            .base
+            .as_ref()
+            .and_then(|base| base.state().data().map(|base| base.data_version));
+
+        let mut cache = self.snapshot.0.lock();
+        if let Some(snapshot) = cache.as_ref() {
+            if snapshot.version == self.data_version
+                && snapshot.base_version == base_version
+                && snapshot.shader == self.shader
+            {
+                return snapshot.clone();
+            }
+        }
+
+        let properties = if self.base.is_some() {
+            let mut properties = FxHashMap::default();
+            self.for_each_resolved_property(|name, value| {
+                properties.insert(name.clone(), value.clone());
+            });
+            properties
+        } else {
+            self.properties.clone()
+        };
+
+        let snapshot = Arc::new(MaterialSnapshot {
+            version: self.data_version,
+            base_version,
+            shader: self.shader.clone(),
+            properties,
+            texture_swizzles: self.texture_swizzles.clone(),
+            render_queue: self.render_queue,
+            sort_bias: self.sort_bias,
+            two_sided: self.two_sided,
+            define_directives: self.define_directives.clone(),
+        });
+        *cache = Some(snapshot.clone());
+        snapshot
+    }
+
+    /// Executes the given closure with the material and coalesces all mutations done in it into a single
+    /// data version increment (see [`Self::data_version`]). The version is not changed if the closure
+    /// does not change anything. It is useful for materials that are heavily modified every frame (for
+    /// example, animated materials), because the renderer has to upload new data only once. Nested calls
+    /// are allowed, the version is increased only once, when the outermost call ends.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{
+    /// #     core::{color::Color, sstorage::ImmutableString},
+    /// #     material::{Material, PropertyValue},
+    /// # };
+    /// let mut material = Material::standard();
+    ///
+    /// material.edit(|material| {
+    ///     material
+    ///         .set_property(
+    ///             &ImmutableString::new("diffuseColor"),
+    ///             PropertyValue::Color(Color::RED),
+    ///         )
+    ///         .unwrap();
+    ///     material
+    ///         .set_property(
+    ///             &ImmutableString::new("emissionStrength"),
+    ///             PropertyValue::Vector3(Default::default()),
+    ///         )
+    ///         .unwrap();
+    /// });
+    /// ```
+    pub fn edit<F, R>(&mut self, func: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        if self.batching {
+            return func(self);
+        }
+
+        self.batching = true;
+        self.batch_modified = false;
+
+        let result = func(self);
+
+        self.batching = false;
+        if std::mem::take(&mut self.batch_modified) {
+            self.data_version += 1;
+        }
+
+        result
+    }
+
+    /// Returns the names of the properties, that were changed (set, added or removed) since the last call of
+    /// this method, and clears the list. The names are sorted. It is intended for render backends that pack
+    /// material properties into uniform buffers (see [`ShaderDefinition::uniform_layout`](shader::ShaderDefinition::uniform_layout))
+    /// and want to upload only the changed parts of the buffers.
+    ///
+    /// Every property is reported as changed when the material is created, deserialized or its shader is changed.
+    /// Material instances (see [`Self::from_base`]) report all their resolved properties in this case, but changes
+    /// of the base material itself are not tracked by the instances.
+    ///
+    /// Keep in mind that the built-in renderer uploads material properties as separate uniforms of shader programs,
+    /// that are shared between materials, so it sets all the properties for every draw call and does not use this
+    /// method.
+    pub fn take_dirty_properties(&mut self) -> Vec<ImmutableString> {
+        let mut names = if std::mem::take(&mut self.all_properties_dirty) {
+            self.dirty_properties.clear();
+            let mut names = Vec::new();
+            self.for_each_resolved_property(|name, _| names.push(name.clone()));
+            names
+        } else {
+            self.dirty_properties.drain().collect()
+        };
+        names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        names
+    }
+
+    fn mark_modified(&mut self) {
+        if self.batching {
+            self.batch_modified = true;
+        } else {
+            self.data_version += 1;
+        }
+    }
+
+    /// Checks formats of the textures bound to the samplers of the material against the requirements declared
+    /// in the shader (see [`TextureFormatRequirement`]) and returns a list of errors for every texture with
+    /// incompatible format. Samplers without a texture and textures that are not loaded yet (or failed to load)
+    /// are skipped, as well as all samplers if the shader itself is not loaded.
+    pub fn check_texture_formats(&self) -> Vec<MaterialError> {
+        let mut errors = Vec::new();
+
+        let mut shader_state = self.shader.state();
+        let Some(shader) = shader_state.data() else {
+            return errors;
+        };
+
+        for definition in shader.definition.properties.iter() {
+            if definition.texture_format == TextureFormatRequirement::Any {
+                continue;
+            }
+
+            let Some(PropertyValue::Sampler {
+                value: Some(texture),
+                ..
+            }) = self.properties.get(&ImmutableString::new(&definition.name))
+            else {
+                continue;
+            };
+
+            let mut texture_state = texture.state();
+            let Some(texture) = texture_state.data() else {
+                continue;
+            };
+
+            let pixel_kind = texture.pixel_kind();
+            if !definition.texture_format.is_satisfied_by(pixel_kind) {
+                errors.push(MaterialError::IncompatibleTextureFormat {
+                    property_name: definition.name.clone(),
+                    required: definition.texture_format,
+                    given: pixel_kind,
+                });
+            }
+        }
+
+        errors
+    }
+
+    /// Checks the properties of the material against the shader definition and returns all the found errors
+    /// at once. [`Self::set_property`] checks the type of a new value only against the current value, so
+    /// materials, that were modified via reflection, loaded from outdated files or created for some other
+    /// shader, could contain properties that the shader does not expect. Such properties are silently ignored
+    /// (or misinterpreted) by the renderer, this method allows tools and tests to find them. The following
+    /// errors are reported:
+    ///
+    /// - [`MaterialError::NoSuchProperty`] - the shader does not declare a property with such name.
+    /// - [`MaterialError::TypeMismatch`] - the type of the value does not match the type declared in the shader,
+    /// `expected` field contains the default value of the property from the shader.
+    /// - [`MaterialError::IncompatibleTextureFormat`] - see [`Self::check_texture_formats`].
+    ///
+    /// The errors are sorted by property names. Material instances (see [`Self::from_base`]) check their own
+    /// (overridden) properties only. Nothing is checked, if the shader is not loaded (yet).
+    pub fn validate(&self) -> Result<(), Vec<MaterialError>> {
+        let mut errors = Vec::new();
+
+        let mut shader_state = self.shader.state();
+        let Some(shader) = shader_state.data() else {
+            return Ok(());
+        };
+
+        for (name, value) in self.properties_sorted() {
+            let Some(definition) = shader
+                .definition
+                .properties
+                .iter()
+                .find(|definition| definition.name == name.as_ref())
+            else {
+                errors.push(MaterialError::NoSuchProperty {
+                    property_name: name.deref().to_owned(),
+                });
+                continue;
+            };
+
+            let expected = PropertyValue::from_property_kind(&definition.kind, None);
+            if std::mem::discriminant(&expected) != std::mem::discriminant(value) {
+                errors.push(MaterialError::TypeMismatch {
+                    property_name: name.deref().to_owned(),
+                    expected,
+                    given: value.clone(),
+                });
+            }
+        }
+        drop(shader_state);
+
+        errors.extend(self.check_texture_formats());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Copies properties of the `other` material into this material, replacing the values with matching names.
+    /// Texture swizzles and load priorities of the copied samplers are copied as well. It is intended for layered
+    /// workflows, when there's a shared base material and a set of partial overrides (skins, damage states, etc.)
+    /// that are applied on top of it:
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{
+    /// #     core::{color::Color, sstorage::ImmutableString},
+    /// #     material::{Material, PropertyValue},
+    /// # };
+    /// let mut material = Material::standard();
+    ///
+    /// let mut damage = Material::standard();
+    /// damage
+    ///     .set_property(
+    ///         &ImmutableString::new("diffuseColor"),
+    ///         PropertyValue::Color(Color::RED),
+    ///     )
+    ///     .unwrap();
+    ///
+    /// material.overlay(&damage).unwrap();
+    /// ```
+    ///
+    /// Only own properties of the `other` material are copied, the values inherited from its base material (see
+    /// [`Self::from_base`]) are not. The shader of this material is left intact, even if the shader of the `other`
+    /// material is different. The values are set using [`Self::set_property`], so a property that does not exist in
+    /// this material or has a different type is skipped and the respective error is returned. The errors are
+    /// sorted by property names, all the other properties are copied anyway.
+    pub fn overlay(&mut self, other: &Material) -> Result<(), Vec<MaterialError>> {
+        let errors = self.edit(|material| {
+            let mut errors = Vec::new();
+
+            for (name, value) in other.properties_sorted() {
+                if let Err(error) = material.set_property(name, value.clone()) {
+                    errors.push(error);
+                    continue;
+                }
+
+                if let PropertyValue::Sampler { .. } = value {
+                    let swizzle = other.texture_swizzle(name);
+                    if material.texture_swizzle(name) != swizzle {
+                        material.set_texture_swizzle(name, swizzle);
+                    }
+                    Log::verify(material.set_texture_priority(name, other.texture_priority(name)));
+                }
+            }
+
+            errors
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Sets loading priority of a texture bound to a sampler with the given name, keeping its texture. Returns
+    /// the previous priority. The priority is stored in the sampler binding, an inherited sampler (see
+    /// [`Self::from_base`]) is overridden by this material. See [`TextureLoadPriority`] docs for more info.
+    pub fn set_texture_priority(
+        &mut self,
+        name: &ImmutableString,
+        priority: TextureLoadPriority,
+    ) -> Result<TextureLoadPriority, MaterialError> {
+        match self.resolved_property(name) {
+            Some(PropertyValue::Sampler {
+                value,
+                fallback,
+                priority: prev,
+            }) => {
+                if prev != priority {
+                    // Makes sure that the material has its own binding, the inherited one is copied.
+                    self.set_property(
+                        name,
+                        PropertyValue::Sampler {
+                            value,
+                            fallback,
+                            priority,
+                        },
+                    )?;
+                    if let Some(PropertyValue::Sampler {
+                        priority: current, ..
+                    }) = self.properties.get_mut(name)
+                    {
+                        *current = priority;
+                    }
+                }
+                Ok(prev)
+            }
+            Some(expected) => Err(MaterialError::TypeMismatch {
+                property_name: name.deref().to_owned(),
+                expected,
+                given: PropertyValue::Sampler {
+                    value: None,
+                    fallback: Default::default(),
+                    priority,
+                },
+            }),
+            None => Err(MaterialError::NoSuchProperty {
+                property_name: name.deref().to_owned(),
+            }),
+        }
+    }
+
+    /// Returns loading priority of a texture bound to a sampler with the given name. Samplers without
+    /// explicit priority (and non-sampler properties) have [`TextureLoadPriority::Normal`] priority.
+    pub fn texture_priority(&self, name: &ImmutableString) -> TextureLoadPriority {
+        match self.resolved_property(name) {
+            Some(PropertyValue::Sampler { priority, .. }) => priority,
+            _ => Default::default(),
+        }
+    }
+
+    /// Sets texture swizzle of a sampler with the given name, [`None`] resets the swizzle, which means that the
+    /// channels of the texture are passed to the shader as is. The swizzle is applied by the renderer when the
+    /// texture is sampled by the material, the same texture could be used with different swizzles in different
+    /// materials. See [`TextureSwizzle`] docs for more info.
+    ///
+    /// Swizzle is a state of a GPU texture, so the renderer keeps a separate GPU copy of a texture for every
+    /// distinct swizzle it is sampled with. It saves the memory of the source texture assets, but not GPU memory.
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{
+    /// #     core::sstorage::ImmutableString,
+    /// #     material::{Material, TextureSwizzle},
+    /// # };
+    /// let mut material = Material::standard();
+    ///
+    /// // Metallic is stored in blue channel of the texture, while the shader reads red channel.
+    /// material.set_texture_swizzle(
+    ///     &ImmutableString::new("metallicTexture"),
+    ///     TextureSwizzle::parse("bbbb"),
+    /// );
+    /// ```
+    pub fn set_texture_swizzle(
+        &mut self,
+        name: &ImmutableString,
+        swizzle: Option<TextureSwizzle>,
+    ) -> Option<TextureSwizzle> {
+        let prev = match swizzle {
+            Some(swizzle) if swizzle != TextureSwizzle::IDENTITY => {
+                self.texture_swizzles.insert(name.clone(), swizzle)
+            }
+            _ => self.texture_swizzles.remove(name),
+        };
+        self.mark_modified();
+        prev
+    }
+
+    /// Returns texture swizzle of a sampler with the given name (if any). See [`Self::set_texture_swizzle`]
+    /// for more info.
+    pub fn texture_swizzle(&self, name: &ImmutableString) -> Option<TextureSwizzle> {
+        self.texture_swizzles.get(name).cloned()
+    }
+
+    /// Overrides the render queue of the shader (see [`ShaderDefinition::render_queue`](shader::ShaderDefinition::render_queue))
+    /// for this material, [`None`] resets the override. Returns the previous override. See [`RenderQueue`] docs for
+    /// more info about the queue ordering.
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::material::{shader::RenderQueue, Material};
+    /// let mut material = Material::standard();
+    ///
+    /// // Draw the material after every opaque surface, but before translucent ones (particles, etc.).
+    /// material.set_render_queue(Some(RenderQueue::AlphaTest));
+    /// ```
+    pub fn set_render_queue(&mut self, render_queue: Option<RenderQueue>) -> Option<RenderQueue> {
+        let prev = std::mem::replace(&mut self.render_queue, render_queue);
+        self.mark_modified();
+        prev
+    }
+
+    /// Returns the render queue override of the material (if any). See [`Self::set_render_queue`] for more info.
+    pub fn render_queue_override(&self) -> Option<RenderQueue> {
+        self.render_queue
+    }
+
+    /// Returns the render queue that is used by the renderer to sort the surfaces with the material. It is either the
+    /// override of the material (see [`Self::set_render_queue`]) or the default queue of the shader. If the shader is
+    /// not loaded, [`RenderQueue::Opaque`] is returned.
+    pub fn render_queue(&self) -> RenderQueue {
+        self.render_queue.unwrap_or_else(|| {
+            self.shader
+                .state()
+                .data()
+                .map(|shader| shader.definition.render_queue)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Sets sort bias of the material. Surfaces in the same render queue are sorted by the bias first (lower values
+    /// are drawn first) and only then by the sort index provided by scene nodes. Default value is zero. Returns the
+    /// previous value.
+    pub fn set_sort_bias(&mut self, sort_bias: i32) -> i32 {
+        let prev = std::mem::replace(&mut self.sort_bias, sort_bias);
+        self.mark_modified();
+        prev
+    }
+
+    /// Returns sort bias of the material. See [`Self::set_sort_bias`] for more info.
+    pub fn sort_bias(&self) -> i32 {
+        self.sort_bias
+    }
+
+    /// Makes the material either two-sided or single-sided. Two-sided materials disable face culling in every render
+    /// pass of the shader, so back faces of the surfaces are drawn as well (and cast shadows). It is useful for thin
+    /// objects like foliage or flags. Returns the previous value.
+    ///
+    /// This is a render state override on top of the draw parameters of the shader, the shader stays the same and all
+    /// the properties are preserved. Keep in mind, that the override could only disable culling: if the shader itself
+    /// has culling disabled (for example, [`ShaderResourceExtension::standard_twosides`]), the material is two-sided
+    /// regardless of this flag. By default, materials are single-sided and use culling settings of their shaders.
+    pub fn set_two_sided(&mut self, two_sided: bool) -> bool {
+        let prev = std::mem::replace(&mut self.two_sided, two_sided);
+        self.mark_modified();
+        prev
+    }
+
+    /// Returns `true` if the material is two-sided. See [`Self::set_two_sided`] for more info.
+    pub fn is_two_sided(&self) -> bool {
+        self.two_sided
+    }
+
+    /// Sets a value of a shader define (a compile-time constant) with the given name. Defines allow a single
+    /// "uber-shader" to have a number of feature toggles (`#ifdef USE_DETAIL_MAP ... #endif`), instead of a
+    /// separate shader per feature combination. Returns the previous value of the define.
+    ///
+    /// The defines are injected as `#define` directives at the beginning of the vertex and fragment code of every
+    /// render pass of the shader. Boolean defines are either defined as `1` or not defined at all, integer defines
+    /// are defined as their value. The name must be a valid GLSL identifier, otherwise the shader will fail to
+    /// compile and the surfaces with the material will not be rendered.
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{core::sstorage::ImmutableString, material::Material};
+    /// let mut material = Material::standard();
+    ///
+    /// material.set_define(&ImmutableString::new("USE_DETAIL_MAP"), true);
+    /// material.set_define(&ImmutableString::new("QUALITY"), 2);
+    /// ```
+    ///
+    /// # Permutations
+    ///
+    /// Every unique set of defines produces a separate variant (permutation) of the shader. The renderer compiles
+    /// permutations on demand, when a surface with the respective set of defines is rendered for the first time,
+    /// and caches them together with the shader, keyed by the set of defines. Materials with the same set of defines
+    /// share the same permutation, so the amount of compiled programs is bound by the amount of unique define sets
+    /// in use, not by the amount of materials. Permutations are discarded together with the shader when it is reloaded
+    /// or unused for a while. Keep in mind, that the first use of a new permutation causes a compilation hitch.
+    pub fn set_define(
+        &mut self,
+        name: &ImmutableString,
+        value: impl Into<ShaderDefineValue>,
+    ) -> Option<ShaderDefineValue> {
+        let prev = self.defines.insert(name.clone(), value.into());
+        self.update_define_directives();
+        self.mark_modified();
+        prev
+    }
+
+    /// Removes a shader define with the given name. Returns the value of the define (if any). See
+    /// [`Self::set_define`] for more info.
+    pub fn remove_define(&mut self, name: &ImmutableString) -> Option<ShaderDefineValue> {
+        let prev = self.defines.remove(name);
+        self.update_define_directives();
+        self.mark_modified();
+        prev
+    }
+
+    /// Returns a value of a shader define with the given name (if any). See [`Self::set_define`] for more info.
+    pub fn define(&self, name: &ImmutableString) -> Option<ShaderDefineValue> {
+        self.defines.get(name).cloned()
+    }
+
+    /// Returns a reference to the shader defines of the material. See [`Self::set_define`] for more info.
+    pub fn defines(&self) -> &FxHashMap<ImmutableString, ShaderDefineValue> {
+        &self.defines
+    }
+
+    /// Returns the `#define` directives produced from the shader defines of the material, sorted by name. An empty
+    /// string means that the material uses the base variant of the shader. The string uniquely identifies the
+    /// shader permutation, so it can be used as a key of permutation caches.
+    pub fn define_directives(&self) -> &str {
+        &self.define_directives
+    }
+
+    fn update_define_directives(&mut self) {
+        let mut defines = self.defines.iter().collect::<Vec<_>>();
+        defines.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+        self.define_directives.clear();
+        for (name, value) in defines {
+            match value {
+                ShaderDefineValue::Bool(true) => {
+                    self.define_directives += &format!("#define {name} 1\n")
+                }
+                ShaderDefineValue::Bool(false) => (),
+                ShaderDefineValue::Int(value) => {
+                    self.define_directives += &format!("#define {name} {value}\n")
+                }
+            }
+        }
+    }
+
+    /// Returns a list of textures bound to the samplers of the material with their loading priorities.
+    /// The list is sorted by priority, textures with higher priority go first. It could be used by
+    /// streaming systems to request the textures from a resource manager in a sensible order.
+    pub fn texture_load_requests(&self) -> Vec<(TextureResource, TextureLoadPriority)> {
+        let mut requests = self
+            .properties
+            .values()
+            .filter_map(|value| match value {
+                PropertyValue::Sampler {
+                    value: Some(texture),
+                    priority,
+                    ..
+                } => Some((texture.clone(), *priority)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        requests.sort_by(|(_, a), (_, b)| b.cmp(a));
+        requests
     }
 
     /// Returns a reference to current shader.
@@ -910,10 +2615,457 @@ impl Material {
         &self.shader
     }
 
-    /// Returns immutable reference to internal property storage.
+    /// Changes the shader of the material, keeping every property whose name and type still exist in the new
+    /// shader. The rest of the properties are removed, their names are returned (sorted). The properties, that
+    /// are declared in the new shader, but missing in the material, are added with their default values (sampler
+    /// defaults are not resolved, see [`Self::ensure_defaults`]). It is useful to upgrade a shader without losing
+    /// the configured values of the material.
+    ///
+    /// The new shader must be loaded to remap the properties. If it is not loaded (yet), all the properties are
+    /// kept as is and nothing is returned, call [`Self::sync_to_shader`] when the shader is loaded to remove the
+    /// properties, that do not exist in it. Material instances (see [`Self::from_base`]) should not be used with
+    /// this method, they use the shader of their base material.
+    pub fn set_shader(&mut self, shader: ShaderResource) -> Vec<ImmutableString> {
+        let mut dropped = Vec::new();
+
+        if let Some(new_shader) = shader.state().data() {
+            for (name, value) in self.properties.iter() {
+                let is_compatible = new_shader
+                    .definition
+                    .properties
+                    .iter()
+                    .find(|definition| definition.name == name.as_ref())
+                    .map_or(false, |definition| {
+                        let expected = PropertyValue::from_property_kind(&definition.kind, None);
+                        std::mem::discriminant(&expected) == std::mem::discriminant(value)
+                    });
+
+                if !is_compatible {
+                    dropped.push(name.clone());
+                }
+            }
+        }
+
+        for name in dropped.iter() {
+            self.properties.remove(name);
+        }
+        dropped.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        self.shader = shader;
+        if self.base.is_none() && !self.ensure_defaults(None) {
+            self.pending_defaults = Some(PendingDefaults(None));
+        }
+        self.all_properties_dirty = true;
+        self.mark_modified();
+
+        dropped
+    }
+
+    /// Sets a shader, that will be used by the renderer instead of the main shader of the material, if the
+    /// main shader failed to load. Returns previous fallback shader. See [`Self::fallback_shader`] for more
+    /// info.
+    pub fn set_fallback_shader(&mut self, shader: ShaderResource) -> Option<ShaderResource> {
+        self.mark_modified();
+        self.fallback_shader.replace(shader)
+    }
+
+    /// Removes the fallback shader of the material, so the renderer will use the default one. Returns
+    /// previous fallback shader.
+    pub fn reset_fallback_shader(&mut self) -> Option<ShaderResource> {
+        self.mark_modified();
+        self.fallback_shader.take()
+    }
+
+    /// Returns a reference to the fallback shader of the material, if any.
+    ///
+    /// # Fallback
+    ///
+    /// The fallback engages only when the main shader of the material is in the
+    /// [`crate::asset::state::ResourceState::LoadError`] state (for example, the file is missing or has
+    /// syntax errors). Objects with a shader that is still loading are not rendered at all, as usual. When
+    /// the fallback engages, the renderer uses the fallback shader of the material or, if there's none, the
+    /// [`crate::material::shader::ShaderResourceExtension::error`] shader, which renders everything in bright
+    /// magenta color, so broken content is clearly visible. The substitution is logged once per broken shader.
+    ///
+    /// This is purely a render-time substitution - the material keeps its main shader and its properties
+    /// as is (the properties are passed to the fallback shader by their names, the ones that the fallback
+    /// shader does not have are ignored), so once the main shader is fixed and reloaded, the material will
+    /// be rendered as usual.
+    pub fn fallback_shader(&self) -> Option<&ShaderResource> {
+        self.fallback_shader.as_ref()
+    }
+
+    /// Returns a full set of editable properties of the material with their types, default values and
+    /// hints (such as description and range of values), as they're declared in the shader. Returns [`None`]
+    /// if the shader is not loaded (yet).
+    ///
+    /// # Caching
+    ///
+    /// The method is intended to be used by the editor to build property editors, so the result is cached
+    /// and subsequent calls are cheap. The cache is invalidated when:
+    ///
+    /// - The shader of the material is replaced (for example, via reflection in the editor).
+    /// - The shader is reloaded (hot reloading or
+    /// [`crate::asset::manager::ResourceManagerState::reload_resource`]). Every reload creates
+    /// a new shader instance, which is detected on the next call.
+    ///
+    /// The cache does not depend on the values of the properties of the material, so changing them does not
+    /// invalidate the cache.
+    pub fn property_schema(&self) -> Option<Arc<[PropertyDefinition]>> {
+        let mut shader_state = self.shader.state();
+        let shader = shader_state.data()?;
+
+        let mut cache = self.property_schema.0.lock();
+        if let Some((cache_index, schema)) = cache.as_ref() {
+            if Arc::ptr_eq(cache_index, &shader.cache_index) {
+                return Some(schema.clone());
+            }
+        }
+
+        let schema = Arc::<[PropertyDefinition]>::from(shader.definition.properties.clone());
+        *cache = Some((shader.cache_index.clone(), schema.clone()));
+        Some(schema)
+    }
+
+    /// Same as [`Self::set_property`], but additionally checks the new value against the range of the property
+    /// declared in the shader (see [`PropertyDefinition::range`]). The range is inclusive on both ends. Out-of-range
+    /// values are either clamped to the range or rejected with [`MaterialError::OutOfRange`] error, depending on
+    /// the given policy. Only floating-point properties (floats, vectors and their arrays) are checked, every
+    /// component of a vector is checked separately. `NaN` is always out of range (it is clamped to the start of
+    /// the range).
+    ///
+    /// Properties without a range, properties of other types and properties of materials which shader is not
+    /// loaded (yet) are not checked at all.
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{
+    /// #     core::sstorage::ImmutableString,
+    /// #     material::{Material, OutOfRangePolicy, PropertyValue},
+    /// # };
+    /// fn set_roughness(material: &mut Material, roughness: f32) {
+    ///     // Let's assume that the shader declares `range: Some((start: 0.0, end: 1.0))` for the property,
+    ///     // then 1.5 will be clamped to 1.0.
+    ///     material
+    ///         .set_property_checked(
+    ///             &ImmutableString::new("roughness"),
+    ///             PropertyValue::Float(roughness),
+    ///             OutOfRangePolicy::Clamp,
+    ///         )
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn set_property_checked(
+        &mut self,
+        name: &ImmutableString,
+        mut new_value: PropertyValue,
+        policy: OutOfRangePolicy,
+    ) -> Result<(), MaterialError> {
+        let range = self.property_schema().and_then(|schema| {
+            schema
+                .iter()
+                .find(|definition| definition.name == **name)
+                .and_then(|definition| definition.range.clone())
+        });
+
+        if let Some(range) = range {
+            let mut out_of_range = false;
+            for_each_float_component(&mut new_value, &mut |value| {
+                if !(*value >= range.start && *value <= range.end) {
+                    out_of_range = true;
+                    if policy == OutOfRangePolicy::Clamp {
+                        *value = value.max(range.start).min(range.end);
+                    }
+                }
+            });
+
+            if out_of_range && policy == OutOfRangePolicy::Reject {
+                return Err(MaterialError::OutOfRange {
+                    property_name: name.deref().to_owned(),
+                    range,
+                    given: new_value,
+                });
+            }
+        }
+
+        self.set_property(name, new_value)
+    }
+
+    /// Returns immutable reference to internal property storage. Iteration order of the storage is unspecified,
+    /// use [`Self::properties_sorted`] if you need a deterministic order.
     pub fn properties(&self) -> &FxHashMap<ImmutableString, PropertyValue> {
         &self.properties
     }
+
+    /// Returns the properties of the material sorted by their names. The same order is used when the material
+    /// is saved, so saving the same material always produces the same output.
+    pub fn properties_sorted(&self) -> Vec<(&ImmutableString, &PropertyValue)> {
+        let mut properties = self.properties.iter().collect::<Vec<_>>();
+        properties.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+        properties
+    }
+
+    /// Checks whether the material is structurally equal to the other material. Unlike pointer-based
+    /// comparison of material resources, this method compares the content of the materials and does
+    /// not depend on load state of the resources, so it could be used to deduplicate materials while
+    /// their textures are still loading. The following is compared:
+    ///
+    /// - Shaders - the shaders must be either the same resource, or external resources with the same
+    /// path.
+    /// - Properties - both materials must have the same set of property names. Non-sampler values must
+    /// be exactly equal (floats are compared by value, so `NaN` is never equal to anything).
+    /// - Samplers - fallback values must be equal and the textures must be either both absent, the same
+    /// resource, or external resources with the same path. Embedded (procedural) textures are equal
+    /// only if they are the same resource.
+    ///
+    /// Texture swizzles, render queue overrides, sort biases, two-sided flags and shader defines must be equal as
+    /// well. Texture load priorities are runtime hints and are not compared. Material instances (see
+    /// [`Self::from_base`]) must have structurally equal bases, their own (overridden) properties are compared.
+    pub fn structurally_eq(&self, other: &Self) -> bool {
+        self.content_eq_with(other, property_values_structurally_eq)
+    }
+
+    /// Checks whether the material has exactly the same content as the other material. It is the same as
+    /// [`Self::structurally_eq`], except that floating-point values are compared by their bit patterns (so `NaN`
+    /// is equal to itself, but `0.0` is not equal to `-0.0`). This makes it consistent with [`Self::content_hash`]:
+    /// materials with equal content always have equal hashes, so the pair could be used to collapse duplicate
+    /// materials (for example, the ones generated procedurally) into shared resources:
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{
+    /// #     fxhash::FxHashMap,
+    /// #     material::{Material, MaterialResource, MaterialResourceExtension},
+    /// # };
+    /// fn deduplicate(
+    ///     cache: &mut FxHashMap<u64, Vec<MaterialResource>>,
+    ///     material: Material,
+    /// ) -> MaterialResource {
+    ///     let bucket = cache.entry(material.content_hash()).or_default();
+    ///     for existing in bucket.iter() {
+    ///         if existing.data_ref().eq_content(&material) {
+    ///             return existing.clone();
+    ///         }
+    ///     }
+    ///     let resource = MaterialResource::new(material);
+    ///     bucket.push(resource.clone());
+    ///     resource
+    /// }
+    /// ```
+    pub fn eq_content(&self, other: &Self) -> bool {
+        self.content_eq_with(other, property_values_bitwise_eq)
+    }
+
+    /// Calculates a hash of the content of the material. The same data as in [`Self::eq_content`] is hashed:
+    /// shader and base material identities (paths of external resources, or the resources themselves for
+    /// embedded ones), all properties sorted by their names (floating-point values are hashed by their bit
+    /// patterns), texture swizzles, render queue override, sort bias, two-sided flag and shader defines.
+    ///
+    /// The hash is deterministic for the same content within a run of the program, but it should not be stored,
+    /// because embedded resources are hashed by their addresses.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+
+        hash_resource(&self.shader, &mut hasher);
+        self.base.is_some().hash(&mut hasher);
+        if let Some(base) = self.base.as_ref() {
+            hash_resource(base, &mut hasher);
+        }
+
+        for (name, value) in self.properties_sorted() {
+            name.as_str().hash(&mut hasher);
+            std::mem::discriminant(value).hash(&mut hasher);
+            if let PropertyValue::Sampler {
+                value, fallback, ..
+            } = value
+            {
+                fallback.hash(&mut hasher);
+                value.is_some().hash(&mut hasher);
+                if let Some(texture) = value {
+                    hash_resource(texture, &mut hasher);
+                }
+            } else {
+                property_value_bits(value).hash(&mut hasher);
+            }
+        }
+
+        let mut swizzles = self.texture_swizzles.iter().collect::<Vec<_>>();
+        swizzles.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+        for (name, swizzle) in swizzles {
+            name.as_str().hash(&mut hasher);
+            swizzle.hash(&mut hasher);
+        }
+
+        self.render_queue.hash(&mut hasher);
+        self.sort_bias.hash(&mut hasher);
+        self.two_sided.hash(&mut hasher);
+        self.define_directives.hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    fn content_eq_with(
+        &self,
+        other: &Self,
+        values_eq: fn(&PropertyValue, &PropertyValue) -> bool,
+    ) -> bool {
+        resources_structurally_eq(&self.shader, &other.shader)
+            && match (self.base.as_ref(), other.base.as_ref()) {
+                (Some(a), Some(b)) => resources_structurally_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+            && self.texture_swizzles == other.texture_swizzles
+            && self.render_queue == other.render_queue
+            && self.sort_bias == other.sort_bias
+            && self.two_sided == other.two_sided
+            && self.define_directives == other.define_directives
+            && self.properties.len() == other.properties.len()
+            && self.properties.iter().all(|(name, value)| {
+                other
+                    .properties
+                    .get(name)
+                    .map_or(false, |other_value| values_eq(value, other_value))
+            })
+    }
+}
+
+// Calls the given function for every floating-point component of the value, does nothing for other types.
+fn for_each_float_component(value: &mut PropertyValue, func: &mut dyn FnMut(&mut f32)) {
+    match value {
+        PropertyValue::Float(v) => func(v),
+        PropertyValue::FloatArray(v) => v.iter_mut().for_each(func),
+        PropertyValue::Vector2(v) => v.iter_mut().for_each(func),
+        PropertyValue::Vector2Array(v) => v.iter_mut().flat_map(|v| v.iter_mut()).for_each(func),
+        PropertyValue::Vector3(v) => v.iter_mut().for_each(func),
+        PropertyValue::Vector3Array(v) => v.iter_mut().flat_map(|v| v.iter_mut()).for_each(func),
+        PropertyValue::Vector4(v) => v.iter_mut().for_each(func),
+        PropertyValue::Vector4Array(v) => v.iter_mut().flat_map(|v| v.iter_mut()).for_each(func),
+        _ => (),
+    }
+}
+
+fn resources_structurally_eq<T>(a: &Resource<T>, b: &Resource<T>) -> bool
+where
+    T: TypedResourceData,
+{
+    if a == b {
+        return true;
+    }
+
+    match (a.kind(), b.kind()) {
+        (ResourceKind::External(a), ResourceKind::External(b)) => a == b,
+        _ => false,
+    }
+}
+
+// Hashes a resource consistently with `resources_structurally_eq`.
+fn hash_resource<T, H>(resource: &Resource<T>, hasher: &mut H)
+where
+    T: TypedResourceData,
+    H: Hasher,
+{
+    match resource.kind() {
+        ResourceKind::External(path) => path.hash(hasher),
+        ResourceKind::Embedded => resource.hash(hasher),
+    }
+}
+
+// Returns bit patterns of all components of a non-sampler value, floats are represented by their bit patterns.
+// Samplers have no components.
+fn property_value_bits(value: &PropertyValue) -> Vec<u64> {
+    fn floats<'a>(values: impl Iterator<Item = &'a f32>) -> Vec<u64> {
+        values.map(|v| v.to_bits() as u64).collect()
+    }
+
+    fn color(color: &Color) -> u64 {
+        u32::from_le_bytes([color.r, color.g, color.b, color.a]) as u64
+    }
+
+    match value {
+        PropertyValue::Float(v) => vec![v.to_bits() as u64],
+        PropertyValue::FloatArray(v) => floats(v.iter()),
+        PropertyValue::Int(v) => vec![*v as u32 as u64],
+        PropertyValue::IntArray(v) => v.iter().map(|v| *v as u32 as u64).collect(),
+        PropertyValue::UInt(v) => vec![*v as u64],
+        PropertyValue::UIntArray(v) => v.iter().map(|v| *v as u64).collect(),
+        PropertyValue::Vector2(v) => floats(v.iter()),
+        PropertyValue::Vector2Array(v) => floats(v.iter().flat_map(|v| v.iter())),
+        PropertyValue::Vector3(v) => floats(v.iter()),
+        PropertyValue::Vector3Array(v) => floats(v.iter().flat_map(|v| v.iter())),
+        PropertyValue::Vector4(v) => floats(v.iter()),
+        PropertyValue::Vector4Array(v) => floats(v.iter().flat_map(|v| v.iter())),
+        PropertyValue::Matrix2(v) => floats(v.iter()),
+        PropertyValue::Matrix2Array(v) => floats(v.iter().flat_map(|v| v.iter())),
+        PropertyValue::Matrix3(v) => floats(v.iter()),
+        PropertyValue::Matrix3Array(v) => floats(v.iter().flat_map(|v| v.iter())),
+        PropertyValue::Matrix4(v) => floats(v.iter()),
+        PropertyValue::Matrix4Array(v) => floats(v.iter().flat_map(|v| v.iter())),
+        PropertyValue::Bool(v) => vec![*v as u64],
+        PropertyValue::Color(v) => vec![color(v)],
+        PropertyValue::ColorArray(v) => v.iter().map(color).collect(),
+        PropertyValue::Double(v) => vec![v.to_bits()],
+        PropertyValue::DoubleArray(v) => v.iter().map(|v| v.to_bits()).collect(),
+        PropertyValue::Sampler { .. } => Vec::new(),
+    }
+}
+
+fn property_values_bitwise_eq(a: &PropertyValue, b: &PropertyValue) -> bool {
+    match (a, b) {
+        (PropertyValue::Sampler { .. }, PropertyValue::Sampler { .. }) => {
+            property_values_structurally_eq(a, b)
+        }
+        _ => {
+            std::mem::discriminant(a) == std::mem::discriminant(b)
+                && property_value_bits(a) == property_value_bits(b)
+        }
+    }
+}
+
+fn property_values_structurally_eq(a: &PropertyValue, b: &PropertyValue) -> bool {
+    match (a, b) {
+        (PropertyValue::Float(a), PropertyValue::Float(b)) => a == b,
+        (PropertyValue::FloatArray(a), PropertyValue::FloatArray(b)) => a == b,
+        (PropertyValue::Int(a), PropertyValue::Int(b)) => a == b,
+        (PropertyValue::IntArray(a), PropertyValue::IntArray(b)) => a == b,
+        (PropertyValue::UInt(a), PropertyValue::UInt(b)) => a == b,
+        (PropertyValue::UIntArray(a), PropertyValue::UIntArray(b)) => a == b,
+        (PropertyValue::Vector2(a), PropertyValue::Vector2(b)) => a == b,
+        (PropertyValue::Vector2Array(a), PropertyValue::Vector2Array(b)) => a == b,
+        (PropertyValue::Vector3(a), PropertyValue::Vector3(b)) => a == b,
+        (PropertyValue::Vector3Array(a), PropertyValue::Vector3Array(b)) => a == b,
+        (PropertyValue::Vector4(a), PropertyValue::Vector4(b)) => a == b,
+        (PropertyValue::Vector4Array(a), PropertyValue::Vector4Array(b)) => a == b,
+        (PropertyValue::Matrix2(a), PropertyValue::Matrix2(b)) => a == b,
+        (PropertyValue::Matrix2Array(a), PropertyValue::Matrix2Array(b)) => a == b,
+        (PropertyValue::Matrix3(a), PropertyValue::Matrix3(b)) => a == b,
+        (PropertyValue::Matrix3Array(a), PropertyValue::Matrix3Array(b)) => a == b,
+        (PropertyValue::Matrix4(a), PropertyValue::Matrix4(b)) => a == b,
+        (PropertyValue::Matrix4Array(a), PropertyValue::Matrix4Array(b)) => a == b,
+        (PropertyValue::Bool(a), PropertyValue::Bool(b)) => a == b,
+        (PropertyValue::Color(a), PropertyValue::Color(b)) => a == b,
+        (PropertyValue::Double(a), PropertyValue::Double(b)) => a == b,
+        (PropertyValue::DoubleArray(a), PropertyValue::DoubleArray(b)) => a == b,
+        (PropertyValue::ColorArray(a), PropertyValue::ColorArray(b)) => a == b,
+        (
+            PropertyValue::Sampler {
+                value: a,
+                fallback: a_fallback,
+                ..
+            },
+            PropertyValue::Sampler {
+                value: b,
+                fallback: b_fallback,
+                ..
+            },
+        ) => {
+            a_fallback == b_fallback
+                && match (a, b) {
+                    (Some(a), Some(b)) => resources_structurally_eq(a, b),
+                    (None, None) => true,
+                    _ => false,
+                }
+        }
+        _ => false,
+    }
 }
 
 /// Shared material is a material instance that can be used across multiple objects. It is useful
@@ -946,6 +3098,29 @@ pub trait MaterialResourceExtension {
         drop(header);
         material
     }
+
+    /// Creates a copy of the material resource, that shares the shader with the original material. The copy is
+    /// always embedded (procedural), which makes it suitable for spawning lots of variants of a single material.
+    ///
+    /// # Aliasing
+    ///
+    /// The copy gets its own property values, changing them does not affect the original material and vice
+    /// versa. Resources, that are referenced by the material (the shader, the textures, the base material and the
+    /// fallback shader), are shared by reference - only their handles are cloned, which is cheap. This means that
+    /// both materials always use exactly the same shader, including its reloads. If the material is not loaded, the
+    /// result is the same as [`Self::deep_copy_as_embedded`].
+    fn deep_copy_keep_shader(&self) -> MaterialResource {
+        let copy = self.deep_copy_as_embedded();
+        let mut state = copy.state();
+        if let Some(material) = state.data() {
+            // The copy is a new material, that has no previous versions.
+            material.data_version = 0;
+            material.snapshot = Default::default();
+            material.all_properties_dirty = true;
+        }
+        drop(state);
+        copy
+    }
 }
 
 impl MaterialResourceExtension for MaterialResource {
@@ -1002,6 +3177,7 @@ where
                 PropertyValue::Sampler {
                     value: old_texture,
                     fallback: SamplerFallback::White,
+                    priority: Default::default(),
                 },
             ));
             return Some(MaterialResource::new_ok(Default::default(), material));
@@ -1009,3 +3185,841 @@ where
     }
     None
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{manager::ResourceManager, untyped::ResourceKind},
+        core::{
+            algebra::Vector2,
+            color::Color,
+            sstorage::ImmutableString,
+            visitor::{Visit, Visitor},
+        },
+        material::{
+            shader::{SamplerFallback, Shader, ShaderResource, ShaderResourceExtension},
+            Material, MaterialError, MaterialResource, MaterialResourceExtension, OutOfRangePolicy,
+            PropertyValue, ShaderDefineValue, TextureLoadPriority,
+        },
+        resource::texture::{
+            TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+        },
+    };
+    use fxhash::FxHashMap;
+    use std::sync::Arc;
+
+    fn texture(kind: TextureKind, size: usize) -> TextureResource {
+        TextureResource::from_bytes(
+            kind,
+            TexturePixelKind::RGBA8,
+            vec![255; size * 4],
+            ResourceKind::Embedded,
+        )
+        .unwrap()
+    }
+
+    fn float(material: &Material, name: &ImmutableString) -> f32 {
+        match material.property_ref(name) {
+            Some(PropertyValue::Float(value)) => *value,
+            value => panic!("unexpected value {value:?}"),
+        }
+    }
+
+    fn material_with_ranges() -> Material {
+        let code = r#"
+            (
+                name: "TestShader",
+                properties: [
+                    (
+                        name: "roughness",
+                        kind: Float(0.5),
+                        range: Some((start: 0.0, end: 1.0)),
+                    ),
+                    (
+                        name: "offset",
+                        kind: Vector2((0.0, 0.0)),
+                        range: Some((start: -1.0, end: 1.0)),
+                    ),
+                    (
+                        name: "scale",
+                        kind: Float(1.0),
+                    ),
+                ],
+                passes: [],
+            )
+            "#;
+        let shader = ShaderResource::from_str(code, ResourceKind::Embedded).unwrap();
+        Material::from_shader(shader, None)
+    }
+
+    #[test]
+    fn test_set_property_checked_in_range() {
+        let mut material = material_with_ranges();
+        let roughness = ImmutableString::new("roughness");
+
+        for policy in [OutOfRangePolicy::Clamp, OutOfRangePolicy::Reject] {
+            for value in [0.0, 0.25, 1.0] {
+                material
+                    .set_property_checked(&roughness, PropertyValue::Float(value), policy)
+                    .unwrap();
+                assert_eq!(float(&material, &roughness), value);
+            }
+        }
+
+        // Properties without a range are not checked.
+        let scale = ImmutableString::new("scale");
+        material
+            .set_property_checked(
+                &scale,
+                PropertyValue::Float(100.0),
+                OutOfRangePolicy::Reject,
+            )
+            .unwrap();
+        assert_eq!(float(&material, &scale), 100.0);
+    }
+
+    #[test]
+    fn test_set_property_checked_out_of_range() {
+        let mut material = material_with_ranges();
+        let roughness = ImmutableString::new("roughness");
+
+        material
+            .set_property_checked(
+                &roughness,
+                PropertyValue::Float(1.5),
+                OutOfRangePolicy::Clamp,
+            )
+            .unwrap();
+        assert_eq!(float(&material, &roughness), 1.0);
+
+        assert!(matches!(
+            material.set_property_checked(
+                &roughness,
+                PropertyValue::Float(-0.5),
+                OutOfRangePolicy::Reject,
+            ),
+            Err(MaterialError::OutOfRange { .. })
+        ));
+        // Rejected value must not modify the property.
+        assert_eq!(float(&material, &roughness), 1.0);
+
+        // Each component of a vector is clamped separately.
+        let offset = ImmutableString::new("offset");
+        material
+            .set_property_checked(
+                &offset,
+                PropertyValue::Vector2(Vector2::new(-2.0, 0.5)),
+                OutOfRangePolicy::Clamp,
+            )
+            .unwrap();
+        assert!(matches!(
+            material.property_ref(&offset),
+            Some(PropertyValue::Vector2(v)) if *v == Vector2::new(-1.0, 0.5)
+        ));
+    }
+
+    #[test]
+    fn test_define_directives() {
+        let mut material = Material::standard();
+        assert_eq!(material.define_directives(), "");
+
+        material.set_define(&ImmutableString::new("USE_FOG"), true);
+        material.set_define(&ImmutableString::new("QUALITY"), 2);
+        material.set_define(&ImmutableString::new("USE_DETAIL_MAP"), false);
+
+        // Directives are sorted by name, so the key does not depend on insertion order. Disabled
+        // flags are not defined at all.
+        assert_eq!(
+            material.define_directives(),
+            "#define QUALITY 2\n#define USE_FOG 1\n"
+        );
+        assert_eq!(
+            material.define(&ImmutableString::new("QUALITY")),
+            Some(ShaderDefineValue::Int(2))
+        );
+
+        material.remove_define(&ImmutableString::new("QUALITY"));
+        assert_eq!(material.define_directives(), "#define USE_FOG 1\n");
+    }
+
+    #[test]
+    fn test_snapshot_versioning() {
+        let mut material = material_with_ranges();
+        let roughness = ImmutableString::new("roughness");
+
+        let snapshot = material.snapshot();
+        // No edits - the same snapshot.
+        assert!(Arc::ptr_eq(&snapshot, &material.snapshot()));
+
+        material
+            .set_property(&roughness, PropertyValue::Float(0.25))
+            .unwrap();
+
+        // Existing snapshot is not affected by the edit.
+        assert!(matches!(
+            snapshot.property_ref(&roughness),
+            Some(PropertyValue::Float(v)) if *v == 0.5
+        ));
+
+        let new_snapshot = material.snapshot();
+        assert!(!Arc::ptr_eq(&snapshot, &new_snapshot));
+        assert_eq!(new_snapshot.version(), material.data_version());
+        assert!(matches!(
+            new_snapshot.property_ref(&roughness),
+            Some(PropertyValue::Float(v)) if *v == 0.25
+        ));
+    }
+
+    #[test]
+    fn test_material_instance() {
+        let roughness = ImmutableString::new("roughness");
+        let base = MaterialResource::new(material_with_ranges());
+        let mut instance = Material::from_base(base.clone());
+
+        // Everything is resolved through the base at first.
+        assert!(instance.property_ref(&roughness).is_none());
+        assert!(matches!(
+            instance.resolved_property(&roughness),
+            Some(PropertyValue::Float(v)) if v == 0.5
+        ));
+
+        // Type is checked against the base.
+        assert!(matches!(
+            instance.set_property(&roughness, PropertyValue::Int(1)),
+            Err(MaterialError::TypeMismatch { .. })
+        ));
+        assert!(instance.property_ref(&roughness).is_none());
+
+        instance
+            .set_property(&roughness, PropertyValue::Float(0.25))
+            .unwrap();
+        assert_eq!(float(&instance, &roughness), 0.25);
+        assert_eq!(float(&base.data_ref(), &roughness), 0.5);
+
+        let mut resolved = Vec::new();
+        instance.for_each_resolved_property(|name, _| resolved.push(name.clone()));
+        assert_eq!(
+            resolved.iter().filter(|name| **name == roughness).count(),
+            1
+        );
+
+        assert!(instance.reset_property_override(&roughness).is_some());
+        assert!(matches!(
+            instance.resolved_property(&roughness),
+            Some(PropertyValue::Float(v)) if v == 0.5
+        ));
+    }
+
+    #[test]
+    fn test_cube_texture() {
+        let mut material = Material::standard();
+        let name = ImmutableString::new("diffuseTexture");
+
+        let rectangle = texture(
+            TextureKind::Rectangle {
+                width: 1,
+                height: 1,
+            },
+            1,
+        );
+        assert!(matches!(
+            material.set_cube_texture(&name, Some(rectangle)),
+            Err(MaterialError::IncompatibleTextureKind { .. })
+        ));
+
+        let cube = texture(
+            TextureKind::Cube {
+                width: 1,
+                height: 1,
+            },
+            6,
+        );
+        material
+            .set_cube_texture(&name, Some(cube.clone()))
+            .unwrap();
+        assert_eq!(
+            material.property_ref(&name).unwrap().as_cube_texture(),
+            Some(cube)
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut material = material_with_ranges();
+        assert!(material.validate().is_ok());
+
+        material
+            .properties
+            .insert(ImmutableString::new("roughness"), PropertyValue::Int(1));
+        material
+            .properties
+            .insert(ImmutableString::new("typo"), PropertyValue::Float(1.0));
+
+        let errors = material.validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            MaterialError::TypeMismatch { property_name, .. } if property_name == "roughness"
+        ));
+        assert!(matches!(
+            &errors[1],
+            MaterialError::NoSuchProperty { property_name } if property_name == "typo"
+        ));
+    }
+
+    #[test]
+    fn test_double_property() {
+        let code = r#"
+            (
+                name: "TestShader",
+                properties: [
+                    (
+                        name: "time",
+                        kind: Double(0.25),
+                    ),
+                    (
+                        name: "weights",
+                        kind: DoubleArray([1.0, 2.0]),
+                    ),
+                ],
+                passes: [],
+            )
+            "#;
+        let shader = ShaderResource::from_str(code, ResourceKind::Embedded).unwrap();
+        let mut material = Material::from_shader(shader, None);
+
+        let time = ImmutableString::new("time");
+        let weights = ImmutableString::new("weights");
+        assert_eq!(
+            material.property_ref(&time).unwrap().as_double(),
+            Some(0.25)
+        );
+        assert_eq!(
+            material.property_ref(&weights).unwrap().as_double_array(),
+            Some([1.0, 2.0].as_slice())
+        );
+
+        material
+            .set_property(&time, PropertyValue::Double(1.0e-9))
+            .unwrap();
+        assert_eq!(
+            material.property_ref(&time).unwrap().as_double(),
+            Some(1.0e-9)
+        );
+        assert!(matches!(
+            material.set_property(&time, PropertyValue::Float(1.0)),
+            Err(MaterialError::TypeMismatch { .. })
+        ));
+        assert!(material.validate().is_ok());
+    }
+
+    #[test]
+    fn test_overlay() {
+        let mut material = material_with_ranges();
+        let roughness = ImmutableString::new("roughness");
+        let scale = ImmutableString::new("scale");
+
+        let mut overlay = material_with_ranges();
+        overlay
+            .set_property(&roughness, PropertyValue::Float(0.75))
+            .unwrap();
+        overlay
+            .properties
+            .insert(ImmutableString::new("typo"), PropertyValue::Float(1.0));
+        overlay
+            .properties
+            .insert(scale.clone(), PropertyValue::Int(2));
+
+        let version = material.data_version();
+        let errors = material.overlay(&overlay).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            MaterialError::TypeMismatch { property_name, .. } if property_name == "scale"
+        ));
+        assert!(matches!(
+            &errors[1],
+            MaterialError::NoSuchProperty { property_name } if property_name == "typo"
+        ));
+
+        assert_eq!(float(&material, &roughness), 0.75);
+        assert_eq!(float(&material, &scale), 1.0);
+        assert_eq!(material.data_version(), version + 1);
+    }
+
+    #[test]
+    fn test_deterministic_save() {
+        let material = material_with_ranges();
+        let names = material
+            .properties_sorted()
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["offset", "roughness", "scale"]);
+
+        // A map with different capacity has different iteration order.
+        let mut other = material.clone();
+        let mut properties = FxHashMap::with_capacity_and_hasher(1024, Default::default());
+        properties.extend(
+            material
+                .properties
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone())),
+        );
+        other.properties = properties;
+
+        let save = |mut material: Material| {
+            let mut visitor = Visitor::new();
+            material.visit("Material", &mut visitor).unwrap();
+            visitor.save_text()
+        };
+        assert_eq!(save(material), save(other));
+    }
+
+    #[test]
+    fn test_ensure_defaults_with_pending_shader() {
+        let shader = ShaderResource::new_pending(ResourceKind::Embedded);
+        let name = ImmutableString::new("scale");
+
+        let mut material = Material::from_shader(shader.clone(), None);
+        assert!(material.properties().is_empty());
+        assert!(!material.ensure_defaults(None));
+        assert!(material.properties().is_empty());
+
+        shader.clone().into_untyped().commit_ok(
+            Shader::from_string(
+                r#"
+                (
+                    name: "TestShader",
+                    properties: [
+                        (
+                            name: "scale",
+                            kind: Float(2.0),
+                        ),
+                    ],
+                    passes: [],
+                )
+                "#,
+            )
+            .unwrap(),
+        );
+
+        assert!(material.ensure_defaults(None));
+        assert_eq!(float(&material, &name), 2.0);
+
+        // Existing values are never overwritten.
+        material
+            .set_property(&name, PropertyValue::Float(3.0))
+            .unwrap();
+        assert!(material.ensure_defaults(None));
+        assert_eq!(float(&material, &name), 3.0);
+    }
+
+    #[test]
+    fn test_populate_pending_defaults() {
+        let shader = ShaderResource::new_pending(ResourceKind::Embedded);
+        let name = ImmutableString::new("scale");
+
+        let mut material = Material::from_shader(shader.clone(), None);
+        material.populate_pending_defaults();
+        assert!(material.properties().is_empty());
+
+        shader.clone().into_untyped().commit_ok(
+            Shader::from_string(
+                r#"
+                (
+                    name: "TestShader",
+                    properties: [
+                        (
+                            name: "scale",
+                            kind: Float(2.0),
+                        ),
+                    ],
+                    passes: [],
+                )
+                "#,
+            )
+            .unwrap(),
+        );
+
+        material.populate_pending_defaults();
+        assert_eq!(float(&material, &name), 2.0);
+        assert!(material.pending_defaults.is_none());
+
+        // Existing values are never overwritten.
+        material
+            .set_property(&name, PropertyValue::Float(3.0))
+            .unwrap();
+        material.populate_pending_defaults();
+        assert_eq!(float(&material, &name), 3.0);
+    }
+
+    #[test]
+    fn test_from_shader_with_manager() {
+        let code = r#"
+            (
+                name: "TestShader",
+                properties: [
+                    (
+                        name: "diffuseTexture",
+                        kind: Sampler(default: Some("foo.png"), fallback: White),
+                    ),
+                ],
+                passes: [],
+            )
+            "#;
+        let shader = ShaderResource::from_str(code, ResourceKind::Embedded).unwrap();
+        let name = ImmutableString::new("diffuseTexture");
+
+        let material = Material::from_shader(shader.clone(), None);
+        assert!(material.property_ref(&name).unwrap().as_sampler().is_none());
+
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        let material = Material::from_shader_with_manager(shader, &resource_manager);
+        let texture = material.property_ref(&name).unwrap().as_sampler().unwrap();
+        assert_eq!(texture.kind(), ResourceKind::External("foo.png".into()));
+    }
+
+    #[test]
+    fn test_color_array() {
+        let code = r#"
+            (
+                name: "TestShader",
+                properties: [
+                    (
+                        name: "palette",
+                        kind: ColorArray([(r: 255, g: 255, b: 255, a: 255)]),
+                    ),
+                ],
+                passes: [],
+            )
+            "#;
+        let shader = ShaderResource::from_str(code, ResourceKind::Embedded).unwrap();
+        let mut material = Material::from_shader(shader, None);
+
+        let palette = ImmutableString::new("palette");
+        assert_eq!(
+            material.property_ref(&palette).unwrap().as_color_array(),
+            Some([Color::WHITE].as_slice())
+        );
+
+        material
+            .set_property(
+                &palette,
+                PropertyValue::ColorArray(vec![Color::RED, Color::GREEN]),
+            )
+            .unwrap();
+        assert_eq!(
+            material.property_ref(&palette).unwrap().as_color_array(),
+            Some([Color::RED, Color::GREEN].as_slice())
+        );
+        assert!(matches!(
+            material.set_property(&palette, PropertyValue::Color(Color::RED)),
+            Err(MaterialError::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let a = material_with_ranges();
+        let mut b = material_with_ranges();
+        assert!(a.eq_content(&b));
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        let scale = ImmutableString::new("scale");
+        b.set_property(&scale, PropertyValue::Float(-0.0)).unwrap();
+        let mut c = b.clone();
+        c.set_property(&scale, PropertyValue::Float(0.0)).unwrap();
+        assert!(!a.eq_content(&b));
+        assert!(b.structurally_eq(&c));
+        assert!(!b.eq_content(&c));
+        assert_ne!(b.content_hash(), c.content_hash());
+
+        b.set_property(&scale, PropertyValue::Float(f32::NAN))
+            .unwrap();
+        let c = b.clone();
+        assert!(!b.structurally_eq(&c));
+        assert!(b.eq_content(&c));
+        assert_eq!(b.content_hash(), c.content_hash());
+    }
+
+    #[test]
+    fn test_set_properties() {
+        let mut material = material_with_ranges();
+        let version = material.data_version();
+
+        let errors = material
+            .set_properties([
+                (
+                    ImmutableString::new("roughness"),
+                    PropertyValue::Float(0.25),
+                ),
+                (ImmutableString::new("typo"), PropertyValue::Float(1.0)),
+                (ImmutableString::new("scale"), PropertyValue::Int(2)),
+                (
+                    ImmutableString::new("offset"),
+                    PropertyValue::Vector2(Vector2::new(0.5, 0.5)),
+                ),
+            ])
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            MaterialError::NoSuchProperty { property_name } if property_name == "typo"
+        ));
+        assert!(matches!(
+            &errors[1],
+            MaterialError::TypeMismatch { property_name, .. } if property_name == "scale"
+        ));
+        assert_eq!(float(&material, &ImmutableString::new("roughness")), 0.25);
+        assert_eq!(material.data_version(), version + 1);
+    }
+
+    #[test]
+    fn test_dirty_properties() {
+        let mut material = material_with_ranges();
+        let names = |names: Vec<ImmutableString>| {
+            names
+                .into_iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Everything is dirty after creation.
+        assert_eq!(
+            names(material.take_dirty_properties()),
+            ["offset", "roughness", "scale"]
+        );
+        assert!(material.take_dirty_properties().is_empty());
+
+        material
+            .set_property(&ImmutableString::new("scale"), PropertyValue::Float(2.0))
+            .unwrap();
+        assert!(material
+            .set_property(&ImmutableString::new("roughness"), PropertyValue::Int(2))
+            .is_err());
+        assert_eq!(names(material.take_dirty_properties()), ["scale"]);
+        assert!(material.take_dirty_properties().is_empty());
+    }
+
+    #[test]
+    fn test_set_shader() {
+        let mut material = material_with_ranges();
+        material
+            .set_property(&ImmutableString::new("scale"), PropertyValue::Float(2.0))
+            .unwrap();
+
+        let code = r#"
+            (
+                name: "NewShader",
+                properties: [
+                    (
+                        name: "scale",
+                        kind: Float(1.0),
+                    ),
+                    (
+                        name: "roughness",
+                        kind: Int(1),
+                    ),
+                    (
+                        name: "metallic",
+                        kind: Float(0.0),
+                    ),
+                ],
+                passes: [],
+            )
+            "#;
+        let shader = ShaderResource::from_str(code, ResourceKind::Embedded).unwrap();
+
+        let dropped = material.set_shader(shader.clone());
+        assert_eq!(dropped, ["offset", "roughness"].map(ImmutableString::new));
+        assert_eq!(material.shader(), &shader);
+        assert_eq!(float(&material, &ImmutableString::new("scale")), 2.0);
+        assert_eq!(float(&material, &ImmutableString::new("metallic")), 0.0);
+        assert!(matches!(
+            material.property_ref(&ImmutableString::new("roughness")),
+            Some(PropertyValue::Int(1))
+        ));
+        assert_eq!(material.properties().len(), 3);
+    }
+
+    #[test]
+    fn test_deep_copy_keep_shader() {
+        let original = MaterialResource::new(material_with_ranges());
+        let copy = original.deep_copy_keep_shader();
+        assert_ne!(original, copy);
+        assert!(copy.kind().is_embedded());
+        assert_eq!(original.data_ref().shader(), copy.data_ref().shader());
+
+        let scale = ImmutableString::new("scale");
+        copy.data_ref()
+            .set_property(&scale, PropertyValue::Float(3.0))
+            .unwrap();
+        assert_eq!(float(&copy.data_ref(), &scale), 3.0);
+        assert_eq!(float(&original.data_ref(), &scale), 1.0);
+    }
+
+    #[test]
+    fn test_texture_fallback() {
+        let code = r#"
+            (
+                name: "TestShader",
+                properties: [
+                    (
+                        name: "maskTexture",
+                        kind: Sampler(default: None, fallback: White),
+                    ),
+                    (
+                        name: "scale",
+                        kind: Float(1.0),
+                    ),
+                ],
+                passes: [],
+            )
+            "#;
+        let shader = ShaderResource::from_str(code, ResourceKind::Embedded).unwrap();
+        let mut a = Material::from_shader(shader.clone(), None);
+        let b = Material::from_shader(shader, None);
+
+        let mask = ImmutableString::new("maskTexture");
+        let texture = texture(
+            TextureKind::Rectangle {
+                width: 1,
+                height: 1,
+            },
+            1,
+        );
+        a.set_texture(&mask, Some(texture.clone())).unwrap();
+
+        assert_eq!(
+            a.set_texture_fallback(&mask, SamplerFallback::Black)
+                .unwrap(),
+            SamplerFallback::White
+        );
+        assert_eq!(a.texture_fallback(&mask), Some(SamplerFallback::Black));
+        assert_eq!(b.texture_fallback(&mask), Some(SamplerFallback::White));
+        assert_eq!(a.property_ref(&mask).unwrap().as_sampler(), Some(texture));
+
+        assert!(matches!(
+            a.set_texture_fallback(&ImmutableString::new("scale"), SamplerFallback::Black),
+            Err(MaterialError::TypeMismatch { .. })
+        ));
+        assert_eq!(a.texture_fallback(&ImmutableString::new("scale")), None);
+    }
+
+    #[test]
+    fn test_texture_priority_is_stored_in_binding() {
+        let code = r#"
+            (
+                name: "TestShader",
+                properties: [
+                    (
+                        name: "diffuseTexture",
+                        kind: Sampler(default: None, fallback: White),
+                    ),
+                    (
+                        name: "detailTexture",
+                        kind: Sampler(default: None, fallback: White),
+                    ),
+                    (
+                        name: "scale",
+                        kind: Float(1.0),
+                    ),
+                ],
+                passes: [],
+            )
+            "#;
+        let shader = ShaderResource::from_str(code, ResourceKind::Embedded).unwrap();
+        let mut material = Material::from_shader(shader, None);
+
+        let diffuse = ImmutableString::new("diffuseTexture");
+        let detail = ImmutableString::new("detailTexture");
+        let make_texture = || {
+            texture(
+                TextureKind::Rectangle {
+                    width: 1,
+                    height: 1,
+                },
+                1,
+            )
+        };
+        let diffuse_texture = make_texture();
+        let detail_texture = make_texture();
+        material
+            .set_texture(&diffuse, Some(diffuse_texture.clone()))
+            .unwrap();
+        material
+            .set_texture(&detail, Some(detail_texture.clone()))
+            .unwrap();
+
+        assert_eq!(
+            material
+                .set_texture_priority(&detail, TextureLoadPriority::Low)
+                .unwrap(),
+            TextureLoadPriority::Normal
+        );
+        material
+            .set_texture_priority(&diffuse, TextureLoadPriority::High)
+            .unwrap();
+        assert!(matches!(
+            material.property_ref(&detail),
+            Some(PropertyValue::Sampler {
+                priority: TextureLoadPriority::Low,
+                ..
+            })
+        ));
+
+        // Changing the texture keeps the priority of the binding.
+        let new_detail_texture = make_texture();
+        material
+            .set_texture(&detail, Some(new_detail_texture.clone()))
+            .unwrap();
+        assert_eq!(material.texture_priority(&detail), TextureLoadPriority::Low);
+
+        let requests = material.texture_load_requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0], (diffuse_texture, TextureLoadPriority::High));
+        assert_eq!(requests[1], (new_detail_texture, TextureLoadPriority::Low));
+
+        // Instances inherit the priority and override the binding when it changes.
+        let base = MaterialResource::new(material);
+        let mut instance = Material::from_base(base.clone());
+        assert_eq!(instance.texture_priority(&detail), TextureLoadPriority::Low);
+        assert!(instance.property_ref(&detail).is_none());
+        instance
+            .set_texture_priority(&detail, TextureLoadPriority::High)
+            .unwrap();
+        assert_eq!(
+            instance.texture_priority(&detail),
+            TextureLoadPriority::High
+        );
+        assert_eq!(
+            base.data_ref().texture_priority(&detail),
+            TextureLoadPriority::Low
+        );
+
+        assert!(matches!(
+            instance.set_texture_priority(&ImmutableString::new("scale"), TextureLoadPriority::Low),
+            Err(MaterialError::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_standard_material_tint() {
+        let tint = ImmutableString::new("tintColor");
+        let material = MaterialResource::new(Material::standard());
+        let instance = Material::from_base(material.clone());
+        assert!(matches!(
+            material.data_ref().property_ref(&tint),
+            Some(PropertyValue::Color(color)) if *color == Color::WHITE
+        ));
+
+        // Instances of the material get the tint too.
+        material.data_ref().set_tint(Color::RED).unwrap();
+        assert!(matches!(
+            instance.resolved_property(&tint),
+            Some(PropertyValue::Color(color)) if color == Color::RED
+        ));
+    }
+}
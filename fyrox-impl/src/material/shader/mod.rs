@@ -16,7 +16,15 @@
 //!             name: "diffuseTexture",
 //!
 //!             // Value has limited set of possible variants.
-//!             value: Sampler(default: None, fallback: White)
+//!             value: Sampler(default: None, fallback: White),
+//!
+//!             // Optional requirement for the format of a texture, makes sense only for samplers.
+//!             // See `TextureFormatRequirement` docs for more info.
+//!             texture_format: MinChannels(3),
+//!
+//!             // Optional hints for the editor.
+//!             description: "Diffuse (albedo) texture of the surface.",
+//!             range: None,
 //!         )
 //!     ],
 //!
@@ -111,6 +119,10 @@
 //!                 "#;
 //!         )
 //!     ],
+//!
+//!     // Optional default render queue of the materials that use the shader. Either Opaque (default),
+//!     // AlphaTest or Transparent. See `RenderQueue` docs for more info.
+//!     render_queue: Opaque,
 //! )
 //! ```
 //!
@@ -232,6 +244,7 @@ use crate::{
     asset::{io::ResourceIo, untyped::ResourceKind, Resource, ResourceData, SHADER_RESOURCE_UUID},
     core::{
         algebra::{Matrix2, Matrix3, Matrix4, Vector2, Vector3, Vector4},
+        color::Color,
         io::FileLoadError,
         reflect::prelude::*,
         sparse::AtomicIndex,
@@ -241,6 +254,7 @@ use crate::{
     },
     lazy_static::lazy_static,
     renderer::framework::framebuffer::DrawParameters,
+    resource::texture::TexturePixelKind,
 };
 use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
@@ -251,6 +265,7 @@ use std::{
     fmt::{Display, Formatter},
     fs::File,
     io::{Cursor, Write},
+    ops::Range,
     path::{Path, PathBuf},
 };
 
@@ -293,6 +308,12 @@ pub const STANDARD_SPRITE_SHADER_NAME: &str = "StandardSprite";
 /// A source code of the standard terrain shader.
 pub const STANDARD_TERRAIN_SHADER_SRC: &str = include_str!("standard/terrain.shader");
 
+/// A name of the error shader.
+pub const ERROR_SHADER_NAME: &str = "Error";
+
+/// A source code of the error shader.
+pub const ERROR_SHADER_SRC: &str = include_str!("standard/error.shader");
+
 /// A list of names of standard shaders.
 pub const STANDARD_SHADER_NAMES: [&str; 6] = [
     STANDARD_SHADER_NAME,
@@ -351,7 +372,7 @@ impl TypeUuidProvider for Shader {
 ///
 /// Fallback value is also helpful to catch missing textures, you'll definitely know the texture is
 /// missing by very specific value in the fallback texture.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Visit, Eq, Reflect)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Visit, Eq, Hash, Reflect)]
 pub enum SamplerFallback {
     /// A 1x1px white texture.
     White,
@@ -368,7 +389,7 @@ impl Default for SamplerFallback {
 }
 
 /// Shader property with default value.
-#[derive(Serialize, Deserialize, Debug, PartialEq, Reflect, Visit)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Reflect, Visit)]
 pub enum PropertyKind {
     /// Real number.
     Float(f32),
@@ -457,6 +478,15 @@ pub enum PropertyKind {
         /// Default fallback value. See [`SamplerFallback`] for more info.
         fallback: SamplerFallback,
     },
+
+    /// Double-precision real number.
+    Double(f64),
+
+    /// Double-precision real number array.
+    DoubleArray(Vec<f64>),
+
+    /// An array of sRGB colors. See [`Self::Color`] for more info about conversion.
+    ColorArray(Vec<Color>),
 }
 
 impl Default for PropertyKind {
@@ -465,13 +495,61 @@ impl Default for PropertyKind {
     }
 }
 
+/// A requirement for the format of a texture bound to a sampler. It is used to catch bugs, when a texture of
+/// incompatible format is bound to a sampler (for example, a single-channel texture as a normal map). See
+/// [`crate::material::Material::check_texture_formats`] for more info.
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Reflect, Visit)]
+pub enum TextureFormatRequirement {
+    /// Any texture format is allowed.
+    #[default]
+    Any,
+    /// A texture must have at least the given amount of color channels.
+    MinChannels(u8),
+    /// A texture must have exactly the given amount of color channels.
+    Channels(u8),
+    /// A texture must store floating-point values (for example, height maps or other data textures).
+    FloatingPoint,
+}
+
+impl TextureFormatRequirement {
+    /// Checks if the given pixel kind satisfies the requirement.
+    pub fn is_satisfied_by(&self, pixel_kind: TexturePixelKind) -> bool {
+        match *self {
+            TextureFormatRequirement::Any => true,
+            TextureFormatRequirement::MinChannels(count) => {
+                pixel_kind.channel_count() >= count as usize
+            }
+            TextureFormatRequirement::Channels(count) => {
+                pixel_kind.channel_count() == count as usize
+            }
+            TextureFormatRequirement::FloatingPoint => pixel_kind.is_floating_point(),
+        }
+    }
+}
+
 /// Shader property definition.
-#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Reflect, Visit)]
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, Reflect, Visit)]
 pub struct PropertyDefinition {
     /// A name of the property.
     pub name: String,
     /// A kind of property with default value.
     pub kind: PropertyKind,
+    /// A requirement for the format of a texture bound to the property. It makes sense only for samplers
+    /// and it is optional in shader source: `texture_format: MinChannels(2)`.
+    #[serde(default)]
+    #[visit(optional)]
+    pub texture_format: TextureFormatRequirement,
+    /// Optional human-readable description of the property, it is used as a hint in the editor. It is
+    /// optional in shader source: `description: "Defines how rough the surface is."`.
+    #[serde(default)]
+    #[visit(optional)]
+    pub description: String,
+    /// Optional range of values of the property, it is used as a hint in the editor and it is enforced by
+    /// [`crate::material::Material::set_property_checked`]. It makes sense only for numeric properties. It is
+    /// optional in shader source: `range: Some((start: 0.0, end: 1.0))`.
+    #[serde(default)]
+    #[visit(optional)]
+    pub range: Option<Range<f32>>,
 }
 
 /// A render pass definition. See [`ShaderResource`] docs for more info about render passes.
@@ -487,6 +565,40 @@ pub struct RenderPassDefinition {
     pub fragment_shader: String,
 }
 
+/// Render queue defines the order in which the renderer draws surfaces within a render pass. Surfaces of a queue
+/// are drawn only after all surfaces of the previous queues were drawn: [`Self::Opaque`] first, then
+/// [`Self::AlphaTest`] and [`Self::Transparent`] last. Within a queue, surfaces are ordered by the sort bias of their
+/// materials (lower first, see [`crate::material::Material::set_sort_bias`]) and then by the sort index provided by
+/// scene nodes (usually, back-to-front distance to the camera).
+///
+/// Every shader defines the default queue for its materials, a material could override it (see
+/// [`crate::material::Material::set_render_queue`]).
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Visit,
+    Reflect,
+)]
+#[repr(u32)]
+pub enum RenderQueue {
+    /// Queue for fully opaque surfaces. This is the default queue.
+    #[default]
+    Opaque = 0,
+    /// Queue for surfaces that discard some of their fragments (foliage, fences, decals, etc.).
+    AlphaTest = 1,
+    /// Queue for translucent surfaces (particles, glass, etc.).
+    Transparent = 2,
+}
+
 /// A definition of the shader.
 #[derive(Default, Serialize, Deserialize, Debug, PartialEq, Reflect, Visit)]
 pub struct ShaderDefinition {
@@ -496,9 +608,96 @@ pub struct ShaderDefinition {
     pub passes: Vec<RenderPassDefinition>,
     /// A set of property definitions.
     pub properties: Vec<PropertyDefinition>,
+    /// Default render queue of the materials that use the shader. See [`RenderQueue`] docs for more info.
+    #[serde(default)]
+    #[visit(optional)]
+    pub render_queue: RenderQueue,
+}
+
+/// Description of a single non-sampler property of a shader packed into a uniform block. See
+/// [`ShaderDefinition::uniform_layout`] for more info.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformField {
+    /// A name of the property.
+    pub name: String,
+    /// Offset of the property (in bytes) from the beginning of the uniform block.
+    pub offset: usize,
+    /// Size of the property (in bytes), including internal padding of arrays and matrices.
+    pub size: usize,
+    /// Base alignment (in bytes) of the property.
+    pub alignment: usize,
+}
+
+fn align_up(offset: usize, alignment: usize) -> usize {
+    (offset + alignment - 1) / alignment * alignment
+}
+
+impl PropertyKind {
+    // Returns (size, alignment) of the property according to std140 rules, `None` for samplers.
+    fn std140_size_and_alignment(&self) -> Option<(usize, usize)> {
+        // Array elements and matrix columns are padded to the size of vec4 in std140.
+        fn array(element_size: usize, count: usize) -> (usize, usize) {
+            let stride = align_up(element_size, 16);
+            (stride * count, 16)
+        }
+
+        Some(match self {
+            PropertyKind::Float(_)
+            | PropertyKind::Int(_)
+            | PropertyKind::UInt(_)
+            | PropertyKind::Bool(_) => (4, 4),
+            PropertyKind::Vector2(_) => (8, 8),
+            PropertyKind::Vector3(_) => (12, 16),
+            PropertyKind::Vector4(_) | PropertyKind::Color { .. } => (16, 16),
+            PropertyKind::Matrix2(_) => array(8, 2),
+            PropertyKind::Matrix3(_) => array(12, 3),
+            PropertyKind::Matrix4(_) => array(16, 4),
+            PropertyKind::FloatArray(v) => array(4, v.len()),
+            PropertyKind::IntArray(v) => array(4, v.len()),
+            PropertyKind::UIntArray(v) => array(4, v.len()),
+            PropertyKind::Vector2Array(v) => array(8, v.len()),
+            PropertyKind::Vector3Array(v) => array(12, v.len()),
+            PropertyKind::Vector4Array(v) => array(16, v.len()),
+            PropertyKind::Matrix2Array(v) => array(32, v.len()),
+            PropertyKind::Matrix3Array(v) => array(48, v.len()),
+            PropertyKind::Matrix4Array(v) => array(64, v.len()),
+            PropertyKind::Double(_) => (8, 8),
+            PropertyKind::DoubleArray(v) => array(8, v.len()),
+            PropertyKind::ColorArray(v) => array(16, v.len()),
+            PropertyKind::Sampler { .. } => return None,
+        })
+    }
 }
 
 impl ShaderDefinition {
+    /// Returns a layout of all non-sampler properties of the shader packed into a uniform block in the
+    /// order of declaration. The layout follows [std140](https://registry.khronos.org/OpenGL/specs/gl/glspec45.core.pdf#page=159)
+    /// rules: scalars are aligned to 4 bytes (doubles - to 8 bytes), `vec2` - to 8 bytes, `vec3` and `vec4` - to 16 bytes; each
+    /// element of an array and each column of a matrix is padded to 16 bytes. Colors are packed as `vec4`,
+    /// samplers are opaque and are not included in the layout. Array sizes are taken from the default
+    /// values of the properties.
+    ///
+    /// Keep in mind that the built-in renderer uploads material properties as separate uniforms, this
+    /// method is intended for custom render backends that want to pack material properties into a uniform
+    /// buffer and for debugging purposes.
+    pub fn uniform_layout(&self) -> Vec<UniformField> {
+        let mut offset = 0;
+        self.properties
+            .iter()
+            .filter_map(|property| {
+                let (size, alignment) = property.kind.std140_size_and_alignment()?;
+                let field = UniformField {
+                    name: property.name.clone(),
+                    offset: align_up(offset, alignment),
+                    size,
+                    alignment,
+                };
+                offset = field.offset + size;
+                Some(field)
+            })
+            .collect()
+    }
+
     fn from_buf(buf: Vec<u8>) -> Result<Self, ShaderError> {
         Ok(ron::de::from_reader(Cursor::new(buf))?)
     }
@@ -618,6 +817,11 @@ pub trait ShaderResourceExtension: Sized {
     /// Returns an instance of standard two-sides terrain shader.
     fn standard_twosides() -> Self;
 
+    /// Returns an instance of error shader. It renders everything in bright magenta color and it is
+    /// used as the default fallback for materials with broken shaders (see
+    /// [`crate::material::Material::set_fallback_shader`]).
+    fn error() -> Self;
+
     /// Returns a list of standard shader.
     fn standard_shaders() -> Vec<ShaderResource>;
 }
@@ -651,6 +855,10 @@ impl ShaderResourceExtension for ShaderResource {
         STANDARD_TWOSIDES.clone()
     }
 
+    fn error() -> Self {
+        ERROR.clone()
+    }
+
     fn standard_shaders() -> Vec<ShaderResource> {
         vec![
             Self::standard(),
@@ -659,6 +867,7 @@ impl ShaderResourceExtension for ShaderResource {
             Self::standard_sprite(),
             Self::standard_terrain(),
             Self::standard_twosides(),
+            Self::error(),
         ]
     }
 }
@@ -705,11 +914,21 @@ lazy_static! {
     );
 }
 
+lazy_static! {
+    static ref ERROR: ShaderResource = ShaderResource::new_ok(
+        ERROR_SHADER_NAME.into(),
+        Shader::from_string(ERROR_SHADER_SRC).unwrap(),
+    );
+}
+
 #[cfg(test)]
 mod test {
-    use crate::material::shader::{
-        PropertyDefinition, PropertyKind, RenderPassDefinition, SamplerFallback, ShaderDefinition,
-        ShaderResource, ShaderResourceExtension,
+    use crate::{
+        core::algebra::{Matrix3, Vector3},
+        material::shader::{
+            PropertyDefinition, PropertyKind, RenderPassDefinition, SamplerFallback,
+            ShaderDefinition, ShaderResource, ShaderResourceExtension, UniformField,
+        },
     };
 
     #[test]
@@ -765,6 +984,9 @@ mod test {
                     default: None,
                     fallback: SamplerFallback::White,
                 },
+                texture_format: Default::default(),
+                description: Default::default(),
+                range: None,
             }],
             passes: vec![RenderPassDefinition {
                 name: "GBuffer".to_string(),
@@ -772,8 +994,66 @@ mod test {
                 vertex_shader: "<CODE>".to_string(),
                 fragment_shader: "<CODE>".to_string(),
             }],
+            render_queue: Default::default(),
         };
 
         assert_eq!(data.definition, reference_definition);
     }
+
+    #[test]
+    fn test_uniform_layout() {
+        let property = |name: &str, kind| PropertyDefinition {
+            name: name.to_string(),
+            kind,
+            texture_format: Default::default(),
+            description: Default::default(),
+            range: None,
+        };
+
+        let definition = ShaderDefinition {
+            properties: vec![
+                property("a", PropertyKind::Float(0.0)),
+                property("b", PropertyKind::Vector3(Vector3::default())),
+                property(
+                    "texture",
+                    PropertyKind::Sampler {
+                        default: None,
+                        fallback: SamplerFallback::White,
+                    },
+                ),
+                property("c", PropertyKind::Float(0.0)),
+                property("d", PropertyKind::FloatArray(vec![0.0; 3])),
+                property("e", PropertyKind::Matrix3(Matrix3::identity())),
+                property(
+                    "f",
+                    PropertyKind::Color {
+                        r: 0,
+                        g: 0,
+                        b: 0,
+                        a: 0,
+                    },
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let field = |name: &str, offset, size, alignment| UniformField {
+            name: name.to_string(),
+            offset,
+            size,
+            alignment,
+        };
+
+        assert_eq!(
+            definition.uniform_layout(),
+            vec![
+                field("a", 0, 4, 4),
+                field("b", 16, 12, 16),
+                field("c", 28, 4, 4),
+                field("d", 32, 48, 16),
+                field("e", 80, 48, 16),
+                field("f", 128, 16, 16),
+            ]
+        );
+    }
 }
@@ -51,6 +51,10 @@
             name: "diffuseColor",
             kind: Color(r: 255, g: 255, b: 255, a: 255),
         ),
+        (
+            name: "tintColor",
+            kind: Color(r: 255, g: 255, b: 255, a: 255),
+        ),
         (
             name: "parallaxCenter",
             kind: Float(0.0),
@@ -59,6 +63,22 @@
             name: "parallaxScale",
             kind: Float(0.08),
         ),
+        (
+            name: "useVertexColors",
+            kind: Bool(false),
+        ),
+        (
+            name: "metallicFactor",
+            kind: Float(1.0),
+        ),
+        (
+            name: "roughnessFactor",
+            kind: Float(1.0),
+        ),
+        (
+            name: "packedMetallicRoughness",
+            kind: Bool(false),
+        ),
     ],
 
     passes: [
@@ -92,6 +112,7 @@
                 layout(location = 4) in vec4 boneWeights;
                 layout(location = 5) in vec4 boneIndices;
                 layout(location = 6) in vec2 vertexSecondTexCoord;
+                layout(location = 7) in vec4 vertexColor;
 
                 // Define uniforms with reserved names. Fyrox will automatically provide
                 // required data to these uniforms.
@@ -106,6 +127,7 @@
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out vec4 color;
 
                 void main()
                 {
@@ -156,6 +178,7 @@
                     texCoord = vertexTexCoord;
                     position = vec3(fyrox_worldMatrix * localPosition);
                     secondTexCoord = vertexSecondTexCoord;
+                    color = vertexColor;
 
                     gl_Position = fyrox_worldViewProjection * localPosition;
                 }
@@ -181,8 +204,13 @@
                 uniform uint layerIndex;
                 uniform vec3 emissionStrength;
                 uniform vec4 diffuseColor;
+                uniform vec4 tintColor;
                 uniform float parallaxCenter;
                 uniform float parallaxScale;
+                uniform bool useVertexColors;
+                uniform float metallicFactor;
+                uniform float roughnessFactor;
+                uniform bool packedMetallicRoughness;
 
                 // Define uniforms with reserved names. Fyrox will automatically provide
                 // required data to these uniforms.
@@ -195,6 +223,7 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in vec4 color;
 
                 void main()
                 {
@@ -215,7 +244,10 @@
                         tc = texCoord * texCoordScale;
                     }
 
-                    outColor = diffuseColor * texture(diffuseTexture, tc);
+                    outColor = tintColor * diffuseColor * texture(diffuseTexture, tc);
+                    if (useVertexColors) {
+                        outColor *= color;
+                    }
 
                     // Alpha test.
                     if (outColor.a < 0.5) {
@@ -226,8 +258,15 @@
                     vec4 n = normalize(texture(normalTexture, tc) * 2.0 - 1.0);
                     outNormal = vec4(normalize(tangentSpace * n.xyz) * 0.5 + 0.5, 1.0);
 
-                    outMaterial.x = texture(metallicTexture, tc).r;
-                    outMaterial.y = texture(roughnessTexture, tc).r;
+                    if (packedMetallicRoughness) {
+                        // glTF convention: roughness is stored in green channel, metallic - in blue.
+                        // Factors are used only in this mode, non-packed textures are used as is.
+                        outMaterial.x = texture(metallicTexture, tc).b * metallicFactor;
+                        outMaterial.y = texture(roughnessTexture, tc).g * roughnessFactor;
+                    } else {
+                        outMaterial.x = texture(metallicTexture, tc).r;
+                        outMaterial.y = texture(roughnessTexture, tc).r;
+                    }
                     outMaterial.z = texture(aoTexture, tc).r;
                     outMaterial.a = 1.0;
 
@@ -283,6 +322,7 @@
 
                 out vec3 position;
                 out vec2 texCoord;
+                out vec4 color;
 
                 void main()
                 {
@@ -312,6 +352,7 @@
                     }
                     gl_Position = fyrox_worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
+                    color = vertexColor;
                 }
                "#,
 
@@ -319,14 +360,20 @@
                r#"
                 uniform sampler2D diffuseTexture;
                 uniform vec4 diffuseColor;
+                uniform vec4 tintColor;
+                uniform bool useVertexColors;
 
                 out vec4 FragColor;
 
                 in vec2 texCoord;
+                in vec4 color;
 
                 void main()
                 {
-                    FragColor = diffuseColor * texture(diffuseTexture, texCoord);
+                    FragColor = tintColor * diffuseColor * texture(diffuseTexture, texCoord);
+                    if (useVertexColors) {
+                        FragColor *= color;
+                    }
                 }
                "#,
         ),
@@ -359,6 +406,7 @@
                 layout(location = 1) in vec2 vertexTexCoord;
                 layout(location = 4) in vec4 boneWeights;
                 layout(location = 5) in vec4 boneIndices;
+                layout(location = 7) in vec4 vertexColor;
 
                 uniform mat4 fyrox_worldViewProjection;
                 uniform bool fyrox_useSkeletalAnimation;
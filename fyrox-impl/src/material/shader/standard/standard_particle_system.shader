@@ -111,4 +111,6 @@
                "#,
         )
     ],
-)
\ No newline at end of file
+
+    render_queue: Transparent,
+)
@@ -43,6 +43,10 @@
             name: "heightMapTexture",
             kind: Sampler(default: None, fallback: White),
         ),
+        (
+            name: "holeMaskTexture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
         (
             name: "nodeUvOffsets",
             kind: Vector4((0.0, 0.0, 0.0, 0.0)),
@@ -51,10 +55,26 @@
             name: "texCoordScale",
             kind: Vector2((1.0, 1.0)),
         ),
+        (
+            name: "texCoordOffset",
+            kind: Vector2((0.0, 0.0)),
+        ),
+        (
+            name: "texCoordRotation",
+            kind: Float(0.0),
+        ),
         (
             name: "layerIndex",
             kind: UInt(0),
         ),
+        (
+            name: "layerBlendMode",
+            kind: UInt(0),
+        ),
+        (
+            name: "layerHeightRange",
+            kind: Vector2((0.0, 1.0)),
+        ),
         (
             name: "emissionStrength",
             kind: Vector3((2.0, 2.0, 2.0)),
@@ -87,9 +107,10 @@
                 depth_write: true,
                 stencil_test: None,
                 depth_test: true,
+                // Colors are premultiplied by the mask in the fragment shader, this allows additive layers.
                 blend: Some(BlendParameters(
                     func: BlendFunc(
-                        sfactor: SrcAlpha,
+                        sfactor: One,
                         dfactor: OneMinusSrcAlpha,
                         alpha_sfactor: SrcAlpha,
                         alpha_dfactor: OneMinusSrcAlpha,
@@ -129,6 +150,7 @@
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out float localHeight;
 
                 void main()
                 {
@@ -145,6 +167,7 @@
                     texCoord = actualTexCoords;
                     position = vec3(fyrox_worldMatrix * finalVertexPosition);
                     secondTexCoord = vertexSecondTexCoord;
+                    localHeight = height;
                     gl_Position = fyrox_worldViewProjection * finalVertexPosition;
                 }
                 "#,
@@ -166,9 +189,14 @@
                 uniform sampler2D lightmapTexture;
                 uniform sampler2D aoTexture;
                 uniform vec2 texCoordScale;
+                uniform vec2 texCoordOffset;
+                uniform float texCoordRotation;
                 uniform uint layerIndex;
+                uniform uint layerBlendMode;
+                uniform vec2 layerHeightRange;
                 uniform vec3 emissionStrength;
                 uniform sampler2D maskTexture;
+                uniform sampler2D holeMaskTexture;
                 uniform vec4 diffuseColor;
                 uniform float parallaxCenter;
                 uniform float parallaxScale;
@@ -184,24 +212,32 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in float localHeight;
 
                 void main()
                 {
+                    if (texture(holeMaskTexture, texCoord).r > 0.5) discard;
+
                     mat3 tangentSpace = mat3(tangent, binormal, normal);
                     vec3 toFragment = normalize(position - fyrox_cameraPosition);
 
+                    // Scale, then rotate, then offset - see Layer::transform_uv.
+                    float rotationSin = sin(texCoordRotation);
+                    float rotationCos = cos(texCoordRotation);
+                    vec2 layerTexCoord = mat2(rotationCos, rotationSin, -rotationSin, rotationCos) * (texCoord * texCoordScale) + texCoordOffset;
+
                     vec2 tc;
                     if (fyrox_usePOM) {
                         vec3 toFragmentTangentSpace = normalize(transpose(tangentSpace) * toFragment);
                         tc = S_ComputeParallaxTextureCoordinates(
                             heightTexture,
                             toFragmentTangentSpace,
-                            texCoord * texCoordScale,
+                            layerTexCoord,
                             parallaxCenter,
                             parallaxScale
                         );
                     } else {
-                        tc = texCoord * texCoordScale;
+                        tc = layerTexCoord;
                     }
 
                     outColor = diffuseColor * texture(diffuseTexture, tc);
@@ -221,10 +257,27 @@
 
                     float mask = texture(maskTexture, texCoord).r;
 
-                    outColor.a = mask;
-                    outAmbient.a = mask;
-                    outNormal.a = mask;
-                    outMaterial.a = mask;
+                    // Height-based - see LayerBlendMode::height_weight.
+                    if (layerBlendMode == 2u) {
+                        float range = layerHeightRange.y - layerHeightRange.x;
+                        float t = range == 0.0
+                            ? step(layerHeightRange.y, localHeight)
+                            : clamp((localHeight - layerHeightRange.x) / range, 0.0, 1.0);
+                        mask *= t * t * (3.0 - 2.0 * t);
+                    }
+
+                    if (layerBlendMode == 1u) {
+                        // Additive - add the color and keep everything else as is.
+                        outColor = vec4(outColor.rgb * mask, 0.0);
+                        outNormal = vec4(0.0);
+                        outAmbient = vec4(0.0);
+                        outMaterial = vec4(0.0);
+                    } else {
+                        outColor = vec4(outColor.rgb * mask, mask);
+                        outAmbient = vec4(outAmbient.rgb * mask, mask);
+                        outNormal = vec4(outNormal.rgb * mask, mask);
+                        outMaterial = vec4(outMaterial.rgb * mask, mask);
+                    }
                 }
                 "#,
         ),
@@ -288,7 +341,11 @@
            fragment_shader:
                r#"
                 uniform sampler2D diffuseTexture;
+                uniform sampler2D holeMaskTexture;
                 uniform vec4 diffuseColor;
+                uniform vec2 texCoordScale;
+                uniform vec2 texCoordOffset;
+                uniform float texCoordRotation;
 
                 out vec4 FragColor;
 
@@ -296,7 +353,13 @@
 
                 void main()
                 {
-                    FragColor = diffuseColor * texture(diffuseTexture, texCoord);
+                    if (texture(holeMaskTexture, texCoord).r > 0.5) discard;
+
+                    float rotationSin = sin(texCoordRotation);
+                    float rotationCos = cos(texCoordRotation);
+                    vec2 layerTexCoord = mat2(rotationCos, rotationSin, -rotationSin, rotationCos) * (texCoord * texCoordScale) + texCoordOffset;
+
+                    FragColor = diffuseColor * texture(diffuseTexture, layerTexCoord);
                 }
                "#,
         ),
@@ -350,12 +413,14 @@
             fragment_shader:
                 r#"
                 uniform sampler2D diffuseTexture;
+                uniform sampler2D holeMaskTexture;
 
                 in vec2 texCoord;
 
                 void main()
                 {
                     if (texture(diffuseTexture, texCoord).a < 0.2) discard;
+                    if (texture(holeMaskTexture, texCoord).r > 0.5) discard;
                 }
                 "#,
         ),
@@ -409,12 +474,14 @@
             fragment_shader:
                 r#"
                 uniform sampler2D diffuseTexture;
+                uniform sampler2D holeMaskTexture;
 
                 in vec2 texCoord;
 
                 void main()
                 {
                     if (texture(diffuseTexture, texCoord).a < 0.2) discard;
+                    if (texture(holeMaskTexture, texCoord).r > 0.5) discard;
                 }
                 "#,
         ),
@@ -471,6 +538,7 @@
             fragment_shader:
                 r#"
                 uniform sampler2D diffuseTexture;
+                uniform sampler2D holeMaskTexture;
 
                 uniform vec3 fyrox_lightPosition;
 
@@ -482,6 +550,7 @@
                 void main()
                 {
                     if (texture(diffuseTexture, texCoord).a < 0.2) discard;
+                    if (texture(holeMaskTexture, texCoord).r > 0.5) discard;
                     depth = length(fyrox_lightPosition - worldPosition);
                 }
                 "#,
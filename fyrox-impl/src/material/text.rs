@@ -0,0 +1,309 @@
+//! Text (RON) representation of materials. Unlike the binary format, it is human-readable, so materials could
+//! be edited by hand and reviewed in version control systems.
+//!
+//! # Format
+//!
+//! A material is stored as a RON struct with the following fields:
+//!
+//! ```ron
+//! (
+//!     shader: "path/to/shader.shader",
+//!     properties: {
+//!         "diffuseColor": Color(r: 255, g: 255, b: 255, a: 255),
+//!         "diffuseTexture": Sampler(default: Some("path/to/texture.png"), fallback: White),
+//!         "texCoordScale": Vector2((1.0, 1.0)),
+//!     },
+//!     texture_swizzles: {
+//!         "metallicTexture": "bbbb",
+//!     },
+//! )
+//! ```
+//!
+//! Property values have exactly the same format as default values of properties in shader definitions (see
+//! [`PropertyKind`]), samplers store a path to their texture in the `default` field. The properties are sorted
+//! by their names, so saving the same material always produces the same output. All the other fields are
+//! optional: `base` (a path to the base material, see [`Material::from_base`]), `fallback_shader`,
+//! `texture_priorities`, `texture_swizzles`, `render_queue`, `sort_bias`, `two_sided` and `defines`.
+//!
+//! # Limitations
+//!
+//! Resources are stored as paths, so embedded (procedural) resources cannot be saved in this format. The shader
+//! and the base material must be external resources, otherwise saving fails. Embedded textures are saved as
+//! samplers without a texture (the fallback value is used).
+
+use crate::{
+    asset::{io::ResourceIo, manager::ResourceManager},
+    core::sstorage::ImmutableString,
+    material::{
+        shader::{PropertyKind, RenderQueue, Shader},
+        Material, MaterialError, PropertyValue, ShaderDefineValue, TextureLoadPriority,
+        TextureSwizzle,
+    },
+};
+use fxhash::FxHashMap;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[derive(Serialize, Deserialize)]
+struct MaterialRon {
+    shader: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fallback_shader: Option<PathBuf>,
+    properties: BTreeMap<String, PropertyKind>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    texture_priorities: BTreeMap<String, TextureLoadPriority>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    texture_swizzles: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    render_queue: Option<RenderQueue>,
+    #[serde(default)]
+    sort_bias: i32,
+    #[serde(default)]
+    two_sided: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    defines: BTreeMap<String, ShaderDefineValue>,
+}
+
+fn property_value_to_kind(value: &PropertyValue) -> PropertyKind {
+    match value {
+        PropertyValue::Float(v) => PropertyKind::Float(*v),
+        PropertyValue::FloatArray(v) => PropertyKind::FloatArray(v.clone()),
+        PropertyValue::Int(v) => PropertyKind::Int(*v),
+        PropertyValue::IntArray(v) => PropertyKind::IntArray(v.clone()),
+        PropertyValue::UInt(v) => PropertyKind::UInt(*v),
+        PropertyValue::UIntArray(v) => PropertyKind::UIntArray(v.clone()),
+        PropertyValue::Vector2(v) => PropertyKind::Vector2(*v),
+        PropertyValue::Vector2Array(v) => PropertyKind::Vector2Array(v.clone()),
+        PropertyValue::Vector3(v) => PropertyKind::Vector3(*v),
+        PropertyValue::Vector3Array(v) => PropertyKind::Vector3Array(v.clone()),
+        PropertyValue::Vector4(v) => PropertyKind::Vector4(*v),
+        PropertyValue::Vector4Array(v) => PropertyKind::Vector4Array(v.clone()),
+        PropertyValue::Matrix2(v) => PropertyKind::Matrix2(*v),
+        PropertyValue::Matrix2Array(v) => PropertyKind::Matrix2Array(v.clone()),
+        PropertyValue::Matrix3(v) => PropertyKind::Matrix3(*v),
+        PropertyValue::Matrix3Array(v) => PropertyKind::Matrix3Array(v.clone()),
+        PropertyValue::Matrix4(v) => PropertyKind::Matrix4(*v),
+        PropertyValue::Matrix4Array(v) => PropertyKind::Matrix4Array(v.clone()),
+        PropertyValue::Bool(v) => PropertyKind::Bool(*v),
+        PropertyValue::Color(v) => PropertyKind::Color {
+            r: v.r,
+            g: v.g,
+            b: v.b,
+            a: v.a,
+        },
+        PropertyValue::Sampler {
+            value, fallback, ..
+        } => PropertyKind::Sampler {
+            default: value
+                .as_ref()
+                .and_then(|texture| texture.kind().into_path()),
+            fallback: *fallback,
+        },
+        PropertyValue::Double(v) => PropertyKind::Double(*v),
+        PropertyValue::DoubleArray(v) => PropertyKind::DoubleArray(v.clone()),
+        PropertyValue::ColorArray(v) => PropertyKind::ColorArray(v.clone()),
+    }
+}
+
+fn collect_sorted<V, R>(
+    map: &FxHashMap<ImmutableString, V>,
+    func: impl Fn(&V) -> R,
+) -> BTreeMap<String, R> {
+    map.iter()
+        .map(|(name, value)| (name.to_string(), func(value)))
+        .collect()
+}
+
+impl Material {
+    /// Serializes the material into a RON string. See [module docs](self) for the format description. Fails if
+    /// the shader or the base material (if any) is an embedded resource.
+    pub fn to_ron_string(&self) -> Result<String, Box<dyn Error>> {
+        let Some(shader) = self.shader.kind().into_path() else {
+            return Err("An embedded shader cannot be saved in RON format.".into());
+        };
+
+        let base = match self.base.as_ref() {
+            Some(base) => match base.kind().into_path() {
+                Some(path) => Some(path),
+                None => {
+                    return Err("An embedded base material cannot be saved in RON format.".into())
+                }
+            },
+            None => None,
+        };
+
+        let material = MaterialRon {
+            shader,
+            base,
+            fallback_shader: self
+                .fallback_shader
+                .as_ref()
+                .and_then(|shader| shader.kind().into_path()),
+            properties: collect_sorted(&self.properties, property_value_to_kind),
+            texture_priorities: self
+                .properties
+                .iter()
+                .filter_map(|(name, value)| match value {
+                    PropertyValue::Sampler { priority, .. }
+                        if *priority != TextureLoadPriority::Normal =>
+                    {
+                        Some((name.to_string(), *priority))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            texture_swizzles: collect_sorted(&self.texture_swizzles, |swizzle| swizzle.to_string()),
+            render_queue: self.render_queue,
+            sort_bias: self.sort_bias,
+            two_sided: self.two_sided,
+            defines: collect_sorted(&self.defines, |value| *value),
+        };
+
+        Ok(ron::ser::to_string_pretty(
+            &material,
+            PrettyConfig::default(),
+        )?)
+    }
+
+    /// Saves the material into a file in RON format. See [module docs](self) for the format description. The
+    /// file could be loaded back using [`Self::from_file`] (or [`Self::from_file_ron`]), the format is detected
+    /// automatically, so the usual `.material` extension could be used for both formats.
+    pub fn save_ron(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let ron = self.to_ron_string()?;
+        let mut file = File::create(path)?;
+        file.write_all(ron.as_bytes())?;
+        Ok(())
+    }
+
+    /// Deserializes a material from a RON string. See [module docs](self) for the format description. The shader,
+    /// the base material and the textures are requested from the given resource manager.
+    pub fn from_ron_str(
+        str: &str,
+        resource_manager: &ResourceManager,
+    ) -> Result<Self, MaterialError> {
+        let data: MaterialRon = ron::de::from_str(str)?;
+
+        let mut material =
+            Material::from_shader(resource_manager.request::<Shader>(data.shader), None);
+
+        if let Some(base) = data.base {
+            material.properties.clear();
+            material.base = Some(resource_manager.request::<Material>(base));
+        }
+
+        material.fallback_shader = data
+            .fallback_shader
+            .map(|path| resource_manager.request::<Shader>(path));
+
+        for (name, kind) in data.properties {
+            material.properties.insert(
+                ImmutableString::new(name),
+                PropertyValue::from_property_kind(&kind, Some(resource_manager)),
+            );
+        }
+
+        // Priorities are stored in the sampler bindings, so only the samplers of the material itself could have
+        // them (an inherited sampler gets an own binding when its priority changes).
+        for (name, priority) in data.texture_priorities {
+            if let Some(PropertyValue::Sampler {
+                priority: binding_priority,
+                ..
+            }) = material.properties.get_mut(&ImmutableString::new(name))
+            {
+                *binding_priority = priority;
+            }
+        }
+
+        for (name, swizzle) in data.texture_swizzles {
+            material
+                .set_texture_swizzle(&ImmutableString::new(name), TextureSwizzle::parse(&swizzle));
+        }
+
+        material.render_queue = data.render_queue;
+        material.sort_bias = data.sort_bias;
+        material.two_sided = data.two_sided;
+        material.defines = data
+            .defines
+            .into_iter()
+            .map(|(name, value)| (ImmutableString::new(name), value))
+            .collect();
+        material.update_define_directives();
+
+        // The material was just created, it has no previous versions.
+        material.data_version = 0;
+
+        Ok(material)
+    }
+
+    /// Loads a material in RON format from file. See [module docs](self) for the format description.
+    pub async fn from_file_ron<P>(
+        path: P,
+        io: &dyn ResourceIo,
+        resource_manager: &ResourceManager,
+    ) -> Result<Self, MaterialError>
+    where
+        P: AsRef<Path>,
+    {
+        let content = io.load_file(path.as_ref()).await?;
+        let str = String::from_utf8_lossy(&content);
+        Self::from_ron_str(&str, resource_manager)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        core::{color::Color, sstorage::ImmutableString},
+        material::{
+            shader::{ShaderResource, ShaderResourceExtension},
+            Material, MaterialResource, MaterialResourceExtension, PropertyValue,
+            ShaderDefineValue, TextureSwizzle,
+        },
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_ron_round_trip() {
+        let resource_manager = ResourceManager::new(Arc::new(Default::default()));
+        let shader = ShaderResource::standard();
+        resource_manager
+            .state()
+            .built_in_resources
+            .insert(shader.kind().path_owned().unwrap(), shader.into_untyped());
+
+        let diffuse_color = ImmutableString::new("diffuseColor");
+        let metallic_texture = ImmutableString::new("metallicTexture");
+
+        let mut material = Material::standard();
+        material
+            .set_property(&diffuse_color, PropertyValue::Color(Color::RED))
+            .unwrap();
+        material.set_texture_swizzle(&metallic_texture, TextureSwizzle::parse("bbbb"));
+        material.set_define(&ImmutableString::new("USE_FOG"), true);
+        material.set_sort_bias(3);
+
+        let ron = material.to_ron_string().unwrap();
+        let restored = Material::from_ron_str(&ron, &resource_manager).unwrap();
+
+        assert!(material.structurally_eq(&restored));
+        assert_eq!(ron, restored.to_ron_string().unwrap());
+        assert_eq!(
+            restored.define(&ImmutableString::new("USE_FOG")),
+            Some(ShaderDefineValue::Bool(true))
+        );
+
+        // Embedded resources cannot be referenced by a path.
+        let instance = Material::from_base(MaterialResource::new(material));
+        assert!(instance.to_ron_string().is_err());
+    }
+}
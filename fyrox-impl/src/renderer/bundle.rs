@@ -9,7 +9,7 @@ use crate::{
         sstorage::ImmutableString,
     },
     graph::BaseSceneGraph,
-    material::MaterialResource,
+    material::{shader::RenderQueue, MaterialResource},
     renderer::{cache::TimeToLive, framework::geometry_buffer::ElementRange},
     scene::{
         graph::Graph,
@@ -145,6 +145,8 @@ pub struct RenderDataBundle {
     pub render_path: RenderPath,
     /// A decal layer index of the bundle.
     pub decal_layer_index: u8,
+    render_queue: RenderQueue,
+    sort_bias: i32,
     sort_index: u64,
 }
 
@@ -288,12 +290,27 @@ impl RenderDataBundleStorage {
         storage
     }
 
-    /// Sorts the bundles by their respective sort index.
+    /// Sorts the bundles by render queue and sort bias of their materials and then by their respective sort
+    /// index. See [`RenderQueue`] docs for more info.
     pub fn sort(&mut self) {
-        self.bundles.sort_unstable_by_key(|b| b.sort_index);
+        self.bundles
+            .sort_unstable_by_key(|b| (b.render_queue, b.sort_bias, b.sort_index));
     }
 }
 
+// Render queue and sort bias are fetched once per bundle, when it is created. Materials, that were created
+// while their shaders were loading, get the default values of their properties here as well.
+fn render_order(material: &MaterialResource) -> (RenderQueue, i32) {
+    let mut state = material.state();
+    state
+        .data()
+        .map(|material| {
+            material.populate_pending_defaults();
+            (material.render_queue(), material.sort_bias())
+        })
+        .unwrap_or_default()
+}
+
 impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
     /// Adds a new mesh to the bundle storage using the given set of vertices and triangles. This
     /// method automatically creates a render bundle according to a hash of the following parameters:
@@ -358,10 +375,13 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
                 SurfaceData::new(vertex_buffer, triangle_buffer),
             );
 
+            let (render_queue, sort_bias) = render_order(material);
             self.bundle_map.insert(key, self.bundles.len());
             let persistent_identifier = PersistentIdentifier::new_combined(&data, node_handle, 0);
             self.bundles.push(RenderDataBundle {
                 data,
+                render_queue,
+                sort_bias,
                 sort_index,
                 instances: vec![
                     // Each bundle must have at least one instance to be rendered.
@@ -419,9 +439,12 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
         let bundle = if let Some(&bundle_index) = self.bundle_map.get(&key) {
             self.bundles.get_mut(bundle_index).unwrap()
         } else {
+            let (render_queue, sort_bias) = render_order(material);
             self.bundle_map.insert(key, self.bundles.len());
             self.bundles.push(RenderDataBundle {
                 data: data.clone(),
+                render_queue,
+                sort_bias,
                 sort_index,
                 instances: Default::default(),
                 material: material.clone(),
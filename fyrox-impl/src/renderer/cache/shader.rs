@@ -2,13 +2,17 @@ use crate::renderer::cache::TemporaryCache;
 use crate::renderer::framework::error::FrameworkError;
 use crate::{
     core::sstorage::ImmutableString,
-    material::shader::{Shader, ShaderResource},
+    material::{
+        shader::{Shader, ShaderResource, ShaderResourceExtension},
+        Material,
+    },
     renderer::framework::{
         framebuffer::DrawParameters, gpu_program::GpuProgram, state::PipelineState,
     },
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_core::log::Log;
+use std::borrow::Cow;
 
 pub struct RenderPassData {
     pub program: GpuProgram,
@@ -21,15 +25,34 @@ pub struct ShaderSet {
 
 impl ShaderSet {
     pub fn new(state: &PipelineState, shader: &Shader) -> Result<Self, FrameworkError> {
+        Self::with_defines(state, shader, "")
+    }
+
+    /// Compiles a permutation of the shader, `define_directives` are inserted at the beginning of the
+    /// code of every render pass (see [`crate::material::Material::define_directives`]).
+    pub fn with_defines(
+        state: &PipelineState,
+        shader: &Shader,
+        define_directives: &str,
+    ) -> Result<Self, FrameworkError> {
         let mut map = FxHashMap::default();
         for render_pass in shader.definition.passes.iter() {
             let program_name = format!("{}_{}", shader.definition.name, render_pass.name);
-            match GpuProgram::from_source(
-                state,
-                &program_name,
-                &render_pass.vertex_shader,
-                &render_pass.fragment_shader,
-            ) {
+            let (vertex_shader, fragment_shader) = if define_directives.is_empty() {
+                (
+                    Cow::Borrowed(render_pass.vertex_shader.as_str()),
+                    Cow::Borrowed(render_pass.fragment_shader.as_str()),
+                )
+            } else {
+                (
+                    Cow::Owned(format!("{define_directives}{}", render_pass.vertex_shader)),
+                    Cow::Owned(format!(
+                        "{define_directives}{}",
+                        render_pass.fragment_shader
+                    )),
+                )
+            };
+            match GpuProgram::from_source(state, &program_name, &vertex_shader, &fragment_shader) {
                 Ok(gpu_program) => {
                     map.insert(
                         ImmutableString::new(&render_pass.name),
@@ -52,9 +75,21 @@ impl ShaderSet {
     }
 }
 
+/// Compiled variants of a shader. The base variant (without any defines) is compiled when the shader is
+/// added to the cache, other permutations are compiled on first use and stored by their `#define`
+/// directives. Permutations that failed to compile are stored as well (as `None`), so the renderer
+/// won't try to compile them again every frame.
+pub struct ShaderPermutations {
+    pub base: ShaderSet,
+    pub permutations: FxHashMap<String, Option<ShaderSet>>,
+}
+
 #[derive(Default)]
 pub struct ShaderCache {
-    pub(super) cache: TemporaryCache<ShaderSet>,
+    pub(super) cache: TemporaryCache<ShaderPermutations>,
+    // Keys of the broken shaders, that were already substituted with a fallback. It is used to log
+    // the substitution only once.
+    reported_broken_shaders: FxHashSet<u64>,
 }
 
 impl ShaderCache {
@@ -76,9 +111,14 @@ impl ShaderCache {
             match self.cache.get_or_insert_with(
                 &shader_state.cache_index,
                 Default::default(),
-                || ShaderSet::new(pipeline_state, shader_state),
+                || {
+                    ShaderSet::new(pipeline_state, shader_state).map(|base| ShaderPermutations {
+                        base,
+                        permutations: Default::default(),
+                    })
+                },
             ) {
-                Ok(shader_set) => Some(shader_set),
+                Ok(permutations) => Some(&permutations.base),
                 Err(error) => {
                     Log::err(format!("{}", error));
                     None
@@ -89,11 +129,94 @@ impl ShaderCache {
         }
     }
 
+    /// Returns a permutation of the shader for the given `#define` directives (see
+    /// [`crate::material::Material::define_directives`]), compiling it if needed. Empty directives
+    /// mean the base variant of the shader.
+    pub fn get_permutation(
+        &mut self,
+        pipeline_state: &PipelineState,
+        shader: &ShaderResource,
+        define_directives: &str,
+    ) -> Option<&ShaderSet> {
+        if define_directives.is_empty() {
+            return self.get(pipeline_state, shader);
+        }
+
+        let mut shader_state = shader.state();
+        let shader_state = shader_state.data()?;
+
+        let permutations = match self.cache.get_mut_or_insert_with(
+            &shader_state.cache_index,
+            Default::default(),
+            || {
+                ShaderSet::new(pipeline_state, shader_state).map(|base| ShaderPermutations {
+                    base,
+                    permutations: Default::default(),
+                })
+            },
+        ) {
+            Ok(permutations) => permutations,
+            Err(error) => {
+                Log::err(format!("{}", error));
+                return None;
+            }
+        };
+
+        if !permutations.permutations.contains_key(define_directives) {
+            let result = ShaderSet::with_defines(pipeline_state, shader_state, define_directives);
+            let permutation = match result {
+                Ok(shader_set) => Some(shader_set),
+                Err(error) => {
+                    Log::err(format!(
+                        "Failed to compile a permutation of {} shader with defines:\n{}Reason: {}",
+                        shader_state.definition.name, define_directives, error
+                    ));
+                    None
+                }
+            };
+            permutations
+                .permutations
+                .insert(define_directives.to_owned(), permutation);
+        }
+
+        permutations.permutations[define_directives].as_ref()
+    }
+
+    /// Returns a permutation of the shader of the given material (see [`Self::get_permutation`]). If the
+    /// shader of the material failed to load, its fallback shader is used instead (see
+    /// [`Material::fallback_shader`]).
+    pub fn get_material_permutation(
+        &mut self,
+        pipeline_state: &PipelineState,
+        material: &Material,
+    ) -> Option<&ShaderSet> {
+        let shader = material.shader();
+        if !shader.is_failed_to_load() {
+            return self.get_permutation(pipeline_state, shader, material.define_directives());
+        }
+
+        let fallback = material
+            .fallback_shader()
+            .cloned()
+            .unwrap_or_else(ShaderResource::error);
+
+        if self.reported_broken_shaders.insert(shader.key()) {
+            Log::warn(format!(
+                "Shader {} failed to load, {} shader is used instead.",
+                shader.kind(),
+                fallback.kind()
+            ));
+        }
+
+        self.get_permutation(pipeline_state, &fallback, material.define_directives())
+    }
+
     pub fn update(&mut self, dt: f32) {
         self.cache.update(dt)
     }
 
     pub fn clear(&mut self) {
         self.cache.clear();
+        self.reported_broken_shaders.clear();
     }
 }
@@ -12,13 +12,24 @@ use crate::{
             state::PipelineState,
         },
     },
-    resource::texture::TextureResource,
+    resource::texture::{TextureKind, TextureResource},
+    scene::terrain::TextureDirtyRegion,
 };
 use std::{cell::RefCell, rc::Rc};
 
 pub(crate) struct TextureRenderData {
     pub gpu_texture: Rc<RefCell<GpuTexture>>,
     pub modifications_counter: u64,
+    // Copies of the texture with non-default swizzle masks. Swizzle is a state of a texture object, so a
+    // texture that is sampled with a swizzle needs its own copy, otherwise every other user of the texture
+    // would see the swizzled channels.
+    swizzled: Vec<SwizzledTexture>,
+}
+
+struct SwizzledTexture {
+    swizzle: [i32; 4],
+    gpu_texture: Rc<RefCell<GpuTexture>>,
+    modifications_counter: u64,
 }
 
 #[derive(Default)]
@@ -42,9 +53,68 @@ fn create_gpu_texture(
     .map(|gpu_texture| TextureRenderData {
         gpu_texture: Rc::new(RefCell::new(gpu_texture)),
         modifications_counter: texture.modifications_count(),
+        swizzled: Default::default(),
     })
 }
 
+fn create_swizzled_gpu_texture(
+    state: &PipelineState,
+    texture: &Texture,
+    swizzle: [i32; 4],
+) -> Result<SwizzledTexture, FrameworkError> {
+    let data = create_gpu_texture(state, texture)?;
+    data.gpu_texture
+        .borrow_mut()
+        .bind_mut(state, 0)
+        .set_swizzle(Some(swizzle));
+    Ok(SwizzledTexture {
+        swizzle,
+        gpu_texture: data.gpu_texture,
+        modifications_counter: data.modifications_counter,
+    })
+}
+
+// Syncs sampling parameters of the GPU texture with the texture resource.
+fn sync_sampling_parameters(
+    gpu_texture: &mut GpuTexture,
+    state: &PipelineState,
+    texture: &Texture,
+) {
+    let new_mag_filter = texture.magnification_filter().into();
+    if gpu_texture.magnification_filter() != new_mag_filter {
+        gpu_texture
+            .bind_mut(state, 0)
+            .set_magnification_filter(new_mag_filter);
+    }
+
+    let new_min_filter = texture.minification_filter().into();
+    if gpu_texture.minification_filter() != new_min_filter {
+        gpu_texture
+            .bind_mut(state, 0)
+            .set_minification_filter(new_min_filter);
+    }
+
+    if gpu_texture.anisotropy().ne(&texture.anisotropy_level()) {
+        gpu_texture
+            .bind_mut(state, 0)
+            .set_anisotropy(texture.anisotropy_level());
+    }
+
+    let new_s_wrap_mode = texture.s_wrap_mode().into();
+    if gpu_texture.s_wrap_mode() != new_s_wrap_mode {
+        gpu_texture
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, new_s_wrap_mode);
+    }
+
+    let new_t_wrap_mode = texture.t_wrap_mode().into();
+    if gpu_texture.t_wrap_mode() != new_t_wrap_mode {
+        gpu_texture
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::T, new_t_wrap_mode);
+    }
+}
+
 impl TextureCache {
     /// Unconditionally uploads requested texture into GPU memory, previous GPU texture will be automatically
     /// destroyed.
@@ -108,41 +178,7 @@ impl TextureCache {
                         }
                     }
 
-                    let mut gpu_texture = entry.gpu_texture.borrow_mut();
-
-                    let new_mag_filter = texture.magnification_filter().into();
-                    if gpu_texture.magnification_filter() != new_mag_filter {
-                        gpu_texture
-                            .bind_mut(state, 0)
-                            .set_magnification_filter(new_mag_filter);
-                    }
-
-                    let new_min_filter = texture.minification_filter().into();
-                    if gpu_texture.minification_filter() != new_min_filter {
-                        gpu_texture
-                            .bind_mut(state, 0)
-                            .set_minification_filter(new_min_filter);
-                    }
-
-                    if gpu_texture.anisotropy().ne(&texture.anisotropy_level()) {
-                        gpu_texture
-                            .bind_mut(state, 0)
-                            .set_anisotropy(texture.anisotropy_level());
-                    }
-
-                    let new_s_wrap_mode = texture.s_wrap_mode().into();
-                    if gpu_texture.s_wrap_mode() != new_s_wrap_mode {
-                        gpu_texture
-                            .bind_mut(state, 0)
-                            .set_wrap(Coordinate::S, new_s_wrap_mode);
-                    }
-
-                    let new_t_wrap_mode = texture.t_wrap_mode().into();
-                    if gpu_texture.t_wrap_mode() != new_t_wrap_mode {
-                        gpu_texture
-                            .bind_mut(state, 0)
-                            .set_wrap(Coordinate::T, new_t_wrap_mode);
-                    }
+                    sync_sampling_parameters(&mut entry.gpu_texture.borrow_mut(), state, texture);
 
                     return Some(&entry.gpu_texture);
                 }
@@ -162,6 +198,132 @@ impl TextureCache {
         None
     }
 
+    /// Same as [`Self::get`], but returns a GPU texture with the given swizzle mask (see
+    /// [`GpuTexture::set_swizzle`]). [`None`] swizzle gives the same texture as [`Self::get`]. The swizzle
+    /// is set only once, when a copy of the texture with the swizzle is created, so the texture returned by
+    /// [`Self::get`] is never affected. Every distinct swizzle of a texture takes the same amount of GPU
+    /// memory as the texture itself, the copy is re-uploaded entirely when the texture is modified.
+    pub fn get_swizzled(
+        &mut self,
+        state: &PipelineState,
+        texture_resource: &TextureResource,
+        swizzle: Option<[i32; 4]>,
+    ) -> Option<&Rc<RefCell<GpuTexture>>> {
+        let Some(swizzle) = swizzle else {
+            return self.get(state, texture_resource);
+        };
+
+        // Make sure the main texture exists, the copies are stored with it.
+        if self.get(state, texture_resource).is_none() {
+            return None;
+        }
+
+        let mut texture_data_guard = texture_resource.state();
+        let texture = texture_data_guard.data()?;
+        let entry = self.map.get_mut(&texture.cache_index)?;
+
+        let index = match entry.swizzled.iter().position(|s| s.swizzle == swizzle) {
+            Some(index) => {
+                if entry.swizzled[index].modifications_counter != texture.modifications_count() {
+                    match create_swizzled_gpu_texture(state, texture, swizzle) {
+                        Ok(swizzled) => entry.swizzled[index] = swizzled,
+                        Err(e) => Log::writeln(
+                            MessageKind::Error,
+                            format!(
+                                "Unable to upload new swizzled texture data to GPU. Reason: {:?}",
+                                e
+                            ),
+                        ),
+                    }
+                }
+                index
+            }
+            None => match create_swizzled_gpu_texture(state, texture, swizzle) {
+                Ok(swizzled) => {
+                    entry.swizzled.push(swizzled);
+                    entry.swizzled.len() - 1
+                }
+                Err(e) => {
+                    Log::writeln(
+                        MessageKind::Error,
+                        format!(
+                            "Failed to create swizzled GPU texture from {} texture. Reason: {:?}",
+                            texture_resource.kind(),
+                            e
+                        ),
+                    );
+                    return None;
+                }
+            },
+        };
+
+        let swizzled = &entry.swizzled[index];
+        sync_sampling_parameters(&mut swizzled.gpu_texture.borrow_mut(), state, texture);
+        Some(&swizzled.gpu_texture)
+    }
+
+    /// Uploads only the modified region of a texture to GPU memory. The region is uploaded only if the GPU
+    /// texture contains the data of the texture right before the first modification in the region and no
+    /// other modifications were made. Otherwise the method does nothing and the entire texture will be
+    /// uploaded on next [`Self::get`] call as usual.
+    pub(crate) fn update_region(&mut self, state: &PipelineState, region: &TextureDirtyRegion) {
+        let mut texture_data_guard = region.texture.state();
+        let Some(texture) = texture_data_guard.data() else {
+            return;
+        };
+
+        if texture.modifications_count() != region.modifications_count || texture.mip_count() != 1 {
+            return;
+        }
+
+        let Some(entry) = self.map.get_mut(&texture.cache_index) else {
+            return;
+        };
+
+        if entry.modifications_counter != region.base_modifications_count {
+            return;
+        }
+
+        let TextureKind::Rectangle { width, height } = texture.kind() else {
+            return;
+        };
+
+        let data = texture.data();
+        let pixel_count = (width * height) as usize;
+        if pixel_count == 0 || data.len() % pixel_count != 0 {
+            return;
+        }
+        let pixel_size = data.len() / pixel_count;
+
+        let size = region.size();
+        let row_size = size.x as usize * pixel_size;
+        let mut region_data = Vec::with_capacity(row_size * size.y as usize);
+        for y in region.min.y..=region.max.y {
+            let begin = (y * width + region.min.x) as usize * pixel_size;
+            region_data.extend_from_slice(&data[begin..begin + row_size]);
+        }
+
+        let result = entry
+            .gpu_texture
+            .borrow_mut()
+            .bind_mut(state, 0)
+            .set_data_region(
+                region.min.x as usize,
+                region.min.y as usize,
+                size.x as usize,
+                size.y as usize,
+                &region_data,
+            )
+            .map(|_| ());
+        match result {
+            Ok(()) => entry.modifications_counter = region.modifications_count,
+            Err(e) => Log::writeln(
+                MessageKind::Error,
+                format!("Unable to upload texture region to GPU. Reason: {:?}", e),
+            ),
+        }
+    }
+
     pub fn update(&mut self, dt: f32) {
         self.map.update(dt)
     }
@@ -22,6 +22,7 @@ use crate::{
             error::FrameworkError, framebuffer::FrameBuffer, gpu_texture::GpuTexture,
             state::PipelineState,
         },
+        material_draw_params,
         storage::MatrixStorageCache,
         GeometryCache, LightData, MaterialContext, QualitySettings, RenderPassStatistics,
     },
@@ -173,12 +174,14 @@ impl ForwardRenderer {
                 .and_then(|c| c.blend_shape_storage.clone());
 
             let Some(render_pass) = shader_cache
-                .get(state, material.shader())
+                .get_material_permutation(state, material)
                 .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
             else {
                 continue;
             };
 
+            let draw_params = material_draw_params(material, &render_pass.draw_params);
+
             for instance in bundle.instances.iter() {
                 let view_projection = if instance.depth_offset != 0.0 {
                     let mut projection = camera.projection_matrix();
@@ -193,7 +196,7 @@ impl ForwardRenderer {
                     state,
                     viewport,
                     &render_pass.program,
-                    &render_pass.draw_params,
+                    &draw_params,
                     instance.element_range,
                     |mut program_binding| {
                         apply_material(MaterialContext {
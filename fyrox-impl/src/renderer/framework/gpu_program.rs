@@ -405,6 +405,16 @@ impl<'a, 'b> GpuProgramBinding<'a, 'b> {
         }
         self
     }
+
+    #[inline(always)]
+    pub fn set_srgb_color_slice(
+        &mut self,
+        location: &UniformLocation,
+        value: &[Color],
+    ) -> &mut Self {
+        let rgba = value.iter().map(|c| c.as_frgba()).collect::<Vec<_>>();
+        self.set_vector4_slice(location, &rgba)
+    }
 }
 
 #[inline]
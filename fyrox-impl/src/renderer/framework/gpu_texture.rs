@@ -1,12 +1,12 @@
 use crate::{
-    core::color::Color,
+    core::{algebra::Vector3, color::Color},
     renderer::framework::{error::FrameworkError, state::PipelineState},
     resource::texture::{
         TextureKind, TextureMagnificationFilter, TextureMinificationFilter, TexturePixelKind,
         TextureWrapMode,
     },
 };
-use glow::{HasContext, PixelPackData, COMPRESSED_RED_RGTC1, COMPRESSED_RG_RGTC2};
+use glow::{HasContext, PixelPackData, PixelUnpackData, COMPRESSED_RED_RGTC1, COMPRESSED_RG_RGTC2};
 use std::marker::PhantomData;
 use std::rc::Weak;
 
@@ -385,10 +385,34 @@ pub struct GpuTexture {
     r_wrap_mode: WrapMode,
     anisotropy: f32,
     pixel_kind: PixelKind,
+    swizzle: Option<[i32; 4]>,
     // Force compiler to not implement Send and Sync, because OpenGL is not thread-safe.
     thread_mark: PhantomData<*const u8>,
 }
 
+const IDENTITY_SWIZZLE_MASK: [i32; 4] = [
+    glow::RED as i32,
+    glow::GREEN as i32,
+    glow::BLUE as i32,
+    glow::ALPHA as i32,
+];
+
+// Combines default swizzle mask of the pixel kind with the user-defined swizzle. User-defined swizzle
+// selects channels from the texture as it is seen after applying the default swizzle.
+fn swizzle_mask(pixel_kind: PixelKind, swizzle: Option<[i32; 4]>) -> Option<[i32; 4]> {
+    let default = pixel_kind.pixel_descriptor().swizzle_mask;
+    let Some(swizzle) = swizzle else {
+        return default;
+    };
+    let default = default.unwrap_or(IDENTITY_SWIZZLE_MASK);
+    Some(swizzle.map(
+        |channel| match IDENTITY_SWIZZLE_MASK.iter().position(|c| *c == channel) {
+            Some(index) => default[index],
+            None => channel,
+        },
+    ))
+}
+
 fn ceil_div_4(x: usize) -> usize {
     (x + 3) / 4
 }
@@ -602,7 +626,7 @@ pub struct TextureBinding<'a> {
     sampler_index: u32,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum CubeMapFace {
     PositiveX,
     NegativeX,
@@ -625,6 +649,100 @@ impl CubeMapFace {
     }
 }
 
+/// Coordinate system convention of a cube map. It defines how the faces of a cube map are oriented in the
+/// world space.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CubeMapConvention {
+    /// Right-handed Y-up world with OpenGL cube map face orientation. This is the convention used by the
+    /// engine. Side faces (±X, ±Z) have `-Y` as up vector, `+Y` face has `+Z` as up vector and `-Y` face has
+    /// `-Z` as up vector.
+    #[default]
+    OpenGl,
+    /// Left-handed Y-up world with DirectX cube map face orientation, it is common for cube maps produced by
+    /// DirectX-based tools. Z axis of a left-handed world points in the opposite direction, so `+Z` face looks
+    /// at `-Z` world direction and `-Z` face looks at `+Z` world direction. Side faces (±X, ±Z) have `+Y` as up
+    /// vector, `±Y` faces have the same up vectors as in [`Self::OpenGl`].
+    DirectX,
+    /// Right-handed Z-up world with OpenGL cube map face orientation. It is the [`Self::OpenGl`] convention
+    /// rotated by 90 degrees around the X axis, so the `±Y` faces look at `±Z` world directions and `±Z` faces
+    /// look at `∓Y` world directions.
+    ZUp,
+}
+
+/// Describes orientation of a single face of a cube map: a direction the face is looking at and its up vector.
+/// It could be used to build view matrices to render a scene into a cube map.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CubeMapFaceDescriptor {
+    pub face: CubeMapFace,
+    pub look: Vector3<f32>,
+    pub up: Vector3<f32>,
+}
+
+impl CubeMapFaceDescriptor {
+    /// Returns descriptors of all six faces of a cube map in the engine's convention ([`CubeMapConvention::OpenGl`]).
+    pub fn cube_faces() -> [Self; 6] {
+        [
+            Self {
+                face: CubeMapFace::PositiveX,
+                look: Vector3::new(1.0, 0.0, 0.0),
+                up: Vector3::new(0.0, -1.0, 0.0),
+            },
+            Self {
+                face: CubeMapFace::NegativeX,
+                look: Vector3::new(-1.0, 0.0, 0.0),
+                up: Vector3::new(0.0, -1.0, 0.0),
+            },
+            Self {
+                face: CubeMapFace::PositiveY,
+                look: Vector3::new(0.0, 1.0, 0.0),
+                up: Vector3::new(0.0, 0.0, 1.0),
+            },
+            Self {
+                face: CubeMapFace::NegativeY,
+                look: Vector3::new(0.0, -1.0, 0.0),
+                up: Vector3::new(0.0, 0.0, -1.0),
+            },
+            Self {
+                face: CubeMapFace::PositiveZ,
+                look: Vector3::new(0.0, 0.0, 1.0),
+                up: Vector3::new(0.0, -1.0, 0.0),
+            },
+            Self {
+                face: CubeMapFace::NegativeZ,
+                look: Vector3::new(0.0, 0.0, -1.0),
+                up: Vector3::new(0.0, -1.0, 0.0),
+            },
+        ]
+    }
+
+    /// Returns descriptors of all six faces of a cube map in the given convention. See [`CubeMapConvention`]
+    /// docs for the exact mapping of each convention.
+    pub fn cube_faces_with_convention(convention: CubeMapConvention) -> [Self; 6] {
+        let mut faces = Self::cube_faces();
+        match convention {
+            CubeMapConvention::OpenGl => (),
+            CubeMapConvention::DirectX => {
+                for face in faces.iter_mut() {
+                    // Left-handed to right-handed: (x, y, z) -> (x, y, -z).
+                    face.look.z = -face.look.z;
+                    if face.look.y == 0.0 {
+                        face.up = Vector3::y();
+                    }
+                }
+            }
+            CubeMapConvention::ZUp => {
+                // Rotation by 90 degrees around X axis: (x, y, z) -> (x, -z, y).
+                let rotate = |v: Vector3<f32>| Vector3::new(v.x, -v.z, v.y);
+                for face in faces.iter_mut() {
+                    face.look = rotate(face.look);
+                    face.up = rotate(face.up);
+                }
+            }
+        }
+        faces
+    }
+}
+
 impl<'a> TextureBinding<'a> {
     pub fn set_anisotropy(self, anisotropy: f32) -> Self {
         unsafe {
@@ -690,6 +808,33 @@ impl<'a> TextureBinding<'a> {
         self
     }
 
+    /// Sets swizzle mask (a set of `glow::RED`, `glow::GREEN`, `glow::BLUE`, `glow::ALPHA`, `glow::ZERO`,
+    /// `glow::ONE`) of the texture, that will be applied on top of the default swizzle mask of the pixel
+    /// kind. [`None`] resets the swizzle to the default one.
+    pub fn set_swizzle(self, swizzle: Option<[i32; 4]>) -> Self {
+        let mask = swizzle_mask(self.texture.pixel_kind, swizzle).unwrap_or(IDENTITY_SWIZZLE_MASK);
+        self.apply_swizzle_mask(&mask);
+        self.texture.swizzle = swizzle;
+        self
+    }
+
+    fn apply_swizzle_mask(&self, mask: &[i32; 4]) {
+        if self
+            .state
+            .gl
+            .supported_extensions()
+            .contains("GL_ARB_texture_swizzle")
+        {
+            unsafe {
+                self.state.gl.tex_parameter_i32_slice(
+                    self.texture.kind.gl_texture_target(),
+                    glow::TEXTURE_SWIZZLE_RGBA,
+                    mask,
+                );
+            }
+        }
+    }
+
     pub fn set_border_color(self, #[allow(unused_variables)] color: Color) -> Self {
         #[cfg(not(target_arch = "wasm32"))]
         unsafe {
@@ -790,7 +935,7 @@ impl<'a> TextureBinding<'a> {
                 data_type,
                 format,
                 internal_format,
-                swizzle_mask,
+                ..
             } = pixel_kind.pixel_descriptor();
 
             let is_compressed = pixel_kind.is_compressed();
@@ -801,19 +946,8 @@ impl<'a> TextureBinding<'a> {
                     .pixel_store_i32(glow::UNPACK_ALIGNMENT, alignment);
             }
 
-            if let Some(swizzle_mask) = swizzle_mask {
-                if self
-                    .state
-                    .gl
-                    .supported_extensions()
-                    .contains("GL_ARB_texture_swizzle")
-                {
-                    self.state.gl.tex_parameter_i32_slice(
-                        target,
-                        glow::TEXTURE_SWIZZLE_RGBA,
-                        &swizzle_mask,
-                    );
-                }
+            if let Some(swizzle_mask) = swizzle_mask(pixel_kind, self.texture.swizzle) {
+                self.apply_swizzle_mask(&swizzle_mask);
             }
 
             let mut mip_byte_offset = 0;
@@ -995,6 +1129,81 @@ impl<'a> TextureBinding<'a> {
         Ok(self)
     }
 
+    /// Uploads new data to a rectangular region of the first mip level of a rectangle texture. The data
+    /// must contain tightly packed pixels of the region only, row by row. Compressed textures are not
+    /// supported.
+    pub fn set_data_region(
+        self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        data: &[u8],
+    ) -> Result<Self, FrameworkError> {
+        let GpuTextureKind::Rectangle {
+            width: texture_width,
+            height: texture_height,
+        } = self.texture.kind
+        else {
+            return Err(FrameworkError::Custom(
+                "Only rectangle textures support partial updates!".to_string(),
+            ));
+        };
+
+        let pixel_kind = self.texture.pixel_kind;
+        if pixel_kind.is_compressed() {
+            return Err(FrameworkError::Custom(
+                "Compressed textures do not support partial updates!".to_string(),
+            ));
+        }
+
+        if x + width > texture_width || y + height > texture_height {
+            return Err(FrameworkError::Custom(format!(
+                "Region {x};{y} {width}x{height} is out of bounds of {texture_width}x{texture_height} texture!"
+            )));
+        }
+
+        let expected_data_size = image_2d_size_bytes(pixel_kind, width, height);
+        if data.len() != expected_data_size {
+            return Err(FrameworkError::InvalidTextureData {
+                expected_data_size,
+                actual_data_size: data.len(),
+            });
+        }
+
+        let PixelDescriptor {
+            data_type, format, ..
+        } = pixel_kind.pixel_descriptor();
+
+        unsafe {
+            self.state.set_texture(
+                self.sampler_index,
+                glow::TEXTURE_2D,
+                Some(self.texture.texture),
+            );
+
+            if let Some(alignment) = pixel_kind.unpack_alignment() {
+                self.state
+                    .gl
+                    .pixel_store_i32(glow::UNPACK_ALIGNMENT, alignment);
+            }
+
+            self.state.gl.tex_sub_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                format,
+                data_type,
+                PixelUnpackData::Slice(data),
+            );
+        }
+
+        Ok(self)
+    }
+
     pub fn read_pixels(&self, state: &PipelineState) -> Vec<u8> {
         unsafe {
             if let GpuTextureKind::Rectangle { width, height } = self.texture.kind {
@@ -1066,6 +1275,7 @@ impl GpuTexture {
                 r_wrap_mode: WrapMode::Repeat,
                 anisotropy: 1.0,
                 pixel_kind,
+                swizzle: None,
                 thread_mark: PhantomData,
             };
 
@@ -1160,6 +1370,10 @@ impl GpuTexture {
     pub fn pixel_kind(&self) -> PixelKind {
         self.pixel_kind
     }
+
+    pub fn swizzle(&self) -> Option<[i32; 4]> {
+        self.swizzle
+    }
 }
 
 impl Drop for GpuTexture {
@@ -1171,3 +1385,84 @@ impl Drop for GpuTexture {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        renderer::framework::gpu_texture::{
+            swizzle_mask, CubeMapConvention, CubeMapFace, CubeMapFaceDescriptor, PixelKind,
+        },
+    };
+
+    fn check(convention: CubeMapConvention, expected: [(CubeMapFace, [f32; 3], [f32; 3]); 6]) {
+        let faces = CubeMapFaceDescriptor::cube_faces_with_convention(convention);
+        for (descriptor, (face, look, up)) in faces.iter().zip(expected) {
+            assert_eq!(descriptor.face, face);
+            assert_eq!(descriptor.look, Vector3::from(look), "{face:?} look");
+            assert_eq!(descriptor.up, Vector3::from(up), "{face:?} up");
+        }
+    }
+
+    #[test]
+    fn test_cube_map_conventions() {
+        check(
+            CubeMapConvention::OpenGl,
+            [
+                (CubeMapFace::PositiveX, [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+                (CubeMapFace::NegativeX, [-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+                (CubeMapFace::PositiveY, [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+                (CubeMapFace::NegativeY, [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+                (CubeMapFace::PositiveZ, [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+                (CubeMapFace::NegativeZ, [0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+            ],
+        );
+        check(
+            CubeMapConvention::DirectX,
+            [
+                (CubeMapFace::PositiveX, [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+                (CubeMapFace::NegativeX, [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+                (CubeMapFace::PositiveY, [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+                (CubeMapFace::NegativeY, [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+                (CubeMapFace::PositiveZ, [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+                (CubeMapFace::NegativeZ, [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ],
+        );
+        check(
+            CubeMapConvention::ZUp,
+            [
+                (CubeMapFace::PositiveX, [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+                (CubeMapFace::NegativeX, [-1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+                (CubeMapFace::PositiveY, [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+                (CubeMapFace::NegativeY, [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+                (CubeMapFace::PositiveZ, [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+                (CubeMapFace::NegativeZ, [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_swizzle_mask_composition() {
+        let (r, g, b, a) = (
+            glow::RED as i32,
+            glow::GREEN as i32,
+            glow::BLUE as i32,
+            glow::ALPHA as i32,
+        );
+        let (zero, one) = (glow::ZERO as i32, glow::ONE as i32);
+
+        // No default swizzle, the user-defined one is used as is.
+        assert_eq!(swizzle_mask(PixelKind::RGBA8, None), None);
+        assert_eq!(
+            swizzle_mask(PixelKind::RGBA8, Some([b, g, r, one])),
+            Some([b, g, r, one])
+        );
+
+        // The user-defined swizzle selects channels after the default swizzle (rrr1) was applied.
+        assert_eq!(swizzle_mask(PixelKind::L8, None), Some([r, r, r, one]));
+        assert_eq!(
+            swizzle_mask(PixelKind::L8, Some([a, g, zero, r])),
+            Some([one, r, zero, r])
+        );
+    }
+}
@@ -478,6 +478,9 @@ impl Default for PolygonFillMode {
 }
 
 impl PipelineState {
+    /// Shader location of the optional vertex color attribute, that is used by the standard shaders.
+    pub const VERTEX_COLOR_LOCATION: u32 = 7;
+
     pub fn new(
         #[allow(unused_mut)] mut context: glow::Context,
         gl_kind: GlKind,
@@ -485,6 +488,10 @@ impl PipelineState {
         unsafe {
             context.depth_func(CompareFunc::default() as u32);
 
+            // Vertex colors are optional, make sure that meshes without them are rendered as if they
+            // had white vertex colors.
+            context.vertex_attrib_4_f32(Self::VERTEX_COLOR_LOCATION, 1.0, 1.0, 1.0, 1.0);
+
             #[cfg(debug_assertions)]
             {
                 use crate::core::log::{Log, MessageKind};
@@ -35,6 +35,7 @@ use crate::{
             state::{BlendFactor, BlendFunc, PipelineState},
         },
         gbuffer::decal::DecalShader,
+        material_draw_params,
         storage::MatrixStorageCache,
         GeometryCache, MaterialContext, RenderPassStatistics, TextureCache,
     },
@@ -322,12 +323,14 @@ impl GBuffer {
                 .and_then(|c| c.blend_shape_storage.clone());
 
             let Some(render_pass) = shader_cache
-                .get(state, material.shader())
+                .get_material_permutation(state, material)
                 .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
             else {
                 continue;
             };
 
+            let draw_params = material_draw_params(material, &render_pass.draw_params);
+
             for instance in bundle.instances.iter() {
                 let apply_uniforms = |mut program_binding: GpuProgramBinding| {
                     let view_projection = if instance.depth_offset != 0.0 {
@@ -373,7 +376,7 @@ impl GBuffer {
                     state,
                     viewport,
                     &render_pass.program,
-                    &render_pass.draw_params,
+                    &draw_params,
                     instance.element_range,
                     apply_uniforms,
                 )?;
@@ -52,7 +52,7 @@ use crate::{
     gui::draw::DrawingContext,
     material::{
         shader::{SamplerFallback, Shader, ShaderResource, ShaderResourceExtension},
-        Material, PropertyValue,
+        Material, PropertyValue, SwizzleSource, TextureSwizzle,
     },
     renderer::{
         bloom::BloomRenderer,
@@ -82,7 +82,7 @@ use crate::{
         ui_renderer::{UiRenderContext, UiRenderer},
     },
     resource::texture::{Texture, TextureKind, TextureResource},
-    scene::{camera::Camera, mesh::surface::SurfaceData, Scene, SceneContainer},
+    scene::{camera::Camera, mesh::surface::SurfaceData, terrain::Terrain, Scene, SceneContainer},
 };
 use fxhash::FxHashMap;
 use fyrox_core::algebra::Vector4;
@@ -97,6 +97,7 @@ use glutin::{
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::any::TypeId;
+use std::borrow::Cow;
 use std::{cell::RefCell, collections::hash_map::Entry, rc::Rc, sync::mpsc::Receiver};
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 #[cfg(not(target_arch = "wasm32"))]
@@ -119,6 +120,22 @@ pub fn is_shadow_pass(render_pass_name: &str) -> bool {
         || render_pass_name == &**POINT_SHADOW_PASS_NAME
 }
 
+/// Applies render state overrides of the material (see [`Material::set_two_sided`]) to the draw parameters
+/// of a render pass.
+pub(crate) fn material_draw_params<'a>(
+    material: &Material,
+    draw_params: &'a DrawParameters,
+) -> Cow<'a, DrawParameters> {
+    if material.is_two_sided() && draw_params.cull_face.is_some() {
+        Cow::Owned(DrawParameters {
+            cull_face: None,
+            ..draw_params.clone()
+        })
+    } else {
+        Cow::Borrowed(draw_params)
+    }
+}
+
 /// Shadow map precision allows you to select compromise between quality and performance.
 #[derive(
     Copy,
@@ -933,6 +950,19 @@ pub struct MaterialContext<'a, 'b, 'c> {
     pub volume_dummy: &'a Rc<RefCell<GpuTexture>>,
 }
 
+fn swizzle_mask(swizzle: TextureSwizzle) -> [i32; 4] {
+    swizzle.0.map(|source| {
+        (match source {
+            SwizzleSource::Red => glow::RED,
+            SwizzleSource::Green => glow::GREEN,
+            SwizzleSource::Blue => glow::BLUE,
+            SwizzleSource::Alpha => glow::ALPHA,
+            SwizzleSource::Zero => glow::ZERO,
+            SwizzleSource::One => glow::ONE,
+        }) as i32
+    })
+}
+
 #[allow(missing_docs)] // TODO
 pub fn apply_material(ctx: MaterialContext) {
     let built_in_uniforms = &ctx.program_binding.program.built_in_uniform_locations;
@@ -1056,8 +1086,8 @@ pub fn apply_material(ctx: MaterialContext) {
             .set_i32(location, ctx.blend_shapes_weights.len() as i32);
     }
 
-    // Apply material properties.
-    for (name, value) in ctx.material.properties() {
+    // Apply material properties, including the ones inherited from the base material.
+    ctx.material.for_each_resolved_property(|name, value| {
         if let Some(uniform) = ctx.program_binding.uniform_location(name) {
             match value {
                 PropertyValue::Float(v) => {
@@ -1090,18 +1120,28 @@ pub fn apply_material(ctx: MaterialContext) {
                 PropertyValue::Color(v) => {
                     ctx.program_binding.set_srgb_color(&uniform, v);
                 }
+                PropertyValue::ColorArray(v) => {
+                    ctx.program_binding.set_srgb_color_slice(&uniform, v);
+                }
                 PropertyValue::Bool(v) => {
                     ctx.program_binding.set_bool(&uniform, *v);
                 }
-                PropertyValue::Sampler { value, fallback } => {
-                    let texture = value
-                        .as_ref()
-                        .and_then(|t| ctx.texture_cache.get(ctx.program_binding.state, t))
-                        .unwrap_or(match fallback {
-                            SamplerFallback::White => ctx.white_dummy,
-                            SamplerFallback::Normal => ctx.normal_dummy,
-                            SamplerFallback::Black => ctx.black_dummy,
-                        });
+                PropertyValue::Sampler {
+                    value, fallback, ..
+                } => {
+                    // Swizzled textures are separate copies, so the swizzle does not affect other users
+                    // of the texture. Dummy textures are never swizzled.
+                    let swizzle = ctx.material.texture_swizzle(name).map(swizzle_mask);
+                    let texture = value.as_ref().and_then(|t| {
+                        ctx.texture_cache
+                            .get_swizzled(ctx.program_binding.state, t, swizzle)
+                    });
+
+                    let texture = texture.unwrap_or(match fallback {
+                        SamplerFallback::White => ctx.white_dummy,
+                        SamplerFallback::Normal => ctx.normal_dummy,
+                        SamplerFallback::Black => ctx.black_dummy,
+                    });
 
                     ctx.program_binding.set_texture(&uniform, texture);
                 }
@@ -1132,9 +1172,31 @@ pub fn apply_material(ctx: MaterialContext) {
                 PropertyValue::Matrix4Array(v) => {
                     ctx.program_binding.set_matrix4_array(&uniform, v);
                 }
+                PropertyValue::Double(v) => {
+                    warn_double_precision_down_conversion();
+                    ctx.program_binding.set_f32(&uniform, *v as f32);
+                }
+                PropertyValue::DoubleArray(v) => {
+                    warn_double_precision_down_conversion();
+                    let v = v.iter().map(|v| *v as f32).collect::<Vec<_>>();
+                    ctx.program_binding.set_f32_slice(&uniform, &v);
+                }
             }
         }
-    }
+    });
+}
+
+// The OpenGL 3.3 and OpenGL ES 3.0 backends have no double-precision uniforms (`GL_ARB_gpu_shader_fp64`
+// is an optional extension that is not exposed by the framework), so double-precision properties are
+// down-converted to single precision. The warning is logged only once to not flood the log every frame.
+fn warn_double_precision_down_conversion() {
+    static WARNING: std::sync::Once = std::sync::Once::new();
+    WARNING.call_once(|| {
+        Log::warn(
+            "Double-precision material properties are not supported by the GPU, they will be \
+            down-converted to single precision.",
+        )
+    });
 }
 
 impl Renderer {
@@ -1542,6 +1604,19 @@ impl Renderer {
             // Clamp to [1.0; infinity] range.
             .sup(&Vector2::new(1.0, 1.0));
 
+        // Upload only modified parts of terrain textures, it is much faster than uploading the entire
+        // textures when terrains are edited continuously.
+        for terrain in graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Terrain>())
+        {
+            for chunk in terrain.chunks_ref() {
+                for region in chunk.take_dirty_regions() {
+                    self.texture_cache.update_region(&self.state, &region);
+                }
+            }
+        }
+
         let state = &mut self.state;
 
         let scene_associated_data = self
@@ -1925,3 +2000,30 @@ impl Renderer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{material::TextureSwizzle, renderer::swizzle_mask};
+
+    #[test]
+    fn test_swizzle_mask() {
+        assert_eq!(
+            swizzle_mask(TextureSwizzle::parse("gba1").unwrap()),
+            [
+                glow::GREEN as i32,
+                glow::BLUE as i32,
+                glow::ALPHA as i32,
+                glow::ONE as i32
+            ]
+        );
+        assert_eq!(
+            swizzle_mask(TextureSwizzle::parse("r000").unwrap()),
+            [
+                glow::RED as i32,
+                glow::ZERO as i32,
+                glow::ZERO as i32,
+                glow::ZERO as i32
+            ]
+        );
+    }
+}
@@ -279,12 +279,11 @@ impl CsmRenderer {
                     .as_ref()
                     .and_then(|c| c.blend_shape_storage.clone());
 
-                let Some(render_pass) =
-                    shader_cache
-                        .get(state, material.shader())
-                        .and_then(|shader_set| {
-                            shader_set.render_passes.get(&DIRECTIONAL_SHADOW_PASS_NAME)
-                        })
+                let Some(render_pass) = shader_cache
+                    .get_material_permutation(state, material)
+                    .and_then(|shader_set| {
+                        shader_set.render_passes.get(&DIRECTIONAL_SHADOW_PASS_NAME)
+                    })
                 else {
                     continue;
                 };
@@ -296,7 +295,7 @@ impl CsmRenderer {
                         viewport,
                         &render_pass.program,
                         &DrawParameters {
-                            cull_face: Some(CullFace::Back),
+                            cull_face: (!material.is_two_sided()).then_some(CullFace::Back),
                             color_write: ColorMask::all(false),
                             depth_write: true,
                             stencil_test: None,
@@ -13,11 +13,12 @@ use crate::{
             error::FrameworkError,
             framebuffer::{Attachment, AttachmentKind, FrameBuffer},
             gpu_texture::{
-                Coordinate, CubeMapFace, GpuTexture, GpuTextureKind, MagnificationFilter,
+                Coordinate, CubeMapFaceDescriptor, GpuTexture, GpuTextureKind, MagnificationFilter,
                 MinificationFilter, PixelKind, WrapMode,
             },
             state::PipelineState,
         },
+        material_draw_params,
         shadow::cascade_size,
         storage::MatrixStorageCache,
         GeometryCache, MaterialContext, RenderPassStatistics, ShadowMapPrecision,
@@ -32,13 +33,7 @@ pub struct PointShadowMapRenderer {
     precision: ShadowMapPrecision,
     cascades: [FrameBuffer; 3],
     size: usize,
-    faces: [PointShadowCubeMapFace; 6],
-}
-
-struct PointShadowCubeMapFace {
-    face: CubeMapFace,
-    look: Vector3<f32>,
-    up: Vector3<f32>,
+    faces: [CubeMapFaceDescriptor; 6],
 }
 
 pub(crate) struct PointShadowMapRenderContext<'a> {
@@ -137,38 +132,7 @@ impl PointShadowMapRenderer {
                 make_cascade(state, cascade_size(size, 2), precision)?,
             ],
             size,
-            faces: [
-                PointShadowCubeMapFace {
-                    face: CubeMapFace::PositiveX,
-                    look: Vector3::new(1.0, 0.0, 0.0),
-                    up: Vector3::new(0.0, -1.0, 0.0),
-                },
-                PointShadowCubeMapFace {
-                    face: CubeMapFace::NegativeX,
-                    look: Vector3::new(-1.0, 0.0, 0.0),
-                    up: Vector3::new(0.0, -1.0, 0.0),
-                },
-                PointShadowCubeMapFace {
-                    face: CubeMapFace::PositiveY,
-                    look: Vector3::new(0.0, 1.0, 0.0),
-                    up: Vector3::new(0.0, 0.0, 1.0),
-                },
-                PointShadowCubeMapFace {
-                    face: CubeMapFace::NegativeY,
-                    look: Vector3::new(0.0, -1.0, 0.0),
-                    up: Vector3::new(0.0, 0.0, -1.0),
-                },
-                PointShadowCubeMapFace {
-                    face: CubeMapFace::PositiveZ,
-                    look: Vector3::new(0.0, 0.0, 1.0),
-                    up: Vector3::new(0.0, -1.0, 0.0),
-                },
-                PointShadowCubeMapFace {
-                    face: CubeMapFace::NegativeZ,
-                    look: Vector3::new(0.0, 0.0, -1.0),
-                    up: Vector3::new(0.0, -1.0, 0.0),
-                },
-            ],
+            faces: CubeMapFaceDescriptor::cube_faces(),
         })
     }
 
@@ -271,19 +235,21 @@ impl PointShadowMapRenderer {
                     .and_then(|c| c.blend_shape_storage.clone());
 
                 let Some(render_pass) = shader_cache
-                    .get(state, material.shader())
+                    .get_material_permutation(state, material)
                     .and_then(|shader_set| shader_set.render_passes.get(&POINT_SHADOW_PASS_NAME))
                 else {
                     continue;
                 };
 
+                let draw_params = material_draw_params(material, &render_pass.draw_params);
+
                 for instance in bundle.instances.iter() {
                     statistics += framebuffer.draw(
                         geometry,
                         state,
                         viewport,
                         &render_pass.program,
-                        &render_pass.draw_params,
+                        &draw_params,
                         instance.element_range,
                         |mut program_binding| {
                             apply_material(MaterialContext {
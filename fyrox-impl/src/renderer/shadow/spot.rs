@@ -181,7 +181,7 @@ impl SpotShadowMapRenderer {
                 .and_then(|c| c.blend_shape_storage.clone());
 
             let Some(render_pass) = shader_cache
-                .get(state, material.shader())
+                .get_material_permutation(state, material)
                 .and_then(|shader_set| shader_set.render_passes.get(&SPOT_SHADOW_PASS_NAME))
             else {
                 continue;
@@ -194,7 +194,7 @@ impl SpotShadowMapRenderer {
                     viewport,
                     &render_pass.program,
                     &DrawParameters {
-                        cull_face: Some(CullFace::Back),
+                        cull_face: (!material.is_two_sided()).then_some(CullFace::Back),
                         color_write: ColorMask::all(false),
                         depth_write: true,
                         stencil_test: None,
@@ -43,7 +43,10 @@ use crate::{
         base::BaseBuilder,
         graph::Graph,
         mesh::{
-            buffer::{VertexAttributeUsage, VertexBuffer, VertexWriteTrait},
+            buffer::{
+                VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
+                VertexBuffer, VertexTrait, VertexWriteTrait,
+            },
             surface::{
                 BlendShape, BlendShapesContainer, InputBlendShapeData, Surface, SurfaceData,
                 SurfaceResource, VertexWeightSet,
@@ -61,6 +64,7 @@ use crate::{
 use fxhash::{FxHashMap, FxHashSet};
 use fyrox_resource::io::ResourceIo;
 use fyrox_resource::untyped::ResourceKind;
+use lazy_static::lazy_static;
 use std::{cmp::Ordering, path::Path};
 
 /// Input angles in degrees
@@ -152,6 +156,7 @@ struct UnpackedVertex {
     normal: Vector3<f32>,
     tangent: Vector3<f32>,
     uv: Vector2<f32>,
+    color: Vector4<u8>,
     // Set of weights for skinning.
     weights: Option<VertexWeightSet>,
 }
@@ -182,6 +187,72 @@ impl Into<StaticVertex> for UnpackedVertex {
     }
 }
 
+impl UnpackedVertex {
+    fn into_colored<V>(self) -> ColoredVertex<V>
+    where
+        UnpackedVertex: Into<V>,
+    {
+        ColoredVertex {
+            color: self.color,
+            vertex: self.into(),
+        }
+    }
+}
+
+/// A vertex with per-vertex color, that is put at shader location 7, where the standard shaders expect
+/// it. Color is a part of the vertex, so the vertices that differ only by color are not merged.
+#[derive(Copy, Clone, PartialEq, Hash)]
+#[repr(C)]
+struct ColoredVertex<V> {
+    vertex: V,
+    color: Vector4<u8>,
+}
+
+fn colored_layout(layout: &[VertexAttributeDescriptor]) -> Vec<VertexAttributeDescriptor> {
+    layout
+        .iter()
+        .map(|attribute| VertexAttributeDescriptor {
+            usage: attribute.usage,
+            data_type: attribute.data_type,
+            size: attribute.size,
+            divisor: attribute.divisor,
+            shader_location: attribute.shader_location,
+            normalized: attribute.normalized,
+        })
+        .chain(std::iter::once(VertexAttributeDescriptor {
+            usage: VertexAttributeUsage::Color,
+            data_type: VertexAttributeDataType::U8,
+            size: 4,
+            divisor: 0,
+            shader_location: 7,
+            normalized: true,
+        }))
+        .collect()
+}
+
+lazy_static! {
+    static ref COLORED_STATIC_VERTEX_LAYOUT: Vec<VertexAttributeDescriptor> =
+        colored_layout(StaticVertex::layout());
+    static ref COLORED_ANIMATED_VERTEX_LAYOUT: Vec<VertexAttributeDescriptor> =
+        colored_layout(AnimatedVertex::layout());
+}
+
+impl VertexTrait for ColoredVertex<StaticVertex> {
+    fn layout() -> &'static [VertexAttributeDescriptor] {
+        &COLORED_STATIC_VERTEX_LAYOUT
+    }
+}
+
+impl VertexTrait for ColoredVertex<AnimatedVertex> {
+    fn layout() -> &'static [VertexAttributeDescriptor] {
+        &COLORED_ANIMATED_VERTEX_LAYOUT
+    }
+}
+
+fn color_to_u8(color: Vector4<f32>) -> Vector4<u8> {
+    color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
 fn convert_vertex(
     geom: &FbxMeshGeometry,
     geometric_transform: &Matrix4<f32>,
@@ -207,6 +278,11 @@ fn convert_vertex(
         None => Vector2::default(),
     };
 
+    let color = match geom.colors.as_ref() {
+        Some(colors) => color_to_u8(*colors.get(index, index_in_polygon)?),
+        None => Vector4::repeat(u8::MAX),
+    };
+
     let material = match geom.materials.as_ref() {
         Some(materials) => *materials.get(material_index, index_in_polygon)?,
         None => 0,
@@ -219,6 +295,7 @@ fn convert_vertex(
         normal: geometric_transform.transform_vector(&normal),
         tangent: geometric_transform.transform_vector(&tangent),
         uv: Vector2::new(uv.x, 1.0 - uv.y), // Invert Y because OpenGL has origin at left *bottom* corner.
+        color,
         surface_index: material as usize,
         weights: if geom.deformers.is_empty() {
             None
@@ -232,13 +309,44 @@ fn convert_vertex(
 enum FbxMeshBuilder {
     Static(RawMeshBuilder<StaticVertex>),
     Animated(RawMeshBuilder<AnimatedVertex>),
+    ColoredStatic(RawMeshBuilder<ColoredVertex<StaticVertex>>),
+    ColoredAnimated(RawMeshBuilder<ColoredVertex<AnimatedVertex>>),
 }
 
 impl FbxMeshBuilder {
+    fn new(geom: &FbxMeshGeometry) -> Self {
+        match (geom.deformers.is_empty(), geom.colors.is_some()) {
+            (true, false) => FbxMeshBuilder::Static(RawMeshBuilder::new(1024, 1024)),
+            (false, false) => FbxMeshBuilder::Animated(RawMeshBuilder::new(1024, 1024)),
+            (true, true) => FbxMeshBuilder::ColoredStatic(RawMeshBuilder::new(1024, 1024)),
+            (false, true) => FbxMeshBuilder::ColoredAnimated(RawMeshBuilder::new(1024, 1024)),
+        }
+    }
+
+    fn vertex_count(&self) -> usize {
+        match self {
+            FbxMeshBuilder::Static(builder) => builder.vertex_count(),
+            FbxMeshBuilder::Animated(builder) => builder.vertex_count(),
+            FbxMeshBuilder::ColoredStatic(builder) => builder.vertex_count(),
+            FbxMeshBuilder::ColoredAnimated(builder) => builder.vertex_count(),
+        }
+    }
+
+    fn insert(&mut self, vertex: UnpackedVertex) -> bool {
+        match self {
+            FbxMeshBuilder::Static(builder) => builder.insert(vertex.into()),
+            FbxMeshBuilder::Animated(builder) => builder.insert(vertex.into()),
+            FbxMeshBuilder::ColoredStatic(builder) => builder.insert(vertex.into_colored()),
+            FbxMeshBuilder::ColoredAnimated(builder) => builder.insert(vertex.into_colored()),
+        }
+    }
+
     fn build(self) -> SurfaceData {
         match self {
             FbxMeshBuilder::Static(builder) => SurfaceData::from_raw_mesh(builder.build()),
             FbxMeshBuilder::Animated(builder) => SurfaceData::from_raw_mesh(builder.build()),
+            FbxMeshBuilder::ColoredStatic(builder) => SurfaceData::from_raw_mesh(builder.build()),
+            FbxMeshBuilder::ColoredAnimated(builder) => SurfaceData::from_raw_mesh(builder.build()),
         }
     }
 }
@@ -413,6 +521,7 @@ async fn create_surfaces(
                                 PropertyValue::Sampler {
                                     value: Some(texture),
                                     fallback: usage,
+                                    priority: Default::default(),
                                 },
                             ) {
                                 Log::writeln(
@@ -484,11 +593,7 @@ async fn convert_mesh(
 
         let mut data_set = vec![
             FbxSurfaceData {
-                base_mesh_builder: if geom.deformers.is_empty() {
-                    FbxMeshBuilder::Static(RawMeshBuilder::new(1024, 1024))
-                } else {
-                    FbxMeshBuilder::Animated(RawMeshBuilder::new(1024, 1024))
-                },
+                base_mesh_builder: FbxMeshBuilder::new(geom),
                 blend_shapes: blend_shapes
                     .iter()
                     .map(|bs_channel| {
@@ -530,17 +635,8 @@ async fn convert_mesh(
                     )?;
                     let data = data_set.get_mut(vertex.surface_index).unwrap();
                     let weights = vertex.weights;
-                    let final_index;
-                    let is_unique_vertex = match data.base_mesh_builder {
-                        FbxMeshBuilder::Static(ref mut builder) => {
-                            final_index = builder.vertex_count();
-                            builder.insert(vertex.clone().into())
-                        }
-                        FbxMeshBuilder::Animated(ref mut builder) => {
-                            final_index = builder.vertex_count();
-                            builder.insert(vertex.clone().into())
-                        }
-                    };
+                    let final_index = data.base_mesh_builder.vertex_count();
+                    let is_unique_vertex = data.base_mesh_builder.insert(vertex);
                     if is_unique_vertex {
                         if let Some(skin_data) = weights {
                             data.skin_data.push(skin_data);
@@ -958,3 +1054,58 @@ pub async fn load_to_scene<P: AsRef<Path>>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3, Vector4},
+        resource::fbx::{ColoredVertex, UnpackedVertex},
+        scene::mesh::{
+            buffer::{VertexAttributeUsage, VertexTrait},
+            vertex::{AnimatedVertex, StaticVertex},
+        },
+        utils::raw_mesh::RawMeshBuilder,
+    };
+
+    fn layout_size<T: VertexTrait>() -> usize {
+        T::layout()
+            .iter()
+            .map(|a| a.size as usize * a.data_type.size() as usize)
+            .sum()
+    }
+
+    #[test]
+    fn test_colored_vertex_layout() {
+        assert_eq!(
+            layout_size::<ColoredVertex<StaticVertex>>(),
+            std::mem::size_of::<ColoredVertex<StaticVertex>>()
+        );
+        assert_eq!(
+            layout_size::<ColoredVertex<AnimatedVertex>>(),
+            std::mem::size_of::<ColoredVertex<AnimatedVertex>>()
+        );
+
+        let color = ColoredVertex::<StaticVertex>::layout().last().unwrap();
+        assert_eq!(color.usage, VertexAttributeUsage::Color);
+        assert_eq!(color.shader_location, 7);
+    }
+
+    #[test]
+    fn test_colored_vertices_are_not_merged() {
+        let vertex = |color| UnpackedVertex {
+            surface_index: 0,
+            position: Vector3::default(),
+            normal: Vector3::y(),
+            tangent: Vector3::x(),
+            uv: Vector2::default(),
+            color,
+            weights: None,
+        };
+
+        let mut builder = RawMeshBuilder::<ColoredVertex<StaticVertex>>::new(3, 3);
+        assert!(builder.insert(vertex(Vector4::new(255, 0, 0, 255)).into_colored()));
+        assert!(builder.insert(vertex(Vector4::new(0, 255, 0, 255)).into_colored()));
+        assert!(!builder.insert(vertex(Vector4::new(255, 0, 0, 255)).into_colored()));
+        assert_eq!(builder.vertex_count(), 2);
+    }
+}
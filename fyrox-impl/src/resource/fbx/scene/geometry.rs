@@ -1,7 +1,7 @@
 use crate::resource::fbx::scene::FbxBlendShapeChannel;
 use crate::{
     core::{
-        algebra::{Vector2, Vector3},
+        algebra::{Vector2, Vector3, Vector4},
         pool::Handle,
     },
     resource::fbx::{
@@ -23,6 +23,7 @@ pub struct FbxMeshGeometry {
     pub uvs: Option<FbxLayerElement<Vector2<f32>>>,
     pub materials: Option<FbxLayerElement<i32>>,
     pub tangents: Option<FbxLayerElement<Vector3<f32>>>,
+    pub colors: Option<FbxLayerElement<Vector4<f32>>>,
     #[allow(dead_code)] // TODO: Use binormals.
     pub binormals: Option<FbxLayerElement<Vector3<f32>>>,
 
@@ -128,6 +129,33 @@ fn read_uvs(
     }
 }
 
+fn read_colors(
+    geom_node_handle: Handle<FbxNode>,
+    nodes: &FbxNodeContainer,
+) -> Result<Option<FbxLayerElement<Vector4<f32>>>, FbxError> {
+    if let Ok(layer_element_color) = nodes.find(geom_node_handle, "LayerElementColor") {
+        Ok(Some(FbxLayerElement::new(
+            nodes,
+            layer_element_color,
+            "Colors",
+            |attributes| {
+                let mut colors = Vec::with_capacity(attributes.len() / 4);
+                for color in attributes.chunks_exact(4) {
+                    colors.push(Vector4::new(
+                        color[0].as_f32()?,
+                        color[1].as_f32()?,
+                        color[2].as_f32()?,
+                        color[3].as_f32()?,
+                    ));
+                }
+                Ok(colors)
+            },
+        )?))
+    } else {
+        Ok(None)
+    }
+}
+
 fn read_materials(
     geom_node_handle: Handle<FbxNode>,
     nodes: &FbxNodeContainer,
@@ -164,6 +192,7 @@ impl FbxMeshGeometry {
             uvs: read_uvs(geom_node_handle, nodes)?,
             materials: read_materials(geom_node_handle, nodes)?,
             tangents: read_tangents(geom_node_handle, nodes)?,
+            colors: read_colors(geom_node_handle, nodes)?,
             binormals: read_binormals(geom_node_handle, nodes)?,
             deformers: Vec::new(),
         })
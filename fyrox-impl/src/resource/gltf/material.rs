@@ -287,6 +287,7 @@ fn set_texture(
         PropertyValue::Sampler {
             value: Some(tex),
             fallback,
+            priority: Default::default(),
         },
     ) {
         Ok(()) => Ok(()),
@@ -4,8 +4,8 @@ use crate::core::math::Vector3Ext;
 use crate::fxhash::FxHashMap;
 use crate::scene::mesh;
 use crate::scene::mesh::buffer::{
-    self, TriangleBuffer, ValidationError, VertexAttributeUsage, VertexBuffer, VertexReadTrait,
-    VertexTrait,
+    self, TriangleBuffer, ValidationError, VertexAttributeDataType, VertexAttributeDescriptor,
+    VertexAttributeUsage, VertexBuffer, VertexReadTrait, VertexTrait,
 };
 use crate::scene::mesh::surface::{InputBlendShapeData, SurfaceData};
 use crate::scene::mesh::vertex::{AnimatedVertex, SimpleVertex, StaticVertex};
@@ -394,16 +394,47 @@ where
     F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>,
 {
     let reader = primitive.reader(get_buffer_data.clone());
-    if reader.read_weights(0).is_some() {
+    let mut vertex_buffer = if reader.read_weights(0).is_some() {
         let vs: Vec<AnimatedVertex> = AnimatedVertex::convert(primitive, get_buffer_data)?;
-        Ok(VertexBuffer::new(vs.len(), vs)?)
+        VertexBuffer::new(vs.len(), vs)?
     } else if reader.read_normals().is_some() {
         let vs: Vec<StaticVertex> = StaticVertex::convert(primitive, get_buffer_data)?;
-        Ok(VertexBuffer::new(vs.len(), vs)?)
+        VertexBuffer::new(vs.len(), vs)?
     } else {
         let vs: Vec<SimpleVertex> = SimpleVertex::convert(primitive, get_buffer_data)?;
-        Ok(VertexBuffer::new(vs.len(), vs)?)
+        VertexBuffer::new(vs.len(), vs)?
+    };
+    if let Some(colors) = reader.read_colors(0) {
+        add_vertex_colors(&mut vertex_buffer, colors.into_rgba_u8())?;
+    }
+    Ok(vertex_buffer)
+}
+
+/// Puts per-vertex colors at shader location 7, where the standard shaders expect them.
+fn add_vertex_colors<I>(vertex_buffer: &mut VertexBuffer, colors: I) -> Result<()>
+where
+    I: Iterator<Item = [u8; 4]>,
+{
+    vertex_buffer.modify().add_attribute(
+        VertexAttributeDescriptor {
+            usage: VertexAttributeUsage::Color,
+            data_type: VertexAttributeDataType::U8,
+            size: 4,
+            divisor: 0,
+            shader_location: 7,
+            normalized: true,
+        },
+        Vector4::<u8>::repeat(u8::MAX),
+    )?;
+    if let Some(view) = vertex_buffer.attribute_view_mut::<Vector4<u8>>(VertexAttributeUsage::Color)
+    {
+        for (i, color) in colors.enumerate() {
+            if let Some(vertex_color) = view.get(i) {
+                *vertex_color = Vector4::from(color);
+            }
+        }
     }
+    Ok(())
 }
 
 trait GltfVertexConvert: VertexTrait {
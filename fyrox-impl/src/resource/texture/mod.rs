@@ -981,6 +981,42 @@ impl TexturePixelKind {
             | Self::RG8RGTC => None,
         }
     }
+
+    /// Returns amount of color channels of the pixel.
+    pub fn channel_count(&self) -> usize {
+        match self {
+            Self::R8
+            | Self::R16
+            | Self::R8RGTC
+            | Self::Luminance8
+            | Self::Luminance16
+            | Self::R32F
+            | Self::R16F => 1,
+            Self::RG8
+            | Self::RG16
+            | Self::RG8RGTC
+            | Self::LuminanceAlpha8
+            | Self::LuminanceAlpha16 => 2,
+            Self::RGB8 | Self::BGR8 | Self::RGB16 | Self::DXT1RGB | Self::RGB32F | Self::RGB16F => {
+                3
+            }
+            Self::RGBA8
+            | Self::BGRA8
+            | Self::RGBA16
+            | Self::DXT1RGBA
+            | Self::DXT3RGBA
+            | Self::DXT5RGBA
+            | Self::RGBA32F => 4,
+        }
+    }
+
+    /// Returns `true` if the pixel stores floating-point values, `false` - otherwise.
+    pub fn is_floating_point(&self) -> bool {
+        matches!(
+            self,
+            Self::RGB32F | Self::RGBA32F | Self::RGB16F | Self::R32F | Self::R16F
+        )
+    }
 }
 
 /// An error that may occur during texture operations.
@@ -97,15 +97,55 @@ pub mod prelude {
 
 /// Extension trait for [`LayerMask`].
 pub trait LayerMaskExt {
-    /// Creates a layer mask for every descendant node starting from specified `root` (included). It could
-    /// be useful if you have an entire node hierarchy (for example, lower part of a body) that needs to
-    /// be filtered out.
-    fn from_hierarchy(graph: &Graph, root: Handle<Node>) -> Self;
+    /// Creates a layer mask for every descendant node starting from specified `root`. The root itself is
+    /// added to the mask only if `include_root` is `true`. It could be useful if you have an entire node
+    /// hierarchy (for example, lower part of a body) that needs to be filtered out.
+    ///
+    /// ```rust
+    /// # use fyrox_impl::{
+    /// #     core::pool::Handle,
+    /// #     scene::{animation::absm::{LayerMask, LayerMaskExt}, graph::Graph, node::Node},
+    /// # };
+    /// fn upper_body_mask(graph: &Graph, hips: Handle<Node>, spine: Handle<Node>) -> LayerMask {
+    ///     // Exclude everything (legs included) except the spine and its descendants.
+    ///     let mut mask = LayerMask::from_hierarchy(graph, hips, true);
+    ///     mask.include_subtree(graph, spine);
+    ///     mask
+    /// }
+    /// ```
+    fn from_hierarchy(graph: &Graph, root: Handle<Node>, include_root: bool) -> Self;
+
+    /// Adds every node of the hierarchy starting from specified `root` (included) to the mask, so the nodes
+    /// will not be animated. Handles that are already in the mask are ignored.
+    fn exclude_subtree(&mut self, graph: &Graph, root: Handle<Node>);
+
+    /// Removes every node of the hierarchy starting from specified `root` (included) from the mask, so the
+    /// nodes will be animated again.
+    fn include_subtree(&mut self, graph: &Graph, root: Handle<Node>);
 }
 
 impl LayerMaskExt for LayerMask {
-    fn from_hierarchy(graph: &Graph, root: Handle<Node>) -> Self {
-        Self::from(graph.traverse_handle_iter(root).collect::<Vec<_>>())
+    fn from_hierarchy(graph: &Graph, root: Handle<Node>, include_root: bool) -> Self {
+        Self::from(
+            graph
+                .traverse_handle_iter(root)
+                .filter(|handle| include_root || *handle != root)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn exclude_subtree(&mut self, graph: &Graph, root: Handle<Node>) {
+        for handle in graph.traverse_handle_iter(root) {
+            if !self.contains(handle) {
+                self.add(handle);
+            }
+        }
+    }
+
+    fn include_subtree(&mut self, graph: &Graph, root: Handle<Node>) {
+        for handle in graph.traverse_handle_iter(root) {
+            self.remove(handle);
+        }
     }
 }
 
@@ -129,6 +129,7 @@ impl Hash for RectangleVertex {
 ///             PropertyValue::Sampler {
 ///                 value: texture,
 ///                 fallback: SamplerFallback::White,
+///                 priority: Default::default(),
 ///             },
 ///         )
 ///         // This could fail, if you have a custom material without diffuseTexture property.
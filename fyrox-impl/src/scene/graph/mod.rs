@@ -699,6 +699,7 @@ impl Graph {
                             PropertyValue::Sampler {
                                 value: Some(texture),
                                 fallback: SamplerFallback::Black,
+                                priority: Default::default(),
                             },
                         ) {
                             Log::writeln(
@@ -763,6 +764,7 @@ impl Graph {
                                 PropertyValue::Sampler {
                                     value: entry.texture.clone(),
                                     fallback: SamplerFallback::Black,
+                                    priority: Default::default(),
                                 },
                             ) {
                                 Log::writeln(
@@ -993,6 +995,7 @@ impl Graph {
 
         self.performance_statistics.sound_update_time =
             self.sound_context.state().full_render_duration();
+        self.sound_context.native.dispatch_finished_callbacks();
 
         if let Some(overrides) = switches.node_overrides.as_ref() {
             for handle in overrides {
@@ -46,7 +46,10 @@ use rapier3d::{
         Collider, ColliderBuilder, ColliderHandle, ColliderSet, Cuboid, InteractionGroups,
         NarrowPhase, Ray, SharedShape,
     },
-    parry::query::ShapeCastOptions,
+    parry::{
+        query::ShapeCastOptions,
+        shape::{HeightField, HeightFieldCellStatus},
+    },
     pipeline::{DebugRenderPipeline, EventHandler, PhysicsPipeline, QueryPipeline},
     prelude::JointAxis,
 };
@@ -632,18 +635,26 @@ fn make_heightfield(terrain: &Terrain) -> SharedShape {
     let ncols = height_map_size.x * terrain.width_chunks().len() as u32;
 
     // Combine height map of each chunk into bigger one.
+    let heightfield_data = terrain.build_heightfield_collider();
     let mut ox = 0;
     let mut oz = 0;
     let mut data = vec![0.0; (nrows * ncols) as usize];
+    let mut removed_cells = Vec::new();
     for cz in 0..terrain.length_chunks().len() {
         for cx in 0..terrain.width_chunks().len() {
-            let chunk = &terrain.chunks_ref()[cz * terrain.width_chunks().len() + cx];
-            let texture = chunk.heightmap().data_ref();
-            let height_map = texture.data_of_type::<f32>().unwrap();
+            let chunk = &heightfield_data.chunks[cz * terrain.width_chunks().len() + cx];
             for iy in 0..height_map_size.y {
                 for ix in 0..height_map_size.x {
-                    let value = height_map[(iy * height_map_size.x + ix) as usize] * scale.y;
+                    let value = chunk.heights[(iy * height_map_size.x + ix) as usize] * scale.y;
                     data[((ox + ix) * nrows + oz + iy) as usize] = value;
+
+                    // Holes are cut out of the collider by removing the cells around them.
+                    if ix + 1 < height_map_size.x
+                        && iy + 1 < height_map_size.y
+                        && chunk.is_cell_removed(Vector2::new(ix, iy))
+                    {
+                        removed_cells.push(((oz + iy) as usize, (ox + ix) as usize));
+                    }
                 }
             }
 
@@ -654,7 +665,7 @@ fn make_heightfield(terrain: &Terrain) -> SharedShape {
         oz += height_map_size.y;
     }
 
-    SharedShape::heightfield(
+    let mut heightfield = HeightField::new(
         DMatrix::from_data(VecStorage::new(
             Dyn(nrows as usize),
             Dyn(ncols as usize),
@@ -665,7 +676,12 @@ fn make_heightfield(terrain: &Terrain) -> SharedShape {
             1.0,
             terrain.chunk_size().y * scale.z * terrain.length_chunks().len() as f32,
         ),
-    )
+    );
+    for (i, j) in removed_cells {
+        heightfield.set_cell_status(i, j, HeightFieldCellStatus::CELL_REMOVED);
+    }
+
+    SharedShape::new(heightfield)
 }
 
 // Converts descriptor in a shared shape.
@@ -171,6 +171,7 @@ impl Visit for ParticleSystemRng {
 ///                         .request::<Texture>(Path::new("data/particles/smoke_04.tga")),
 ///                 ),
 ///                 fallback: Default::default(),
+///                 priority: Default::default(),
 ///             },
 ///         )
 ///         .unwrap();
@@ -2,6 +2,7 @@
 
 use crate::{
     core::{
+        algebra::Vector3,
         log::{Log, MessageKind},
         pool::Handle,
         visitor::prelude::*,
@@ -11,7 +12,7 @@ use crate::{
 use fxhash::FxHashSet;
 use fyrox_sound::{
     bus::AudioBusGraph,
-    context::DistanceModel,
+    context::{DistanceModel, OutputTapCallback, OutputTapId, VoiceStealingPolicy},
     renderer::Renderer,
     source::{SoundSource, SoundSourceBuilder, Status},
 };
@@ -60,6 +61,38 @@ impl<'a> SoundContextGuard<'a> {
         self.guard.distance_model()
     }
 
+    /// Sets maximum amount of simultaneously playing sounds. See [`fyrox_sound::context::State::set_max_voices`]
+    /// for more info.
+    pub fn set_max_voices(&mut self, max_voices: Option<usize>) {
+        self.guard.set_max_voices(max_voices);
+    }
+
+    /// Returns maximum amount of simultaneously playing sounds.
+    pub fn max_voices(&self) -> Option<usize> {
+        self.guard.max_voices()
+    }
+
+    /// Sets new voice stealing policy. See [`VoiceStealingPolicy`] docs for more info.
+    pub fn set_voice_stealing_policy(&mut self, policy: VoiceStealingPolicy) {
+        self.guard.set_voice_stealing_policy(policy);
+    }
+
+    /// Returns current voice stealing policy.
+    pub fn voice_stealing_policy(&self) -> VoiceStealingPolicy {
+        self.guard.voice_stealing_policy()
+    }
+
+    /// Adds a new output tap, that receives the final mix of the context. See
+    /// [`fyrox_sound::context::State::add_output_tap`] for more info and the callback contract.
+    pub fn add_output_tap(&mut self, callback: OutputTapCallback) -> OutputTapId {
+        self.guard.add_output_tap(callback)
+    }
+
+    /// Removes an output tap with the given identifier.
+    pub fn remove_output_tap(&mut self, id: OutputTapId) -> bool {
+        self.guard.remove_output_tap(id)
+    }
+
     /// Normalizes given frequency using context's sampling rate. Normalized frequency then can be used
     /// to create filters.
     pub fn normalize_frequency(&self, f: f32) -> f32 {
@@ -91,6 +124,13 @@ impl<'a> SoundContextGuard<'a> {
         self.guard.set_renderer(renderer)
     }
 
+    /// Sets new renderer and smoothly crossfades the output of the previous renderer with the output of
+    /// the new one during the given time. See [`fyrox_sound::context::State::set_renderer_with_crossfade`]
+    /// for more info.
+    pub fn set_renderer_with_crossfade(&mut self, renderer: Renderer, duration: Duration) {
+        self.guard.set_renderer_with_crossfade(renderer, duration)
+    }
+
     /// Destroys all backing sound entities.
     pub fn destroy_sound_sources(&mut self) {
         self.guard.sources_mut().clear();
@@ -139,9 +179,10 @@ impl SoundContext {
         }
     }
 
-    pub(crate) fn set_sound_position(&mut self, sound: &Sound) {
+    pub(crate) fn set_sound_position(&mut self, sound: &Sound, position: Vector3<f32>) {
         if let Some(source) = self.native.state().try_get_source_mut(sound.native.get()) {
-            source.set_position(sound.global_position());
+            source.set_position(position);
+            source.set_velocity(sound.velocity());
         }
     }
 
@@ -216,6 +257,12 @@ impl SoundContext {
             sound.audio_bus.try_sync_model(|audio_bus| {
                 source.set_bus(audio_bus);
             });
+            sound.priority.try_sync_model(|v| {
+                source.set_priority(v);
+            });
+            sound.reverb_send.try_sync_model(|v| {
+                source.set_reverb_send(v);
+            });
         } else {
             match SoundSourceBuilder::new()
                 .with_gain(sound.gain())
@@ -226,10 +273,13 @@ impl SoundContext {
                 .with_status(sound.status())
                 .with_playback_time(Duration::from_secs_f32(sound.playback_time()))
                 .with_position(sound.global_position())
+                .with_velocity(sound.velocity())
                 .with_radius(sound.radius())
                 .with_max_distance(sound.max_distance())
                 .with_bus(sound.audio_bus())
                 .with_rolloff_factor(sound.rolloff_factor())
+                .with_priority(sound.priority())
+                .with_reverb_send(sound.reverb_send())
                 .build()
             {
                 Ok(source) => {
@@ -254,3 +304,50 @@ impl SoundContext {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::Handle},
+        scene::{
+            base::BaseBuilder,
+            node::Node,
+            sound::{context::SoundContext, Sound, SoundBuilder},
+        },
+    };
+
+    fn make_sound() -> Node {
+        let mut node = SoundBuilder::new(BaseBuilder::new()).build_node();
+        node.cast_mut::<Sound>().unwrap().velocity = Vector3::new(1.0, 2.0, 3.0);
+        node
+    }
+
+    #[test]
+    fn test_native_source_is_created_with_velocity() {
+        let mut context = SoundContext::new();
+        let node = make_sound();
+        let sound = node.cast::<Sound>().unwrap();
+
+        context.sync_to_sound(Handle::NONE, sound, None);
+
+        let state = context.native.state();
+        let source = state.source(sound.native.get());
+        assert_eq!(source.velocity(), Vector3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_set_sound_position_syncs_velocity() {
+        let mut context = SoundContext::new();
+        let mut node = make_sound();
+        context.sync_to_sound(Handle::NONE, node.cast::<Sound>().unwrap(), None);
+
+        let sound = node.cast_mut::<Sound>().unwrap();
+        sound.velocity = Vector3::new(-4.0, 0.0, 5.0);
+        context.set_sound_position(sound, Vector3::new(1.0, 1.0, 1.0));
+
+        let state = context.native.state();
+        let source = state.source(sound.native.get());
+        assert_eq!(source.position(), Vector3::new(1.0, 1.0, 1.0));
+        assert_eq!(source.velocity(), Vector3::new(-4.0, 0.0, 5.0));
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     core::{
-        algebra::Matrix4,
+        algebra::{Matrix4, Vector3},
         math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq},
         pool::Handle,
         reflect::prelude::*,
@@ -26,7 +26,7 @@ pub use fyrox_sound::{
         DataSource, SoundBuffer, SoundBufferResource, SoundBufferResourceLoadError,
     },
     bus::*,
-    context::{DistanceModel, SAMPLE_RATE},
+    context::{DistanceModel, VoiceStealingPolicy, SAMPLE_RATE},
     dsp::{filters::*, DelayLine},
     effects::*,
     engine::SoundEngine,
@@ -103,6 +103,35 @@ pub struct Sound {
     )]
     audio_bus: InheritableVariable<String>,
 
+    #[visit(optional)]
+    #[reflect(setter = "set_priority")]
+    priority: InheritableVariable<i32>,
+
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_reverb_send",
+        min_value = 0.0,
+        max_value = 1.0,
+        step = 0.05,
+        description = "An amount of the signal of the sound, that will be sent to the reverb audio bus."
+    )]
+    reverb_send: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(
+        setter = "set_follow",
+        description = "A node, which position will be used as the position of the sound."
+    )]
+    follow: InheritableVariable<Handle<Node>>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    velocity: Vector3<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    last_follow_position: Option<Vector3<f32>>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) native: Cell<Handle<SoundSource>>,
@@ -139,6 +168,11 @@ impl Default for Sound {
             playback_time: Default::default(),
             spatial_blend: InheritableVariable::new_modified(1.0),
             audio_bus: InheritableVariable::new_modified(AudioBusGraph::PRIMARY_BUS.to_string()),
+            priority: InheritableVariable::new_modified(0),
+            reverb_send: InheritableVariable::new_modified(0.0),
+            follow: InheritableVariable::new_modified(Handle::NONE),
+            velocity: Default::default(),
+            last_follow_position: None,
             native: Default::default(),
         }
     }
@@ -161,6 +195,11 @@ impl Clone for Sound {
             playback_time: self.playback_time.clone(),
             spatial_blend: self.spatial_blend.clone(),
             audio_bus: self.audio_bus.clone(),
+            priority: self.priority.clone(),
+            reverb_send: self.reverb_send.clone(),
+            follow: self.follow.clone(),
+            velocity: self.velocity,
+            last_follow_position: self.last_follow_position,
             // Do not copy. The copy will have its own native representation.
             native: Default::default(),
         }
@@ -204,6 +243,31 @@ impl Sound {
         *self.play_once
     }
 
+    /// Sets priority of the sound. Sounds with lower priority are stopped first when the amount of
+    /// playing sounds exceeds the voice budget of the sound context. See
+    /// [`context::SoundContextGuard::set_max_voices`] for more info.
+    pub fn set_priority(&mut self, priority: i32) -> i32 {
+        self.priority.set_value_and_mark_modified(priority)
+    }
+
+    /// Returns priority of the sound.
+    pub fn priority(&self) -> i32 {
+        *self.priority
+    }
+
+    /// Sets the amount of the signal of the sound, that will be sent to the reverb audio bus. The dry
+    /// signal always goes to the audio bus of the sound. See [`SoundSource::set_reverb_send`] for more
+    /// info.
+    pub fn set_reverb_send(&mut self, amount: f32) -> f32 {
+        self.reverb_send
+            .set_value_and_mark_modified(amount.clamp(0.0, 1.0))
+    }
+
+    /// Returns the amount of the signal of the sound, that will be sent to the reverb audio bus.
+    pub fn reverb_send(&self) -> f32 {
+        *self.reverb_send
+    }
+
     /// Sets spatial blend factor. It defines how much the source will be 2D and 3D sound at the same
     /// time. Set it to 0.0 to make the sound fully 2D and 1.0 to make it fully 3D. Middle values
     /// will make sound proportionally 2D and 3D at the same time.
@@ -363,6 +427,66 @@ impl Sound {
     pub fn audio_bus(&self) -> &str {
         &self.audio_bus
     }
+
+    /// Sets a node, which the sound should follow. Every frame the position of the sound will be taken
+    /// from the global position of the node, instead of the sound's own transform. It allows to attach
+    /// a sound to a node without making the sound a child of the node (for example, when the node is
+    /// an instance of a prefab that should not be modified). [`Handle::NONE`] disables following.
+    ///
+    /// # Velocity
+    ///
+    /// While following a node, velocity of the sound is calculated from the difference of positions
+    /// of the node between frames, see [`Self::velocity`].
+    ///
+    /// # Removal of the followed node
+    ///
+    /// If the followed node is removed from the graph, the sound stops following it: the handle is
+    /// reset to [`Handle::NONE`], the velocity is reset to zero and the sound returns to the position
+    /// defined by its own transform. The sound itself is not stopped or removed.
+    pub fn set_follow(&mut self, node: Handle<Node>) -> Handle<Node> {
+        self.last_follow_position = None;
+        self.velocity = Vector3::default();
+        self.follow.set_value_and_mark_modified(node)
+    }
+
+    /// Returns a handle of a node, which the sound follows. See [`Self::set_follow`] for more info.
+    pub fn follow(&self) -> Handle<Node> {
+        *self.follow
+    }
+
+    /// Returns velocity (in units per second) of the sound, calculated from the movement of the
+    /// followed node (see [`Self::set_follow`]). It is always zero if the sound does not follow any
+    /// node. The velocity could be used to implement doppler effect, for example, by modifying pitch
+    /// of the sound.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    fn update_follow(&mut self, context: &mut UpdateContext) {
+        if self.follow.is_none() {
+            return;
+        }
+
+        match context.nodes.try_borrow(*self.follow) {
+            Some(node) => {
+                let position = node.global_position();
+                if let Some(last_position) = self.last_follow_position {
+                    if context.dt > 0.0 {
+                        self.velocity = (position - last_position).scale(1.0 / context.dt);
+                    }
+                }
+                self.last_follow_position = Some(position);
+                context.sound_context.set_sound_position(self, position);
+            }
+            None => {
+                // The followed node was removed, fall back to the own transform.
+                self.set_follow(Handle::NONE);
+                context
+                    .sound_context
+                    .set_sound_position(self, self.global_position());
+            }
+        }
+    }
 }
 
 impl NodeTrait for Sound {
@@ -397,8 +521,12 @@ impl NodeTrait for Sound {
     }
 
     fn sync_transform(&self, new_global_transform: &Matrix4<f32>, context: &mut SyncContext) {
-        if !m4x4_approx_eq(new_global_transform, &self.global_transform()) {
-            context.sound_context.set_sound_position(self);
+        // Position of a sound, that follows some node, is updated in `update`.
+        if self.follow.is_none() && !m4x4_approx_eq(new_global_transform, &self.global_transform())
+        {
+            context
+                .sound_context
+                .set_sound_position(self, self.global_position());
         }
     }
 
@@ -411,6 +539,7 @@ impl NodeTrait for Sound {
     }
 
     fn update(&mut self, context: &mut UpdateContext) {
+        self.update_follow(context);
         context.sound_context.sync_with_sound(self);
     }
 
@@ -452,6 +581,9 @@ pub struct SoundBuilder {
     playback_time: Duration,
     spatial_blend: f32,
     audio_bus: String,
+    priority: i32,
+    reverb_send: f32,
+    follow: Handle<Node>,
 }
 
 impl SoundBuilder {
@@ -472,6 +604,9 @@ impl SoundBuilder {
             spatial_blend: 1.0,
             playback_time: Default::default(),
             audio_bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            priority: 0,
+            reverb_send: 0.0,
+            follow: Handle::NONE,
         }
     }
 
@@ -540,6 +675,21 @@ impl SoundBuilder {
         fn with_audio_bus(audio_bus: String)
     );
 
+    define_with!(
+        /// Sets desired priority. See [`Sound::set_priority`] for more info.
+        fn with_priority(priority: i32)
+    );
+
+    define_with!(
+        /// Sets desired reverb send amount. See [`Sound::set_reverb_send`] for more info.
+        fn with_reverb_send(reverb_send: f32)
+    );
+
+    define_with!(
+        /// Sets a node to follow. See [`Sound::set_follow`] for more info.
+        fn with_follow(follow: Handle<Node>)
+    );
+
     /// Creates a new [`Sound`] node.
     #[must_use]
     pub fn build_sound(self) -> Sound {
@@ -558,6 +708,11 @@ impl SoundBuilder {
             playback_time: self.playback_time.as_secs_f32().into(),
             spatial_blend: self.spatial_blend.into(),
             audio_bus: self.audio_bus.into(),
+            priority: self.priority.into(),
+            reverb_send: self.reverb_send.clamp(0.0, 1.0).into(),
+            follow: self.follow.into(),
+            velocity: Default::default(),
+            last_follow_position: None,
             native: Default::default(),
         }
     }
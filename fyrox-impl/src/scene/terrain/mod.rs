@@ -6,10 +6,14 @@ use crate::scene::node::RdcControlFlow;
 use crate::{
     asset::Resource,
     core::{
-        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        algebra::{Matrix4, Point3, Rotation2, Vector2, Vector3, Vector4},
         arrayvec::ArrayVec,
         log::Log,
-        math::{aabb::AxisAlignedBoundingBox, ray::Ray, ray_rect_intersection, Rect},
+        math::{
+            aabb::AxisAlignedBoundingBox, curve::Curve, ray::Ray, ray_rect_intersection,
+            Matrix4Ext, Rect,
+        },
+        parking_lot::Mutex,
         pool::Handle,
         reflect::prelude::*,
         sstorage::ImmutableString,
@@ -18,7 +22,7 @@ use crate::{
         visitor::{prelude::*, PodVecView},
         TypeUuidProvider,
     },
-    material::{Material, MaterialResource, PropertyValue},
+    material::{shader::SamplerFallback, Material, MaterialResource, PropertyValue},
     renderer::{
         self,
         bundle::{RenderContext, SurfaceInstanceData},
@@ -42,19 +46,85 @@ use fyrox_graph::BaseSceneGraph;
 use fyrox_resource::untyped::ResourceKind;
 use half::f16;
 use image::{imageops::FilterType, ImageBuffer, Luma};
+use serde::{Deserialize, Serialize};
 use std::{
     cell::Cell,
     cmp::Ordering,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     ops::{Deref, DerefMut, Range},
+    path::Path,
 };
+use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 mod geometry;
+mod noise;
 mod quadtree;
 
+pub use noise::{NoiseBlendMode, NoiseKind, NoiseParams};
+
 /// Current implementation version marker.
 pub const VERSION: u8 = 1;
 
+/// Defines how a layer is composited over the layers below it.
+#[derive(
+    Copy, Clone, Default, PartialEq, Debug, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum LayerBlendMode {
+    /// The layer covers the layers below it according to its mask.
+    #[default]
+    Normal,
+    /// The color of the layer (multiplied by its mask) is added to the color of the layers below it. Normals and
+    /// other surface properties of the layers below are kept. Could be used for glowing or wet spots, etc.
+    Additive,
+    /// The mask of the layer is modulated by the height of the terrain: the mask is fully suppressed at
+    /// `min_height` and is kept as is at `max_height`, with a smooth transition between them. Could be used,
+    /// for example, to automatically put snow on mountain tops. If `min_height` is greater than `max_height`,
+    /// the transition is inverted (lower parts of the terrain are covered).
+    HeightBased {
+        /// Local height of the terrain where the layer starts to appear.
+        min_height: f32,
+        /// Local height of the terrain where the layer is fully visible.
+        max_height: f32,
+    },
+}
+
+uuid_provider!(LayerBlendMode = "c3b7f0a2-5e1d-4c8b-9f6a-2d4e8a1b7c39");
+
+impl LayerBlendMode {
+    /// Returns an index of the mode, that is used by the standard terrain shader.
+    pub fn shader_index(&self) -> u32 {
+        match self {
+            LayerBlendMode::Normal => 0,
+            LayerBlendMode::Additive => 1,
+            LayerBlendMode::HeightBased { .. } => 2,
+        }
+    }
+
+    /// Calculates the weight of a layer at the given local height of the terrain, exactly as the standard terrain
+    /// shader does. The weight must be multiplied with the mask of the layer.
+    pub fn height_weight(&self, height: f32) -> f32 {
+        match *self {
+            LayerBlendMode::HeightBased {
+                min_height,
+                max_height,
+            } => {
+                let range = max_height - min_height;
+                let t = if range == 0.0 {
+                    if height >= max_height {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                } else {
+                    ((height - min_height) / range).clamp(0.0, 1.0)
+                };
+                t * t * (3.0 - 2.0 * t)
+            }
+            _ => 1.0,
+        }
+    }
+}
+
 /// Layers is a material Terrain can have as many layers as you want, but each layer slightly decreases
 /// performance, so keep amount of layers on reasonable level (1 - 5 should be enough for most
 /// cases).
@@ -73,6 +143,28 @@ pub struct Layer {
     /// Name of the node uv offsets property in the material.
     #[visit(optional)]
     pub node_uv_offsets_property_name: String,
+
+    /// Name of the hole mask sampler property in the material. See [`HoleMask`] docs for more info.
+    #[visit(optional)]
+    pub hole_mask_property_name: String,
+
+    /// Scale of the texture coordinates of the layer, larger values make the textures tile more densely. See
+    /// [`Terrain::set_layer_uv_transform`] for more info.
+    #[visit(optional)]
+    pub uv_scale: Vector2<f32>,
+
+    /// Offset of the texture coordinates of the layer. See [`Terrain::set_layer_uv_transform`] for more info.
+    #[visit(optional)]
+    pub uv_offset: Vector2<f32>,
+
+    /// Rotation (in radians) of the texture coordinates of the layer. See [`Terrain::set_layer_uv_transform`]
+    /// for more info.
+    #[visit(optional)]
+    pub uv_rotation: f32,
+
+    /// Defines how the layer is composited over the layers below it. See [`LayerBlendMode`] docs for more info.
+    #[visit(optional)]
+    pub blend_mode: LayerBlendMode,
 }
 
 uuid_provider!(Layer = "7439d5fd-43a9-45f0-bd7c-76cf4d2ec22e");
@@ -84,8 +176,236 @@ impl Default for Layer {
             mask_property_name: "maskTexture".to_string(),
             height_map_property_name: "heightMapTexture".to_string(),
             node_uv_offsets_property_name: "nodeUvOffsets".to_string(),
+            hole_mask_property_name: "holeMaskTexture".to_string(),
+            uv_scale: Vector2::new(1.0, 1.0),
+            uv_offset: Vector2::default(),
+            uv_rotation: 0.0,
+            blend_mode: Default::default(),
+        }
+    }
+}
+
+impl Layer {
+    /// Name of the texture coordinates scale property of the standard terrain shader.
+    pub const UV_SCALE_PROPERTY_NAME: &'static str = "texCoordScale";
+
+    /// Name of the texture coordinates offset property of the standard terrain shader.
+    pub const UV_OFFSET_PROPERTY_NAME: &'static str = "texCoordOffset";
+
+    /// Name of the texture coordinates rotation property of the standard terrain shader.
+    pub const UV_ROTATION_PROPERTY_NAME: &'static str = "texCoordRotation";
+
+    /// Name of the blend mode property of the standard terrain shader, see [`LayerBlendMode::shader_index`].
+    pub const BLEND_MODE_PROPERTY_NAME: &'static str = "layerBlendMode";
+
+    /// Name of the height range property of the standard terrain shader, that is used by
+    /// [`LayerBlendMode::HeightBased`].
+    pub const HEIGHT_RANGE_PROPERTY_NAME: &'static str = "layerHeightRange";
+
+    /// Returns `true` if the texture coordinates transform of the layer is identity (unit scale, no offset and
+    /// no rotation).
+    pub fn is_uv_transform_identity(&self) -> bool {
+        self.uv_scale == Vector2::new(1.0, 1.0)
+            && self.uv_offset == Vector2::default()
+            && self.uv_rotation == 0.0
+    }
+
+    /// Applies the texture coordinates transform of the layer to the given texture coordinates, exactly as the
+    /// standard terrain shader does: the coordinates are scaled first, then rotated around the origin and then
+    /// offset.
+    pub fn transform_uv(&self, uv: Vector2<f32>) -> Vector2<f32> {
+        let scaled = uv.component_mul(&self.uv_scale);
+        let (sin, cos) = self.uv_rotation.sin_cos();
+        Vector2::new(
+            scaled.x * cos - scaled.y * sin,
+            scaled.x * sin + scaled.y * cos,
+        ) + self.uv_offset
+    }
+}
+
+/// Sets a property of a layer material, but only if the shader of the material declares the property. Custom shaders
+/// are not required to declare every property of the standard terrain shader, such properties are silently skipped
+/// instead of logging an error on every frame. Materials that were created before the property was added to their
+/// shader get it with its default value first. Returns `true` if the property was set.
+fn set_layer_property(material: &mut Material, name: &str, value: PropertyValue) -> bool {
+    let declared = material
+        .property_schema()
+        .map_or(false, |schema| schema.iter().any(|d| d.name == name));
+    if !declared {
+        return false;
+    }
+
+    let name = ImmutableString::new(name);
+    if material.base().is_none() && material.property_ref(&name).is_none() {
+        material.ensure_defaults(None);
+    }
+
+    match material.set_property(&name, value) {
+        Ok(()) => true,
+        Err(err) => {
+            Log::err(format!(
+                "Unable to set {name} property of terrain material. Reason: {err:?}"
+            ));
+            false
+        }
+    }
+}
+
+/// Converts Red component of pixels of the given texture to normalized `f32` values. Returns `None` if the
+/// pixel format of the texture is not supported.
+fn texture_red_channel(texture: &Texture) -> Option<Vec<f32>> {
+    fn convert<T, C>(texture: &Texture, mut mapper: C) -> Option<Vec<f32>>
+    where
+        T: Sized,
+        C: Fn(&T) -> f32,
+    {
+        texture
+            .mip_level_data_of_type::<T>(0)
+            .map(|v| v.iter().map(&mut mapper).collect::<Vec<_>>())
+    }
+
+    match texture.pixel_kind() {
+        TexturePixelKind::R8 | TexturePixelKind::Luminance8 => {
+            convert::<u8, _>(texture, |v| *v as f32 / u8::MAX as f32)
+        }
+        TexturePixelKind::RGB8 => {
+            #[repr(C)]
+            struct Rgb8 {
+                r: u8,
+                g: u8,
+                b: u8,
+            }
+            convert::<Rgb8, _>(texture, |v| v.r as f32 / u8::MAX as f32)
+        }
+        TexturePixelKind::RGBA8 => {
+            #[repr(C)]
+            struct Rgba8 {
+                r: u8,
+                g: u8,
+                b: u8,
+                a: u8,
+            }
+            convert::<Rgba8, _>(texture, |v| v.r as f32 / u8::MAX as f32)
+        }
+        TexturePixelKind::RG8 | TexturePixelKind::LuminanceAlpha8 => {
+            #[repr(C)]
+            struct Rg8 {
+                r: u8,
+                g: u8,
+            }
+            convert::<Rg8, _>(texture, |v| v.r as f32 / u8::MAX as f32)
+        }
+        TexturePixelKind::R16 | TexturePixelKind::Luminance16 => {
+            convert::<u16, _>(texture, |v| *v as f32 / u16::MAX as f32)
+        }
+        TexturePixelKind::RG16 | TexturePixelKind::LuminanceAlpha16 => {
+            #[repr(C)]
+            struct Rg16 {
+                r: u16,
+                g: u16,
+            }
+            convert::<Rg16, _>(texture, |v| v.r as f32 / u16::MAX as f32)
+        }
+        TexturePixelKind::BGR8 => {
+            #[repr(C)]
+            struct Bgr8 {
+                b: u8,
+                g: u8,
+                r: u8,
+            }
+            convert::<Bgr8, _>(texture, |v| v.r as f32 / u8::MAX as f32)
+        }
+        TexturePixelKind::BGRA8 => {
+            #[repr(C)]
+            struct Bgra8 {
+                r: u8,
+                g: u8,
+                b: u8,
+                a: u8,
+            }
+            convert::<Bgra8, _>(texture, |v| v.r as f32 / u8::MAX as f32)
+        }
+        TexturePixelKind::RGB16 => {
+            #[repr(C)]
+            struct Rgb16 {
+                r: u16,
+                g: u16,
+                b: u16,
+            }
+            convert::<Rgb16, _>(texture, |v| v.r as f32 / u16::MAX as f32)
+        }
+        TexturePixelKind::RGBA16 => {
+            #[repr(C)]
+            struct Rgba16 {
+                r: u16,
+                g: u16,
+                b: u16,
+                a: u16,
+            }
+            convert::<Rgba16, _>(texture, |v| v.r as f32 / u16::MAX as f32)
+        }
+        TexturePixelKind::RGB32F => {
+            #[repr(C)]
+            struct Rgb32F {
+                r: f32,
+                g: f32,
+                b: f32,
+            }
+            convert::<Rgb32F, _>(texture, |v| v.r)
+        }
+        TexturePixelKind::RGBA32F => {
+            #[repr(C)]
+            struct Rgba32F {
+                r: f32,
+                g: f32,
+                b: f32,
+                a: f32,
+            }
+            convert::<Rgba32F, _>(texture, |v| v.r)
+        }
+        TexturePixelKind::RGB16F => {
+            #[repr(C)]
+            struct Rgb16F {
+                r: f16,
+                g: f16,
+                b: f16,
+            }
+            convert::<Rgb16F, _>(texture, |v| v.r.to_f32())
+        }
+        TexturePixelKind::R32F => convert::<f32, _>(texture, |v| *v),
+        TexturePixelKind::R16F => convert::<f16, _>(texture, |v| v.to_f32()),
+        _ => None,
+    }
+}
+
+/// Returns the index of a layer, which mask has the highest weight at the given normalized position of the
+/// chunk. Ties are resolved in favor of the last layer.
+fn dominant_layer(chunk: &Chunk, normalized: Vector2<f32>) -> Option<usize> {
+    let mut dominant = None;
+    let mut max_weight = 0;
+    for (index, mask) in chunk.layer_masks.iter().enumerate() {
+        let data = mask.data_ref();
+        let TextureKind::Rectangle { width, height } = data.kind() else {
+            continue;
+        };
+        if width == 0 || height == 0 {
+            continue;
+        }
+        let pixel = Vector2::new(
+            (normalized.x.clamp(0.0, 1.0) * (width - 1) as f32).round() as u32,
+            (normalized.y.clamp(0.0, 1.0) * (height - 1) as f32).round() as u32,
+        );
+        let weight = data
+            .data()
+            .get((pixel.y * width + pixel.x) as usize)
+            .copied()
+            .unwrap_or_default();
+        if dominant.is_none() || weight >= max_weight {
+            dominant = Some(index);
+            max_weight = weight;
         }
     }
+    dominant
 }
 
 /// Extract the &[f32] from a TextureResource to create a QuadTree, or panic.
@@ -160,6 +480,257 @@ pub struct Chunk {
     /// Layer blending masks of the chunk.
     #[reflect(hidden)]
     pub layer_masks: Vec<TextureResource>,
+    #[reflect(hidden)]
+    hole_mask: HoleMask,
+    #[reflect(hidden)]
+    dirty_regions: DirtyRegions,
+}
+
+/// Hole mask defines which parts of a chunk are cut out of the terrain. It has one value per height map
+/// pixel; a pixel marked as a hole is not rendered and is ignored by queries like [`Terrain::has_hole_at`].
+/// Internally, the mask is stored in a `R8` texture, that is passed to layer materials via
+/// [`Layer::hole_mask_property_name`], so the renderer and the queries always use the same data. A chunk
+/// without any holes does not have the texture at all.
+#[derive(Debug, Default, PartialEq, Visit)]
+pub struct HoleMask {
+    texture: Option<TextureResource>,
+}
+
+impl Clone for HoleMask {
+    // Deep cloning.
+    fn clone(&self) -> Self {
+        Self {
+            texture: self.texture.as_ref().map(|t| t.deep_clone()),
+        }
+    }
+}
+
+static EMPTY_HOLE_MASK: HoleMask = HoleMask { texture: None };
+
+impl HoleMask {
+    /// Returns a texture of the mask, if the mask has any data. Non-zero values of the texture are holes.
+    pub fn texture(&self) -> Option<&TextureResource> {
+        self.texture.as_ref()
+    }
+
+    /// Returns size of the mask in pixels. An empty mask has zero size.
+    pub fn size(&self) -> Vector2<u32> {
+        self.texture
+            .as_ref()
+            .and_then(|t| match t.data_ref().kind() {
+                TextureKind::Rectangle { width, height } => Some(Vector2::new(width, height)),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if the mask does not have any data, which means that there are no holes.
+    pub fn is_empty(&self) -> bool {
+        self.texture.is_none()
+    }
+
+    /// Returns `true` if the given pixel is a hole. Pixels outside the mask are never holes.
+    pub fn is_hole(&self, pixel: Vector2<u32>) -> bool {
+        let Some(texture) = self.texture.as_ref() else {
+            return false;
+        };
+        let data = texture.data_ref();
+        let TextureKind::Rectangle { width, height } = data.kind() else {
+            return false;
+        };
+        if pixel.x >= width || pixel.y >= height {
+            return false;
+        }
+        data.data()
+            .get((pixel.y * width + pixel.x) as usize)
+            .map_or(false, |value| *value != 0)
+    }
+
+    /// Returns `true` if there is a hole at the given normalized position, where `(0, 0)` is the first pixel
+    /// of the mask and `(1, 1)` is the last one. The nearest pixel is used, the same way as brushes map
+    /// pixels to positions.
+    pub fn is_hole_at(&self, normalized: Vector2<f32>) -> bool {
+        let size = self.size();
+        if size.x == 0 || size.y == 0 || normalized.x < 0.0 || normalized.y < 0.0 {
+            return false;
+        }
+        let pixel = Vector2::new(
+            (normalized.x * size.x.saturating_sub(1) as f32).round() as u32,
+            (normalized.y * size.y.saturating_sub(1) as f32).round() as u32,
+        );
+        self.is_hole(pixel)
+    }
+}
+
+/// Height data of a single chunk of a terrain, that could be used to build a heightfield collider. See
+/// [`Terrain::build_heightfield_collider`] for more info.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeightfieldChunk {
+    /// Position of the chunk on the chunk grid of the terrain.
+    pub grid_position: Vector2<i32>,
+    /// Position of the chunk origin (its first sample) in local coordinates of the terrain.
+    pub position: Vector3<f32>,
+    /// Size of the chunk along X and Z axes in local coordinates of the terrain.
+    pub physical_size: Vector2<f32>,
+    /// Amount of samples along X and Z axes, it is equal to the size of the height map of the chunk.
+    pub size: Vector2<u32>,
+    /// Heights of the samples in local coordinates of the terrain, row by row: a sample at `(x, z)` is
+    /// stored at `z * size.x + x`. Samples are evenly spread over the chunk, the first and the last samples
+    /// of a row lie on the edges of the chunk.
+    pub heights: Vec<f32>,
+    /// Hole flags of the samples, they're laid out the same way as the heights. It is empty if the chunk
+    /// does not have any holes.
+    pub holes: Vec<bool>,
+    // Modification counts of the height map and the hole mask, at which the data was built.
+    height_map_modifications: u64,
+    hole_mask_modifications: Option<u64>,
+}
+
+impl HeightfieldChunk {
+    fn new(chunk: &Chunk) -> Self {
+        let size = chunk.height_map_size;
+        let heights = chunk
+            .heightmap()
+            .data_ref()
+            .data_of_type::<f32>()
+            .map(|heights| heights.to_vec())
+            .unwrap_or_else(|| vec![0.0; (size.x * size.y) as usize]);
+
+        let holes = if chunk.hole_mask.is_empty() {
+            Vec::new()
+        } else {
+            (0..size.y)
+                .flat_map(|z| (0..size.x).map(move |x| Vector2::new(x, z)))
+                .map(|pixel| chunk.hole_mask.is_hole(pixel))
+                .collect()
+        };
+
+        let (height_map_modifications, hole_mask_modifications) = Self::modifications_counts(chunk);
+
+        Self {
+            grid_position: chunk.grid_position,
+            position: chunk.position,
+            physical_size: chunk.physical_size,
+            size,
+            heights,
+            holes,
+            height_map_modifications,
+            hole_mask_modifications,
+        }
+    }
+
+    fn modifications_counts(chunk: &Chunk) -> (u64, Option<u64>) {
+        (
+            chunk.heightmap().data_ref().modifications_count(),
+            chunk
+                .hole_mask
+                .texture()
+                .map(|texture| texture.data_ref().modifications_count()),
+        )
+    }
+
+    fn is_outdated(&self, chunk: &Chunk) -> bool {
+        self.grid_position != chunk.grid_position
+            || self.size != chunk.height_map_size
+            || (self.height_map_modifications, self.hole_mask_modifications)
+                != Self::modifications_counts(chunk)
+    }
+
+    /// Returns the height of a sample at the given position, or [`None`] if the position is outside the chunk.
+    pub fn height(&self, sample: Vector2<u32>) -> Option<f32> {
+        if sample.x >= self.size.x || sample.y >= self.size.y {
+            return None;
+        }
+        self.heights
+            .get((sample.y * self.size.x + sample.x) as usize)
+            .cloned()
+    }
+
+    /// Returns `true` if a sample at the given position is a hole.
+    pub fn is_hole(&self, sample: Vector2<u32>) -> bool {
+        sample.x < self.size.x
+            && sample.y < self.size.y
+            && self
+                .holes
+                .get((sample.y * self.size.x + sample.x) as usize)
+                .map_or(false, |hole| *hole)
+    }
+
+    /// Returns `true` if a cell (a quad between four neighbouring samples, where the given position is its
+    /// first sample) must be removed from the collider. A cell is removed if any of its corners is a hole.
+    pub fn is_cell_removed(&self, cell: Vector2<u32>) -> bool {
+        !self.holes.is_empty()
+            && [(0, 0), (1, 0), (0, 1), (1, 1)]
+                .into_iter()
+                .any(|(dx, dz)| self.is_hole(Vector2::new(cell.x + dx, cell.y + dz)))
+    }
+}
+
+/// Height data of a terrain, that could be used to build a heightfield collider for a physics engine. See
+/// [`Terrain::build_heightfield_collider`] for more info.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeightfieldData {
+    /// Height data of every chunk of the terrain, in the same order as [`Terrain::chunks_ref`].
+    pub chunks: Vec<HeightfieldChunk>,
+}
+
+/// A rectangular region of a chunk texture (height map or layer mask), that was modified on CPU side,
+/// but not yet uploaded to GPU. It allows the renderer to upload only the modified part of the texture,
+/// instead of the entire texture. See [`Chunk::take_dirty_regions`] for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureDirtyRegion {
+    /// The texture that was modified.
+    pub texture: TextureResource,
+    /// Minimal corner of the region (in pixels, inclusive).
+    pub min: Vector2<u32>,
+    /// Maximal corner of the region (in pixels, inclusive).
+    pub max: Vector2<u32>,
+    /// Modifications count of the texture (see [`Texture::modifications_count`]) before the first
+    /// modification of the region.
+    pub base_modifications_count: u64,
+    /// Modifications count of the texture (see [`Texture::modifications_count`]) after the last
+    /// modification of the region.
+    pub modifications_count: u64,
+}
+
+impl TextureDirtyRegion {
+    /// Returns size of the region in pixels.
+    pub fn size(&self) -> Vector2<u32> {
+        Vector2::new(self.max.x - self.min.x + 1, self.max.y - self.min.y + 1)
+    }
+}
+
+// Dirty regions are runtime-only data, so they're never compared, cloned or serialized. Mutex is
+// needed, because the renderer takes the regions from immutable scene graph.
+#[derive(Default, Debug)]
+struct DirtyRegions(Mutex<Vec<TextureDirtyRegion>>);
+
+impl PartialEq for DirtyRegions {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+// Finds bounds of pixels that differ in the given images of the given width.
+fn find_changed_bounds<T: PartialEq>(
+    old: &[T],
+    new: &[T],
+    width: u32,
+) -> Option<(Vector2<u32>, Vector2<u32>)> {
+    let mut bounds: Option<(Vector2<u32>, Vector2<u32>)> = None;
+    for (i, _) in old
+        .iter()
+        .zip(new.iter())
+        .enumerate()
+        .filter(|(_, (old, new))| old != new)
+    {
+        let point = Vector2::new(i as u32 % width, i as u32 / width);
+        bounds = Some(match bounds {
+            Some((min, max)) => (min.inf(&point), max.sup(&point)),
+            None => (point, point),
+        });
+    }
+    bounds
 }
 
 uuid_provider!(Chunk = "ae996754-69c1-49ba-9c17-a7bd4be072a9");
@@ -180,7 +751,9 @@ impl Clone for Chunk {
                 .iter()
                 .map(|m| m.deep_clone())
                 .collect::<Vec<_>>(),
+            hole_mask: self.hole_mask.clone(),
             quad_tree: make_quad_tree(&self.heightmap, self.height_map_size, self.block_size),
+            dirty_regions: Default::default(),
         }
     }
 }
@@ -235,6 +808,7 @@ impl Visit for Chunk {
                 self.layer_masks.visit("LayerMasks", &mut region)?;
                 self.grid_position.visit("GridPosition", &mut region)?;
                 let _ = self.block_size.visit("BlockSize", &mut region);
+                let _ = self.hole_mask.visit("HoleMask", &mut region);
             }
             _ => (),
         }
@@ -257,6 +831,8 @@ impl Default for Chunk {
             block_size: Vector2::new(32, 32),
             grid_position: Default::default(),
             layer_masks: Default::default(),
+            hole_mask: Default::default(),
+            dirty_regions: Default::default(),
         }
     }
 }
@@ -268,6 +844,17 @@ impl Chunk {
         map_to_local(self.position)
     }
 
+    /// Checks whether the chunk intersects the given rectangle (in local 2D coordinates of the terrain).
+    /// Borders are inclusive, since the pixels at the borders belong to the chunk.
+    fn intersects(&self, rect: &Rect<f32>) -> bool {
+        let min = self.local_position();
+        let max = min + self.physical_size;
+        rect.x() <= max.x
+            && rect.x() + rect.w() >= min.x
+            && rect.y() <= max.y
+            && rect.y() + rect.h() >= min.y
+    }
+
     /// Returns a reference to height map.
     pub fn heightmap(&self) -> &TextureResource {
         self.heightmap.as_ref().unwrap()
@@ -294,153 +881,7 @@ impl Chunk {
             if let Some(new_height_map_texture) = state.data() {
                 if let TextureKind::Rectangle { width, height } = new_height_map_texture.kind() {
                     if width == self.height_map_size.x && height == self.height_map_size.y {
-                        fn convert<T, C>(texture: &Texture, mut mapper: C) -> Option<Vec<f32>>
-                        where
-                            T: Sized,
-                            C: Fn(&T) -> f32,
-                        {
-                            texture
-                                .mip_level_data_of_type::<T>(0)
-                                .map(|v| v.iter().map(&mut mapper).collect::<Vec<_>>())
-                        }
-
-                        // Try to convert Red component of pixels to R32F format.
-                        let pixels = match new_height_map_texture.pixel_kind() {
-                            TexturePixelKind::R8 | TexturePixelKind::Luminance8 => {
-                                convert::<u8, _>(new_height_map_texture, |v| {
-                                    *v as f32 / u8::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::RGB8 => {
-                                #[repr(C)]
-                                struct Rgb8 {
-                                    r: u8,
-                                    g: u8,
-                                    b: u8,
-                                }
-                                convert::<Rgb8, _>(new_height_map_texture, |v| {
-                                    v.r as f32 / u8::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::RGBA8 => {
-                                #[repr(C)]
-                                struct Rgba8 {
-                                    r: u8,
-                                    g: u8,
-                                    b: u8,
-                                    a: u8,
-                                }
-                                convert::<Rgba8, _>(new_height_map_texture, |v| {
-                                    v.r as f32 / u8::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::RG8 | TexturePixelKind::LuminanceAlpha8 => {
-                                #[repr(C)]
-                                struct Rg8 {
-                                    r: u8,
-                                    g: u8,
-                                }
-                                convert::<Rg8, _>(new_height_map_texture, |v| {
-                                    v.r as f32 / u8::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::R16 | TexturePixelKind::Luminance16 => {
-                                convert::<u16, _>(new_height_map_texture, |v| {
-                                    *v as f32 / u16::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::RG16 | TexturePixelKind::LuminanceAlpha16 => {
-                                #[repr(C)]
-                                struct Rg16 {
-                                    r: u16,
-                                    g: u16,
-                                }
-                                convert::<Rg16, _>(new_height_map_texture, |v| {
-                                    v.r as f32 / u16::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::BGR8 => {
-                                #[repr(C)]
-                                struct Bgr8 {
-                                    b: u8,
-                                    g: u8,
-                                    r: u8,
-                                }
-                                convert::<Bgr8, _>(new_height_map_texture, |v| {
-                                    v.r as f32 / u8::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::BGRA8 => {
-                                #[repr(C)]
-                                struct Bgra8 {
-                                    r: u8,
-                                    g: u8,
-                                    b: u8,
-                                    a: u8,
-                                }
-                                convert::<Bgra8, _>(new_height_map_texture, |v| {
-                                    v.r as f32 / u8::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::RGB16 => {
-                                #[repr(C)]
-                                struct Rgb16 {
-                                    r: u16,
-                                    g: u16,
-                                    b: u16,
-                                }
-                                convert::<Rgb16, _>(new_height_map_texture, |v| {
-                                    v.r as f32 / u16::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::RGBA16 => {
-                                #[repr(C)]
-                                struct Rgba16 {
-                                    r: u16,
-                                    g: u16,
-                                    b: u16,
-                                    a: u16,
-                                }
-                                convert::<Rgba16, _>(new_height_map_texture, |v| {
-                                    v.r as f32 / u16::MAX as f32
-                                })
-                            }
-                            TexturePixelKind::RGB32F => {
-                                #[repr(C)]
-                                struct Rgb32F {
-                                    r: f32,
-                                    g: f32,
-                                    b: f32,
-                                }
-                                convert::<Rgb32F, _>(new_height_map_texture, |v| v.r)
-                            }
-                            TexturePixelKind::RGBA32F => {
-                                #[repr(C)]
-                                struct Rgba32F {
-                                    r: f32,
-                                    g: f32,
-                                    b: f32,
-                                    a: f32,
-                                }
-                                convert::<Rgba32F, _>(new_height_map_texture, |v| v.r)
-                            }
-                            TexturePixelKind::RGB16F => {
-                                #[repr(C)]
-                                struct Rgb16F {
-                                    r: f16,
-                                    g: f16,
-                                    b: f16,
-                                }
-                                convert::<Rgb16F, _>(new_height_map_texture, |v| v.r.to_f32())
-                            }
-                            TexturePixelKind::R32F => {
-                                convert::<f32, _>(new_height_map_texture, |v| *v)
-                            }
-                            TexturePixelKind::R16F => {
-                                convert::<f16, _>(new_height_map_texture, |v| v.to_f32())
-                            }
-                            _ => None,
-                        };
+                        let pixels = texture_red_channel(new_height_map_texture);
 
                         if let Some(pixels) = pixels {
                             if let Some(texture) =
@@ -507,6 +948,86 @@ impl Chunk {
             .debug_draw(&transform, self.height_map_size, self.physical_size, ctx)
     }
 
+    /// Returns a copy of the regions of the chunk textures (height map and layer masks), that were modified
+    /// since the last call of [`Self::take_dirty_regions`]. Only the modifications made by brushes (see
+    /// [`Terrain::draw`] and [`Terrain::apply_brush_delta`]) and by [`Terrain::for_each_height_map_pixel`]
+    /// are tracked. Modifications of the same texture are merged into a single region, that contains all of
+    /// them.
+    pub fn dirty_regions(&self) -> Vec<TextureDirtyRegion> {
+        self.dirty_regions.0.lock().clone()
+    }
+
+    /// Returns the regions of the chunk textures that were modified since the last call of this method and
+    /// clears them. It is used by the renderer to upload only the modified parts of the textures to GPU. A
+    /// region is uploaded partially only if no other modifications of the texture were made since the GPU
+    /// texture was updated last time (see [`TextureDirtyRegion::base_modifications_count`] and
+    /// [`TextureDirtyRegion::modifications_count`]), otherwise the entire texture is uploaded as usual.
+    pub fn take_dirty_regions(&self) -> Vec<TextureDirtyRegion> {
+        std::mem::take(&mut *self.dirty_regions.0.lock())
+    }
+
+    fn mark_dirty(
+        &self,
+        texture: &TextureResource,
+        (min, max): (Vector2<u32>, Vector2<u32>),
+        base_modifications_count: u64,
+        modifications_count: u64,
+    ) {
+        let mut regions = self.dirty_regions.0.lock();
+        if let Some(index) = regions.iter().position(|r| &r.texture == texture) {
+            let region = &mut regions[index];
+            if region.modifications_count == base_modifications_count {
+                region.min = region.min.inf(&min);
+                region.max = region.max.sup(&max);
+                region.modifications_count = modifications_count;
+                return;
+            }
+            // The texture was modified by someone else in the meantime, the region does not cover the
+            // modification and the entire texture must be uploaded.
+            regions.remove(index);
+        }
+        regions.push(TextureDirtyRegion {
+            texture: texture.clone(),
+            min,
+            max,
+            base_modifications_count,
+            modifications_count,
+        });
+    }
+
+    /// Returns a reference to the hole mask of the chunk.
+    pub fn hole_mask(&self) -> &HoleMask {
+        &self.hole_mask
+    }
+
+    /// Marks the given height map pixel as a hole (or removes the hole). The hole mask is created on demand,
+    /// it has the same size as the height map. Returns the previous state of the pixel. Pixels outside the
+    /// height map are ignored.
+    pub fn set_hole(&mut self, pixel: Vector2<u32>, hole: bool) -> bool {
+        if pixel.x >= self.height_map_size.x || pixel.y >= self.height_map_size.y {
+            return false;
+        }
+
+        if self.hole_mask.size() != self.height_map_size {
+            if !hole {
+                return self.hole_mask.is_hole(pixel);
+            }
+            self.hole_mask.texture = Some(create_layer_mask(
+                self.height_map_size.x,
+                self.height_map_size.y,
+                0,
+            ));
+        }
+
+        let texture = self.hole_mask.texture.as_ref().unwrap();
+        let mut data = texture.data_ref();
+        let mut modifier = data.modify();
+        let value = &mut modifier.data_mut()[(pixel.y * self.height_map_size.x + pixel.x) as usize];
+        let old = *value != 0;
+        *value = if hole { 255 } else { 0 };
+        old
+    }
+
     fn set_block_size(&mut self, block_size: Vector2<u32>) {
         self.block_size = block_size;
         self.quad_tree = make_quad_tree(&self.heightmap, self.height_map_size, block_size);
@@ -534,6 +1055,23 @@ pub struct TerrainRayCastResult {
     pub toi: f32,
 }
 
+/// The closest ray-terrain intersection, see [`Terrain::raycast_closest`] for more info.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainHit {
+    /// World-space position of impact point.
+    pub position: Vector3<f32>,
+    /// World-space normal of the surface at impact point.
+    pub normal: Vector3<f32>,
+    /// Index of a chunk that was hit.
+    pub chunk_index: usize,
+    /// Index of a layer, which mask has the highest weight at impact point. If multiple layers have the same
+    /// weight, the last one is taken, because it is drawn on top of the others. `None` if the terrain has no
+    /// layers.
+    pub layer: Option<usize>,
+    /// Time of impact in [0; 1] range where 0 - origin of a ray, 1 - its end.
+    pub toi: f32,
+}
+
 /// Terrain is a height field where each point has fixed coordinates in XZ plane, but variable Y coordinate.
 /// It can be used to create landscapes. It supports multiple layers, where each layer has its own material
 /// and mask.
@@ -584,7 +1122,8 @@ pub struct TerrainRayCastResult {
 /// 1) By using ray casting feature of the physics engine. In this case you need to create a `Heighfield` collider
 /// and use standard [`crate::scene::graph::physics::PhysicsWorld::cast_ray`] method.
 /// 2) By using [`Terrain::raycast`] - this method could provide you more information about intersection point, than
-/// physics-based.
+/// physics-based. If you only need the closest intersection, use [`Terrain::raycast_closest`], it is faster and it
+/// also tells which layer dominates at the intersection point.
 ///
 /// ## Physics
 ///
@@ -924,6 +1463,97 @@ impl Terrain {
         old
     }
 
+    /// Changes the resolution of every chunk by bilinear resampling of its height map and layer masks. Corner
+    /// pixels of height maps are kept in place, so chunks stay seamless. Holes are preserved: pixels under
+    /// holes do not affect surrounding heights, and the hole mask is resampled without filtering, so its edges
+    /// stay sharp. Quad trees of the chunks are rebuilt. Unlike [`Self::set_height_map_size`], this method
+    /// works with any heights, including negative ones. **Warning:** This method is very heavy and should not
+    /// be used at every frame!
+    pub fn resample(&mut self, new_height_map_size: Vector2<u32>, new_mask_size: Vector2<u32>) {
+        let new_height_map_size = new_height_map_size.sup(&Vector2::repeat(2));
+        let new_mask_size = new_mask_size.sup(&Vector2::repeat(1));
+        let mask_size = *self.mask_size;
+
+        for chunk in self.chunks.iter_mut() {
+            let size = chunk.height_map_size;
+
+            let holes = if chunk.hole_mask.is_empty() {
+                None
+            } else {
+                Some(
+                    (0..size.x * size.y)
+                        .map(|i| {
+                            chunk
+                                .hole_mask
+                                .is_hole(Vector2::new(i % size.x, i / size.x))
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            };
+
+            let height_map = resample_bilinear(
+                &chunk.heightmap_owned(),
+                size,
+                new_height_map_size,
+                true,
+                |index| holes.as_ref().map_or(false, |holes| holes[index]),
+            );
+
+            if let Some(holes) = holes {
+                let scale = Vector2::new(
+                    (size.x - 1) as f32 / (new_height_map_size.x - 1) as f32,
+                    (size.y - 1) as f32 / (new_height_map_size.y - 1) as f32,
+                );
+                let hole_mask = create_layer_mask(new_height_map_size.x, new_height_map_size.y, 0);
+                let mut data = hole_mask.data_ref();
+                let mut modifier = data.modify();
+                for (i, value) in modifier.data_mut().iter_mut().enumerate() {
+                    let x = ((i as u32 % new_height_map_size.x) as f32 * scale.x).round() as u32;
+                    let y = ((i as u32 / new_height_map_size.x) as f32 * scale.y).round() as u32;
+                    if holes[(y * size.x + x) as usize] {
+                        *value = 255;
+                    }
+                }
+                drop(modifier);
+                drop(data);
+                chunk.hole_mask.texture = Some(hole_mask);
+            }
+
+            for mask in chunk.layer_masks.iter_mut() {
+                let data = mask
+                    .data_ref()
+                    .data()
+                    .iter()
+                    .map(|value| *value as f32)
+                    .collect::<Vec<_>>();
+                let resampled =
+                    resample_bilinear(&data, mask_size, new_mask_size, false, |_| false);
+                let new_mask = create_layer_mask(new_mask_size.x, new_mask_size.y, 0);
+                for (pixel, value) in new_mask
+                    .data_ref()
+                    .modify()
+                    .data_mut()
+                    .iter_mut()
+                    .zip(resampled)
+                {
+                    *pixel = value.round().clamp(0.0, 255.0) as u8;
+                }
+                *mask = new_mask;
+            }
+
+            chunk.height_map_size = new_height_map_size;
+            chunk.heightmap = Some(make_height_map_texture(height_map, new_height_map_size));
+            chunk.quad_tree =
+                make_quad_tree(&chunk.heightmap, chunk.height_map_size, chunk.block_size);
+            chunk.dirty_regions = Default::default();
+        }
+
+        self.height_map_size
+            .set_value_and_mark_modified(new_height_map_size);
+        self.mask_size.set_value_and_mark_modified(new_mask_size);
+        self.bounding_box_dirty.set(true);
+    }
+
     /// Returns a numeric range along width axis which defines start and end chunk indices on a chunks grid.
     pub fn width_chunks(&self) -> Range<i32> {
         (*self.width_chunks).clone()
@@ -971,7 +1601,11 @@ impl Terrain {
                     let heightmap =
                         vec![0.0; (self.height_map_size.x * self.height_map_size.y) as usize];
                     let new_chunk = Chunk {
-                        quad_tree: QuadTree::new(&heightmap, *self.block_size, *self.block_size),
+                        quad_tree: QuadTree::new(
+                            &heightmap,
+                            *self.height_map_size,
+                            *self.block_size,
+                        ),
                         heightmap: Some(make_height_map_texture(heightmap, self.height_map_size())),
                         position: Vector3::new(
                             x as f32 * self.chunk_size.x,
@@ -995,6 +1629,8 @@ impl Terrain {
                             })
                             .collect::<Vec<_>>(),
                         version: VERSION,
+                        hole_mask: Default::default(),
+                        dirty_regions: Default::default(),
                     };
 
                     new_chunk
@@ -1036,22 +1672,165 @@ impl Terrain {
         project(self.global_transform(), p)
     }
 
-    /// Applies the given function to each pixel of the height map.
-    pub fn for_each_height_map_pixel<F>(&mut self, mut func: F)
-    where
-        F: FnMut(&mut f32, Vector2<f32>),
-    {
-        for chunk in self.chunks.iter_mut() {
-            let mut texture_data = chunk.heightmap.as_ref().unwrap().data_ref();
-            let mut texture_modifier = texture_data.modify();
-            let height_map = texture_modifier.data_mut_of_type::<f32>().unwrap();
+    /// Returns a reference to the hole mask of a chunk at the given position on the chunk grid (see
+    /// [`Self::width_chunks`] and [`Self::length_chunks`]). An empty mask is returned if there is no such
+    /// chunk. See [`HoleMask`] docs for more info.
+    pub fn chunk_hole_mask(&self, chunk_coord: Vector2<i32>) -> &HoleMask {
+        self.chunks
+            .iter()
+            .find(|c| c.grid_position == chunk_coord)
+            .map_or(&EMPTY_HOLE_MASK, |c| &c.hole_mask)
+    }
 
-            for iy in 0..chunk.height_map_size.y {
-                let kz = iy as f32 / (chunk.height_map_size.y - 1) as f32;
-                for ix in 0..chunk.height_map_size.x {
-                    let kx = ix as f32 / (chunk.height_map_size.x - 1) as f32;
+    /// Checks whether there is a hole at the given world-space position in XZ plane. The position is
+    /// projected on the terrain first (see [`Self::project`]). Returns `false` for the positions outside
+    /// the terrain.
+    pub fn has_hole_at(&self, world_xz: Vector2<f32>) -> bool {
+        let Some(local) = self.project(Vector3::new(world_xz.x, 0.0, world_xz.y)) else {
+            return false;
+        };
+        self.chunks.iter().any(|chunk| {
+            let normalized = (local - chunk.local_position()).component_div(&chunk.physical_size);
+            (0.0..=1.0).contains(&normalized.x)
+                && (0.0..=1.0).contains(&normalized.y)
+                && chunk.hole_mask.is_hole_at(normalized)
+        })
+    }
 
-                    let pixel_position = chunk.local_position()
+    /// Samples the height map at the given world-space position using bilinear interpolation between the
+    /// four surrounding height samples. Returns local-space position (in 2D coordinate system of the terrain),
+    /// local-space height and its gradient along X and Z axes of the terrain.
+    fn sample_height(&self, world_pos: Vector3<f32>) -> Option<(Vector2<f32>, f32, Vector2<f32>)> {
+        let local = self.project(world_pos)?;
+
+        let (chunk, normalized) = self.chunks.iter().find_map(|chunk| {
+            let normalized = (local - chunk.local_position()).component_div(&chunk.physical_size);
+            ((0.0..=1.0).contains(&normalized.x) && (0.0..=1.0).contains(&normalized.y))
+                .then_some((chunk, normalized))
+        })?;
+
+        if chunk.hole_mask.is_hole_at(normalized) {
+            return None;
+        }
+
+        let size = chunk.height_map_size;
+        if size.x < 2 || size.y < 2 {
+            return None;
+        }
+
+        let texture = chunk.heightmap.as_ref()?.data_ref();
+        let height_map = texture.data_of_type::<f32>()?;
+
+        let last = size.map(|n| n - 1);
+        let pixel = normalized.component_mul(&last.cast::<f32>());
+        let x = (pixel.x.floor() as u32).min(last.x - 1);
+        let y = (pixel.y.floor() as u32).min(last.y - 1);
+        let (tx, ty) = (pixel.x - x as f32, pixel.y - y as f32);
+
+        let fetch = |x: u32, y: u32| height_map[(y * size.x + x) as usize];
+        let h00 = fetch(x, y);
+        let h10 = fetch(x + 1, y);
+        let h01 = fetch(x, y + 1);
+        let h11 = fetch(x + 1, y + 1);
+
+        let top = h00 + (h10 - h00) * tx;
+        let bottom = h01 + (h11 - h01) * tx;
+        let height = top + (bottom - top) * ty;
+
+        let cell_size = chunk.physical_size.component_div(&last.cast::<f32>());
+        let gradient = Vector2::new(
+            ((h10 - h00) * (1.0 - ty) + (h11 - h01) * ty) / cell_size.x,
+            (bottom - top) / cell_size.y,
+        );
+
+        Some((local, height, gradient))
+    }
+
+    /// Returns the world-space height of the terrain surface at the given world-space position. The position
+    /// is projected on the terrain first (see [`Self::project`]) and then the height is calculated using
+    /// bilinear interpolation between the four surrounding height samples. Returns `None` if the position is
+    /// outside the terrain or over a hole. It could be used to place objects on the terrain, for foot IK, etc.
+    pub fn height_at(&self, world_pos: Vector3<f32>) -> Option<f32> {
+        let (local, height, _) = self.sample_height(world_pos)?;
+        Some(
+            self.global_transform()
+                .transform_point(&Point3::new(local.x, height, local.y))
+                .y,
+        )
+    }
+
+    /// Returns the world-space normal of the terrain surface at the given world-space position. The normal is
+    /// calculated from the same height samples as [`Self::height_at`] and it is consistent with it. Returns
+    /// `None` if the position is outside the terrain or over a hole.
+    pub fn normal_at(&self, world_pos: Vector3<f32>) -> Option<Vector3<f32>> {
+        let (_, _, gradient) = self.sample_height(world_pos)?;
+        let local_normal = Vector3::new(-gradient.x, 1.0, -gradient.y);
+        let normal_matrix = self.global_transform().basis().try_inverse()?.transpose();
+        (normal_matrix * local_normal).try_normalize(f32::EPSILON)
+    }
+
+    /// Builds height data of the terrain, that could be used to create a heightfield collider for a physics
+    /// engine. The data is split into chunks, every chunk contains a grid of heights which is an exact copy
+    /// of its height map (see [`HeightfieldChunk`] docs for the format). The heights are in local coordinates
+    /// of the terrain, the transform of the terrain node (including its scale) must be applied by the
+    /// consumer.
+    ///
+    /// # Holes
+    ///
+    /// Holes (see [`HoleMask`]) are stored as per-sample flags. Physics engines usually remove entire cells of
+    /// a heightfield, so [`HeightfieldChunk::is_cell_removed`] treats every cell that touches a hole as a gap.
+    /// This way, nothing can collide with the terrain where it is not rendered, but the gap in the collider
+    /// could be a bit larger than the visible hole.
+    ///
+    /// # Updating
+    ///
+    /// The data is a copy, so it is not updated automatically when the terrain is edited. Use
+    /// [`Self::update_heightfield_collider`] to update it incrementally.
+    pub fn build_heightfield_collider(&self) -> HeightfieldData {
+        HeightfieldData {
+            chunks: self.chunks.iter().map(HeightfieldChunk::new).collect(),
+        }
+    }
+
+    /// Updates the height data, previously built by [`Self::build_heightfield_collider`], to match the current
+    /// state of the terrain. Only the chunks, which height map or hole mask was modified since the data was
+    /// built, are rebuilt. If the layout of the chunks has changed (for example, the terrain was resized), all
+    /// the data is rebuilt. Returns the indices of the rebuilt chunks, so the collider could be updated
+    /// incrementally as well.
+    pub fn update_heightfield_collider(&self, data: &mut HeightfieldData) -> Vec<usize> {
+        if data.chunks.len() != self.chunks.len() {
+            *data = self.build_heightfield_collider();
+            return (0..data.chunks.len()).collect();
+        }
+
+        let mut rebuilt = Vec::new();
+        for (index, (data_chunk, chunk)) in
+            data.chunks.iter_mut().zip(self.chunks.iter()).enumerate()
+        {
+            if data_chunk.is_outdated(chunk) {
+                *data_chunk = HeightfieldChunk::new(chunk);
+                rebuilt.push(index);
+            }
+        }
+        rebuilt
+    }
+
+    /// Applies the given function to each pixel of the height map.
+    pub fn for_each_height_map_pixel<F>(&mut self, mut func: F)
+    where
+        F: FnMut(&mut f32, Vector2<f32>),
+    {
+        for chunk in self.chunks.iter_mut() {
+            let mut texture_data = chunk.heightmap.as_ref().unwrap().data_ref();
+            let mut texture_modifier = texture_data.modify();
+            let height_map = texture_modifier.data_mut_of_type::<f32>().unwrap();
+
+            for iy in 0..chunk.height_map_size.y {
+                let kz = iy as f32 / (chunk.height_map_size.y - 1) as f32;
+                for ix in 0..chunk.height_map_size.x {
+                    let kx = ix as f32 / (chunk.height_map_size.x - 1) as f32;
+
+                    let pixel_position = chunk.local_position()
                         + Vector2::new(kx * chunk.physical_size.x, kz * chunk.physical_size.y);
 
                     let index = (iy * chunk.height_map_size.x + ix) as usize;
@@ -1061,8 +1840,19 @@ impl Terrain {
             }
 
             drop(texture_modifier);
+            let modifications_count = texture_data.modifications_count();
             drop(texture_data);
 
+            chunk.mark_dirty(
+                chunk.heightmap.as_ref().unwrap(),
+                (
+                    Vector2::new(0, 0),
+                    chunk.height_map_size.map(|n| n.saturating_sub(1)),
+                ),
+                modifications_count - 1,
+                modifications_count,
+            );
+
             chunk.quad_tree =
                 make_quad_tree(&chunk.heightmap, chunk.height_map_size, chunk.block_size);
         }
@@ -1070,80 +1860,536 @@ impl Terrain {
         self.bounding_box_dirty.set(true);
     }
 
+    /// Returns the size (in pixels) of a terrain-wide height map grid, that covers all chunks of the terrain.
+    /// Pixels at the borders of adjacent chunks are shared, so each chunk adds `height_map_size - 1` pixels
+    /// along each axis.
+    pub fn height_map_grid_size(&self) -> Vector2<u32> {
+        let step = self.height_map_size.map(|n| n.saturating_sub(1));
+        let chunks = Vector2::new(
+            self.width_chunks.len() as u32,
+            self.length_chunks.len() as u32,
+        );
+        if chunks.x == 0 || chunks.y == 0 {
+            Vector2::default()
+        } else {
+            step.component_mul(&chunks).add_scalar(1)
+        }
+    }
+
+    /// Writes the height maps of all chunks of the terrain into a single 16-bit grayscale PNG image. The size of
+    /// the image is equal to [`Self::height_map_grid_size`]; X axis of the image is mapped to X axis of the
+    /// terrain and Y axis of the image is mapped to Z axis of the terrain. Heights are linearly mapped from the
+    /// given range to the full `[0; 65535]` range of pixel values, heights outside of the range are clamped.
+    /// Use [`Self::import_heightmap_r16`] with the same height range to read the heights back.
+    pub fn export_heightmap_r16<P: AsRef<Path>>(
+        &self,
+        path: P,
+        height_range: Range<f32>,
+    ) -> Result<(), image::ImageError> {
+        self.heightmap_r16(height_range)
+            .save_with_format(path, image::ImageFormat::Png)
+    }
+
+    /// Reads heights of all chunks from a 16-bit grayscale image (see [`Self::export_heightmap_r16`] for the
+    /// layout of the image). Pixel values in `[0; 65535]` range are linearly mapped to the given height range.
+    /// If the size of the image does not match [`Self::height_map_grid_size`], the image is resampled using
+    /// bilinear filtering. Quad trees of the chunks are rebuilt afterwards.
+    pub fn import_heightmap_r16<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        height_range: Range<f32>,
+    ) -> Result<(), image::ImageError> {
+        let image = image::open(path)?.into_luma16();
+        self.set_heightmap_r16(&image, height_range);
+        Ok(())
+    }
+
+    fn heightmap_r16(&self, height_range: Range<f32>) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+        let size = self.height_map_grid_size();
+        let origin = self.grid_origin();
+        let height_maps = self
+            .chunks
+            .iter()
+            .map(|chunk| chunk.heightmap_owned())
+            .collect::<Vec<_>>();
+        let range = height_range.end - height_range.start;
+
+        ImageBuffer::from_fn(size.x, size.y, |x, y| {
+            let height = self
+                .grid_pixel(origin + Vector2::new(x as i32, y as i32))
+                .map(|(chunk_index, index)| height_maps[chunk_index][index])
+                .unwrap_or_default();
+            let k = if range != 0.0 {
+                ((height - height_range.start) / range).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            Luma([(k * u16::MAX as f32).round() as u16])
+        })
+    }
+
+    fn set_heightmap_r16(
+        &mut self,
+        image: &ImageBuffer<Luma<u16>, Vec<u16>>,
+        height_range: Range<f32>,
+    ) {
+        let grid_size = self.height_map_grid_size();
+        if image.width() == 0 || image.height() == 0 || grid_size.x == 0 || grid_size.y == 0 {
+            return;
+        }
+
+        let origin = self.grid_origin();
+        let fetch = |x: u32, y: u32| {
+            image.get_pixel(x.min(image.width() - 1), y.min(image.height() - 1))[0] as f32
+                / u16::MAX as f32
+        };
+        let sample = |grid_pixel: Vector2<i32>| {
+            // Map grid pixel to the image space, corners of the grid are mapped to corners of the image.
+            let map = |v: i32, grid_size: u32, image_size: u32| {
+                v as f32 * (image_size - 1) as f32 / (grid_size.max(2) - 1) as f32
+            };
+            let u = map(grid_pixel.x, grid_size.x, image.width());
+            let v = map(grid_pixel.y, grid_size.y, image.height());
+            let (x, y) = (u.floor() as u32, v.floor() as u32);
+            let (tx, ty) = (u.fract(), v.fract());
+            let top = fetch(x, y) * (1.0 - tx) + fetch(x + 1, y) * tx;
+            let bottom = fetch(x, y + 1) * (1.0 - tx) + fetch(x + 1, y + 1) * tx;
+            let k = top * (1.0 - ty) + bottom * ty;
+            height_range.start + k * (height_range.end - height_range.start)
+        };
+
+        let height_maps = self
+            .chunks
+            .iter()
+            .enumerate()
+            .map(|(chunk_index, chunk)| {
+                let size = chunk.height_map_size;
+                let chunk_origin = chunk
+                    .grid_position
+                    .component_mul(&size.map(|n| n.saturating_sub(1) as i32))
+                    - origin;
+                let mut height_map = chunk.heightmap_owned();
+                for (index, height) in height_map.iter_mut().enumerate() {
+                    let pixel = Vector2::new(index as u32 % size.x, index as u32 / size.x);
+                    *height = sample(chunk_origin + pixel.map(|n| n as i32));
+                }
+                (chunk_index, height_map)
+            })
+            .collect();
+
+        self.apply_brush_delta(&BrushDelta::HeightMaps(height_maps));
+    }
+
+    /// Returns a position of the first pixel of a terrain-wide height map grid.
+    fn grid_origin(&self) -> Vector2<i32> {
+        Vector2::new(self.width_chunks.start, self.length_chunks.start)
+            .component_mul(&self.height_map_size.map(|n| n.saturating_sub(1) as i32))
+    }
+
     /// Multi-functional drawing method. It uses given brush to modify terrain, see [`Brush`] docs for
-    /// more info.
+    /// more info. It is equivalent to [`Self::compute_brush`] followed by [`Self::apply_brush_delta`].
     pub fn draw(&mut self, brush: &Brush) {
+        let delta = self.compute_brush(brush);
+        self.apply_brush_delta(&delta);
+    }
+
+    /// Computes the terrain data that would be produced by the given brush without modifying the terrain.
+    /// It could be used to show a preview of a brush stroke before actually applying it. The returned delta
+    /// could then be applied by [`Self::apply_brush_delta`], the result will be exactly the same as if
+    /// [`Self::draw`] was called with the same brush.
+    pub fn compute_brush(&self, brush: &Brush) -> BrushDelta {
         let center = project(self.global_transform(), brush.center).unwrap();
+        let bounds = brush.shape.bounds(center);
+        let chunks = || {
+            self.chunks
+                .iter()
+                .enumerate()
+                .filter(move |(_, chunk)| chunk.intersects(&bounds))
+        };
 
         match brush.mode {
-            BrushMode::ModifyHeightMap { amount } => {
-                self.for_each_height_map_pixel(|pixel, pixel_position| {
-                    let k = match brush.shape {
-                        BrushShape::Circle { radius } => {
-                            1.0 - ((center - pixel_position).norm() / radius).powf(2.0)
-                        }
-                        BrushShape::Rectangle { .. } => 1.0,
-                    };
-
-                    if brush.shape.contains(center, pixel_position) {
-                        *pixel += k * amount;
-                    }
-                });
-            }
+            BrushMode::ModifyHeightMap { amount } => BrushDelta::HeightMaps(
+                chunks()
+                    .map(|(chunk_index, chunk)| {
+                        let mut height_map = chunk.heightmap_owned();
+                        for_each_chunk_pixel(
+                            chunk,
+                            chunk.height_map_size,
+                            |index, pixel_position| {
+                                if brush.shape.contains(center, pixel_position) {
+                                    let k = brush.weight(center, pixel_position);
+                                    height_map[index] += k * amount;
+                                }
+                            },
+                        );
+                        (chunk_index, height_map)
+                    })
+                    .collect(),
+            ),
             BrushMode::DrawOnMask { layer, alpha } => {
                 if layer >= self.layers.len() {
-                    return;
+                    return BrushDelta::None;
                 }
 
                 let alpha = alpha.clamp(-1.0, 1.0);
 
-                for chunk in self.chunks.iter_mut() {
-                    let chunk_position = chunk.local_position();
-                    let mut texture_data = chunk.layer_masks[layer].data_ref();
-                    let mut texture_data_mut = texture_data.modify();
-
-                    let (texture_width, texture_height) =
-                        if let TextureKind::Rectangle { width, height } = texture_data_mut.kind() {
-                            (width as usize, height as usize)
-                        } else {
-                            unreachable!("Mask must be a 2D greyscale image!")
-                        };
-
-                    for z in 0..texture_height {
-                        let kz = z as f32 / (texture_height - 1) as f32;
-                        for x in 0..texture_width {
-                            let kx = x as f32 / (texture_width - 1) as f32;
-
-                            let pixel_position = chunk_position
-                                + Vector2::new(
-                                    kx * chunk.physical_size.x,
-                                    kz * chunk.physical_size.y,
-                                );
+                let masks = chunks()
+                    .map(|(chunk_index, chunk)| {
+                        let texture_data = chunk.layer_masks[layer].data_ref();
 
-                            let k = match brush.shape {
-                                BrushShape::Circle { radius } => {
-                                    1.0 - ((center - pixel_position).norm() / radius).powf(4.0)
-                                }
-                                BrushShape::Rectangle { .. } => 1.0,
+                        let size =
+                            if let TextureKind::Rectangle { width, height } = texture_data.kind() {
+                                Vector2::new(width, height)
+                            } else {
+                                unreachable!("Mask must be a 2D greyscale image!")
                             };
 
+                        // We can draw on mask directly, without any problems because it has R8 pixel format.
+                        let mut mask = texture_data.data().to_vec();
+                        for_each_chunk_pixel(chunk, size, |index, pixel_position| {
                             if brush.shape.contains(center, pixel_position) {
-                                // We can draw on mask directly, without any problems because it has R8 pixel format.
-                                let data = texture_data_mut.data_mut();
-                                let pixel = &mut data[z * texture_width + x];
+                                let k = brush.weight(center, pixel_position);
+                                let pixel = &mut mask[index];
                                 *pixel = (*pixel as f32 + k * alpha * 255.0).min(255.0) as u8;
                             }
+                        });
+                        (chunk_index, mask)
+                    })
+                    .collect();
+
+                BrushDelta::LayerMasks { layer, masks }
+            }
+            BrushMode::SmoothHeightMap { strength } => {
+                let strength = strength.clamp(0.0, 1.0);
+                // Neighbours of the pixels at the borders of the brush could belong to the chunks outside of
+                // the brush, so the height maps of every chunk are read (but not copied).
+                let textures = self
+                    .chunks
+                    .iter()
+                    .map(|chunk| chunk.heightmap().data_ref())
+                    .collect::<Vec<_>>();
+                let source_height_maps = textures
+                    .iter()
+                    .map(|texture| texture.data_of_type::<f32>().unwrap())
+                    .collect::<Vec<_>>();
+
+                BrushDelta::HeightMaps(
+                    chunks()
+                        .map(|(chunk_index, chunk)| {
+                            let size = chunk.height_map_size;
+                            let origin = chunk
+                                .grid_position
+                                .component_mul(&size.map(|n| n.saturating_sub(1) as i32));
+                            let mut height_map = source_height_maps[chunk_index].to_vec();
+                            for_each_chunk_pixel(chunk, size, |index, pixel_position| {
+                                if brush.shape.contains(center, pixel_position) {
+                                    let k = brush.weight(center, pixel_position) * strength;
+                                    let pixel = origin
+                                        + Vector2::new(
+                                            (index as u32 % size.x) as i32,
+                                            (index as u32 / size.x) as i32,
+                                        );
+                                    let average = self.average_height(&source_height_maps, pixel);
+                                    let height = &mut height_map[index];
+                                    *height += (average - *height) * k;
+                                }
+                            });
+                            (chunk_index, height_map)
+                        })
+                        .collect(),
+                )
+            }
+            BrushMode::FlattenHeightMap { height, strength } => {
+                let strength = strength.clamp(0.0, 1.0);
+                BrushDelta::HeightMaps(
+                    chunks()
+                        .map(|(chunk_index, chunk)| {
+                            let mut height_map = chunk.heightmap_owned();
+                            for_each_chunk_pixel(
+                                chunk,
+                                chunk.height_map_size,
+                                |index, pixel_position| {
+                                    if brush.shape.contains(center, pixel_position) {
+                                        // Falloff blends the plateau with its surroundings, so there are
+                                        // no hard walls at the border of the brush, unless the falloff is
+                                        // constant.
+                                        let k = brush.weight(center, pixel_position) * strength;
+                                        let pixel = &mut height_map[index];
+                                        *pixel += (height - *pixel) * k;
+                                    }
+                                },
+                            );
+                            (chunk_index, height_map)
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Returns the index of a chunk at the given grid position.
+    fn chunk_index(&self, grid_position: Vector2<i32>) -> Option<usize> {
+        if !self.width_chunks.contains(&grid_position.x)
+            || !self.length_chunks.contains(&grid_position.y)
+        {
+            return None;
+        }
+
+        // Chunks are stored row by row, so the index could be calculated directly in most cases.
+        let width = self.width_chunks.end - self.width_chunks.start;
+        let index = ((grid_position.y - self.length_chunks.start) * width + grid_position.x
+            - self.width_chunks.start) as usize;
+        if self
+            .chunks
+            .get(index)
+            .map_or(false, |chunk| chunk.grid_position == grid_position)
+        {
+            Some(index)
+        } else {
+            self.chunks
+                .iter()
+                .position(|chunk| chunk.grid_position == grid_position)
+        }
+    }
+
+    /// Converts a position of a pixel in a terrain-wide height map grid into a chunk index and an index
+    /// of a pixel in the height map of the chunk. Pixels at the borders of adjacent chunks are shared, so
+    /// the grid has `height_map_size - 1` pixels per chunk along each axis.
+    fn grid_pixel(&self, pixel: Vector2<i32>) -> Option<(usize, usize)> {
+        let size = *self.height_map_size;
+        let step = size.map(|n| n as i32 - 1);
+        if step.x <= 0 || step.y <= 0 {
+            return None;
+        }
+
+        let grid_position = Vector2::new(pixel.x.div_euclid(step.x), pixel.y.div_euclid(step.y));
+        let local = Vector2::new(pixel.x.rem_euclid(step.x), pixel.y.rem_euclid(step.y));
+
+        // A pixel at the border could belong to the previous chunk, if there's no chunk after the border.
+        let mut candidates = ArrayVec::<(Vector2<i32>, Vector2<i32>), 4>::new();
+        candidates.push((grid_position, local));
+        if local.x == 0 {
+            candidates.push((
+                grid_position - Vector2::new(1, 0),
+                Vector2::new(step.x, local.y),
+            ));
+        }
+        if local.y == 0 {
+            candidates.push((
+                grid_position - Vector2::new(0, 1),
+                Vector2::new(local.x, step.y),
+            ));
+        }
+        if local.x == 0 && local.y == 0 {
+            candidates.push((grid_position - Vector2::new(1, 1), step));
+        }
+
+        candidates.into_iter().find_map(|(grid_position, local)| {
+            self.chunk_index(grid_position)
+                .map(|chunk_index| (chunk_index, (local.y * size.x as i32 + local.x) as usize))
+        })
+    }
+
+    /// Calculates a weighted average of heights in 3x3 neighbourhood of the given pixel of a terrain-wide
+    /// height map grid. `height_maps` must contain height maps of every chunk of the terrain. Neighbours
+    /// outside of the terrain are ignored.
+    fn average_height(&self, height_maps: &[&[f32]], pixel: Vector2<i32>) -> f32 {
+        let mut sum = 0.0;
+        let mut total_weight = 0.0;
+        for dy in -1..=1i32 {
+            for dx in -1..=1i32 {
+                let Some((chunk_index, index)) = self.grid_pixel(pixel + Vector2::new(dx, dy))
+                else {
+                    continue;
+                };
+                // Binomial kernel: the closer the neighbour, the bigger its contribution.
+                let weight = ((2 - dx.abs()) * (2 - dy.abs())) as f32;
+                sum += height_maps[chunk_index][index] * weight;
+                total_weight += weight;
+            }
+        }
+        if total_weight > 0.0 {
+            sum / total_weight
+        } else {
+            0.0
+        }
+    }
+
+    /// Computes the terrain data that would be produced by the given stamp without modifying the terrain. Sampled
+    /// heights of the stamp (scaled by Y component of [`HeightMapStamp::scale`] and by the strength) are added
+    /// to the heights of the terrain. Every pixel of the height maps is transformed into the space of the stamp
+    /// by the inverse transform of the stamp, the stamp is sampled using bilinear filtering. Pixels outside the
+    /// stamp are left untouched. The result could be applied by [`Self::apply_brush_delta`], which allows you to
+    /// implement undo in the same way as for brushes. [`BrushDelta::None`] is returned if the texture of the stamp
+    /// is not loaded or its format is not supported.
+    pub fn compute_stamp(&self, stamp: &HeightMapStamp) -> BrushDelta {
+        let Some(center) = project(self.global_transform(), stamp.position) else {
+            return BrushDelta::None;
+        };
+
+        let mut state = stamp.texture.state();
+        let Some(texture) = state.data() else {
+            return BrushDelta::None;
+        };
+        let TextureKind::Rectangle { width, height } = texture.kind() else {
+            return BrushDelta::None;
+        };
+        let Some(pixels) = texture_red_channel(texture) else {
+            return BrushDelta::None;
+        };
+        if width == 0 || height == 0 || pixels.len() < (width * height) as usize {
+            return BrushDelta::None;
+        }
+
+        let size = Vector2::new(stamp.scale.x, stamp.scale.z);
+        if size.x == 0.0 || size.y == 0.0 {
+            return BrushDelta::None;
+        }
+
+        let fetch =
+            |x: u32, y: u32| pixels[(y.min(height - 1) * width + x.min(width - 1)) as usize];
+        let amount = stamp.scale.y * stamp.strength.clamp(0.0, 1.0);
+        let rotation = Rotation2::new(stamp.rotation);
+        let inv_rotation = rotation.inverse();
+
+        // Bounds of the rotated stamp, only the chunks intersecting it could be changed.
+        let corners = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)]
+            .map(|(x, y)| center + rotation * Vector2::new(x, y).component_mul(&size));
+        let min = corners
+            .iter()
+            .fold(corners[0], |min, corner| min.inf(corner));
+        let max = corners
+            .iter()
+            .fold(corners[0], |max, corner| max.sup(corner));
+        let bounds = Rect::new(min.x, min.y, max.x - min.x, max.y - min.y);
+
+        BrushDelta::HeightMaps(
+            self.chunks
+                .iter()
+                .enumerate()
+                .filter(|(_, chunk)| chunk.intersects(&bounds))
+                .map(|(chunk_index, chunk)| {
+                    let mut height_map = chunk.heightmap_owned();
+                    for_each_chunk_pixel(chunk, chunk.height_map_size, |index, pixel_position| {
+                        let uv = (inv_rotation * (pixel_position - center))
+                            .component_div(&size)
+                            .add_scalar(0.5);
+                        if !(0.0..=1.0).contains(&uv.x) || !(0.0..=1.0).contains(&uv.y) {
+                            return;
                         }
+
+                        let u = uv.x * (width - 1) as f32;
+                        let v = uv.y * (height - 1) as f32;
+                        let (x, y) = (u.floor() as u32, v.floor() as u32);
+                        let (tx, ty) = (u.fract(), v.fract());
+                        let top = fetch(x, y) * (1.0 - tx) + fetch(x + 1, y) * tx;
+                        let bottom = fetch(x, y + 1) * (1.0 - tx) + fetch(x + 1, y + 1) * tx;
+                        let sample = top * (1.0 - ty) + bottom * ty;
+
+                        height_map[index] += sample * amount;
+                    });
+                    (chunk_index, height_map)
+                })
+                .collect(),
+        )
+    }
+
+    /// Puts the given stamp on the terrain. It is equivalent to [`Self::compute_stamp`] followed by
+    /// [`Self::apply_brush_delta`].
+    pub fn stamp(&mut self, stamp: &HeightMapStamp) {
+        let delta = self.compute_stamp(stamp);
+        self.apply_brush_delta(&delta);
+    }
+
+    /// Applies terrain data previously computed by [`Self::compute_brush`]. Only the chunks listed in the
+    /// delta are modified (and only their quad trees are rebuilt). Chunks, which data size does not match
+    /// the size of the delta, are left untouched.
+    pub fn apply_brush_delta(&mut self, delta: &BrushDelta) {
+        match delta {
+            BrushDelta::HeightMaps(height_maps) => {
+                for (&chunk_index, new_height_map) in height_maps {
+                    let Some(chunk) = self.chunks.get_mut(chunk_index) else {
+                        Log::err("Invalid chunk index.");
+                        continue;
+                    };
+
+                    let mut texture_data = chunk.heightmap.as_ref().unwrap().data_ref();
+                    let base_modifications_count = texture_data.modifications_count();
+                    let mut texture_modifier = texture_data.modify();
+                    let height_map = texture_modifier.data_mut_of_type::<f32>().unwrap();
+
+                    if height_map.len() != new_height_map.len() {
+                        Log::err("Height map size mismatch.");
+                        continue;
+                    }
+
+                    let bounds =
+                        find_changed_bounds(height_map, new_height_map, chunk.height_map_size.x);
+
+                    height_map.copy_from_slice(new_height_map);
+
+                    drop(texture_modifier);
+                    let modifications_count = texture_data.modifications_count();
+                    drop(texture_data);
+
+                    if let Some(bounds) = bounds {
+                        chunk.mark_dirty(
+                            chunk.heightmap.as_ref().unwrap(),
+                            bounds,
+                            base_modifications_count,
+                            modifications_count,
+                        );
                     }
+
+                    chunk.quad_tree =
+                        make_quad_tree(&chunk.heightmap, chunk.height_map_size, chunk.block_size);
                 }
+
+                self.bounding_box_dirty.set(true);
             }
-            BrushMode::FlattenHeightMap { height } => {
-                self.for_each_height_map_pixel(|pixel, pixel_position| {
-                    if brush.shape.contains(center, pixel_position) {
-                        *pixel = height;
+            BrushDelta::LayerMasks { layer, masks } => {
+                for (&chunk_index, new_mask) in masks {
+                    let Some(chunk) = self.chunks.get(chunk_index) else {
+                        Log::err("Invalid chunk index.");
+                        continue;
+                    };
+                    let Some(mask_resource) = chunk.layer_masks.get(*layer) else {
+                        continue;
+                    };
+
+                    let mut texture_data = mask_resource.data_ref();
+                    let width = match texture_data.kind() {
+                        TextureKind::Rectangle { width, .. } => width,
+                        _ => unreachable!("Mask must be a 2D greyscale image!"),
+                    };
+                    let base_modifications_count = texture_data.modifications_count();
+                    let mut texture_modifier = texture_data.modify();
+                    let mask = texture_modifier.data_mut();
+
+                    if mask.len() != new_mask.len() {
+                        Log::err("Layer mask size mismatch.");
+                        continue;
+                    }
+
+                    let bounds = find_changed_bounds(mask, new_mask, width);
+
+                    mask.copy_from_slice(new_mask);
+
+                    drop(texture_modifier);
+                    let modifications_count = texture_data.modifications_count();
+                    drop(texture_data);
+
+                    if let Some(bounds) = bounds {
+                        chunk.mark_dirty(
+                            mask_resource,
+                            bounds,
+                            base_modifications_count,
+                            modifications_count,
+                        );
                     }
-                });
+                }
             }
+            BrushDelta::None => {}
         }
     }
 
@@ -1260,6 +2506,108 @@ impl Terrain {
         !results.is_empty()
     }
 
+    /// Casts a ray and looks for the closest intersection with the terrain. Unlike [`Self::raycast`], this
+    /// method uses quad trees of the chunks to skip the parts of the terrain that cannot be intersected by the
+    /// ray. Holes are respected, the ray passes through them. Besides the position and the normal of the
+    /// surface, the result contains the index of the dominant layer at the impact point, which could be used,
+    /// for example, to pick footstep sounds.
+    pub fn raycast_closest(&self, ray: &Ray) -> Option<TerrainHit> {
+        let transform = self.global_transform();
+        let inv_transform = transform.try_inverse()?;
+        let local_ray = ray.transform(inv_transform);
+
+        let mut closest: Option<(f32, usize, Vector3<f32>, Vector3<f32>)> = None;
+        let mut leafs = Vec::new();
+        for (chunk_index, chunk) in self.chunks.iter().enumerate() {
+            let size = chunk.height_map_size;
+            if size.x < 2 || size.y < 2 {
+                continue;
+            }
+
+            // Move the ray into the space of the chunk, where its first pixel is at the origin.
+            let chunk_ray = Ray::new(local_ray.origin - chunk.position, local_ray.dir);
+
+            leafs.clear();
+            chunk
+                .quad_tree
+                .raycast_leafs(&chunk_ray, size, chunk.physical_size, &mut leafs);
+            if leafs.is_empty() {
+                continue;
+            }
+
+            let texture = chunk.heightmap.as_ref().unwrap().data_ref();
+            let height_map = texture.data_of_type::<f32>().unwrap();
+            let cell_size = chunk
+                .physical_size
+                .component_div(&size.map(|n| (n - 1) as f32));
+            let vertex = |x: u32, y: u32| {
+                Vector3::new(
+                    x as f32 * cell_size.x,
+                    height_map[(y * size.x + x) as usize],
+                    y as f32 * cell_size.y, // Remember Z -> Y mapping!
+                )
+            };
+
+            for leaf in leafs.iter() {
+                let end = (leaf.position + leaf.size).inf(&size.map(|n| n - 1));
+                for iy in leaf.position.y..end.y {
+                    for ix in leaf.position.x..end.x {
+                        let v0 = vertex(ix, iy);
+                        let v1 = vertex(ix, iy + 1);
+                        let v2 = vertex(ix + 1, iy + 1);
+                        let v3 = vertex(ix + 1, iy);
+
+                        for vertices in &[[v0, v1, v2], [v2, v3, v0]] {
+                            let Some((toi, intersection)) =
+                                chunk_ray.triangle_intersection(vertices)
+                            else {
+                                continue;
+                            };
+
+                            if closest
+                                .as_ref()
+                                .map_or(false, |(closest_toi, ..)| *closest_toi <= toi)
+                            {
+                                continue;
+                            }
+
+                            let normalized = Vector2::new(intersection.x, intersection.z)
+                                .component_div(&chunk.physical_size);
+                            if chunk.hole_mask.is_hole_at(normalized) {
+                                continue;
+                            }
+
+                            let normal = (vertices[1] - vertices[0])
+                                .cross(&(vertices[2] - vertices[0]))
+                                .try_normalize(f32::EPSILON)
+                                .unwrap_or_else(Vector3::y);
+
+                            closest = Some((toi, chunk_index, intersection, normal));
+                        }
+                    }
+                }
+            }
+        }
+
+        let (toi, chunk_index, intersection, normal) = closest?;
+        let chunk = &self.chunks[chunk_index];
+        let normalized =
+            Vector2::new(intersection.x, intersection.z).component_div(&chunk.physical_size);
+        let normal_matrix = transform.basis().try_inverse()?.transpose();
+
+        Some(TerrainHit {
+            position: transform
+                .transform_point(&Point3::from(intersection + chunk.position))
+                .coords,
+            normal: (normal_matrix * normal)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y),
+            chunk_index,
+            layer: dominant_layer(chunk, normalized),
+            toi,
+        })
+    }
+
     /// Sets new terrain layers.
     pub fn set_layers(&mut self, layers: Vec<Layer>) -> Vec<Layer> {
         self.layers.set_value_and_mark_modified(layers)
@@ -1295,6 +2643,79 @@ impl Terrain {
         (layer, layer_masks)
     }
 
+    /// Moves the layer at `from` index to `to` index together with its blending masks in every chunk, the
+    /// layers in between are shifted. Blending masks are moved as is, so the layer keeps its painted data. Returns
+    /// `false` (and does nothing) if any of the indices is out of bounds.
+    pub fn move_layer(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.layers.len() || to >= self.layers.len() {
+            return false;
+        }
+
+        if from != to {
+            let layers = self.layers.get_value_mut_and_mark_modified();
+            let layer = layers.remove(from);
+            layers.insert(to, layer);
+
+            for chunk in self.chunks.iter_mut() {
+                let mask = chunk.layer_masks.remove(from);
+                chunk.layer_masks.insert(to, mask);
+            }
+        }
+
+        true
+    }
+
+    /// Replaces the material of a layer at the given index and returns the previous material. Blending masks
+    /// of the layer are left untouched. Returns `None` (and does nothing) if there's no layer with the given
+    /// index.
+    pub fn set_layer_material(
+        &mut self,
+        layer_index: usize,
+        material: MaterialResource,
+    ) -> Option<MaterialResource> {
+        if layer_index >= self.layers.len() {
+            return None;
+        }
+
+        let layer = &mut self.layers.get_value_mut_and_mark_modified()[layer_index];
+        Some(std::mem::replace(&mut layer.material, material))
+    }
+
+    /// Sets the texture coordinates transform of a layer at the given index. The transform controls tiling of the
+    /// textures of the layer: the texture coordinates are scaled first, then rotated (`rotation` is in radians)
+    /// and then offset. Returns `false` (and does nothing) if there's no layer with the given index.
+    ///
+    /// The transform is passed to the material of the layer when the terrain is rendered, using the properties
+    /// listed in [`Layer::UV_SCALE_PROPERTY_NAME`], [`Layer::UV_OFFSET_PROPERTY_NAME`] and
+    /// [`Layer::UV_ROTATION_PROPERTY_NAME`], so it works with the standard terrain shader (or any custom shader with
+    /// the same properties) and does not require any changes to the material itself. If the transform is identity
+    /// (the default), the values set in the material are used as is, so materials with manually adjusted
+    /// `texCoordScale` keep working.
+    ///
+    /// ```no_run
+    /// # use fyrox_impl::{core::algebra::Vector2, scene::terrain::Terrain};
+    /// fn make_grass_tile_densely(terrain: &mut Terrain) {
+    ///     terrain.set_layer_uv_transform(0, Vector2::new(8.0, 8.0), Vector2::default(), 0.0);
+    /// }
+    /// ```
+    pub fn set_layer_uv_transform(
+        &mut self,
+        layer_index: usize,
+        scale: Vector2<f32>,
+        offset: Vector2<f32>,
+        rotation: f32,
+    ) -> bool {
+        if layer_index >= self.layers.len() {
+            return false;
+        }
+
+        let layer = &mut self.layers.get_value_mut_and_mark_modified()[layer_index];
+        layer.uv_scale = scale;
+        layer.uv_offset = offset;
+        layer.uv_rotation = rotation;
+        true
+    }
+
     /// Removes last terrain layer together with its respective blending masks from each chunk.
     pub fn pop_layer(&mut self) -> Option<(Layer, Vec<TextureResource>)> {
         if self.layers.is_empty() {
@@ -1409,6 +2830,33 @@ impl Terrain {
 
             drop(texture);
 
+            if let Some(hole_mask) = chunk.hole_mask.texture.as_mut() {
+                let data = hole_mask.data_ref();
+                let hole_mask_image = ImageBuffer::<Luma<u8>, Vec<u8>>::from_vec(
+                    chunk.height_map_size.x,
+                    chunk.height_map_size.y,
+                    data.data().to_vec(),
+                )
+                .unwrap();
+                drop(data);
+
+                // Holes must stay sharp, so no filtering here.
+                let resampled_hole_mask = image::imageops::resize(
+                    &hole_mask_image,
+                    new_size.x,
+                    new_size.y,
+                    FilterType::Nearest,
+                )
+                .into_raw();
+
+                *hole_mask = create_layer_mask(new_size.x, new_size.y, 0);
+                hole_mask
+                    .data_ref()
+                    .modify()
+                    .data_mut()
+                    .copy_from_slice(&resampled_hole_mask);
+            }
+
             chunk.height_map_size = new_size;
             chunk.heightmap = Some(make_height_map_texture(resampled_heightmap, new_size));
         }
@@ -1532,6 +2980,7 @@ impl NodeTrait for Terrain {
                         PropertyValue::Sampler {
                             value: Some(chunk.layer_masks[layer_index].clone()),
                             fallback: Default::default(),
+                            priority: Default::default(),
                         },
                     ),
                     "Unable to set mask texture for terrain material.",
@@ -1543,11 +2992,63 @@ impl NodeTrait for Terrain {
                         PropertyValue::Sampler {
                             value: chunk.heightmap.clone(),
                             fallback: Default::default(),
+                            priority: Default::default(),
                         },
                     ),
                     "Unable to set height map texture for terrain material.",
                 );
 
+                if !layer.is_uv_transform_identity() {
+                    set_layer_property(
+                        &mut material,
+                        Layer::UV_SCALE_PROPERTY_NAME,
+                        PropertyValue::Vector2(layer.uv_scale),
+                    );
+                    set_layer_property(
+                        &mut material,
+                        Layer::UV_OFFSET_PROPERTY_NAME,
+                        PropertyValue::Vector2(layer.uv_offset),
+                    );
+                    set_layer_property(
+                        &mut material,
+                        Layer::UV_ROTATION_PROPERTY_NAME,
+                        PropertyValue::Float(layer.uv_rotation),
+                    );
+                }
+
+                // Normal mode does not touch the material, so custom shaders without blend mode properties
+                // keep working.
+                if layer.blend_mode != LayerBlendMode::Normal {
+                    set_layer_property(
+                        &mut material,
+                        Layer::BLEND_MODE_PROPERTY_NAME,
+                        PropertyValue::UInt(layer.blend_mode.shader_index()),
+                    );
+                    if let LayerBlendMode::HeightBased {
+                        min_height,
+                        max_height,
+                    } = layer.blend_mode
+                    {
+                        set_layer_property(
+                            &mut material,
+                            Layer::HEIGHT_RANGE_PROPERTY_NAME,
+                            PropertyValue::Vector2(Vector2::new(min_height, max_height)),
+                        );
+                    }
+                }
+
+                if let Some(hole_mask) = chunk.hole_mask.texture() {
+                    set_layer_property(
+                        &mut material,
+                        &layer.hole_mask_property_name,
+                        PropertyValue::Sampler {
+                            value: Some(hole_mask.clone()),
+                            fallback: SamplerFallback::Black,
+                            priority: Default::default(),
+                        },
+                    );
+                }
+
                 for node in selection {
                     let kx = node.position.x as f32 / self.height_map_size.x as f32;
                     let kz = node.position.y as f32 / self.height_map_size.y as f32;
@@ -1639,7 +3140,7 @@ impl NodeTrait for Terrain {
 }
 
 /// Shape of a brush.
-#[derive(Copy, Clone, Reflect, Debug)]
+#[derive(Copy, Clone, Reflect, Debug, Serialize, Deserialize)]
 pub enum BrushShape {
     /// Circle with given radius.
     Circle {
@@ -1670,20 +3171,184 @@ impl BrushShape {
             .contains(pixel_position),
         }
     }
+
+    /// Returns a rectangle, that encloses the brush with the given center.
+    fn bounds(&self, brush_center: Vector2<f32>) -> Rect<f32> {
+        let half_size = match *self {
+            BrushShape::Circle { radius } => Vector2::repeat(radius.abs()),
+            BrushShape::Rectangle { width, length } => Vector2::new(width, length).abs() * 0.5,
+        };
+        Rect::new(
+            brush_center.x - half_size.x,
+            brush_center.y - half_size.y,
+            half_size.x * 2.0,
+            half_size.y * 2.0,
+        )
+    }
+
+    /// Returns a distance from the center of the brush to the given point, normalized to `[0.0; 1.0]` range,
+    /// where `1.0` is the edge of the brush.
+    fn normalized_distance(&self, brush_center: Vector2<f32>, pixel_position: Vector2<f32>) -> f32 {
+        let offset = pixel_position - brush_center;
+        let distance = match *self {
+            BrushShape::Circle { radius } => offset.norm() / radius,
+            BrushShape::Rectangle { width, length } => {
+                (offset.x.abs() / (width * 0.5)).max(offset.y.abs() / (length * 0.5))
+            }
+        };
+        if distance.is_finite() {
+            distance.clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Falloff curve of a brush. It defines how the contribution of a brush fades from its center to its edges.
+#[derive(
+    Clone,
+    Default,
+    PartialEq,
+    Reflect,
+    Visit,
+    Debug,
+    Serialize,
+    Deserialize,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum BrushFalloff {
+    /// Contribution decreases linearly with distance.
+    Linear,
+    /// Contribution smoothly decreases with distance (inverted smoothstep), with soft center and soft edges.
+    Smooth,
+    /// Contribution quickly decreases near the center and slowly fades at the edges.
+    Sharp,
+    /// Contribution is the same for every point of the brush.
+    Constant,
+    /// Contribution is defined by a user curve, which maps normalized distance from the brush center (`0.0` is
+    /// the center, `1.0` is the edge) to a weight. Values of the curve are clamped to `[0.0; 1.0]` range.
+    Curve(#[serde(with = "curve_serde")] Curve),
+    /// Contribution depends on the shape and the mode of the brush: it is `1 - d²` for circle brushes, that
+    /// modify heights, `1 - d⁴` for circle brushes, that draw on masks, and constant for rectangle brushes and
+    /// for flattening (`d` is the normalized distance from the brush center). This is how brushes worked
+    /// before falloffs were added, so it is the default one.
+    #[default]
+    Classic,
+}
+
+/// Serde support for [`BrushFalloff::Curve`]. Curves do not implement serde traits, so only their keys are
+/// stored.
+mod curve_serde {
+    use crate::core::math::curve::{Curve, CurveKey, CurveKeyKind};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    enum KeyKind {
+        Constant,
+        Linear,
+        Cubic {
+            left_tangent: f32,
+            right_tangent: f32,
+        },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Key {
+        location: f32,
+        value: f32,
+        kind: KeyKind,
+    }
+
+    pub fn serialize<S: Serializer>(curve: &Curve, serializer: S) -> Result<S::Ok, S::Error> {
+        curve
+            .keys()
+            .iter()
+            .map(|key| Key {
+                location: key.location,
+                value: key.value,
+                kind: match key.kind {
+                    CurveKeyKind::Constant => KeyKind::Constant,
+                    CurveKeyKind::Linear => KeyKind::Linear,
+                    CurveKeyKind::Cubic {
+                        left_tangent,
+                        right_tangent,
+                    } => KeyKind::Cubic {
+                        left_tangent,
+                        right_tangent,
+                    },
+                },
+            })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Curve, D::Error> {
+        let keys = Vec::<Key>::deserialize(deserializer)?;
+        Ok(Curve::from(
+            keys.into_iter()
+                .map(|key| {
+                    let kind = match key.kind {
+                        KeyKind::Constant => CurveKeyKind::Constant,
+                        KeyKind::Linear => CurveKeyKind::Linear,
+                        KeyKind::Cubic {
+                            left_tangent,
+                            right_tangent,
+                        } => CurveKeyKind::Cubic {
+                            left_tangent,
+                            right_tangent,
+                        },
+                    };
+                    CurveKey::new(key.location, key.value, kind)
+                })
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+uuid_provider!(BrushFalloff = "8d3e2f5c-7f64-4a6e-9a47-61e3f0a1d2b4");
+
+impl BrushFalloff {
+    /// Calculates a weight of brush contribution at the given normalized distance from the brush center,
+    /// where `0.0` is the center of the brush and `1.0` is its edge. Returns a value in `[0.0; 1.0]` range.
+    /// [`Self::Classic`] falloff depends on the brush, this method uses its circle height profile for it.
+    pub fn weight(&self, normalized_distance: f32) -> f32 {
+        let t = normalized_distance.clamp(0.0, 1.0);
+        match self {
+            BrushFalloff::Linear => 1.0 - t,
+            BrushFalloff::Smooth => 1.0 - t * t * (3.0 - 2.0 * t),
+            BrushFalloff::Sharp => (1.0 - t) * (1.0 - t),
+            BrushFalloff::Constant => 1.0,
+            BrushFalloff::Curve(curve) => curve.value_at(t).clamp(0.0, 1.0),
+            BrushFalloff::Classic => 1.0 - t * t,
+        }
+    }
 }
 
 /// Paint mode of a brush. It defines operation that will be performed on the terrain.
-#[derive(Clone, PartialEq, PartialOrd, Reflect, Debug)]
+#[derive(Clone, PartialEq, PartialOrd, Reflect, Debug, Serialize, Deserialize)]
 pub enum BrushMode {
     /// Modifies height map.
     ModifyHeightMap {
         /// An offset for height map.
         amount: f32,
     },
-    /// Flattens height map.
+    /// Smooths height map by moving heights towards a weighted average of their neighbours. It could be used
+    /// to fix jagged slopes.
+    SmoothHeightMap {
+        /// Strength of smoothing in `[0.0; 1.0]` range, where `1.0` replaces heights with the average of their
+        /// neighbours.
+        strength: f32,
+    },
+    /// Flattens height map by moving heights towards the given height.
     FlattenHeightMap {
         /// Fixed height value for flattening.
         height: f32,
+        /// Strength of flattening in `[0.0; 1.0]` range, where `1.0` sets heights to the target height at
+        /// the center of the brush.
+        #[serde(default = "default_flatten_strength")]
+        strength: f32,
     },
     /// Draws on a given layer.
     DrawOnMask {
@@ -1697,16 +3362,90 @@ pub enum BrushMode {
 
 uuid_provider!(BrushMode = "48ad4cac-05f3-485a-b2a3-66812713841f");
 
-/// Brush is used to modify terrain. It supports multiple shapes and modes.
-#[derive(Clone, Reflect, Debug)]
+fn default_flatten_strength() -> f32 {
+    1.0
+}
+
+/// Terrain data produced by a brush, see [`Terrain::compute_brush`] for more info.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BrushDelta {
+    /// New height maps of the chunks touched by the brush, keyed by chunk index.
+    HeightMaps(BTreeMap<usize, Vec<f32>>),
+    /// New masks of a layer of the chunks touched by the brush.
+    LayerMasks {
+        /// Index of the layer.
+        layer: usize,
+        /// New mask data of the chunks touched by the brush, keyed by chunk index.
+        masks: BTreeMap<usize, Vec<u8>>,
+    },
+    /// The brush does not change anything.
+    None,
+}
+
+fn for_each_chunk_pixel<F>(chunk: &Chunk, size: Vector2<u32>, mut func: F)
+where
+    F: FnMut(usize, Vector2<f32>),
+{
+    for iy in 0..size.y {
+        let kz = iy as f32 / (size.y - 1) as f32;
+        for ix in 0..size.x {
+            let kx = ix as f32 / (size.x - 1) as f32;
+
+            let pixel_position = chunk.local_position()
+                + Vector2::new(kx * chunk.physical_size.x, kz * chunk.physical_size.y);
+
+            func((iy * size.x + ix) as usize, pixel_position)
+        }
+    }
+}
+
+/// Brush is used to modify terrain. It supports multiple shapes and modes. Brush settings (everything except
+/// its center) could be serialized using `serde`, which could be used to store brush presets.
+#[derive(Clone, Reflect, Debug, Serialize, Deserialize)]
 pub struct Brush {
     /// Center of the brush.
     #[reflect(hidden)]
+    #[serde(skip)]
     pub center: Vector3<f32>,
     /// Shape of the brush.
     pub shape: BrushShape,
     /// Paint mode of the brush.
     pub mode: BrushMode,
+    /// Falloff curve of the brush. It is applied to all brush modes.
+    #[serde(default)]
+    pub falloff: BrushFalloff,
+}
+
+impl Brush {
+    /// Returns a weight of brush contribution at the given point, according to brush shape and falloff.
+    fn weight(&self, center: Vector2<f32>, pixel_position: Vector2<f32>) -> f32 {
+        let distance = self.shape.normalized_distance(center, pixel_position);
+        match (&self.falloff, self.shape, &self.mode) {
+            (BrushFalloff::Classic, BrushShape::Rectangle { .. }, _)
+            | (BrushFalloff::Classic, _, BrushMode::FlattenHeightMap { .. }) => 1.0,
+            (BrushFalloff::Classic, _, BrushMode::DrawOnMask { .. }) => 1.0 - distance.powf(4.0),
+            (falloff, _, _) => falloff.weight(distance),
+        }
+    }
+}
+
+/// A height map stamp is used to put a detail height map (a rock, a crater, etc.) on a terrain, see
+/// [`Terrain::compute_stamp`] for more info.
+#[derive(Clone, Debug)]
+pub struct HeightMapStamp {
+    /// A grayscale texture with normalized heights of the stamp. Only Red channel of the texture is used. X axis
+    /// of the texture is mapped to X axis of the terrain and Y axis of the texture is mapped to Z axis of the
+    /// terrain (before rotation).
+    pub texture: TextureResource,
+    /// World-space position of the center of the stamp. It is projected on the terrain.
+    pub position: Vector3<f32>,
+    /// Rotation angle (in radians) of the stamp in XZ plane of the terrain.
+    pub rotation: f32,
+    /// Scale of the stamp. X and Z components define the size of the stamp in local coordinates of the terrain,
+    /// Y component defines the height of white pixels of the stamp (negative values could be used to dig).
+    pub scale: Vector3<f32>,
+    /// Strength of the stamp in `[0.0; 1.0]` range, which is used to blend the stamp with the terrain.
+    pub strength: f32,
 }
 
 /// Terrain builder allows you to quickly build a terrain with required features.
@@ -1722,6 +3461,71 @@ pub struct TerrainBuilder {
     decal_layer_index: u8,
 }
 
+/// Bilinearly resamples a row-major image of the given size. If `corner_aligned` is `true`, the corner
+/// pixels of the source are mapped to the corner pixels of the result (height maps share border pixels
+/// between chunks), otherwise the centers of pixels are mapped the same way as texture sampling does.
+/// Pixels, for which `skip` returns `true`, do not contribute to the result, unless every neighbour pixel
+/// is skipped.
+fn resample_bilinear(
+    source: &[f32],
+    source_size: Vector2<u32>,
+    new_size: Vector2<u32>,
+    corner_aligned: bool,
+    skip: impl Fn(usize) -> bool,
+) -> Vec<f32> {
+    let map = |i: u32, source: u32, new: u32| {
+        if corner_aligned {
+            if new > 1 {
+                i as f32 * (source - 1) as f32 / (new - 1) as f32
+            } else {
+                0.0
+            }
+        } else {
+            ((i as f32 + 0.5) * source as f32 / new as f32 - 0.5).max(0.0)
+        }
+    };
+
+    let mut result = Vec::with_capacity((new_size.x * new_size.y) as usize);
+    for y in 0..new_size.y {
+        let v = map(y, source_size.y, new_size.y);
+        let y0 = (v.floor() as u32).min(source_size.y - 1);
+        let y1 = (y0 + 1).min(source_size.y - 1);
+        let ty = v - y0 as f32;
+        for x in 0..new_size.x {
+            let u = map(x, source_size.x, new_size.x);
+            let x0 = (u.floor() as u32).min(source_size.x - 1);
+            let x1 = (x0 + 1).min(source_size.x - 1);
+            let tx = u - x0 as f32;
+
+            let samples = [
+                (x0, y0, (1.0 - tx) * (1.0 - ty)),
+                (x1, y0, tx * (1.0 - ty)),
+                (x0, y1, (1.0 - tx) * ty),
+                (x1, y1, tx * ty),
+            ];
+
+            let mut sum = 0.0;
+            let mut total_weight = 0.0;
+            let mut plain = 0.0;
+            for (x, y, weight) in samples {
+                let index = (y * source_size.x + x) as usize;
+                plain += source[index] * weight;
+                if !skip(index) {
+                    sum += source[index] * weight;
+                    total_weight += weight;
+                }
+            }
+
+            result.push(if total_weight > 0.0 {
+                sum / total_weight
+            } else {
+                plain
+            });
+        }
+    }
+    result
+}
+
 fn create_layer_mask(width: u32, height: u32, value: u8) -> TextureResource {
     let mask = TextureResource::from_bytes(
         TextureKind::Rectangle { width, height },
@@ -1837,6 +3641,8 @@ impl TerrainBuilder {
                         .collect::<Vec<_>>(),
                     version: VERSION,
                     block_size: self.block_size,
+                    hole_mask: Default::default(),
+                    dirty_regions: Default::default(),
                 };
 
                 chunks.push(chunk);
@@ -1867,3 +3673,674 @@ impl TerrainBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            math::{
+                curve::{Curve, CurveKey, CurveKeyKind},
+                ray::Ray,
+            },
+            sstorage::ImmutableString,
+        },
+        material::{
+            shader::{SamplerFallback, ShaderResource, ShaderResourceExtension},
+            Material, PropertyValue,
+        },
+        resource::texture::{
+            TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+        },
+        scene::{
+            base::BaseBuilder,
+            terrain::{
+                set_layer_property, Brush, BrushDelta, BrushFalloff, BrushMode, BrushShape,
+                HeightMapStamp, HeightfieldChunk, Layer, LayerBlendMode, Terrain, TerrainBuilder,
+            },
+        },
+    };
+    use fyrox_resource::untyped::ResourceKind;
+    use image::{ImageBuffer, Luma};
+
+    #[test]
+    fn test_brush_falloff() {
+        for falloff in [
+            BrushFalloff::Linear,
+            BrushFalloff::Smooth,
+            BrushFalloff::Sharp,
+            BrushFalloff::Constant,
+        ] {
+            assert_eq!(falloff.weight(0.0), 1.0);
+        }
+
+        assert!(BrushFalloff::Smooth.weight(1.0).abs() < 1.0e-6);
+        assert!(BrushFalloff::Smooth.weight(0.99) < 0.001);
+        assert_eq!(BrushFalloff::Smooth.weight(0.5), 0.5);
+        assert_eq!(BrushFalloff::Constant.weight(1.0), 1.0);
+
+        let mut curve = Curve::default();
+        curve.add_key(CurveKey::new(0.0, 1.0, CurveKeyKind::Linear));
+        curve.add_key(CurveKey::new(0.5, 0.0, CurveKeyKind::Linear));
+        curve.add_key(CurveKey::new(1.0, 2.0, CurveKeyKind::Linear));
+        let falloff = BrushFalloff::Curve(curve);
+        assert_eq!(falloff.weight(0.0), 1.0);
+        assert_eq!(falloff.weight(0.25), 0.5);
+        assert_eq!(falloff.weight(0.5), 0.0);
+        // Values of the curve are clamped.
+        assert_eq!(falloff.weight(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_brush_dirty_region() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(16.0, 16.0))
+            .with_height_map_size(Vector2::new(17, 17))
+            .with_width_chunks(0..1)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+
+        // Pixels of the height map are 1.0 apart, so the brush covers pixels in [7; 10] range on both axes.
+        terrain.draw(&Brush {
+            center: Vector3::new(8.5, 0.0, 8.5),
+            shape: BrushShape::Rectangle {
+                width: 4.0,
+                length: 4.0,
+            },
+            mode: BrushMode::FlattenHeightMap {
+                height: 1.0,
+                strength: 1.0,
+            },
+            falloff: BrushFalloff::Constant,
+        });
+
+        let chunk = &terrain.chunks_ref()[0];
+        let regions = chunk.take_dirty_regions();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(&regions[0].texture, chunk.heightmap());
+        assert_eq!(regions[0].min, Vector2::new(7, 7));
+        assert_eq!(regions[0].max, Vector2::new(10, 10));
+        assert_eq!(regions[0].size(), Vector2::new(4, 4));
+        assert!(chunk.dirty_regions().is_empty());
+    }
+
+    #[test]
+    fn test_hole_queries() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(16.0, 16.0))
+            .with_height_map_size(Vector2::new(17, 17))
+            .with_width_chunks(0..1)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+
+        assert!(terrain.chunk_hole_mask(Vector2::new(0, 0)).is_empty());
+        assert!(!terrain.has_hole_at(Vector2::new(3.0, 5.0)));
+
+        assert!(!terrain.chunks_mut()[0].set_hole(Vector2::new(3, 5), true));
+
+        let mask = terrain.chunk_hole_mask(Vector2::new(0, 0));
+        assert_eq!(mask.size(), Vector2::new(17, 17));
+        assert!(mask.is_hole(Vector2::new(3, 5)));
+        assert!(terrain.has_hole_at(Vector2::new(3.0, 5.0)));
+        assert!(!terrain.has_hole_at(Vector2::new(4.0, 5.0)));
+
+        // Out-of-bounds queries.
+        assert!(!terrain.has_hole_at(Vector2::new(-10.0, 5.0)));
+        assert!(terrain.chunk_hole_mask(Vector2::new(3, 3)).is_empty());
+    }
+
+    #[test]
+    fn test_layer_uv_transform() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_layers(vec![Layer::default()])
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+        assert!(terrain.layers()[0].is_uv_transform_identity());
+        assert_eq!(
+            terrain.layers()[0].transform_uv(Vector2::new(0.25, 0.5)),
+            Vector2::new(0.25, 0.5)
+        );
+
+        assert!(terrain.set_layer_uv_transform(
+            0,
+            Vector2::new(2.0, 4.0),
+            Vector2::new(0.5, 0.0),
+            std::f32::consts::FRAC_PI_2,
+        ));
+        assert!(!terrain.set_layer_uv_transform(
+            1,
+            Vector2::new(2.0, 2.0),
+            Vector2::default(),
+            0.0
+        ));
+
+        let layer = &terrain.layers()[0];
+        assert!(!layer.is_uv_transform_identity());
+        // (0.25, 0.5) -> scale -> (0.5, 2.0) -> rotate 90 degrees -> (-2.0, 0.5) -> offset -> (-1.5, 0.5)
+        let uv = layer.transform_uv(Vector2::new(0.25, 0.5));
+        assert!((uv - Vector2::new(-1.5, 0.5)).norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_flat_heightfield() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(16.0, 16.0))
+            .with_height_map_size(Vector2::new(17, 17))
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+
+        let mut data = terrain.build_heightfield_collider();
+        assert_eq!(data.chunks.len(), 2);
+        for chunk in data.chunks.iter() {
+            assert_eq!(chunk.size, Vector2::new(17, 17));
+            assert_eq!(chunk.heights.len(), 17 * 17);
+            assert!(chunk.heights.iter().all(|height| *height == 0.0));
+            assert!(chunk.holes.is_empty());
+            assert!(!chunk.is_cell_removed(Vector2::new(0, 0)));
+        }
+
+        // Nothing has changed.
+        assert!(terrain.update_heightfield_collider(&mut data).is_empty());
+
+        terrain.chunks_mut()[1].set_hole(Vector2::new(3, 5), true);
+        assert_eq!(terrain.update_heightfield_collider(&mut data), [1]);
+        let chunk: &HeightfieldChunk = &data.chunks[1];
+        assert!(chunk.is_hole(Vector2::new(3, 5)));
+        assert!(chunk.is_cell_removed(Vector2::new(2, 4)));
+        assert!(chunk.is_cell_removed(Vector2::new(3, 5)));
+        assert!(!chunk.is_cell_removed(Vector2::new(4, 5)));
+    }
+
+    #[test]
+    fn test_move_layer() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..1)
+            .with_layers(vec![
+                Layer::default(),
+                Layer {
+                    mask_property_name: "First".to_string(),
+                    ..Default::default()
+                },
+                Layer {
+                    mask_property_name: "Second".to_string(),
+                    ..Default::default()
+                },
+            ])
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+
+        let masks = terrain
+            .chunks_ref()
+            .iter()
+            .map(|chunk| chunk.layer_masks[2].clone())
+            .collect::<Vec<_>>();
+
+        assert!(terrain.move_layer(2, 0));
+        assert_eq!(terrain.layers()[0].mask_property_name, "Second");
+        assert_eq!(terrain.layers()[2].mask_property_name, "First");
+        for (chunk, mask) in terrain.chunks_ref().iter().zip(masks.iter()) {
+            assert_eq!(&chunk.layer_masks[0], mask);
+        }
+
+        assert!(terrain.move_layer(0, 2));
+        assert_eq!(terrain.layers()[2].mask_property_name, "Second");
+        for (chunk, mask) in terrain.chunks_ref().iter().zip(masks.iter()) {
+            assert_eq!(&chunk.layer_masks[2], mask);
+        }
+
+        assert!(!terrain.move_layer(0, 3));
+    }
+
+    #[test]
+    fn test_smooth_brush() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(4.0, 4.0))
+            .with_height_map_size(Vector2::new(5, 5))
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+
+        // Make a spike at the border of the chunks, its pixel is shared by both chunks.
+        terrain.for_each_height_map_pixel(|height, position| {
+            if position == Vector2::new(4.0, 2.0) {
+                *height = 16.0;
+            }
+        });
+
+        terrain.draw(&Brush {
+            center: Vector3::new(4.0, 0.0, 2.0),
+            shape: BrushShape::Circle { radius: 0.5 },
+            mode: BrushMode::SmoothHeightMap { strength: 1.0 },
+            falloff: BrushFalloff::Constant,
+        });
+
+        // Spike is replaced by the weighted average of its neighbourhood, and the result is the same on
+        // both sides of the border.
+        let chunks = terrain.chunks_ref();
+        let left = chunks[0].heightmap_owned();
+        let right = chunks[1].heightmap_owned();
+        assert_eq!(left[2 * 5 + 4], 4.0);
+        assert_eq!(right[2 * 5], 4.0);
+        // Neighbours are untouched, since they're outside of the brush.
+        assert_eq!(left[2 * 5 + 3], 0.0);
+    }
+
+    #[test]
+    fn test_flatten_brush() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(16.0, 16.0))
+            .with_height_map_size(Vector2::new(17, 17))
+            .with_width_chunks(0..1)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+
+        let mut brush = Brush {
+            center: Vector3::new(8.0, 0.0, 8.0),
+            shape: BrushShape::Circle { radius: 4.0 },
+            mode: BrushMode::FlattenHeightMap {
+                height: 2.0,
+                strength: 0.5,
+            },
+            falloff: BrushFalloff::Linear,
+        };
+        terrain.draw(&brush);
+
+        let height_map = terrain.chunks_ref()[0].heightmap_owned();
+        // Center of the brush is moved halfway to the target height.
+        assert_eq!(height_map[8 * 17 + 8], 1.0);
+        // Heights are blended towards the border of the brush.
+        assert_eq!(height_map[8 * 17 + 10], 0.5);
+        assert_eq!(height_map[8 * 17 + 12], 0.0);
+
+        // Constant falloff with full strength produces a plateau with hard walls.
+        brush.falloff = BrushFalloff::Constant;
+        brush.mode = BrushMode::FlattenHeightMap {
+            height: 2.0,
+            strength: 1.0,
+        };
+        terrain.draw(&brush);
+        let height_map = terrain.chunks_ref()[0].heightmap_owned();
+        assert_eq!(height_map[8 * 17 + 8], 2.0);
+        assert_eq!(height_map[8 * 17 + 11], 2.0);
+        assert_eq!(height_map[8 * 17 + 12], 0.0);
+    }
+
+    #[test]
+    fn test_heightmap_r16() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(4.0, 4.0))
+            .with_height_map_size(Vector2::new(5, 5))
+            .with_width_chunks(-1..1)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+        assert_eq!(terrain.height_map_grid_size(), Vector2::new(9, 5));
+
+        terrain.for_each_height_map_pixel(|height, position| *height = position.x + 4.0);
+
+        let image = terrain.heightmap_r16(0.0..8.0);
+        assert_eq!(image.dimensions(), (9, 5));
+        assert_eq!(image.get_pixel(0, 0)[0], 0);
+        assert_eq!(image.get_pixel(4, 2)[0], 32768);
+        assert_eq!(image.get_pixel(8, 4)[0], u16::MAX);
+
+        // Image of a different size is resampled.
+        let image = ImageBuffer::from_fn(3, 2, |x, _| Luma([(x * u16::MAX as u32 / 2) as u16]));
+        terrain.set_heightmap_r16(&image, 0.0..8.0);
+        for chunk in terrain.chunks_ref() {
+            let x = chunk.local_position().x;
+            for (index, height) in chunk.heightmap_owned().iter().enumerate() {
+                let expected = x + (index % 5) as f32 + 4.0;
+                assert!((height - expected).abs() < 1.0e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_height_at() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(4.0, 4.0))
+            .with_height_map_size(Vector2::new(5, 5))
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+
+        // A slope along X axis.
+        terrain.for_each_height_map_pixel(|height, position| *height = position.x * 0.5);
+
+        assert_eq!(terrain.height_at(Vector3::new(1.5, 10.0, 2.25)), Some(0.75));
+        assert_eq!(terrain.height_at(Vector3::new(6.0, 0.0, 4.0)), Some(3.0));
+        assert_eq!(terrain.height_at(Vector3::new(-1.0, 0.0, 2.0)), None);
+        assert_eq!(terrain.height_at(Vector3::new(1.0, 0.0, 5.0)), None);
+
+        let normal = terrain.normal_at(Vector3::new(5.5, 0.0, 1.0)).unwrap();
+        let expected = Vector3::new(-0.5, 1.0, 0.0).normalize();
+        assert!((normal - expected).norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_raycast_closest() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(8.0, 8.0))
+            .with_height_map_size(Vector2::new(9, 9))
+            .with_block_size(Vector2::new(2, 2))
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..1)
+            .with_layers(vec![Layer::default(), Layer::default()])
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+
+        terrain.for_each_height_map_pixel(|height, _| *height = 1.0);
+        terrain.draw(&Brush {
+            center: Vector3::new(12.0, 0.0, 4.0),
+            shape: BrushShape::Circle { radius: 2.0 },
+            mode: BrushMode::DrawOnMask {
+                layer: 1,
+                alpha: 1.0,
+            },
+            falloff: BrushFalloff::Constant,
+        });
+
+        let hit = terrain
+            .raycast_closest(&Ray::from_two_points(
+                Vector3::new(3.5, 10.0, 4.5),
+                Vector3::new(3.5, -10.0, 4.5),
+            ))
+            .unwrap();
+        assert_eq!(hit.chunk_index, 0);
+        assert!((hit.position - Vector3::new(3.5, 1.0, 4.5)).norm() < 1.0e-5);
+        assert!((hit.normal - Vector3::y()).norm() < 1.0e-5);
+        assert_eq!(hit.layer, Some(0));
+        assert!((hit.toi - 0.45).abs() < 1.0e-5);
+
+        let hit = terrain
+            .raycast_closest(&Ray::from_two_points(
+                Vector3::new(12.0, 10.0, 4.0),
+                Vector3::new(12.0, -10.0, 4.0),
+            ))
+            .unwrap();
+        assert_eq!(hit.chunk_index, 1);
+        assert_eq!(hit.layer, Some(1));
+
+        // Ray misses the terrain.
+        assert!(terrain
+            .raycast_closest(&Ray::from_two_points(
+                Vector3::new(20.0, 10.0, 4.0),
+                Vector3::new(20.0, -10.0, 4.0),
+            ))
+            .is_none());
+    }
+
+    #[test]
+    fn test_height_map_stamp() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(8.0, 8.0))
+            .with_height_map_size(Vector2::new(9, 9))
+            .with_width_chunks(0..1)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+
+        let mut stamp = HeightMapStamp {
+            texture: TextureResource::from_bytes(
+                TextureKind::Rectangle {
+                    width: 2,
+                    height: 2,
+                },
+                TexturePixelKind::R8,
+                vec![255; 4],
+                ResourceKind::Embedded,
+            )
+            .unwrap(),
+            position: Vector3::new(4.0, 0.0, 4.0),
+            rotation: 0.0,
+            scale: Vector3::new(4.0, 2.0, 4.0),
+            strength: 0.5,
+        };
+        terrain.stamp(&stamp);
+
+        let height_map = terrain.chunks_ref()[0].heightmap_owned();
+        assert_eq!(height_map[4 * 9 + 4], 1.0);
+        assert_eq!(height_map[4 * 9 + 6], 1.0);
+        assert_eq!(height_map[6 * 9 + 6], 1.0);
+        assert_eq!(height_map[4 * 9 + 7], 0.0);
+
+        // Rotated stamp does not cover the corners of the non-rotated one.
+        stamp.rotation = 45.0f32.to_radians();
+        terrain.stamp(&stamp);
+        let height_map = terrain.chunks_ref()[0].heightmap_owned();
+        assert_eq!(height_map[4 * 9 + 6], 2.0);
+        assert_eq!(height_map[6 * 9 + 6], 1.0);
+    }
+
+    #[test]
+    fn test_layer_height_blend_weight() {
+        assert_eq!(LayerBlendMode::Normal.height_weight(-100.0), 1.0);
+        assert_eq!(LayerBlendMode::Additive.height_weight(100.0), 1.0);
+
+        let snow = LayerBlendMode::HeightBased {
+            min_height: 10.0,
+            max_height: 20.0,
+        };
+        assert_eq!(snow.height_weight(5.0), 0.0);
+        assert_eq!(snow.height_weight(15.0), 0.5);
+        assert_eq!(snow.height_weight(25.0), 1.0);
+
+        let lowlands = LayerBlendMode::HeightBased {
+            min_height: 20.0,
+            max_height: 10.0,
+        };
+        assert_eq!(lowlands.height_weight(5.0), 1.0);
+        assert_eq!(lowlands.height_weight(25.0), 0.0);
+    }
+
+    #[test]
+    fn test_resample() {
+        let mut node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(4.0, 4.0))
+            .with_height_map_size(Vector2::new(5, 5))
+            .with_mask_size(Vector2::new(4, 4))
+            .with_layers(vec![Layer::default()])
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+        terrain.for_each_height_map_pixel(|height, position| *height = position.x - 4.0);
+        terrain.chunks_mut()[0].set_hole(Vector2::new(2, 2), true);
+        terrain.chunks_mut()[0]
+            .heightmap
+            .as_ref()
+            .unwrap()
+            .data_ref()
+            .modify()
+            .data_mut_of_type::<f32>()
+            .unwrap()[2 * 5 + 2] = 100.0;
+
+        terrain.resample(Vector2::new(9, 9), Vector2::new(8, 8));
+
+        assert_eq!(terrain.height_map_size(), Vector2::new(9, 9));
+        assert_eq!(terrain.mask_size(), Vector2::new(8, 8));
+
+        let chunks = terrain.chunks_ref();
+        let first = chunks[0].heightmap_owned();
+        let second = chunks[1].heightmap_owned();
+        assert_eq!(first.len(), 81);
+        // Linear slopes are kept as is, and the chunks are still seamless.
+        for row in 0..9 {
+            assert!((second[row * 9 + 1] - 0.5).abs() < 1.0e-5);
+            assert_eq!(first[row * 9 + 8], second[row * 9]);
+        }
+        // The height under the hole does not leak into its surroundings.
+        assert!((first[9 + 1] + 3.5).abs() < 1.0e-5);
+        // Only three of four neighbours are used here.
+        assert!((first[3 * 9 + 3] + 8.0 / 3.0).abs() < 1.0e-5);
+        assert!(chunks[0].hole_mask().is_hole(Vector2::new(4, 4)));
+        assert!(!chunks[0].hole_mask().is_hole(Vector2::new(2, 2)));
+        assert!(chunks[1].hole_mask().is_empty());
+
+        // Masks are resampled too.
+        let mask = chunks[0].layer_masks[0].data_ref();
+        assert_eq!(mask.data().len(), 64);
+        assert!(mask.data().iter().all(|value| *value == 255));
+    }
+
+    #[test]
+    fn test_brush_delta_contains_touched_chunks_only() {
+        let node = TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(4.0, 4.0))
+            .with_height_map_size(Vector2::new(5, 5))
+            .with_width_chunks(0..3)
+            .with_length_chunks(0..1)
+            .build_node();
+        let terrain = node.cast::<Terrain>().unwrap();
+
+        let delta_chunks =
+            |center: Vector3<f32>, mode: BrushMode| match terrain.compute_brush(&Brush {
+                center,
+                shape: BrushShape::Circle { radius: 1.0 },
+                mode,
+                falloff: BrushFalloff::Constant,
+            }) {
+                BrushDelta::HeightMaps(height_maps) => height_maps.into_keys().collect::<Vec<_>>(),
+                _ => unreachable!(),
+            };
+
+        for mode in [
+            BrushMode::ModifyHeightMap { amount: 1.0 },
+            BrushMode::SmoothHeightMap { strength: 1.0 },
+            BrushMode::FlattenHeightMap {
+                height: 1.0,
+                strength: 1.0,
+            },
+        ] {
+            assert_eq!(delta_chunks(Vector3::new(2.0, 0.0, 2.0), mode.clone()), [0]);
+            // The brush at the border touches both adjacent chunks.
+            assert_eq!(delta_chunks(Vector3::new(8.0, 0.0, 2.0), mode), [1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_brush_falloff_curve_serde() {
+        let curve = Curve::from(vec![
+            CurveKey::new(0.0, 1.0, CurveKeyKind::Linear),
+            CurveKey::new(0.5, 0.25, CurveKeyKind::new_cubic(0.1, 0.2)),
+            CurveKey::new(1.0, 0.0, CurveKeyKind::Constant),
+        ]);
+        let falloff = BrushFalloff::Curve(curve.clone());
+
+        let text = ron::to_string(&falloff).unwrap();
+        let BrushFalloff::Curve(loaded) = ron::from_str::<BrushFalloff>(&text).unwrap() else {
+            panic!("Curve falloff must be deserialized as a curve!");
+        };
+
+        assert_eq!(loaded.keys().len(), curve.keys().len());
+        for (loaded, key) in loaded.keys().iter().zip(curve.keys()) {
+            assert_eq!(loaded.location, key.location);
+            assert_eq!(loaded.value, key.value);
+            assert_eq!(loaded.kind, key.kind);
+        }
+    }
+
+    #[test]
+    fn test_default_brush_falloff() {
+        let brush = |shape: BrushShape, mode: BrushMode| Brush {
+            center: Default::default(),
+            shape,
+            mode,
+            falloff: Default::default(),
+        };
+        let circle = BrushShape::Circle { radius: 2.0 };
+        let rectangle = BrushShape::Rectangle {
+            width: 4.0,
+            length: 4.0,
+        };
+        let height = BrushMode::ModifyHeightMap { amount: 1.0 };
+        let mask = BrushMode::DrawOnMask {
+            layer: 0,
+            alpha: 1.0,
+        };
+
+        // The same profiles as before falloffs were added.
+        for x in [0.0, 0.5, 1.0, 1.5] {
+            let center = Vector2::default();
+            let pixel = Vector2::new(x, 0.0);
+            let d = x / 2.0f32;
+            assert_eq!(
+                brush(circle, height.clone()).weight(center, pixel),
+                1.0 - d * d
+            );
+            assert_eq!(
+                brush(circle, mask.clone()).weight(center, pixel),
+                1.0 - d.powf(4.0)
+            );
+            assert_eq!(brush(rectangle, height.clone()).weight(center, pixel), 1.0);
+            assert_eq!(brush(rectangle, mask.clone()).weight(center, pixel), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_set_layer_property() {
+        let offset = ImmutableString::new(Layer::UV_OFFSET_PROPERTY_NAME);
+
+        let mut material = Material::standard_terrain();
+        assert!(set_layer_property(
+            &mut material,
+            Layer::UV_OFFSET_PROPERTY_NAME,
+            PropertyValue::Vector2(Vector2::new(0.5, 0.25)),
+        ));
+        assert!(matches!(
+            material.property_ref(&offset),
+            Some(PropertyValue::Vector2(v)) if *v == Vector2::new(0.5, 0.25)
+        ));
+
+        assert!(set_layer_property(
+            &mut material,
+            Layer::BLEND_MODE_PROPERTY_NAME,
+            PropertyValue::UInt(LayerBlendMode::Normal.shader_index()),
+        ));
+
+        // Custom shaders without the property are skipped.
+        let code = r#"
+            (
+                name: "CustomTerrainShader",
+                properties: [
+                    (
+                        name: "maskTexture",
+                        kind: Sampler(default: None, fallback: White),
+                    ),
+                ],
+                passes: [],
+            )
+            "#;
+        let shader = ShaderResource::from_str(code, ResourceKind::Embedded).unwrap();
+        let mut material = Material::from_shader(shader, None);
+        assert!(!set_layer_property(
+            &mut material,
+            Layer::UV_OFFSET_PROPERTY_NAME,
+            PropertyValue::Vector2(Vector2::new(0.5, 0.25)),
+        ));
+        assert!(material.property_ref(&offset).is_none());
+        assert!(!set_layer_property(
+            &mut material,
+            Layer::HEIGHT_RANGE_PROPERTY_NAME,
+            PropertyValue::Vector2(Vector2::new(0.0, 1.0)),
+        ));
+        assert!(!set_layer_property(
+            &mut material,
+            &Layer::default().hole_mask_property_name,
+            PropertyValue::Sampler {
+                value: None,
+                fallback: SamplerFallback::Black,
+                priority: Default::default(),
+            },
+        ));
+    }
+}
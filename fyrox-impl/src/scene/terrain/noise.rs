@@ -0,0 +1,344 @@
+//! Procedural noise for terrain generation, see [`NoiseParams`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector2,
+        math::Rect,
+        rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng},
+    },
+    scene::terrain::{BrushDelta, Terrain},
+};
+use std::collections::BTreeMap;
+
+/// Kind of gradient noise, that is used as a base for every octave of fractal noise.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum NoiseKind {
+    /// Classic Perlin noise. It is aligned to a square grid, which could be noticeable on some terrains.
+    #[default]
+    Perlin,
+    /// Simplex noise. It has fewer directional artifacts than Perlin noise.
+    Simplex,
+}
+
+/// Defines how generated noise is combined with existing heights of a terrain.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum NoiseBlendMode {
+    /// Existing heights are replaced with the noise.
+    Set,
+    /// The noise is added to existing heights.
+    #[default]
+    Add,
+}
+
+/// Parameters of layered fractal noise (also known as fractional Brownian motion, or fBm), that could be
+/// applied to a terrain using [`Terrain::apply_noise`]. Every octave of the noise has its frequency multiplied
+/// by [`Self::lacunarity`] and its amplitude multiplied by [`Self::persistence`] compared to the previous one.
+///
+/// The noise is sampled in local coordinates of the terrain, so there are no seams between chunks. The output
+/// is fully defined by the parameters, which means that the same seed always produces the same terrain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseParams {
+    /// Kind of noise of each octave.
+    pub kind: NoiseKind,
+    /// Seed of the noise. The same seed always produces the same noise.
+    pub seed: u64,
+    /// Total number of octaves. More octaves add more fine details.
+    pub octaves: u32,
+    /// Frequency of the first octave in cycles per local unit of the terrain.
+    pub frequency: f32,
+    /// Amplitude of the first octave in local units of the terrain.
+    pub amplitude: f32,
+    /// Frequency multiplier of each next octave.
+    pub lacunarity: f32,
+    /// Amplitude multiplier of each next octave.
+    pub persistence: f32,
+    /// Defines how the noise is combined with existing heights.
+    pub blend_mode: NoiseBlendMode,
+    /// An optional region (in local 2D coordinates of the terrain) to apply the noise to. `None` means that
+    /// the noise will be applied to the whole terrain.
+    pub region: Option<Rect<f32>>,
+}
+
+impl Default for NoiseParams {
+    fn default() -> Self {
+        Self {
+            kind: Default::default(),
+            seed: 0,
+            octaves: 4,
+            frequency: 0.01,
+            amplitude: 10.0,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            blend_mode: Default::default(),
+            region: None,
+        }
+    }
+}
+
+/// Gradient noise generator with a permutation table defined by a seed.
+struct NoiseGenerator {
+    permutation: [u8; 512],
+    offsets: Vec<Vector2<f32>>,
+}
+
+impl NoiseGenerator {
+    fn new(seed: u64, octaves: u32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut table = (0..=255u8).collect::<Vec<_>>();
+        table.shuffle(&mut rng);
+
+        let mut permutation = [0; 512];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = table[i & 255];
+        }
+
+        // Each octave is shifted by a random offset, otherwise all octaves would be equal to zero at the
+        // origin.
+        let offsets = (0..octaves)
+            .map(|_| Vector2::new(rng.gen_range(-256.0..256.0), rng.gen_range(-256.0..256.0)))
+            .collect();
+
+        Self {
+            permutation,
+            offsets,
+        }
+    }
+
+    fn hash(&self, x: i32, y: i32) -> u8 {
+        let x = (x & 255) as usize;
+        let y = (y & 255) as usize;
+        self.permutation[self.permutation[x] as usize + y]
+    }
+
+    fn gradient(hash: u8, x: f32, y: f32) -> f32 {
+        match hash & 7 {
+            0 => x + y,
+            1 => -x + y,
+            2 => x - y,
+            3 => -x - y,
+            4 => x,
+            5 => -x,
+            6 => y,
+            _ => -y,
+        }
+    }
+
+    fn perlin(&self, p: Vector2<f32>) -> f32 {
+        fn fade(t: f32) -> f32 {
+            t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+        }
+
+        fn lerp(a: f32, b: f32, t: f32) -> f32 {
+            a + (b - a) * t
+        }
+
+        let x0 = p.x.floor();
+        let y0 = p.y.floor();
+        let (xi, yi) = (x0 as i32, y0 as i32);
+        let (xf, yf) = (p.x - x0, p.y - y0);
+        let (u, v) = (fade(xf), fade(yf));
+
+        let n00 = Self::gradient(self.hash(xi, yi), xf, yf);
+        let n10 = Self::gradient(self.hash(xi + 1, yi), xf - 1.0, yf);
+        let n01 = Self::gradient(self.hash(xi, yi + 1), xf, yf - 1.0);
+        let n11 = Self::gradient(self.hash(xi + 1, yi + 1), xf - 1.0, yf - 1.0);
+
+        lerp(lerp(n00, n10, u), lerp(n01, n11, u), v)
+    }
+
+    fn simplex(&self, p: Vector2<f32>) -> f32 {
+        const F2: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+        const G2: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+
+        // Skew the input space to find the simplex cell.
+        let s = (p.x + p.y) * F2;
+        let i = (p.x + s).floor();
+        let j = (p.y + s).floor();
+        let t = (i + j) * G2;
+        let x0 = p.x - (i - t);
+        let y0 = p.y - (j - t);
+
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+
+        let x1 = x0 - i1 as f32 + G2;
+        let y1 = y0 - j1 as f32 + G2;
+        let x2 = x0 - 1.0 + 2.0 * G2;
+        let y2 = y0 - 1.0 + 2.0 * G2;
+
+        let (i, j) = (i as i32, j as i32);
+        let corner = |hash: u8, x: f32, y: f32| {
+            let t = 0.5 - x * x - y * y;
+            if t < 0.0 {
+                0.0
+            } else {
+                let t2 = t * t;
+                t2 * t2 * Self::gradient(hash, x, y)
+            }
+        };
+
+        let n0 = corner(self.hash(i, j), x0, y0);
+        let n1 = corner(self.hash(i + i1, j + j1), x1, y1);
+        let n2 = corner(self.hash(i + 1, j + 1), x2, y2);
+
+        // Scale the result to roughly [-1; 1] range.
+        70.0 * (n0 + n1 + n2)
+    }
+
+    fn fbm(&self, params: &NoiseParams, position: Vector2<f32>) -> f32 {
+        let mut frequency = params.frequency;
+        let mut amplitude = params.amplitude;
+        let mut sum = 0.0;
+        for offset in self.offsets.iter() {
+            let p = position * frequency + offset;
+            let noise = match params.kind {
+                NoiseKind::Perlin => self.perlin(p),
+                NoiseKind::Simplex => self.simplex(p),
+            };
+            sum += noise * amplitude;
+            frequency *= params.lacunarity;
+            amplitude *= params.persistence;
+        }
+        sum
+    }
+}
+
+impl Terrain {
+    /// Computes the terrain data that would be produced by the given noise without modifying the terrain.
+    /// The result could be applied by [`Self::apply_brush_delta`], which allows you to implement undo in the
+    /// same way as for brushes. See [`NoiseParams`] docs for more info.
+    pub fn compute_noise(&self, params: &NoiseParams) -> BrushDelta {
+        let generator = NoiseGenerator::new(params.seed, params.octaves);
+
+        let mut height_maps = BTreeMap::new();
+        for (chunk_index, chunk) in self.chunks.iter().enumerate() {
+            if let Some(region) = params.region.as_ref() {
+                if !chunk.intersects(region) {
+                    continue;
+                }
+            }
+
+            let mut height_map = chunk.heightmap_owned();
+            super::for_each_chunk_pixel(chunk, chunk.height_map_size, |index, pixel_position| {
+                if params
+                    .region
+                    .as_ref()
+                    .map_or(true, |region| region.contains(pixel_position))
+                {
+                    let noise = generator.fbm(params, pixel_position);
+                    let height = &mut height_map[index];
+                    match params.blend_mode {
+                        NoiseBlendMode::Set => *height = noise,
+                        NoiseBlendMode::Add => *height += noise,
+                    }
+                }
+            });
+            height_maps.insert(chunk_index, height_map);
+        }
+
+        BrushDelta::HeightMaps(height_maps)
+    }
+
+    /// Applies layered fractal noise to the height maps of the terrain. It is equivalent to
+    /// [`Self::compute_noise`] followed by [`Self::apply_brush_delta`]. See [`NoiseParams`] docs for more info.
+    pub fn apply_noise(&mut self, params: NoiseParams) {
+        let delta = self.compute_noise(&params);
+        self.apply_brush_delta(&delta);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, math::Rect},
+        scene::{
+            base::BaseBuilder,
+            node::Node,
+            terrain::{
+                noise::{NoiseBlendMode, NoiseGenerator, NoiseKind, NoiseParams},
+                Terrain, TerrainBuilder,
+            },
+        },
+    };
+
+    fn make_terrain() -> Node {
+        TerrainBuilder::new(BaseBuilder::new())
+            .with_chunk_size(Vector2::new(16.0, 16.0))
+            .with_height_map_size(Vector2::new(17, 17))
+            .with_width_chunks(0..2)
+            .with_length_chunks(0..1)
+            .build_node()
+    }
+
+    fn height_maps(node: &Node) -> Vec<Vec<f32>> {
+        node.cast::<Terrain>()
+            .unwrap()
+            .chunks_ref()
+            .iter()
+            .map(|chunk| chunk.heightmap_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_noise_is_zero_at_lattice_points() {
+        let generator = NoiseGenerator::new(123, 1);
+        assert_eq!(generator.perlin(Vector2::new(3.0, -5.0)), 0.0);
+        assert_eq!(generator.simplex(Vector2::new(0.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn test_apply_noise() {
+        for kind in [NoiseKind::Perlin, NoiseKind::Simplex] {
+            let params = NoiseParams {
+                kind,
+                seed: 42,
+                frequency: 0.1,
+                amplitude: 4.0,
+                blend_mode: NoiseBlendMode::Set,
+                ..Default::default()
+            };
+
+            let mut a = make_terrain();
+            let mut b = make_terrain();
+            a.cast_mut::<Terrain>().unwrap().apply_noise(params.clone());
+            b.cast_mut::<Terrain>().unwrap().apply_noise(params.clone());
+
+            // Same seed - same result.
+            let a = height_maps(&a);
+            assert_eq!(a, height_maps(&b));
+            assert!(a.iter().flatten().any(|h| *h != 0.0));
+            // Sum of amplitudes of all octaves limits the heights.
+            assert!(a.iter().flatten().all(|h| h.abs() <= 8.0));
+            // There are no seams between chunks.
+            for row in 0..17 {
+                assert_eq!(a[0][row * 17 + 16], a[1][row * 17]);
+            }
+
+            // Different seed - different result.
+            let mut c = make_terrain();
+            c.cast_mut::<Terrain>().unwrap().apply_noise(NoiseParams {
+                seed: 43,
+                ..params.clone()
+            });
+            assert_ne!(a, height_maps(&c));
+        }
+    }
+
+    #[test]
+    fn test_apply_noise_to_region() {
+        let mut node = make_terrain();
+        let terrain = node.cast_mut::<Terrain>().unwrap();
+        terrain.for_each_height_map_pixel(|height, _| *height = 1.0);
+        terrain.apply_noise(NoiseParams {
+            frequency: 0.1,
+            region: Some(Rect::new(0.0, 0.0, 8.0, 8.0)),
+            ..Default::default()
+        });
+
+        let height_maps = height_maps(&node);
+        // Second chunk is outside of the region.
+        assert!(height_maps[1].iter().all(|h| *h == 1.0));
+        assert!(height_maps[0].iter().any(|h| *h != 1.0));
+        assert_eq!(height_maps[0][16 * 17 + 16], 1.0);
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     core::{
         algebra::{Matrix4, Vector2, Vector3},
         color::Color,
-        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum},
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, ray::Ray},
     },
     scene::debug::SceneDrawingContext,
 };
@@ -34,9 +34,12 @@ pub struct QuadTreeNode {
     /// This determines whether we should render this node directly (if `level` is high enough)
     /// or whether we should render this node's children (if `level` is too low).
     pub level: u32,
-    /// The minimum of all terrain height data within the area this node represents.
+    /// The minimum of all terrain height data within the area this node represents. The area includes
+    /// the first row and column of the neighbouring nodes, because the cells at the edges of the node are
+    /// connected to them.
     pub min_height: f32,
-    /// The maximum of all terrain height data within the area this node represents.
+    /// The maximum of all terrain height data within the area this node represents. See
+    /// [`Self::min_height`] for the details about the area.
     pub max_height: f32,
     /// A number that is unique to each node in the tree, increment as the tree is constructed
     /// so that each constructed node gets a value one greater than the previous node.
@@ -113,8 +116,8 @@ impl QuadTreeNode {
     ) -> Self {
         let mut min_height = f32::MAX;
         let mut max_height = f32::MIN;
-        for y in position.y..((position.y + node_size.y).min(height_map_size.y)) {
-            for x in position.x..((position.x + node_size.x).min(height_map_size.x)) {
+        for y in position.y..((position.y + node_size.y + 1).min(height_map_size.y)) {
+            for x in position.x..((position.x + node_size.x + 1).min(height_map_size.x)) {
                 let height = height_map[(y * height_map_size.x + x) as usize];
                 if height < min_height {
                     min_height = height;
@@ -187,6 +190,42 @@ impl QuadTreeNode {
         }
     }
 
+    fn raycast_leafs<'a>(
+        &'a self,
+        ray: &Ray,
+        height_map_size: Vector2<u32>,
+        cell_size: Vector2<f32>,
+        leafs: &mut Vec<&'a QuadTreeNode>,
+    ) {
+        let last = height_map_size.map(|n| n.saturating_sub(1));
+        let end = (self.position + self.size).inf(&last);
+        let min = Vector3::new(
+            self.position.x as f32 * cell_size.x,
+            self.min_height,
+            self.position.y as f32 * cell_size.y,
+        );
+        let max = Vector3::new(
+            end.x as f32 * cell_size.x,
+            self.max_height,
+            end.y as f32 * cell_size.y,
+        );
+
+        if ray.box_intersection(&min, &max).is_none() {
+            return;
+        }
+
+        match self.kind {
+            QuadTreeNodeKind::Leaf => leafs.push(self),
+            QuadTreeNodeKind::Branch {
+                leafs: ref children,
+            } => {
+                for child in children {
+                    child.raycast_leafs(ray, height_map_size, cell_size, leafs);
+                }
+            }
+        }
+    }
+
     /// Construct an AABB for the node.
     /// * transform: Transformation matrix to apply to the AABB just before it is returned.
     /// * height_map_size: The overall size of the whole of the height map data that this node is a part of.
@@ -373,6 +412,26 @@ impl QuadTree {
         Self { max_level, root }
     }
 
+    /// Collects leaf nodes, which bounds are intersected by the given ray. The ray must be in the local
+    /// coordinate system of the chunk of this QuadTree, where the first pixel of the height map is at the
+    /// origin and X and Z axes go along the columns and the rows of the height map respectively.
+    /// * `height_map_size`: The size of the height data of this QuadTree's chunk in rows and columns.
+    /// * `physical_size`: The size of the chunk in local units.
+    pub fn raycast_leafs<'a>(
+        &'a self,
+        ray: &Ray,
+        height_map_size: Vector2<u32>,
+        physical_size: Vector2<f32>,
+        leafs: &mut Vec<&'a QuadTreeNode>,
+    ) {
+        if height_map_size.x < 2 || height_map_size.y < 2 {
+            return;
+        }
+        let cell_size = physical_size.component_div(&height_map_size.map(|n| (n - 1) as f32));
+        self.root
+            .raycast_leafs(ray, height_map_size, cell_size, leafs);
+    }
+
     /// Determine the size and position of terrain geometry instances that are needed in order to render the chunk of this QuadTree.
     /// * `transform`: The matrix transformation to apply to the rendered height map geometry.
     /// * `height_map_size`: The size of the height data of this QuadTree's chunk in rows and columns.
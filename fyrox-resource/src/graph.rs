@@ -1,7 +1,14 @@
 //! Resource dependency graph. See [`ResourceDependencyGraph`] docs for more info.
 
-use crate::{collect_used_resources, state::ResourceState, untyped::UntypedResource};
+use crate::{
+    collect_used_resources,
+    io::ResourceIo,
+    state::ResourceState,
+    untyped::{ResourceKind, UntypedResource},
+};
 use fxhash::FxHashSet;
+use fyrox_core::futures::executor::block_on;
+use std::cell::OnceCell;
 
 /// A node of [`ResourceDependencyGraph`].
 pub struct ResourceGraphNode {
@@ -9,6 +16,8 @@ pub struct ResourceGraphNode {
     pub resource: UntypedResource,
     /// A list of children nodes of the graph.
     pub children: Vec<ResourceGraphNode>,
+    size: OnceCell<Option<u64>>,
+    total_size: OnceCell<u64>,
 }
 
 impl ResourceGraphNode {
@@ -36,6 +45,43 @@ impl ResourceGraphNode {
         Self {
             resource: resource.clone(),
             children,
+            size: Default::default(),
+            total_size: Default::default(),
+        }
+    }
+
+    /// Returns the size (in bytes) of the resource file, if it is known. Embedded resources and
+    /// resources, which files are inaccessible, do not have a size. The size is fetched using the
+    /// given resource IO on first request and then cached.
+    pub fn size(&self, io: &dyn ResourceIo) -> Option<u64> {
+        *self.size.get_or_init(|| match self.resource.kind() {
+            ResourceKind::External(path) => block_on(io.file_size(&path)).ok(),
+            ResourceKind::Embedded => None,
+        })
+    }
+
+    /// Returns the total size (in bytes) of the resource and all its dependencies, directly or indirectly.
+    /// Every resource is counted only once, even if it is used by multiple resources of the sub-graph.
+    /// Resources with unknown size (see [`Self::size`]) are ignored. The size is calculated on first request
+    /// and then cached.
+    pub fn total_size(&self, io: &dyn ResourceIo) -> u64 {
+        *self.total_size.get_or_init(|| {
+            let mut visited = FxHashSet::default();
+            let mut total = 0;
+            self.for_each_node(&mut |node| {
+                if visited.insert(node.resource.clone()) {
+                    total += node.size(io).unwrap_or_default();
+                }
+            });
+            total
+        })
+    }
+
+    fn for_each_node<F: FnMut(&ResourceGraphNode)>(&self, func: &mut F) {
+        func(self);
+
+        for child in self.children.iter() {
+            child.for_each_node(func)
         }
     }
 
@@ -95,12 +141,57 @@ impl ResourceDependencyGraph {
 }
 #[cfg(test)]
 mod test {
-    use std::path::PathBuf;
+    use std::{
+        path::{Path, PathBuf},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use fyrox_core::{io::FileLoadError, uuid::Uuid};
 
-    use fyrox_core::uuid::Uuid;
+    use crate::io::ResourceIoFuture;
 
     use super::*;
 
+    #[derive(Default)]
+    struct CountingResourceIo {
+        loads: AtomicUsize,
+    }
+
+    impl ResourceIo for CountingResourceIo {
+        fn load_file<'a>(
+            &'a self,
+            path: &'a Path,
+        ) -> ResourceIoFuture<'a, Result<Vec<u8>, FileLoadError>> {
+            self.loads.fetch_add(1, Ordering::SeqCst);
+            let result = match path.to_str() {
+                Some("/foo") => Ok(vec![0; 10]),
+                Some("/bar") => Ok(vec![0; 20]),
+                _ => Err(FileLoadError::Custom("Not found".to_string())),
+            };
+            Box::pin(std::future::ready(result))
+        }
+
+        fn move_file<'a>(
+            &'a self,
+            _source: &'a Path,
+            _dest: &'a Path,
+        ) -> ResourceIoFuture<'a, Result<(), FileLoadError>> {
+            Box::pin(std::future::ready(Ok(())))
+        }
+
+        fn exists<'a>(&'a self, _path: &'a Path) -> ResourceIoFuture<'a, bool> {
+            Box::pin(std::future::ready(true))
+        }
+
+        fn is_file<'a>(&'a self, _path: &'a Path) -> ResourceIoFuture<'a, bool> {
+            Box::pin(std::future::ready(true))
+        }
+
+        fn is_dir<'a>(&'a self, _path: &'a Path) -> ResourceIoFuture<'a, bool> {
+            Box::pin(std::future::ready(false))
+        }
+    }
+
     #[test]
     fn resource_graph_node_new() {
         let resource = UntypedResource::default();
@@ -183,4 +274,38 @@ mod test {
         graph.for_each(&mut |r: &UntypedResource| uuids.push(r.type_uuid()));
         assert_eq!(uuids, [Uuid::default(), Uuid::default()]);
     }
+
+    #[test]
+    fn resource_graph_node_size() {
+        let io = CountingResourceIo::default();
+
+        let bar = UntypedResource::new_pending(PathBuf::from("/bar").into(), Uuid::default());
+        let mut node = ResourceGraphNode::new(&UntypedResource::new_pending(
+            PathBuf::from("/foo").into(),
+            Uuid::default(),
+        ));
+        node.children.push(ResourceGraphNode::new(&bar));
+        node.children.push(ResourceGraphNode::new(&bar));
+        node.children
+            .push(ResourceGraphNode::new(&UntypedResource::new_pending(
+                PathBuf::from("/missing").into(),
+                Uuid::default(),
+            )));
+        node.children
+            .push(ResourceGraphNode::new(&UntypedResource::default()));
+
+        // Sizes are not fetched until requested.
+        assert_eq!(io.loads.load(Ordering::SeqCst), 0);
+
+        assert_eq!(node.size(&io), Some(10));
+        assert_eq!(node.children[0].size(&io), Some(20));
+        assert_eq!(node.children[2].size(&io), None);
+        assert_eq!(node.children[3].size(&io), None);
+        assert_eq!(io.loads.load(Ordering::SeqCst), 3);
+
+        // The shared resource is counted once and cached sizes are not fetched again.
+        assert_eq!(node.total_size(&io), 30);
+        assert_eq!(node.total_size(&io), 30);
+        assert_eq!(io.loads.load(Ordering::SeqCst), 3);
+    }
 }
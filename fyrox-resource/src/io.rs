@@ -83,6 +83,17 @@ pub trait ResourceIo: Send + Sync + 'static {
         })
     }
 
+    /// Attempts to get the size (in bytes) of the file at the provided path.
+    ///
+    /// Default implementation loads the entire file contents from `load_file`
+    /// and returns its length
+    fn file_size<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, Result<u64, FileLoadError>> {
+        Box::pin(async move {
+            let bytes = self.load_file(path).await?;
+            Ok(bytes.len() as u64)
+        })
+    }
+
     /// Used to check whether a path exists
     fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool>;
 
@@ -196,6 +207,12 @@ impl ResourceIo for FsResourceIo {
         })
     }
 
+    /// Android and wasm should use the default implementation that loads the entire file
+    #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+    fn file_size<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, Result<u64, FileLoadError>> {
+        Box::pin(async move { Ok(std::fs::metadata(path)?.len()) })
+    }
+
     fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
         Box::pin(fyrox_core::io::exists(path))
     }
@@ -34,7 +34,7 @@ fn main() {
     context
         .state()
         .set_renderer(Renderer::HrtfRenderer(HrtfRenderer::new(
-            HrirSphereResource::from_hrir_sphere(hrir_sphere, hrir_path.into()),
+            HrirSphereResource::from_hrir_sphere(hrir_sphere, hrir_path.clone().into()),
         )));
 
     // Create some sounds.
@@ -72,6 +72,7 @@ fn main() {
     // Move source sound around listener for some time.
     let start_time = time::Instant::now();
     let mut angle = 0.0f32;
+    let mut last_reload_time = time::Instant::now();
     while (time::Instant::now() - start_time).as_secs() < 360 {
         // Separate scope for update to make sure that mutex lock will be released before
         // thread::sleep will be called so context can actually work in background thread.
@@ -87,6 +88,19 @@ fn main() {
 
             angle += 1.6;
 
+            // Reload HRIR sphere every 10 seconds, it could be used to compare different HRTF datasets
+            // on the fly. Playing sounds are not interrupted.
+            if last_reload_time.elapsed().as_secs() >= 10 {
+                last_reload_time = time::Instant::now();
+                let hrir_sphere = HrirSphere::from_file(&hrir_path, context::SAMPLE_RATE).unwrap();
+                if let Renderer::HrtfRenderer(hrtf_renderer) = context.state().renderer_mut() {
+                    hrtf_renderer.set_hrir_sphere(HrirSphereResource::from_hrir_sphere(
+                        hrir_sphere,
+                        hrir_path.clone().into(),
+                    ));
+                }
+            }
+
             println!(
                 "Sound render time {:?}",
                 context.state().full_render_duration()
@@ -41,6 +41,9 @@ pub struct GenericBuffer {
     pub(crate) sample_rate: usize,
     #[visit(skip)]
     pub(crate) channel_duration_in_samples: usize,
+    // Sample rate of the data source, before any resampling was done.
+    #[visit(skip)]
+    pub(crate) original_sample_rate: usize,
 }
 
 impl GenericBuffer {
@@ -73,6 +76,7 @@ impl GenericBuffer {
                         samples,
                         channel_count,
                         sample_rate,
+                        original_sample_rate: sample_rate,
                     })
                 }
             }
@@ -103,6 +107,7 @@ impl GenericBuffer {
 
                 Ok(Self {
                     sample_rate: decoder.get_sample_rate(),
+                    original_sample_rate: decoder.get_sample_rate(),
                     channel_count: decoder.get_channel_count(),
                     channel_duration_in_samples: decoder.channel_duration_in_samples(),
                     samples: decoder.into_samples(),
@@ -140,6 +145,80 @@ impl GenericBuffer {
         self.sample_rate
     }
 
+    /// Returns sample rate of the data source the buffer was created from. It differs from
+    /// [`Self::sample_rate`] only if the buffer was resampled.
+    #[inline]
+    pub fn original_sample_rate(&self) -> usize {
+        self.original_sample_rate
+    }
+
+    /// Returns `true` if the buffer was resampled to some other sample rate after it was
+    /// loaded, `false` - otherwise.
+    #[inline]
+    pub fn is_resampled(&self) -> bool {
+        self.sample_rate != self.original_sample_rate
+    }
+
+    // Resamples the buffer to the given sample rate using linear interpolation between adjacent
+    // samples. See `SoundBuffer::resample_to` for more info.
+    pub(crate) fn resample(&mut self, sample_rate: usize) -> bool {
+        if sample_rate == 0
+            || self.sample_rate == 0
+            || self.sample_rate == sample_rate
+            || self.channel_count == 0
+            || self.samples.is_empty()
+        {
+            return false;
+        }
+
+        let channel_count = self.channel_count;
+        let frame_count = self.samples.len() / channel_count;
+        let ratio = self.sample_rate as f64 / sample_rate as f64;
+        let new_frame_count = ((frame_count as f64) / ratio).round().max(1.0) as usize;
+
+        let mut samples = Vec::with_capacity(new_frame_count * channel_count);
+        for i in 0..new_frame_count {
+            let position = i as f64 * ratio;
+            let index = (position as usize).min(frame_count - 1);
+            let next = (index + 1).min(frame_count - 1);
+            let w = (position - index as f64).clamp(0.0, 1.0) as f32;
+            for channel in 0..channel_count {
+                let a = self.samples[index * channel_count + channel];
+                let b = self.samples[next * channel_count + channel];
+                samples.push(a * (1.0 - w) + b * w);
+            }
+        }
+
+        self.samples = samples;
+        self.sample_rate = sample_rate;
+        self.channel_duration_in_samples = new_frame_count;
+
+        true
+    }
+
+    // Converts the buffer to the given amount of channels. Mono buffers are converted to stereo by
+    // duplicating samples, stereo buffers are converted to mono by averaging left and right channels.
+    // See `SoundBuffer::convert_channels` for more info.
+    pub(crate) fn convert_channels(&mut self, channel_count: usize) -> bool {
+        match (self.channel_count, channel_count) {
+            (1, 2) => {
+                self.samples = self.samples.iter().flat_map(|&s| [s, s]).collect();
+            }
+            (2, 1) => {
+                self.samples = self
+                    .samples
+                    .chunks_exact(2)
+                    .map(|lr| (lr[0] + lr[1]) * 0.5)
+                    .collect();
+            }
+            _ => return false,
+        }
+
+        self.channel_count = channel_count;
+
+        true
+    }
+
     /// Returns exact time length of the buffer.
     #[inline]
     pub fn duration(&self) -> Duration {
@@ -155,3 +234,78 @@ impl GenericBuffer {
         self.channel_duration_in_samples
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::buffer::{generic::GenericBuffer, DataSource};
+
+    fn make_buffer(channel_count: usize, samples: Vec<f32>) -> GenericBuffer {
+        GenericBuffer::new(DataSource::Raw {
+            sample_rate: 44100,
+            channel_count,
+            samples,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mono_to_stereo() {
+        let mut buffer = make_buffer(1, vec![0.1, 0.2, 0.3]);
+
+        assert!(buffer.convert_channels(2));
+        assert_eq!(buffer.channel_count(), 2);
+        assert_eq!(buffer.samples(), &[0.1, 0.1, 0.2, 0.2, 0.3, 0.3]);
+        assert_eq!(buffer.channel_duration_in_samples(), 3);
+    }
+
+    #[test]
+    fn test_stereo_to_mono() {
+        let mut buffer = make_buffer(2, vec![1.0, 0.0, 0.5, 0.5, -1.0, 1.0]);
+
+        assert!(buffer.convert_channels(1));
+        assert_eq!(buffer.channel_count(), 1);
+        assert_eq!(buffer.samples(), &[0.5, 0.5, 0.0]);
+        assert_eq!(buffer.channel_duration_in_samples(), 3);
+    }
+
+    #[test]
+    fn test_same_channel_count() {
+        let mut buffer = make_buffer(2, vec![1.0, 0.0]);
+
+        assert!(!buffer.convert_channels(2));
+        assert!(!buffer.convert_channels(3));
+        assert_eq!(buffer.samples(), &[1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_upsample() {
+        let mut buffer = make_buffer(1, vec![0.0, 1.0, 0.0]);
+
+        assert!(buffer.resample(88200));
+        assert_eq!(buffer.sample_rate(), 88200);
+        assert_eq!(buffer.original_sample_rate(), 44100);
+        assert!(buffer.is_resampled());
+        assert_eq!(buffer.samples(), &[0.0, 0.5, 1.0, 0.5, 0.0, 0.0]);
+        assert_eq!(buffer.channel_duration_in_samples(), 6);
+    }
+
+    #[test]
+    fn test_downsample_stereo() {
+        let mut buffer = make_buffer(2, vec![0.0, 1.0, 1.0, 0.0, 2.0, 2.0, 3.0, 3.0]);
+
+        assert!(buffer.resample(22050));
+        assert_eq!(buffer.channel_count(), 2);
+        assert_eq!(buffer.samples(), &[0.0, 1.0, 2.0, 2.0]);
+        assert_eq!(buffer.channel_duration_in_samples(), 2);
+    }
+
+    #[test]
+    fn test_resample_to_same_rate() {
+        let mut buffer = make_buffer(1, vec![0.1, 0.2]);
+
+        assert!(!buffer.resample(44100));
+        assert!(!buffer.resample(0));
+        assert!(!buffer.is_resampled());
+        assert_eq!(buffer.samples(), &[0.1, 0.2]);
+    }
+}
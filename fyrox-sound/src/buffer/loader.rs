@@ -1,6 +1,9 @@
 //! Sound buffer loader.
 
-use crate::buffer::{DataSource, SoundBuffer};
+use crate::{
+    buffer::{DataSource, SoundBuffer},
+    context::SAMPLE_RATE,
+};
 use fyrox_core::{reflect::prelude::*, uuid::Uuid, TypeUuidProvider};
 use fyrox_resource::{
     io::ResourceIo,
@@ -18,6 +21,12 @@ use std::{path::PathBuf, sync::Arc};
 pub struct SoundBufferImportOptions {
     /// Whether the buffer is streaming or not.
     pub stream: bool,
+    /// Whether the buffer should keep its original sample rate or not. By default, generic
+    /// (non-streaming) buffers are resampled to the sample rate of the output device
+    /// ([`SAMPLE_RATE`]) on load, this flag allows you to opt-out of it. Buffers that already
+    /// have matching sample rate are never resampled.
+    #[serde(default)]
+    pub skip_resampling: bool,
 }
 
 impl ImportOptions for SoundBufferImportOptions {}
@@ -58,7 +67,12 @@ impl ResourceLoader for SoundBufferLoader {
             };
 
             match result {
-                Ok(buffer) => Ok(LoaderPayload::new(buffer)),
+                Ok(mut buffer) => {
+                    if !import_options.skip_resampling {
+                        buffer.resample_to(SAMPLE_RATE as usize);
+                    }
+                    Ok(LoaderPayload::new(buffer))
+                }
                 Err(_) => Err(LoadError::new("Invalid data source.")),
             }
         })
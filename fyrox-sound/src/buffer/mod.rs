@@ -17,6 +17,7 @@ use fyrox_core::{
 };
 use fyrox_resource::{
     io::{FileReader, ResourceIo},
+    untyped::ResourceKind,
     Resource, ResourceData, SOUND_BUFFER_RESOURCE_UUID,
 };
 use std::error::Error;
@@ -195,6 +196,12 @@ pub trait SoundBufferResourceExtension {
 
     /// Tries to create new generic sound buffer from a given data source.
     fn new_generic(data_source: DataSource) -> Result<Resource<SoundBuffer>, DataSource>;
+
+    /// Creates a new embedded generic sound buffer with the content of this buffer converted to the given
+    /// amount of channels. See [`SoundBuffer::convert_channels`] for more info about the conversion. Returns
+    /// `None` if the resource is not loaded, if it is a streaming buffer or if the channel count is not
+    /// supported. If the buffer already has the requested channel count, a copy is returned.
+    fn to_channels(&self, channel_count: usize) -> Option<Resource<SoundBuffer>>;
 }
 
 impl SoundBufferResourceExtension for SoundBufferResource {
@@ -213,6 +220,31 @@ impl SoundBufferResourceExtension for SoundBufferResource {
             SoundBuffer::Generic(GenericBuffer::new(data_source)?),
         ))
     }
+
+    fn to_channels(&self, channel_count: usize) -> Option<Resource<SoundBuffer>> {
+        if !(1..=2).contains(&channel_count) {
+            return None;
+        }
+
+        let mut state = self.state();
+        let SoundBuffer::Generic(generic) = state.data()? else {
+            return None;
+        };
+
+        let mut copy = GenericBuffer {
+            samples: generic.samples.clone(),
+            channel_count: generic.channel_count,
+            sample_rate: generic.sample_rate,
+            channel_duration_in_samples: generic.channel_duration_in_samples,
+            original_sample_rate: generic.original_sample_rate,
+        };
+        copy.convert_channels(channel_count);
+
+        Some(Resource::new_ok(
+            ResourceKind::Embedded,
+            SoundBuffer::Generic(copy),
+        ))
+    }
 }
 
 impl TypeUuidProvider for SoundBuffer {
@@ -233,6 +265,52 @@ impl SoundBuffer {
     pub fn raw_generic(data_source: DataSource) -> Result<Self, DataSource> {
         Ok(Self::Generic(GenericBuffer::new(data_source)?))
     }
+
+    /// Resamples the buffer to the given sample rate. Returns `true` if resampling was done, `false`
+    /// if the buffer already has the requested sample rate or if the buffer is streaming. Streaming
+    /// buffers are never resampled up-front, they're resampled on the fly during playback. Use
+    /// [`GenericBuffer::is_resampled`] to check whether the buffer was resampled or not.
+    ///
+    /// # Quality and cost
+    ///
+    /// The resampler uses linear interpolation between adjacent samples. It is very cheap (a single
+    /// pass over the samples) and it is transparent for upsampling and for small rate differences
+    /// (such as 48000 Hz -> 44100 Hz). It does not do any low-pass filtering, so downsampling by
+    /// large factors may introduce some aliasing of high frequencies.
+    ///
+    /// # Notes
+    ///
+    /// Sound sources resample their buffers on the fly if the sample rate of a buffer does not
+    /// match the sample rate of the output device, but this is done with reduced quality. Buffers
+    /// loaded via [`loader::SoundBufferLoader`] are resampled to [`crate::context::SAMPLE_RATE`]
+    /// automatically, unless [`loader::SoundBufferImportOptions::skip_resampling`] is set.
+    pub fn resample_to(&mut self, sample_rate: usize) -> bool {
+        match self {
+            SoundBuffer::Generic(generic) => generic.resample(sample_rate),
+            SoundBuffer::Streaming(_) => false,
+        }
+    }
+
+    /// Converts the buffer to the given amount of channels (1 - mono, 2 - stereo). Mono buffers are
+    /// converted to stereo by duplicating each sample, stereo buffers are converted to mono by averaging
+    /// left and right channels. Returns `true` if the conversion was done, `false` if the buffer already
+    /// has the requested channel count, if the channel count is not supported or if the buffer is
+    /// streaming. Streaming buffers are decoded on the fly and can't be converted up-front.
+    ///
+    /// # Notes
+    ///
+    /// There's no need to convert mono buffers to stereo for playback, sound sources play mono buffers
+    /// on both channels of the output device automatically. Converting stereo buffers to mono is useful
+    /// for spatial sounds, because spatialization (panning, HRTF) is designed for mono input: panning of
+    /// a stereo buffer attenuates each of its channels separately, so a sound with most of its energy in
+    /// one channel may become inaudible in one ear. See also
+    /// [`crate::source::SoundSource::set_downmix_spatial_stereo`].
+    pub fn convert_channels(&mut self, channel_count: usize) -> bool {
+        match self {
+            SoundBuffer::Generic(generic) => generic.convert_channels(channel_count),
+            SoundBuffer::Streaming(_) => false,
+        }
+    }
 }
 
 impl Default for SoundBuffer {
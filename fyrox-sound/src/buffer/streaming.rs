@@ -174,6 +174,7 @@ impl StreamingBuffer {
             generic: GenericBuffer {
                 samples,
                 sample_rate: streaming_source.sample_rate(),
+                original_sample_rate: streaming_source.sample_rate(),
                 channel_count: streaming_source.channel_count(),
                 channel_duration_in_samples: streaming_source.channel_duration_in_samples(),
             },
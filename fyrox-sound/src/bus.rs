@@ -283,6 +283,54 @@ impl AudioBus {
 /// ```
 ///
 /// If you delete an audio bus to which a bunch of sound sources is bound, then they will simply stop playing.
+///
+/// # Sends
+///
+/// Besides its output bus, a sound source can send a scaled copy of its signal to one more audio bus. This is
+/// the classic send/return scheme: the source is rendered once, then the unmodified ("dry") signal goes to its
+/// output bus and a copy, multiplied by the send amount, goes to the send bus. The send bus processes the copy
+/// with its own effects and "returns" the result to its parent bus, where it is mixed with the dry signal:
+///
+/// ```text
+///                 ┌──────────────── dry (x1.0) ───────────────┐
+///                 │                                           ▼
+///   Source ───────┤                                        Primary ───► Output Device
+///                 │                                           ▲
+///                 └── wet (x send) ──► Reverb ──── return ────┘
+/// ```
+///
+/// The main use case for sends is reverberation. A single reverb on a dedicated bus can be shared by every
+/// source in the scene, while each source decides how much reverb it receives (see
+/// [`crate::source::SoundSource::set_reverb_send`]). Since the send bus receives a copy of the signal, its
+/// reverb should output only the wet part (set its dry coefficient to zero), otherwise the dry signal will be
+/// heard twice. The send is taken after the gain, panning and distance attenuation of the source are applied,
+/// so the reverb fades together with the source.
+///
+/// By default, sources send to a bus called [`AudioBusGraph::REVERB_BUS`] with zero send amount, so nothing
+/// is sent. The reverb bus is not created automatically; if there is no bus with the send name, the send is
+/// ignored and the source is heard dry only.
+///
+/// ```rust
+/// # use fyrox_sound::bus::{AudioBus, AudioBusGraph};
+/// # use fyrox_sound::context::SoundContext;
+/// # use fyrox_sound::effects::{reverb::Reverb, Effect};
+/// # use fyrox_sound::source::SoundSourceBuilder;
+/// let context = SoundContext::new();
+/// let mut state = context.state();
+///
+/// let mut reverb = Reverb::new();
+/// reverb.set_dry(0.0);
+/// reverb.set_wet(1.0);
+/// let mut reverb_bus = AudioBus::new(AudioBusGraph::REVERB_BUS.to_string());
+/// reverb_bus.add_effect(Effect::Reverb(reverb));
+///
+/// let bus_graph = state.bus_graph_mut();
+/// let primary_bus = bus_graph.primary_bus_handle();
+/// bus_graph.add_bus(reverb_bus, primary_bus);
+///
+/// // A distant source, that should sound mostly "wet".
+/// state.add_source(SoundSourceBuilder::new().with_reverb_send(0.8).build().unwrap());
+/// ```
 #[derive(Default, Debug, Clone, Visit, Reflect)]
 pub struct AudioBusGraph {
     buses: Pool<AudioBus>,
@@ -293,6 +341,10 @@ impl AudioBusGraph {
     /// The name of the audio bus that output samples directly to an audio playback device.
     pub const PRIMARY_BUS: &'static str = "Primary";
 
+    /// The default name of the audio bus to which sound sources send their signal for reverberation. See
+    /// [`crate::source::SoundSource::set_reverb_send`] for more info.
+    pub const REVERB_BUS: &'static str = "Reverb";
+
     /// Creates a new audio bus graph. Sound context already has an audio graph instance, so calling
     /// this method is needed only for very specific cases (mostly tests).
     pub fn new() -> Self {
@@ -357,6 +409,20 @@ impl AudioBusGraph {
         })
     }
 
+    /// Adds the given samples multiplied by `gain` to the input buffer of an audio bus with the given name.
+    /// Returns `false` if there's no such bus.
+    pub(crate) fn mix_into_bus(&mut self, name: &str, samples: &[(f32, f32)], gain: f32) -> bool {
+        if let Some(input_buffer) = self.try_get_bus_input_buffer(name) {
+            for ((out_left, out_right), (left, right)) in input_buffer.iter_mut().zip(samples) {
+                *out_left += *left * gain;
+                *out_right += *right * gain;
+            }
+            true
+        } else {
+            false
+        }
+    }
+
     /// Removes an audio bus at the given handle.
     pub fn remove_bus(&mut self, handle: Handle<AudioBus>) -> AudioBus {
         assert_ne!(handle, self.root);
@@ -582,4 +648,28 @@ mod test {
 
         assert_eq!(output_buffer[0], (0.75, 0.75));
     }
+
+    #[test]
+    fn test_send_data_flow() {
+        let mut output_buffer = [(0.0f32, 0.0f32)];
+
+        let mut graph = AudioBusGraph::new();
+
+        let mut reverb_bus = AudioBus::new(AudioBusGraph::REVERB_BUS.to_string());
+        reverb_bus.add_effect(Effect::Attenuate(Attenuate::new(0.5)));
+        graph.add_bus(reverb_bus, graph.root);
+
+        graph.begin_render(output_buffer.len());
+
+        // Simulate a source with 0.5 send amount.
+        let source_samples = [(1.0, 1.0)];
+        assert!(graph.mix_into_bus(AudioBusGraph::PRIMARY_BUS, &source_samples, 1.0));
+        assert!(graph.mix_into_bus(AudioBusGraph::REVERB_BUS, &source_samples, 0.5));
+        assert!(!graph.mix_into_bus("Unknown", &source_samples, 1.0));
+
+        graph.end_render(&mut output_buffer);
+
+        // Dry signal + attenuated return of the send bus.
+        assert_eq!(output_buffer[0], (1.25, 1.25));
+    }
 }
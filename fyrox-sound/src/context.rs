@@ -14,16 +14,18 @@ use crate::bus::AudioBusGraph;
 use crate::{
     listener::Listener,
     pool::Ticket,
-    renderer::{render_source_default, Renderer},
+    renderer::{Renderer, RendererCrossfade},
     source::{SoundSource, Status},
 };
 use fyrox_core::{
+    log::Log,
     pool::{Handle, Pool},
     reflect::prelude::*,
     uuid_provider,
     visitor::prelude::*,
 };
 use std::{
+    fmt::{Debug, Formatter},
     sync::{Arc, Mutex, MutexGuard},
     time::Duration,
 };
@@ -86,6 +88,115 @@ impl Default for DistanceModel {
     }
 }
 
+/// Voice stealing policy defines which sound sources should be stopped when there are more playing
+/// sources than a context allows (see [`State::set_max_voices`]).
+///
+/// Priority of sound sources (see [`SoundSource::set_priority`]) is always taken into account first:
+/// a source is never stopped in favor of a source with lower priority. The policy is used to choose
+/// between sources with the same priority.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Reflect, Visit, AsRefStr, EnumString, VariantNames)]
+#[repr(u32)]
+pub enum VoiceStealingPolicy {
+    /// Sources with the lowest gain at the listener's position (with respect to distance attenuation)
+    /// are stopped first. This is the default policy, it usually gives the least noticeable result.
+    Quietest = 0,
+
+    /// Sources that are playing for the longest time are stopped first.
+    Oldest = 1,
+
+    /// Only priority is taken into account, recently started sources are stopped first, so the
+    /// sources that are already playing are not interrupted.
+    LowestPriority = 2,
+}
+
+uuid_provider!(VoiceStealingPolicy = "7a3d8f0c-54c2-4e8b-9a52-1d3b8f6c2e41");
+
+impl Default for VoiceStealingPolicy {
+    fn default() -> Self {
+        Self::Quietest
+    }
+}
+
+/// Format of the samples passed to output taps (see [`State::add_output_tap`]).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OutputFormat {
+    /// Sample rate of the output in Hz.
+    pub sample_rate: u32,
+    /// Amount of channels in the output. Samples are always passed as `(left, right)` pairs of
+    /// 32-bit floating point numbers in `-1.0..1.0` range, so it is always 2 for now.
+    pub channel_count: usize,
+}
+
+/// A callback that receives mixed output of a sound context. See [`State::add_output_tap`] for more info.
+pub type OutputTapCallback = Box<dyn FnMut(&[(f32, f32)], OutputFormat) + Send>;
+
+/// A unique identifier of an output tap.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OutputTapId(u64);
+
+#[derive(Default)]
+struct OutputTaps {
+    taps: Vec<(OutputTapId, OutputTapCallback)>,
+    next_id: u64,
+    buffer: Vec<(f32, f32)>,
+}
+
+impl Debug for OutputTaps {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OutputTaps {{ count: {} }}", self.taps.len())
+    }
+}
+
+impl Clone for OutputTaps {
+    // Callbacks cannot be cloned, so the clone does not have any taps.
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// A callback that is called when a sound source reaches its end. See [`State::on_finished`] for more info.
+pub type FinishedCallback = Box<dyn FnOnce(Handle<SoundSource>) + Send>;
+
+#[derive(Default)]
+struct FinishedCallbacks {
+    // Callbacks that wait for their sources to reach the end.
+    waiting: Vec<(Handle<SoundSource>, FinishedCallback)>,
+    // Callbacks of the sources that have reached the end, but weren't dispatched yet.
+    ready: Vec<(Handle<SoundSource>, FinishedCallback)>,
+}
+
+impl FinishedCallbacks {
+    fn mark_finished(&mut self, source: Handle<SoundSource>) {
+        let mut i = 0;
+        while i < self.waiting.len() {
+            if self.waiting[i].0 == source {
+                let entry = self.waiting.remove(i);
+                self.ready.push(entry);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+impl Debug for FinishedCallbacks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FinishedCallbacks {{ waiting: {}, ready: {} }}",
+            self.waiting.len(),
+            self.ready.len()
+        )
+    }
+}
+
+impl Clone for FinishedCallbacks {
+    // Callbacks cannot be cloned, so the clone does not have any callbacks.
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
 /// See module docs.
 #[derive(Clone, Default, Debug, Visit)]
 pub struct SoundContext {
@@ -118,10 +229,24 @@ pub struct State {
     bus_graph: AudioBusGraph,
     distance_model: DistanceModel,
     paused: bool,
+    max_voices: Option<usize>,
+    voice_stealing_policy: VoiceStealingPolicy,
     /// A set of flags, that can be used to define what should be skipped during the
     /// serialization of a sound context.
     #[reflect(hidden)]
     pub serialization_options: SerializationOptions,
+    #[reflect(hidden)]
+    output_taps: OutputTaps,
+    // Intermediate buffer for sources with a send. Such sources are rendered here first and then
+    // mixed into their output and send buses.
+    #[reflect(hidden)]
+    send_buffer: Vec<(f32, f32)>,
+    #[reflect(hidden)]
+    finished_callbacks: FinishedCallbacks,
+    #[reflect(hidden)]
+    renderer_crossfade: Option<RendererCrossfade>,
+    #[reflect(hidden)]
+    renderer_generation: u64,
 }
 
 impl State {
@@ -168,6 +293,118 @@ impl State {
         self.distance_model
     }
 
+    /// Sets maximum amount of simultaneously playing sound sources. When there are more playing sources,
+    /// some of them will be stopped according to the voice stealing policy (see [`VoiceStealingPolicy`]
+    /// docs for more info). `None` means that there's no limit, this is the default value.
+    ///
+    /// Voice budget is essential when a game has unbounded amount of sound emitters, because every
+    /// playing source has its own rendering cost (especially with HRTF renderer).
+    pub fn set_max_voices(&mut self, max_voices: Option<usize>) {
+        self.max_voices = max_voices;
+    }
+
+    /// Returns maximum amount of simultaneously playing sound sources.
+    pub fn max_voices(&self) -> Option<usize> {
+        self.max_voices
+    }
+
+    /// Sets new voice stealing policy. See [`VoiceStealingPolicy`] docs for more info.
+    pub fn set_voice_stealing_policy(&mut self, policy: VoiceStealingPolicy) {
+        self.voice_stealing_policy = policy;
+    }
+
+    /// Returns current voice stealing policy.
+    pub fn voice_stealing_policy(&self) -> VoiceStealingPolicy {
+        self.voice_stealing_policy
+    }
+
+    fn steal_voices(&mut self, max_voices: usize) {
+        let mut playing = self
+            .sources
+            .pair_iter()
+            .filter(|(_, s)| s.status() == Status::Playing)
+            .map(|(handle, s)| {
+                (
+                    handle,
+                    s.priority(),
+                    s.age,
+                    s.calculate_audible_gain(&self.listener, self.distance_model),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if playing.len() <= max_voices {
+            return;
+        }
+
+        // Sort sources so the ones that should yield come first.
+        let policy = self.voice_stealing_policy;
+        playing.sort_by(|a, b| {
+            a.1.cmp(&b.1).then_with(|| match policy {
+                VoiceStealingPolicy::Quietest => a.3.total_cmp(&b.3),
+                VoiceStealingPolicy::Oldest => b.2.cmp(&a.2),
+                VoiceStealingPolicy::LowestPriority => a.2.cmp(&b.2),
+            })
+        });
+
+        let excess = playing.len() - max_voices;
+        for (handle, ..) in playing.into_iter().take(excess) {
+            if let Err(err) = self.sources[handle].stop() {
+                Log::err(format!("Unable to stop a sound source. Reason: {err}"));
+            }
+        }
+    }
+
+    /// Adds a new output tap, that receives the final mix of the context every time it is rendered, right
+    /// before it is sent to the output device. It could be used for waveform visualization, recording,
+    /// voice activity detection, etc. There could be any amount of taps, they're called in the order of
+    /// addition. Returns an identifier, that could be used to remove the tap later.
+    ///
+    /// The callback receives a slice of `(left, right)` samples and the format of the samples (see
+    /// [`OutputFormat`]). The mix contains only the sounds of this context, other contexts of the engine
+    /// are not included.
+    ///
+    /// # Performance
+    ///
+    /// The callback is called on the audio thread while the context is locked, so it must be lightweight:
+    /// it must not block (no I/O, no locking of mutexes that could be held for a long time, no waiting),
+    /// and it should avoid allocations. Typical callback just copies the samples to a lock-free ring buffer
+    /// or sends them over a channel, the actual processing should happen on some other thread. Heavy
+    /// callbacks will cause audible glitches.
+    pub fn add_output_tap(&mut self, callback: OutputTapCallback) -> OutputTapId {
+        let id = OutputTapId(self.output_taps.next_id);
+        self.output_taps.next_id += 1;
+        self.output_taps.taps.push((id, callback));
+        id
+    }
+
+    /// Removes an output tap with the given identifier. Returns `true` if the tap was removed, `false` - if
+    /// there's no such tap.
+    pub fn remove_output_tap(&mut self, id: OutputTapId) -> bool {
+        let count = self.output_taps.taps.len();
+        self.output_taps.taps.retain(|(tap_id, _)| *tap_id != id);
+        self.output_taps.taps.len() != count
+    }
+
+    /// Registers a callback, that will be called once the given sound source reaches its end. The callback
+    /// is called only once, for the first time the source reaches its end after the registration, register
+    /// it again if you need to be notified about subsequent playbacks. There could be any amount of
+    /// callbacks per source, they're called in the order of registration. Looping sources never reach their
+    /// end, and explicit [`SoundSource::stop`] calls (including the ones made by voice stealing) do not count
+    /// either. Callbacks of a source are discarded when the source is removed via [`Self::remove_source`].
+    ///
+    /// # Threading
+    ///
+    /// The end of a source is detected by the mixer on the audio thread, but the callback is **not** called
+    /// there. Instead, it is moved to a queue of the context, which must be drained on the main thread by
+    /// [`SoundContext::dispatch_finished_callbacks`]. This means that the callback is free to do anything,
+    /// including locking the context (for example to play another sound), but it will be called with some
+    /// delay (up to the duration of one frame of the mixer plus the interval between dispatches). Fyrox
+    /// engine dispatches the callbacks of scene sound contexts on every update of the scene graph.
+    pub fn on_finished(&mut self, source: Handle<SoundSource>, callback: FinishedCallback) {
+        self.finished_callbacks.waiting.push((source, callback));
+    }
+
     /// Normalizes given frequency using context's sampling rate. Normalized frequency then can be used
     /// to create filters.
     pub fn normalize_frequency(&self, f: f32) -> f32 {
@@ -179,11 +416,38 @@ impl State {
         self.render_duration
     }
 
-    /// Sets new renderer.
+    /// Sets new renderer. The switch is instant, which may cause an audible pop, use
+    /// [`Self::set_renderer_with_crossfade`] to switch smoothly.
     pub fn set_renderer(&mut self, renderer: Renderer) -> Renderer {
+        self.renderer_crossfade = None;
         std::mem::replace(&mut self.renderer, renderer)
     }
 
+    /// Sets new renderer and smoothly crossfades the output of the previous renderer with the output of the
+    /// new one during the given time. Both renderers are running during the crossfade, so it is twice as
+    /// expensive. Zero duration means an instant switch, the same as [`Self::set_renderer`]. If the renderer
+    /// is changed again during the crossfade, the crossfade starts over from the current renderer.
+    pub fn set_renderer_with_crossfade(&mut self, renderer: Renderer, duration: Duration) {
+        let length = (duration.as_secs_f32() * SAMPLE_RATE as f32) as usize;
+        if length == 0 {
+            self.set_renderer(renderer);
+        } else {
+            self.renderer_generation += 1;
+            let old_renderer = std::mem::replace(&mut self.renderer, renderer);
+            self.renderer_crossfade = Some(RendererCrossfade::new(
+                old_renderer,
+                length,
+                self.renderer_generation,
+            ));
+        }
+    }
+
+    /// Returns `true` if the outputs of the previous and the current renderers are crossfaded at the moment.
+    /// See [`Self::set_renderer_with_crossfade`] for more info.
+    pub fn is_renderer_crossfading(&self) -> bool {
+        self.renderer_crossfade.is_some()
+    }
+
     /// Returns shared reference to current renderer.
     pub fn renderer(&self) -> &Renderer {
         &self.renderer
@@ -202,6 +466,9 @@ impl State {
     /// Removes sound source from the context.
     pub fn remove_source(&mut self, source: Handle<SoundSource>) {
         self.sources.free(source);
+        self.finished_callbacks
+            .waiting
+            .retain(|(handle, _)| *handle != source);
     }
 
     /// Returns shared reference to a pool with all sound sources.
@@ -263,41 +530,108 @@ impl State {
                 !done
             });
 
+            if let Some(max_voices) = self.max_voices {
+                self.steal_voices(max_voices);
+            }
+
             self.bus_graph.begin_render(output_device_buffer.len());
 
             // Render sounds to respective audio buses.
-            for source in self
+            for (handle, source) in self
                 .sources
-                .iter_mut()
-                .filter(|s| s.status() == Status::Playing)
+                .pair_iter_mut()
+                .filter(|(_, s)| s.status() == Status::Playing)
             {
                 if let Some(bus_input_buffer) = self.bus_graph.try_get_bus_input_buffer(&source.bus)
                 {
                     source.render(output_device_buffer.len());
+                    source.age += output_device_buffer.len() as u64;
+
+                    // Sources with a send are rendered once into the intermediate buffer, which is
+                    // then mixed into both buses. Other sources are rendered directly to their bus.
+                    let has_send =
+                        source.reverb_send() > 0.0 && source.reverb_send_bus() != source.bus;
+                    let mix_buffer = if has_send {
+                        self.send_buffer.clear();
+                        self.send_buffer
+                            .resize(output_device_buffer.len(), (0.0, 0.0));
+                        self.send_buffer.as_mut_slice()
+                    } else {
+                        bus_input_buffer
+                    };
+
+                    if let Some(crossfade) = self.renderer_crossfade.as_mut() {
+                        crossfade.render_source(
+                            &mut self.renderer,
+                            source,
+                            &self.listener,
+                            self.distance_model,
+                            mix_buffer,
+                        );
+                    } else {
+                        self.renderer.render_source(
+                            source,
+                            &self.listener,
+                            self.distance_model,
+                            mix_buffer,
+                        );
+                    }
+
+                    if has_send {
+                        // Dry signal goes to the output bus as is, wet - to the send bus.
+                        self.bus_graph
+                            .mix_into_bus(&source.bus, &self.send_buffer, 1.0);
+                        self.bus_graph.mix_into_bus(
+                            source.reverb_send_bus(),
+                            &self.send_buffer,
+                            source.reverb_send(),
+                        );
+                    }
 
-                    match self.renderer {
-                        Renderer::Default => {
-                            // Simple rendering path. Much faster (4-5 times) than HRTF path.
-                            render_source_default(
-                                source,
-                                &self.listener,
-                                self.distance_model,
-                                bus_input_buffer,
-                            );
-                        }
-                        Renderer::HrtfRenderer(ref mut hrtf_renderer) => {
-                            hrtf_renderer.render_source(
-                                source,
-                                &self.listener,
-                                self.distance_model,
-                                bus_input_buffer,
-                            );
-                        }
+                    // Rendering stops playing sources only when they reach their end.
+                    if source.status() == Status::Stopped
+                        && !self.finished_callbacks.waiting.is_empty()
+                    {
+                        self.finished_callbacks.mark_finished(handle);
                     }
                 }
             }
 
-            self.bus_graph.end_render(output_device_buffer);
+            self.renderer.end_render();
+            if let Some(crossfade) = self.renderer_crossfade.as_mut() {
+                if crossfade.end_render(output_device_buffer.len()) {
+                    self.renderer_crossfade = None;
+                }
+            }
+
+            if self.output_taps.taps.is_empty() {
+                self.bus_graph.end_render(output_device_buffer);
+            } else {
+                // Render the mix of the context separately, so the taps will receive only the sounds of
+                // this context.
+                let mut mix = std::mem::take(&mut self.output_taps.buffer);
+                mix.clear();
+                mix.resize(output_device_buffer.len(), (0.0, 0.0));
+
+                self.bus_graph.end_render(&mut mix);
+
+                let format = OutputFormat {
+                    sample_rate: SAMPLE_RATE,
+                    channel_count: 2,
+                };
+                for (_, tap) in self.output_taps.taps.iter_mut() {
+                    tap(&mix, format);
+                }
+
+                for ((out_left, out_right), (left, right)) in
+                    output_device_buffer.iter_mut().zip(mix.iter())
+                {
+                    *out_left += *left;
+                    *out_right += *right;
+                }
+
+                self.output_taps.buffer = mix;
+            }
         }
 
         self.render_duration = fyrox_core::instant::Instant::now() - last_time;
@@ -327,7 +661,14 @@ impl SoundContext {
                 bus_graph: AudioBusGraph::new(),
                 distance_model: DistanceModel::InverseDistance,
                 paused: false,
+                max_voices: None,
+                voice_stealing_policy: Default::default(),
                 serialization_options: Default::default(),
+                output_taps: Default::default(),
+                send_buffer: Default::default(),
+                finished_callbacks: Default::default(),
+                renderer_crossfade: None,
+                renderer_generation: 0,
             }))),
         }
     }
@@ -353,6 +694,16 @@ impl SoundContext {
         self.state.as_ref().unwrap().lock().unwrap()
     }
 
+    /// Calls the callbacks of the sound sources, that have reached their end since the last call of this
+    /// method. See [`State::on_finished`] for more info. This method must be called on the main thread
+    /// (typically once per frame), the context is not locked while the callbacks are called.
+    pub fn dispatch_finished_callbacks(&self) {
+        let ready = std::mem::take(&mut self.state().finished_callbacks.ready);
+        for (source, callback) in ready {
+            callback(source);
+        }
+    }
+
     /// Creates deep copy instead of shallow which is done by clone().
     pub fn deep_clone(&self) -> SoundContext {
         SoundContext {
@@ -385,7 +736,212 @@ impl Visit for State {
         self.renderer.visit("Renderer", &mut region)?;
         self.paused.visit("Paused", &mut region)?;
         self.distance_model.visit("DistanceModel", &mut region)?;
+        let _ = self.max_voices.visit("MaxVoices", &mut region);
+        let _ = self
+            .voice_stealing_policy
+            .visit("VoiceStealingPolicy", &mut region);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{DataSource, SoundBufferResource, SoundBufferResourceExtension},
+        context::{SoundContext, State, VoiceStealingPolicy, SAMPLE_RATE},
+        pool::Handle,
+        renderer::Renderer,
+        source::{SoundSource, SoundSourceBuilder, Status},
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    fn add_playing_source(
+        state: &mut State,
+        priority: i32,
+        age: u64,
+        gain: f32,
+    ) -> Handle<SoundSource> {
+        let mut source = SoundSourceBuilder::new()
+            .with_status(Status::Playing)
+            .with_priority(priority)
+            .with_gain(gain)
+            .build()
+            .unwrap();
+        source.age = age;
+        state.add_source(source)
+    }
+
+    fn playing(state: &State, handles: &[Handle<SoundSource>]) -> Vec<bool> {
+        handles
+            .iter()
+            .map(|h| state.source(*h).status() == Status::Playing)
+            .collect()
+    }
+
+    #[test]
+    fn test_voice_stealing_under_limit() {
+        let context = SoundContext::new();
+        let mut state = context.state();
+        state.set_voice_stealing_policy(VoiceStealingPolicy::Oldest);
+
+        let handles = [
+            add_playing_source(&mut state, 0, 10, 1.0),
+            add_playing_source(&mut state, 0, 20, 1.0),
+            add_playing_source(&mut state, 0, 30, 1.0),
+        ];
+
+        state.steal_voices(5);
+        assert_eq!(playing(&state, &handles), [true, true, true]);
+
+        state.steal_voices(3);
+        assert_eq!(playing(&state, &handles), [true, true, true]);
+    }
+
+    #[test]
+    fn test_voice_stealing_quietest() {
+        let context = SoundContext::new();
+        let mut state = context.state();
+        state.set_voice_stealing_policy(VoiceStealingPolicy::Quietest);
+
+        let handles = [
+            add_playing_source(&mut state, 0, 10, 0.2),
+            add_playing_source(&mut state, 0, 20, 0.8),
+            add_playing_source(&mut state, 0, 30, 0.5),
+        ];
+
+        state.steal_voices(1);
+        assert_eq!(playing(&state, &handles), [false, true, false]);
+    }
+
+    #[test]
+    fn test_voice_stealing_oldest() {
+        let context = SoundContext::new();
+        let mut state = context.state();
+        state.set_voice_stealing_policy(VoiceStealingPolicy::Oldest);
+
+        let handles = [
+            add_playing_source(&mut state, 0, 10, 1.0),
+            add_playing_source(&mut state, 0, 30, 1.0),
+            add_playing_source(&mut state, 0, 20, 1.0),
+        ];
+
+        state.steal_voices(2);
+        assert_eq!(playing(&state, &handles), [true, false, true]);
+    }
+
+    #[test]
+    fn test_voice_stealing_lowest_priority() {
+        let context = SoundContext::new();
+        let mut state = context.state();
+        state.set_voice_stealing_policy(VoiceStealingPolicy::LowestPriority);
+
+        let handles = [
+            add_playing_source(&mut state, 1, 10, 1.0),
+            add_playing_source(&mut state, 0, 30, 1.0),
+            add_playing_source(&mut state, 0, 20, 1.0),
+            add_playing_source(&mut state, 0, 5, 1.0),
+        ];
+
+        // The low priority sources yield first, the most recently started one is stopped first.
+        state.steal_voices(3);
+        assert_eq!(playing(&state, &handles), [true, true, true, false]);
+
+        state.steal_voices(1);
+        assert_eq!(playing(&state, &handles), [true, false, false, false]);
+    }
+
+    #[test]
+    fn test_voice_stealing_respects_priority() {
+        let context = SoundContext::new();
+        let mut state = context.state();
+        state.set_voice_stealing_policy(VoiceStealingPolicy::Quietest);
+
+        let handles = [
+            add_playing_source(&mut state, 1, 10, 0.1),
+            add_playing_source(&mut state, 0, 20, 1.0),
+        ];
+
+        state.steal_voices(1);
+        assert_eq!(playing(&state, &handles), [true, false]);
+    }
+
+    #[test]
+    fn test_renderer_crossfade() {
+        let context = SoundContext::new();
+        let mut state = context.state();
+
+        state.set_renderer_with_crossfade(Renderer::Default, Duration::ZERO);
+        assert!(!state.is_renderer_crossfading());
+
+        state.set_renderer_with_crossfade(Renderer::Default, Duration::from_millis(100));
+        assert!(state.is_renderer_crossfading());
+
+        let mut buffer = vec![(0.0, 0.0); SoundContext::SAMPLES_PER_CHANNEL];
+        let length = SAMPLE_RATE as usize / 10;
+        for _ in 0..(length + buffer.len() - 1) / buffer.len() {
+            assert!(state.is_renderer_crossfading());
+            state.render(&mut buffer);
+        }
+        assert!(!state.is_renderer_crossfading());
+
+        // Instant switch cancels the crossfade.
+        state.set_renderer_with_crossfade(Renderer::Default, Duration::from_secs(1));
+        state.set_renderer(Renderer::Default);
+        assert!(!state.is_renderer_crossfading());
+    }
+
+    #[test]
+    fn test_finished_callback_of_one_shot_source() {
+        let context = SoundContext::new();
+
+        let buffer = SoundBufferResource::new_generic(DataSource::Raw {
+            sample_rate: SAMPLE_RATE as usize,
+            channel_count: 1,
+            samples: vec![0.5; 64],
+        })
+        .unwrap();
+
+        let finished = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let mut state = context.state();
+            let source = SoundSourceBuilder::new()
+                .with_buffer(buffer)
+                .with_status(Status::Playing)
+                .build()
+                .unwrap();
+            let handle = state.add_source(source);
+            let flag = finished.clone();
+            state.on_finished(
+                handle,
+                Box::new(move |source| {
+                    assert_eq!(source, handle);
+                    flag.store(true, Ordering::SeqCst);
+                }),
+            );
+            handle
+        };
+
+        // Nothing is dispatched while the source is still playing.
+        context.dispatch_finished_callbacks();
+        assert!(!finished.load(Ordering::SeqCst));
+
+        let mut buffer = vec![(0.0, 0.0); SoundContext::SAMPLES_PER_CHANNEL];
+        {
+            let mut state = context.state();
+            state.render(&mut buffer);
+            assert_eq!(state.source(handle).status(), Status::Stopped);
+        }
+
+        // The callback is not called by the mixer, only by the dispatch.
+        assert!(!finished.load(Ordering::SeqCst));
+        context.dispatch_finished_callbacks();
+        assert!(finished.load(Ordering::SeqCst));
+    }
+}
@@ -3,11 +3,25 @@
 //! ## Overview
 //!
 //! Sound engine manages contexts, feeds output device with data.
+//!
+//! ## Device changes
+//!
+//! Output device could disappear at any time (for example, when headphones are unplugged). The engine
+//! detects this by watching how often the device requests new samples, see [`SoundEngine::update_device`]
+//! for more info. Lost device is re-acquired automatically, all the contexts and their sound sources
+//! stay intact, so the playback continues from the same place on the new device.
 
 use crate::context::{SoundContext, SAMPLE_RATE};
-use fyrox_core::visitor::{Visit, VisitResult, Visitor};
-use std::error::Error;
-use std::sync::{Arc, Mutex, MutexGuard};
+use fyrox_core::{
+    instant::Instant,
+    log::Log,
+    visitor::{Visit, VisitResult, Visitor},
+};
+use std::{
+    error::Error,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
 
 /// Sound engine manages contexts, feeds output device with data. Sound engine instance can be cloned,
 /// however this is always a "shallow" clone, because actual sound engine data is wrapped in Arc.
@@ -20,10 +34,40 @@ impl Default for SoundEngine {
     }
 }
 
+/// Maximum amount of time the output device could spend without requesting new samples. If the device
+/// does not request any samples for longer, it is considered lost. See [`SoundEngine::update_device`].
+pub const DEVICE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Interval between attempts to re-acquire lost output device.
+const DEVICE_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An event of the audio output device. See [`SoundEngine::set_device_event_handler`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeviceEvent {
+    /// Output device stopped requesting samples and was destroyed. The engine will try to re-acquire
+    /// default output device.
+    Lost,
+    /// Output device was re-acquired after a loss.
+    Recovered,
+}
+
+/// Device event handler, see [`SoundEngine::set_device_event_handler`].
+pub type DeviceEventHandler = Box<dyn FnMut(DeviceEvent) + Send>;
+
+#[derive(Copy, Clone)]
+enum DeviceStatus {
+    // There's no device (headless mode or the device was destroyed explicitly).
+    None,
+    Active { last_activity: Instant },
+    Lost { last_attempt: Option<Instant> },
+}
+
 /// Internal state of the sound engine.
 pub struct State {
     contexts: Vec<SoundContext>,
     output_device: Option<Box<dyn tinyaudio::BaseAudioOutputDevice>>,
+    device_status: DeviceStatus,
+    device_event_handler: Option<DeviceEventHandler>,
 }
 
 impl SoundEngine {
@@ -43,6 +87,8 @@ impl SoundEngine {
         Self(Arc::new(Mutex::new(State {
             contexts: Default::default(),
             output_device: None,
+            device_status: DeviceStatus::None,
+            device_event_handler: None,
         })))
     }
 
@@ -66,19 +112,107 @@ impl SoundEngine {
                         )
                     };
 
-                    state.state().render(data);
+                    let mut state = state.state();
+                    state.refresh_device_activity();
+                    state.render(data);
                 }
             },
         )?;
 
-        self.state().output_device = Some(device);
+        let mut state = self.state();
+        state.output_device = Some(device);
+        state.device_status = DeviceStatus::Active {
+            last_activity: Instant::now(),
+        };
 
         Ok(())
     }
 
     /// Destroys current audio output device (if any).
     pub fn destroy_audio_output_device(&self) {
-        self.state().output_device = None;
+        let device = self.state().output_device.take();
+        // The device must be destroyed with unlocked state, because its thread could wait for the lock.
+        // The status is changed only after that, so the device thread can't see it.
+        drop(device);
+        self.state().device_status = DeviceStatus::None;
+    }
+
+    /// Destroys current audio output device (if any) and tries to initialize default audio output device
+    /// again. It could be used to switch the output to a new default device. All the contexts are preserved,
+    /// so the playback continues from the same place. If the initialization fails, the device is considered
+    /// lost and [`Self::update_device`] will try to re-acquire it later.
+    pub fn reinitialize_device(&self) -> Result<(), Box<dyn Error>> {
+        self.destroy_audio_output_device();
+        self.initialize_audio_output_device().map_err(|err| {
+            self.state().device_status = DeviceStatus::Lost {
+                last_attempt: Some(Instant::now()),
+            };
+            err
+        })
+    }
+
+    /// Sets a handler, that will be called when the output device is lost or recovered. The handler is
+    /// called from [`Self::update_device`] with unlocked engine state, so it is safe to access the engine
+    /// from it. Returns the previous handler.
+    pub fn set_device_event_handler(
+        &self,
+        handler: Option<DeviceEventHandler>,
+    ) -> Option<DeviceEventHandler> {
+        std::mem::replace(&mut self.state().device_event_handler, handler)
+    }
+
+    /// Checks the state of the output device and re-acquires it if it was lost. The device is considered
+    /// lost if it did not request any samples for longer than [`DEVICE_TIMEOUT`], which is what happens on
+    /// most platforms when the device is unplugged or disabled. Lost device is destroyed and the engine tries
+    /// to initialize default output device every second until it succeeds. Both events are logged and
+    /// reported to the device event handler (see [`Self::set_device_event_handler`]).
+    ///
+    /// This method should be called periodically (for example, once per frame), the engine does this
+    /// automatically. It does nothing in headless mode.
+    pub fn update_device(&self) {
+        let now = Instant::now();
+        let mut state = self.state();
+        let status = state.device_status;
+        match status {
+            DeviceStatus::Active { last_activity } if now - last_activity > DEVICE_TIMEOUT => {
+                let device = state.output_device.take();
+                drop(state);
+                drop(device);
+                self.state().device_status = DeviceStatus::Lost { last_attempt: None };
+
+                Log::warn("Audio output device was lost. Trying to re-acquire it...");
+                self.fire_device_event(DeviceEvent::Lost);
+            }
+            DeviceStatus::Lost {
+                last_attempt: Some(last_attempt),
+            } if now - last_attempt < DEVICE_RETRY_INTERVAL => return,
+            DeviceStatus::Lost { .. } => drop(state),
+            _ => return,
+        }
+
+        match self.initialize_audio_output_device() {
+            Ok(()) => {
+                Log::info("Audio output device was re-acquired.");
+                self.fire_device_event(DeviceEvent::Recovered);
+            }
+            Err(_) => {
+                self.state().device_status = DeviceStatus::Lost {
+                    last_attempt: Some(now),
+                };
+            }
+        }
+    }
+
+    fn fire_device_event(&self, event: DeviceEvent) {
+        let handler = self.state().device_event_handler.take();
+        if let Some(mut handler) = handler {
+            handler(event);
+            let mut state = self.state();
+            // The handler could be replaced while it was running.
+            if state.device_event_handler.is_none() {
+                state.device_event_handler = Some(handler);
+            }
+        }
     }
 
     /// Provides direct access to actual engine data.
@@ -141,6 +275,14 @@ impl State {
             context.state().render(buf);
         }
     }
+
+    // Called by the device thread each time it requests samples. The status is changed only if the device
+    // is still active, otherwise the device thread could revive a device, that was already destroyed or lost.
+    fn refresh_device_activity(&mut self) {
+        if let DeviceStatus::Active { last_activity } = &mut self.device_status {
+            *last_activity = Instant::now();
+        }
+    }
 }
 
 impl Visit for State {
@@ -156,3 +298,32 @@ impl Visit for State {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::engine::{DeviceStatus, SoundEngine};
+    use std::time::Instant;
+
+    #[test]
+    fn test_refresh_device_activity() {
+        let engine = SoundEngine::without_device();
+        let mut state = engine.state();
+
+        state.refresh_device_activity();
+        assert!(matches!(state.device_status, DeviceStatus::None));
+
+        state.device_status = DeviceStatus::Lost { last_attempt: None };
+        state.refresh_device_activity();
+        assert!(matches!(
+            state.device_status,
+            DeviceStatus::Lost { last_attempt: None }
+        ));
+
+        let last_activity = Instant::now();
+        state.device_status = DeviceStatus::Active { last_activity };
+        state.refresh_device_activity();
+        assert!(
+            matches!(state.device_status, DeviceStatus::Active { last_activity: new } if new >= last_activity)
+        );
+    }
+}
@@ -35,6 +35,12 @@
 //! }
 //! ```
 //!
+//! # Switching HRIR spheres
+//!
+//! HRIR sphere could be changed at runtime using [`HrtfRenderer::set_hrir_sphere`], for example to compare
+//! different HRTF datasets. Currently playing sources are not interrupted, the output of the previous sphere
+//! is smoothly crossfaded with the output of the new one to prevent clicks.
+//!
 //! # Performance
 //!
 //! HRTF is `heavy`. Usually it 4-5 slower than default renderer, this is essential because HRTF requires some heavy
@@ -77,12 +83,30 @@ use std::error::Error;
 use std::path::Path;
 use std::{any::Any, fmt::Debug, fmt::Formatter, path::PathBuf, sync::Arc};
 
+/// A processor of the previous HRIR sphere, which output is faded out after the sphere was changed.
+#[derive(Clone, Debug)]
+struct FadingProcessor {
+    processor: hrtf::HrtfProcessor,
+    // Amount of rendered frames since the beginning of the crossfade.
+    position: usize,
+}
+
 /// See module docs.
 #[derive(Clone, Debug, Default, Reflect)]
 pub struct HrtfRenderer {
     hrir_resource: Option<HrirSphereResource>,
     #[reflect(hidden)]
     processor: Option<hrtf::HrtfProcessor>,
+    #[reflect(hidden)]
+    fading: Option<FadingProcessor>,
+    // Increased every time when the sphere is changed, so sources can detect that their convolution
+    // state belongs to the previous sphere.
+    #[reflect(hidden)]
+    generation: u64,
+    #[reflect(hidden)]
+    new_buffer: Vec<(f32, f32)>,
+    #[reflect(hidden)]
+    fading_buffer: Vec<(f32, f32)>,
 }
 
 impl Visit for HrtfRenderer {
@@ -96,9 +120,18 @@ impl Visit for HrtfRenderer {
 }
 
 impl HrtfRenderer {
+    /// Amount of frames rendered by a sound context (roughly 45 ms each), during which the output of the
+    /// previous HRIR sphere is crossfaded with the output of the new one. See [`Self::set_hrir_sphere`] for
+    /// more info.
+    pub const CROSSFADE_FRAMES: usize = 4;
+
     /// Creates new HRTF renderer using specified HRTF sphere. See module docs for more info.
     pub fn new(hrir_sphere_resource: HrirSphereResource) -> Self {
         Self {
+            fading: None,
+            generation: 0,
+            new_buffer: Default::default(),
+            fading_buffer: Default::default(),
             processor: Some(hrtf::HrtfProcessor::new(
                 {
                     let sphere = hrir_sphere_resource.data_ref().hrir_sphere.clone().unwrap();
@@ -118,11 +151,40 @@ impl HrtfRenderer {
         self.processor = None;
     }
 
+    /// Replaces current HRIR sphere with the given one without interrupting currently playing sources. Unlike
+    /// [`Self::set_hrir_sphere_resource`], the previous sphere keeps working until the new one is fully loaded,
+    /// then the output of the previous sphere is crossfaded with the output of the new one during
+    /// [`Self::CROSSFADE_FRAMES`] frames, so the switch is click-free. If the sphere is changed again during
+    /// the crossfade, the crossfade starts over from the current sphere.
+    pub fn set_hrir_sphere(&mut self, resource: HrirSphereResource) {
+        if let Some(processor) = self.processor.take() {
+            self.fading = Some(FadingProcessor {
+                processor,
+                position: 0,
+            });
+            self.generation += 1;
+        }
+        self.hrir_resource = Some(resource);
+    }
+
     /// Returns current HRIR sphere resource (if any).
     pub fn hrir_sphere_resource(&self) -> Option<HrirSphereResource> {
         self.hrir_resource.clone()
     }
 
+    /// Must be called once after all sources were rendered to advance the crossfade between HRIR spheres.
+    pub(crate) fn end_render(&mut self) {
+        // The crossfade starts only when the new sphere is loaded.
+        if self.processor.is_some() {
+            if let Some(fading) = self.fading.as_mut() {
+                fading.position += 1;
+                if fading.position >= Self::CROSSFADE_FRAMES {
+                    self.fading = None;
+                }
+            }
+        }
+    }
+
     pub(crate) fn render_source(
         &mut self,
         source: &mut SoundSource,
@@ -154,25 +216,82 @@ impl HrtfRenderer {
             * source.calculate_distance_gain(listener, distance_model);
         let new_sampling_vector = source.calculate_sampling_vector(listener);
 
-        if let Some(processor) = self.processor.as_mut() {
-            processor.process_samples(hrtf::HrtfContext {
-                source: &source.frame_samples,
-                output: out_buf,
-                new_sample_vector: hrtf::Vec3::new(
-                    new_sampling_vector.x,
-                    new_sampling_vector.y,
-                    new_sampling_vector.z,
-                ),
-                prev_sample_vector: hrtf::Vec3::new(
-                    source.prev_sampling_vector.x,
-                    source.prev_sampling_vector.y,
-                    source.prev_sampling_vector.z,
-                ),
-                prev_left_samples: &mut source.prev_left_samples,
-                prev_right_samples: &mut source.prev_right_samples,
-                prev_distance_gain: source.prev_distance_gain.unwrap_or(new_distance_gain),
-                new_distance_gain,
-            });
+        let new_sample_vector = hrtf::Vec3::new(
+            new_sampling_vector.x,
+            new_sampling_vector.y,
+            new_sampling_vector.z,
+        );
+        let prev_sample_vector = hrtf::Vec3::new(
+            source.prev_sampling_vector.x,
+            source.prev_sampling_vector.y,
+            source.prev_sampling_vector.z,
+        );
+        let prev_distance_gain = source.prev_distance_gain.unwrap_or(new_distance_gain);
+
+        match (self.processor.as_mut(), self.fading.as_mut()) {
+            (Some(processor), Some(fading)) => {
+                // The convolution state of the source belongs to the previous sphere, keep it for the
+                // fading processor and start from scratch with the new one.
+                if source.hrtf_generation != self.generation {
+                    source.fading_left_samples = std::mem::take(&mut source.prev_left_samples);
+                    source.fading_right_samples = std::mem::take(&mut source.prev_right_samples);
+                    source.hrtf_generation = self.generation;
+                }
+
+                for buffer in [&mut self.new_buffer, &mut self.fading_buffer] {
+                    buffer.clear();
+                    buffer.resize(out_buf.len(), (0.0, 0.0));
+                }
+
+                processor.process_samples(hrtf::HrtfContext {
+                    source: &source.frame_samples,
+                    output: &mut self.new_buffer,
+                    new_sample_vector,
+                    prev_sample_vector,
+                    prev_left_samples: &mut source.prev_left_samples,
+                    prev_right_samples: &mut source.prev_right_samples,
+                    prev_distance_gain,
+                    new_distance_gain,
+                });
+                fading.processor.process_samples(hrtf::HrtfContext {
+                    source: &source.frame_samples,
+                    output: &mut self.fading_buffer,
+                    new_sample_vector,
+                    prev_sample_vector,
+                    prev_left_samples: &mut source.fading_left_samples,
+                    prev_right_samples: &mut source.fading_right_samples,
+                    prev_distance_gain,
+                    new_distance_gain,
+                });
+
+                let total = (Self::CROSSFADE_FRAMES * out_buf.len()) as f32;
+                for (i, ((out_left, out_right), ((new_left, new_right), (old_left, old_right)))) in
+                    out_buf
+                        .iter_mut()
+                        .zip(self.new_buffer.iter().zip(self.fading_buffer.iter()))
+                        .enumerate()
+                {
+                    let k = ((fading.position * out_buf.len() + i) as f32 / total).min(1.0);
+                    *out_left += new_left * k + old_left * (1.0 - k);
+                    *out_right += new_right * k + old_right * (1.0 - k);
+                }
+            }
+            (processor, fading) => {
+                // The new sphere is not loaded yet, keep using the previous one.
+                let processor = processor.or(fading.map(|fading| &mut fading.processor));
+                if let Some(processor) = processor {
+                    processor.process_samples(hrtf::HrtfContext {
+                        source: &source.frame_samples,
+                        output: out_buf,
+                        new_sample_vector,
+                        prev_sample_vector,
+                        prev_left_samples: &mut source.prev_left_samples,
+                        prev_right_samples: &mut source.prev_right_samples,
+                        prev_distance_gain,
+                        new_distance_gain,
+                    });
+                }
+            }
         }
 
         source.prev_sampling_vector = new_sampling_vector;
@@ -267,3 +386,58 @@ impl HrirSphereResourceExt for HrirSphereResource {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::{self, DistanceModel, SoundContext},
+        listener::Listener,
+        renderer::hrtf::{HrirSphereResource, HrirSphereResourceExt, HrtfRenderer},
+        source::SoundSource,
+    };
+    use fyrox_core::algebra::Vector3;
+    use hrtf::HrirSphere;
+    use std::path::PathBuf;
+
+    fn load_sphere() -> HrirSphereResource {
+        let path = PathBuf::from("examples/data/IRC_1002_C.bin");
+        let sphere = HrirSphere::from_file(&path, context::SAMPLE_RATE).unwrap();
+        HrirSphereResource::from_hrir_sphere(sphere, path.into())
+    }
+
+    fn render(renderer: &mut HrtfRenderer, source: &mut SoundSource) -> Vec<(f32, f32)> {
+        source.frame_samples = (0..SoundContext::SAMPLES_PER_CHANNEL)
+            .map(|i| {
+                let v = (i as f32 * 0.05).sin();
+                (v, v)
+            })
+            .collect();
+        let mut out = vec![(0.0, 0.0); SoundContext::SAMPLES_PER_CHANNEL];
+        renderer.render_source(source, &Listener::default(), DistanceModel::None, &mut out);
+        renderer.end_render();
+        out
+    }
+
+    #[test]
+    fn test_set_hrir_sphere() {
+        let mut renderer = HrtfRenderer::new(load_sphere());
+        let mut source = SoundSource::default();
+        source.set_position(Vector3::new(1.0, 0.0, 3.0));
+        render(&mut renderer, &mut source);
+
+        renderer.set_hrir_sphere(load_sphere());
+        assert!(renderer.fading.is_some());
+
+        for _ in 0..HrtfRenderer::CROSSFADE_FRAMES {
+            let out = render(&mut renderer, &mut source);
+            assert!(out.iter().all(|(l, r)| l.is_finite() && r.is_finite()));
+            assert_eq!(source.hrtf_generation, renderer.generation);
+        }
+
+        // The previous sphere is dropped after the crossfade, the source keeps playing.
+        assert!(renderer.fading.is_none());
+        assert!(renderer.processor.is_some());
+        let out = render(&mut renderer, &mut source);
+        assert!(out.iter().any(|(l, r)| *l != 0.0 || *r != 0.0));
+    }
+}
@@ -8,8 +8,11 @@
 #![allow(clippy::float_cmp)]
 
 use crate::{
-    context::DistanceModel, listener::Listener, math, renderer::hrtf::HrtfRenderer,
-    source::SoundSource,
+    context::DistanceModel,
+    listener::Listener,
+    math,
+    renderer::hrtf::HrtfRenderer,
+    source::{RendererState, SoundSource},
 };
 use fyrox_core::math::lerpf;
 use fyrox_core::{
@@ -43,6 +46,111 @@ impl Default for Renderer {
     }
 }
 
+impl Renderer {
+    pub(crate) fn render_source(
+        &mut self,
+        source: &mut SoundSource,
+        listener: &Listener,
+        distance_model: DistanceModel,
+        mix_buffer: &mut [(f32, f32)],
+    ) {
+        match self {
+            Renderer::Default => {
+                // Simple rendering path. Much faster (4-5 times) than HRTF path.
+                render_source_default(source, listener, distance_model, mix_buffer);
+            }
+            Renderer::HrtfRenderer(hrtf_renderer) => {
+                hrtf_renderer.render_source(source, listener, distance_model, mix_buffer);
+            }
+        }
+    }
+
+    /// Must be called once after all sources were rendered.
+    pub(crate) fn end_render(&mut self) {
+        if let Renderer::HrtfRenderer(hrtf_renderer) = self {
+            hrtf_renderer.end_render();
+        }
+    }
+}
+
+/// A previous renderer, that keeps working for a short time after the renderer was changed, so its output
+/// could be crossfaded with the output of the new one. See [`crate::context::State::set_renderer_with_crossfade`]
+/// for more info.
+#[derive(Debug, Clone)]
+pub(crate) struct RendererCrossfade {
+    renderer: Renderer,
+    // Amount of rendered samples (per channel) since the beginning of the crossfade.
+    position: usize,
+    length: usize,
+    generation: u64,
+    new_buffer: Vec<(f32, f32)>,
+    old_buffer: Vec<(f32, f32)>,
+}
+
+impl RendererCrossfade {
+    pub(crate) fn new(renderer: Renderer, length: usize, generation: u64) -> Self {
+        Self {
+            renderer,
+            position: 0,
+            length,
+            generation,
+            new_buffer: Default::default(),
+            old_buffer: Default::default(),
+        }
+    }
+
+    /// Renders the source using both renderers and mixes their outputs.
+    pub(crate) fn render_source(
+        &mut self,
+        renderer: &mut Renderer,
+        source: &mut SoundSource,
+        listener: &Listener,
+        distance_model: DistanceModel,
+        mix_buffer: &mut [(f32, f32)],
+    ) {
+        // The state of the source (gains, convolution history, etc.) belongs to the previous renderer, keep
+        // it for the previous renderer and let the new renderer start from scratch.
+        if source.renderer_generation != self.generation {
+            let mut state = RendererState::default();
+            source.swap_renderer_state(&mut state);
+            source.fading_renderer_state = state;
+            source.renderer_generation = self.generation;
+        }
+
+        for buffer in [&mut self.new_buffer, &mut self.old_buffer] {
+            buffer.clear();
+            buffer.resize(mix_buffer.len(), (0.0, 0.0));
+        }
+
+        renderer.render_source(source, listener, distance_model, &mut self.new_buffer);
+
+        let mut state = std::mem::take(&mut source.fading_renderer_state);
+        source.swap_renderer_state(&mut state);
+        self.renderer
+            .render_source(source, listener, distance_model, &mut self.old_buffer);
+        source.swap_renderer_state(&mut state);
+        source.fading_renderer_state = state;
+
+        for (i, ((out_left, out_right), ((new_left, new_right), (old_left, old_right)))) in
+            mix_buffer
+                .iter_mut()
+                .zip(self.new_buffer.iter().zip(self.old_buffer.iter()))
+                .enumerate()
+        {
+            let k = ((self.position + i) as f32 / self.length as f32).min(1.0);
+            *out_left += new_left * k + old_left * (1.0 - k);
+            *out_right += new_right * k + old_right * (1.0 - k);
+        }
+    }
+
+    /// Advances the crossfade by the given amount of samples. Returns `true` if the crossfade is finished.
+    pub(crate) fn end_render(&mut self, sample_count: usize) -> bool {
+        self.renderer.end_render();
+        self.position += sample_count;
+        self.position >= self.length
+    }
+}
+
 fn render_with_params(
     source: &mut SoundSource,
     left_gain: f32,
@@ -108,3 +216,101 @@ pub(crate) fn render_source_2d_only(source: &mut SoundSource, mix_buffer: &mut [
     source.last_left_gain = Some(left_gain);
     source.last_right_gain = Some(right_gain);
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::{self, DistanceModel, SoundContext},
+        listener::Listener,
+        renderer::{
+            hrtf::{HrirSphereResource, HrirSphereResourceExt, HrtfRenderer},
+            Renderer, RendererCrossfade,
+        },
+        source::SoundSource,
+    };
+    use fyrox_core::algebra::Vector3;
+    use hrtf::HrirSphere;
+    use std::path::PathBuf;
+
+    const LEN: usize = SoundContext::SAMPLES_PER_CHANNEL;
+
+    fn make_renderer() -> Renderer {
+        let path = PathBuf::from("examples/data/IRC_1002_C.bin");
+        let sphere = HrirSphere::from_file(&path, context::SAMPLE_RATE).unwrap();
+        Renderer::HrtfRenderer(HrtfRenderer::new(HrirSphereResource::from_hrir_sphere(
+            sphere,
+            path.into(),
+        )))
+    }
+
+    fn make_source() -> SoundSource {
+        let mut source = SoundSource::default();
+        source.set_position(Vector3::new(1.0, 0.0, 3.0));
+        source
+    }
+
+    fn set_frame(source: &mut SoundSource, frame: usize) {
+        source.frame_samples = (0..LEN)
+            .map(|i| {
+                let v = ((frame * LEN + i) as f32 * 0.05).sin();
+                (v, v)
+            })
+            .collect();
+    }
+
+    fn render(renderer: &mut Renderer, source: &mut SoundSource, frame: usize) -> Vec<(f32, f32)> {
+        set_frame(source, frame);
+        let mut out = vec![(0.0, 0.0); LEN];
+        renderer.render_source(source, &Listener::default(), DistanceModel::None, &mut out);
+        renderer.end_render();
+        out
+    }
+
+    #[test]
+    fn test_crossfade_keeps_separate_hrtf_state() {
+        // The previous renderer keeps rendering the source as if nothing happened.
+        let mut old_renderer = make_renderer();
+        let mut old_source = make_source();
+        let old_out = (0..4)
+            .map(|frame| render(&mut old_renderer, &mut old_source, frame))
+            .collect::<Vec<_>>();
+
+        // The new renderer starts from scratch at the moment of the switch.
+        let mut new_renderer = make_renderer();
+        let mut new_source = make_source();
+        let new_out = (2..4)
+            .map(|frame| render(&mut new_renderer, &mut new_source, frame))
+            .collect::<Vec<_>>();
+
+        let mut renderer = make_renderer();
+        let mut source = make_source();
+        for frame in 0..2 {
+            render(&mut renderer, &mut source, frame);
+        }
+
+        let mut crossfade = RendererCrossfade::new(renderer, 2 * LEN, 1);
+        let mut renderer = make_renderer();
+        for frame in 2..4 {
+            set_frame(&mut source, frame);
+            let mut out = vec![(0.0, 0.0); LEN];
+            crossfade.render_source(
+                &mut renderer,
+                &mut source,
+                &Listener::default(),
+                DistanceModel::None,
+                &mut out,
+            );
+            renderer.end_render();
+            let finished = crossfade.end_render(LEN);
+            assert_eq!(finished, frame == 3);
+
+            for (i, (left, right)) in out.into_iter().enumerate() {
+                let k = ((frame - 2) * LEN + i) as f32 / (2 * LEN) as f32;
+                let (new_left, new_right) = new_out[frame - 2][i];
+                let (old_left, old_right) = old_out[frame][i];
+                assert!((left - (new_left * k + old_left * (1.0 - k))).abs() < 1.0e-5);
+                assert!((right - (new_right * k + old_right * (1.0 - k))).abs() < 1.0e-5);
+            }
+        }
+    }
+}
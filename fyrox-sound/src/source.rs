@@ -37,6 +37,7 @@ use crate::{
 };
 use fyrox_core::{
     algebra::Vector3,
+    math::lerpf,
     reflect::prelude::*,
     uuid_provider,
     visitor::{Visit, VisitResult, Visitor},
@@ -61,6 +62,38 @@ pub enum Status {
 
 uuid_provider!(Status = "1980bded-86cd-4eff-a5db-bab729bdb3ad");
 
+/// A part of the state of a sound source, that is owned by a renderer (gains of the previous frame,
+/// convolution history of HRTF, etc.). It is used to keep the state of two renderers separately, while
+/// they are crossfaded.
+#[derive(Debug, Clone)]
+pub(crate) struct RendererState {
+    last_left_gain: Option<f32>,
+    last_right_gain: Option<f32>,
+    prev_left_samples: Vec<f32>,
+    prev_right_samples: Vec<f32>,
+    prev_sampling_vector: Vector3<f32>,
+    prev_distance_gain: Option<f32>,
+    fading_left_samples: Vec<f32>,
+    fading_right_samples: Vec<f32>,
+    hrtf_generation: u64,
+}
+
+impl Default for RendererState {
+    fn default() -> Self {
+        Self {
+            last_left_gain: None,
+            last_right_gain: None,
+            prev_left_samples: Default::default(),
+            prev_right_samples: Default::default(),
+            prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
+            prev_distance_gain: None,
+            fading_left_samples: Default::default(),
+            fading_right_samples: Default::default(),
+            hrtf_generation: 0,
+        }
+    }
+}
+
 /// See module info.
 #[derive(Debug, Clone, Reflect, Visit)]
 pub struct SoundSource {
@@ -99,6 +132,20 @@ pub struct SoundSource {
     #[visit(optional)]
     pub(crate) bus: String,
     play_once: bool,
+    #[visit(optional)]
+    priority: i32,
+    #[visit(optional)]
+    downmix_spatial_stereo: bool,
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    reverb_send: f32,
+    #[visit(optional)]
+    reverb_send_bus: String,
+    // Amount of samples rendered since the source was started. It is used by voice stealing to
+    // find the oldest sources.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) age: u64,
     // Here we use Option because when source is just created it has no info about it
     // previous left and right channel gains. We can't set it to 1.0 for example
     // because it would give incorrect results: a sound would just start as loud as it
@@ -121,6 +168,8 @@ pub struct SoundSource {
     #[reflect(min_value = 0.0, step = 0.05)]
     radius: f32,
     position: Vector3<f32>,
+    #[visit(optional)]
+    velocity: Vector3<f32>,
     #[reflect(min_value = 0.0, step = 0.05)]
     max_distance: f32,
     #[reflect(min_value = 0.0, step = 0.05)]
@@ -138,6 +187,23 @@ pub struct SoundSource {
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) prev_distance_gain: Option<f32>,
+    // Convolution state of the previous HRIR sphere, that is used while crossfading between spheres.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) fading_left_samples: Vec<f32>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) fading_right_samples: Vec<f32>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) hrtf_generation: u64,
+    // State of the previous renderer, that is used while crossfading between renderers.
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) fading_renderer_state: RendererState,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub(crate) renderer_generation: u64,
 }
 
 impl Default for SoundSource {
@@ -156,18 +222,29 @@ impl Default for SoundSource {
             status: Status::Stopped,
             bus: "Master".to_string(),
             play_once: false,
+            priority: 0,
+            downmix_spatial_stereo: false,
+            reverb_send: 0.0,
+            reverb_send_bus: AudioBusGraph::REVERB_BUS.to_string(),
+            age: 0,
             last_left_gain: None,
             last_right_gain: None,
             frame_samples: Default::default(),
             prev_buffer_sample: (0.0, 0.0),
             radius: 1.0,
             position: Vector3::new(0.0, 0.0, 0.0),
+            velocity: Vector3::new(0.0, 0.0, 0.0),
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
             prev_distance_gain: None,
+            fading_left_samples: Default::default(),
+            fading_right_samples: Default::default(),
+            hrtf_generation: 0,
+            fading_renderer_state: Default::default(),
+            renderer_generation: 0,
         }
     }
 }
@@ -190,7 +267,21 @@ impl SoundSource {
 
     /// Sets spatial blend factor. It defines how much the source will be 2D and 3D sound at the same
     /// time. Set it to 0.0 to make the sound fully 2D and 1.0 to make it fully 3D. Middle values
-    /// will make sound proportionally 2D and 3D at the same time.
+    /// will make sound proportionally 2D and 3D at the same time. The value is clamped to `[0.0; 1.0]`
+    /// range. Fully 2D sound ignores the position of the listener: it has no distance attenuation and
+    /// uses [`Self::panning`] as is.
+    ///
+    /// # Renderers
+    ///
+    /// The exact behaviour depends on the renderer of the sound context:
+    ///
+    /// - [`crate::renderer::Renderer::Default`] - distance attenuation and panning are interpolated between
+    /// their 2D and 3D values using the factor, so the sound is mixed only once.
+    /// - [`crate::renderer::Renderer::HrtfRenderer`] - the sound is rendered twice and the results are
+    /// crossfaded: the 2D mix (gain and panning only) is scaled by `1.0 - k` and the HRTF-processed mix
+    /// (with distance attenuation) is scaled by `k`. This means that HRTF processing cost is paid even for
+    /// partially spatialized sounds, and that the 2D part has no HRTF coloring at all. While the HRIR sphere
+    /// is still loading, only the 2D part is audible.
     pub fn set_spatial_blend(&mut self, k: f32) {
         self.spatial_blend = k.clamp(0.0, 1.0);
     }
@@ -295,6 +386,39 @@ impl SoundSource {
         self.status
     }
 
+    /// Sets priority of the sound source. Priority is used by voice stealing (see
+    /// [`crate::context::State::set_max_voices`]): when there are more playing sources than the
+    /// context allows, sources with lower priority are stopped first. Default value is 0.
+    pub fn set_priority(&mut self, priority: i32) -> &mut Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns priority of the sound source. See [`Self::set_priority`] for more info.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Enables or disables automatic downmixing of stereo buffers for spatial sounds. Spatialization
+    /// (distance-based panning, HRTF) is designed for mono input, when a stereo buffer is used, each of
+    /// its channels is panned separately and a sound with most of its energy in one channel may become
+    /// inaudible in one ear. When enabled, left and right channels of stereo buffers are averaged before
+    /// spatialization, proportionally to [`Self::spatial_blend`] (fully spatial sounds become mono, fully
+    /// 2D sounds are left intact). Mono buffers are not affected. Disabled by default.
+    ///
+    /// Prefer mono buffers for spatial sounds, or convert them up-front using
+    /// [`crate::buffer::SoundBuffer::convert_channels`], it saves some work each frame.
+    pub fn set_downmix_spatial_stereo(&mut self, downmix: bool) -> &mut Self {
+        self.downmix_spatial_stereo = downmix;
+        self
+    }
+
+    /// Returns `true` if stereo buffers are downmixed for spatial sounds. See
+    /// [`Self::set_downmix_spatial_stereo`] for more info.
+    pub fn is_downmix_spatial_stereo(&self) -> bool {
+        self.downmix_spatial_stereo
+    }
+
     /// Changes status to `Playing`.
     pub fn play(&mut self) -> &mut Self {
         self.status = Status::Playing;
@@ -333,6 +457,7 @@ impl SoundSource {
     /// Stops sound source. Automatically rewinds streaming buffers.
     pub fn stop(&mut self) -> Result<(), SoundError> {
         self.status = Status::Stopped;
+        self.age = 0;
 
         self.buf_read_pos = 0.0;
         self.playback_pos = 0.0;
@@ -356,6 +481,18 @@ impl SoundSource {
         self.position
     }
 
+    /// Sets velocity (in units per second) of source in world space. The velocity is not used by
+    /// the renderer directly, but it could be used to implement doppler effect.
+    pub fn set_velocity(&mut self, velocity: Vector3<f32>) -> &mut Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Returns velocity of source.
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
     /// Sets radius of imaginable sphere around source in which no distance attenuation is applied.
     pub fn set_radius(&mut self, radius: f32) -> &mut Self {
         self.radius = radius;
@@ -405,6 +542,32 @@ impl SoundSource {
         &self.bus
     }
 
+    /// Sets the amount of the signal of the source, that will be sent to the reverb bus (see
+    /// [`Self::set_reverb_send_bus`]). The value is clamped to `[0.0; 1.0]` range, where `0.0` means
+    /// that the source is completely "dry" (default) and `1.0` means that the reverb bus receives the
+    /// full signal of the source. The dry signal always goes to the output bus of the source unchanged.
+    /// Typically, distant or indoor sources should have larger send amount than close ones. See
+    /// [`AudioBusGraph`] docs for more info about sends.
+    pub fn set_reverb_send(&mut self, amount: f32) {
+        self.reverb_send = amount.clamp(0.0, 1.0);
+    }
+
+    /// Returns the amount of the signal of the source, that will be sent to the reverb bus.
+    pub fn reverb_send(&self) -> f32 {
+        self.reverb_send
+    }
+
+    /// Sets the name of the audio bus, that will receive the reverb send of the source. Default is
+    /// [`AudioBusGraph::REVERB_BUS`]. If there's no bus with such name, the send is ignored.
+    pub fn set_reverb_send_bus<S: AsRef<str>>(&mut self, bus: S) {
+        bus.as_ref().clone_into(&mut self.reverb_send_bus);
+    }
+
+    /// Returns the name of the audio bus, that receives the reverb send of the source.
+    pub fn reverb_send_bus(&self) -> &str {
+        &self.reverb_send_bus
+    }
+
     // Distance models were taken from OpenAL Specification because it looks like they're
     // standard in industry and there is no need to reinvent it.
     // https://www.openal.org/documentation/openal-1.1-specification.pdf
@@ -429,6 +592,20 @@ impl SoundSource {
         }
     }
 
+    /// Returns approximate gain of the source at the listener's position, it is used to find
+    /// the quietest sources.
+    pub(crate) fn calculate_audible_gain(
+        &self,
+        listener: &Listener,
+        distance_model: DistanceModel,
+    ) -> f32 {
+        lerpf(
+            1.0,
+            self.calculate_distance_gain(listener, distance_model),
+            self.spatial_blend,
+        ) * self.gain
+    }
+
     pub(crate) fn calculate_panning(&self, listener: &Listener) -> f32 {
         (listener.position() - self.position)
             .try_normalize(f32::EPSILON)
@@ -501,6 +678,10 @@ impl SoundSource {
             if let Some(buffer) = state.data() {
                 if self.status == Status::Playing && !buffer.is_empty() {
                     self.render_playing(buffer, amount);
+
+                    if self.downmix_spatial_stereo && buffer.channel_count() == 2 {
+                        self.downmix_frame_samples();
+                    }
                 }
             }
         }
@@ -508,6 +689,20 @@ impl SoundSource {
         self.frame_samples.resize(amount, (0.0, 0.0));
     }
 
+    // Averages left and right channels of rendered samples, proportionally to spatial blend factor.
+    fn downmix_frame_samples(&mut self) {
+        let k = self.spatial_blend;
+        if k == 0.0 {
+            return;
+        }
+
+        for (left, right) in self.frame_samples.iter_mut() {
+            let mid = (*left + *right) * 0.5;
+            *left = lerpf(*left, mid, k);
+            *right = lerpf(*right, mid, k);
+        }
+    }
+
     fn render_playing(&mut self, buffer: &mut SoundBuffer, amount: usize) {
         let mut count = 0;
         loop {
@@ -652,6 +847,28 @@ impl SoundSource {
         rendered
     }
 
+    /// Swaps the renderer-owned part of the state of the source with the given one.
+    pub(crate) fn swap_renderer_state(&mut self, state: &mut RendererState) {
+        std::mem::swap(&mut self.last_left_gain, &mut state.last_left_gain);
+        std::mem::swap(&mut self.last_right_gain, &mut state.last_right_gain);
+        std::mem::swap(&mut self.prev_left_samples, &mut state.prev_left_samples);
+        std::mem::swap(&mut self.prev_right_samples, &mut state.prev_right_samples);
+        std::mem::swap(
+            &mut self.prev_sampling_vector,
+            &mut state.prev_sampling_vector,
+        );
+        std::mem::swap(&mut self.prev_distance_gain, &mut state.prev_distance_gain);
+        std::mem::swap(
+            &mut self.fading_left_samples,
+            &mut state.fading_left_samples,
+        );
+        std::mem::swap(
+            &mut self.fading_right_samples,
+            &mut state.fading_right_samples,
+        );
+        std::mem::swap(&mut self.hrtf_generation, &mut state.hrtf_generation);
+    }
+
     pub(crate) fn frame_samples(&self) -> &[(f32, f32)] {
         &self.frame_samples
     }
@@ -709,13 +926,18 @@ pub struct SoundSourceBuilder {
     looping: bool,
     status: Status,
     play_once: bool,
+    priority: i32,
+    downmix_spatial_stereo: bool,
     playback_time: Duration,
     radius: f32,
     position: Vector3<f32>,
+    velocity: Vector3<f32>,
     max_distance: f32,
     rolloff_factor: f32,
     spatial_blend: f32,
     bus: String,
+    reverb_send: f32,
+    reverb_send_bus: String,
 }
 
 impl Default for SoundSourceBuilder {
@@ -736,13 +958,18 @@ impl SoundSourceBuilder {
             looping: false,
             status: Status::Stopped,
             play_once: false,
+            priority: 0,
+            downmix_spatial_stereo: false,
             playback_time: Default::default(),
             radius: 1.0,
             position: Vector3::new(0.0, 0.0, 0.0),
+            velocity: Vector3::new(0.0, 0.0, 0.0),
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
             spatial_blend: 1.0,
             bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            reverb_send: 0.0,
+            reverb_send_bus: AudioBusGraph::REVERB_BUS.to_string(),
         }
     }
 
@@ -800,6 +1027,18 @@ impl SoundSourceBuilder {
         self
     }
 
+    /// See [`SoundSource::set_priority`]
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// See [`SoundSource::set_downmix_spatial_stereo`]
+    pub fn with_downmix_spatial_stereo(mut self, downmix: bool) -> Self {
+        self.downmix_spatial_stereo = downmix;
+        self
+    }
+
     /// Sets desired name of the source.
     pub fn with_name<N: AsRef<str>>(mut self, name: N) -> Self {
         name.as_ref().clone_into(&mut self.name);
@@ -818,6 +1057,12 @@ impl SoundSourceBuilder {
         self
     }
 
+    /// See [`SoundSource::set_velocity`]
+    pub fn with_velocity(mut self, velocity: Vector3<f32>) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
     /// See `set_radius` of SpatialSource.
     pub fn with_radius(mut self, radius: f32) -> Self {
         self.radius = radius;
@@ -842,6 +1087,18 @@ impl SoundSourceBuilder {
         self
     }
 
+    /// See [`SoundSource::set_reverb_send`]
+    pub fn with_reverb_send(mut self, amount: f32) -> Self {
+        self.reverb_send = amount.clamp(0.0, 1.0);
+        self
+    }
+
+    /// See [`SoundSource::set_reverb_send_bus`]
+    pub fn with_reverb_send_bus<S: AsRef<str>>(mut self, bus: S) -> Self {
+        self.reverb_send_bus = bus.as_ref().to_string();
+        self
+    }
+
     /// Creates new instance of generic sound source. May fail if buffer is invalid.
     pub fn build(self) -> Result<SoundSource, SoundError> {
         let mut source = SoundSource {
@@ -849,6 +1106,8 @@ impl SoundSourceBuilder {
             gain: self.gain,
             pitch: self.pitch,
             play_once: self.play_once,
+            priority: self.priority,
+            downmix_spatial_stereo: self.downmix_spatial_stereo,
             panning: self.panning,
             status: self.status,
             looping: self.looping,
@@ -856,12 +1115,15 @@ impl SoundSourceBuilder {
             frame_samples: Default::default(),
             radius: self.radius,
             position: self.position,
+            velocity: self.velocity,
             max_distance: self.max_distance,
             rolloff_factor: self.rolloff_factor,
             spatial_blend: self.spatial_blend,
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             bus: self.bus,
+            reverb_send: self.reverb_send,
+            reverb_send_bus: self.reverb_send_bus,
             ..Default::default()
         };
 